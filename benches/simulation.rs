@@ -0,0 +1,53 @@
+// rough timings for the paths that clone characters the most: every render clones the party and
+// every greedy ai decision clones the battle once per known move, plus a whole simulated battle
+//   cargo bench --bench simulation
+use std::hint::black_box;
+use std::time::Instant;
+
+use rand::SeedableRng;
+use rand::distributions::{Distribution, Standard};
+use rand::rngs::StdRng;
+
+use kaizo_quest::ai::{AiPolicy, Greedy, Random};
+use kaizo_quest::onion::{OnionBattle, OnionWorld, Status};
+use kaizo_quest::sim::simulate;
+use kaizo_quest::snapshot::{BattleSnapshot, Side, Simulator};
+
+static ITERATIONS: u32 = 100_000;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    // warm up the caches before timing
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    println!("{:<24} {:>8} ns/iter", name, start.elapsed().as_nanos() / ITERATIONS as u128);
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let world: OnionWorld = Standard.sample(&mut rng);
+    let mut battle = OnionBattle::new(world.sample_at_level(20, &mut rng), world.sample_at_level(20, &mut rng));
+    // statuses are what made cloning expensive, so give both sides some
+    battle.player.state.status.insert(Status::Bleed, 2);
+    battle.enemy.state.status.insert(Status::Stun, 1);
+    battle.enemy.state.status.insert(Status::Defend, 0);
+
+    bench("clone character", || { black_box(battle.player.clone()); });
+    bench("clone battle", || { black_box(battle.clone()); });
+    let snapshot = BattleSnapshot::from_battle(&battle);
+    let mut simulator = Simulator::new(&snapshot);
+    bench("branch snapshot", || { black_box(snapshot.clone()); });
+    bench("simulate from snapshot", || {
+        black_box(simulator.simulate_from_snapshot(&snapshot, Side::Player, &world.actions[0]));
+    });
+    bench("greedy choice", || {
+        black_box(Greedy.choose(&battle.enemy, &battle.player, &battle.field, &world.actions, &mut rng));
+    });
+    bench("random battle", || {
+        black_box(simulate(battle.clone(), &Random, &Random, &world.actions, &mut rng));
+    });
+}
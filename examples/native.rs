@@ -0,0 +1,102 @@
+// a native window client, to prove the engine isn't tied to the browser:
+//   cargo run --example native --features native
+// space starts a battle, m re-rolls the enemy before it, 1-4 pick a move, f flees and escape quits;
+// the battle log goes to stdout.
+// when the kaizo wants to learn a move, 1-4 pick the move to forget and 0 keeps them all
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use rand::distributions::{Distribution, Standard};
+
+use kaizo_quest::adapter::{Event, GameLoop, Input};
+use kaizo_quest::onion::{Alignment, OnionCharacter, OnionWorld};
+use kaizo_quest::seed::Seed;
+use kaizo_quest::session::GameSession;
+
+static WIDTH: usize = 320;
+static HEIGHT: usize = 200;
+
+fn color(alignment: Alignment) -> u32 {
+    match alignment {
+        Alignment::Rock => 0x8b5a2b,
+        Alignment::Paper => 0xe0e0d0,
+        Alignment::Scissors => 0xc03030,
+    }
+}
+
+fn fill(buffer: &mut [u32], x: usize, y: usize, width: usize, height: usize, color: u32) {
+    for row in buffer.chunks_mut(WIDTH).skip(y).take(height) {
+        for pixel in row.iter_mut().skip(x).take(width) {
+            *pixel = color;
+        }
+    }
+}
+
+// a health bar in the character's alignment color over a dark track
+fn draw_character(buffer: &mut [u32], character: &OnionCharacter, y: usize) {
+    let max = character.attributes.stats.health.max(1) as usize;
+    let health = character.state.health.max(0) as usize;
+    fill(buffer, 20, y, WIDTH - 40, 16, 0x303030);
+    fill(buffer, 20, y, (WIDTH - 40) * health / max, 16, color(character.state.alignment));
+}
+
+fn main() {
+    // an optional seed argument replays a run
+    let seed = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or_else(Seed::random);
+    println!("Seed: {}", seed);
+    let mut rng = seed.rng();
+    let world: OnionWorld = Standard.sample(&mut rng);
+    let player = world.sample_at_level(5, &mut rng);
+    let mut game = GameLoop::new(GameSession::new(world, player, seed));
+    println!("World: {}", game.session().world_id());
+
+    let mut window = Window::new("Kaizo Quest", WIDTH, HEIGHT, WindowOptions::default()).unwrap();
+    window.set_target_fps(30);
+    let mut buffer = vec![0; WIDTH * HEIGHT];
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let learning = game.session().learning().is_some();
+        for key in window.get_keys_pressed(KeyRepeat::No) {
+            let slot = match key {
+                Key::Key1 => Some(0),
+                Key::Key2 => Some(1),
+                Key::Key3 => Some(2),
+                Key::Key4 => Some(3),
+                _ => None,
+            };
+            match (key, slot) {
+                (Key::Space, _) => game.send(Input::StartBattle),
+                (Key::F, _) => game.send(Input::Flee),
+                (Key::M, _) => game.send(Input::Mulligan),
+                (Key::Key0, _) if learning => game.send(Input::Learn(None)),
+                (_, Some(slot)) if learning => game.send(Input::Learn(Some(slot))),
+                (_, Some(slot)) => game.send(Input::Choose(slot)),
+                _ => (),
+            }
+        }
+        game.update();
+        while let Some(event) = game.poll() {
+            match event {
+                Event::Log(log) => println!("{}", log),
+                Event::BattleEnded(state) => println!("-- {:?} ({}) --", state, game.session().result()),
+                Event::WantsToLearn(action) => println!("-- 1-4 to forget a move for {}, 0 to skip it --", game.session().world().actions[action].name()),
+                Event::Rerolled(left) => println!("-- {} mulligans left --", left),
+                Event::Rejected(error) => println!("{}", error),
+                Event::BattleStarted | Event::Dropped(_) => (),
+            }
+        }
+
+        buffer.iter_mut().for_each(|pixel| *pixel = 0x101018);
+        let session = game.session();
+        match session.battle() {
+            Some(battle) => {
+                draw_character(&mut buffer, &battle.enemy, 40);
+                draw_character(&mut buffer, &battle.player, 140);
+                window.set_title(&format!("{} vs {}", battle.player.name, battle.enemy.name));
+            },
+            None => {
+                draw_character(&mut buffer, session.player(), 140);
+                window.set_title(&format!("{} - press space to battle", session.player().name));
+            },
+        }
+        window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Ability, Name, Stat, States};
+use crate::onion::{Alignment, Field, OnionCharacter, Status};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    // takes this percent less damage from attacks of the alignment
+    Resist(Alignment, u32),
+    // its moves have priority on the first turn of a battle
+    QuickStart,
+    // heals this percent of its max health at the end of every turn
+    Regenerate(u32),
+    // lowers the opponent's stat a stage when the battle starts
+    Intimidate(Stat),
+    // can't be given the status
+    Immunity(Status),
+}
+
+// abilities are plain data, like items
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OnionAbility {
+    pub name: Name,
+    pub effect: Effect,
+}
+
+impl Ability<Alignment, Status, Field> for OnionAbility {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        match self.effect {
+            Effect::Resist(alignment, percent) => format!("Takes {}% less damage from {:?} attacks.", percent, alignment),
+            Effect::QuickStart => "Moves first on the first turn of a battle.".to_string(),
+            Effect::Regenerate(percent) => format!("Heals {}% of its health at the end of every turn.", percent),
+            Effect::Intimidate(stat) => format!("Lowers the enemy's {} when the battle starts.", stat),
+            Effect::Immunity(status) => format!("Can't be given {}.", format!("{:?}", status).to_lowercase()),
+        }
+    }
+
+    fn on_battle_start(&self, holder: &mut OnionCharacter, opponent: &mut OnionCharacter, _: &mut Field) -> States {
+        match self.effect {
+            Effect::Intimidate(stat) => {
+                opponent.change_stage(stat, -1);
+                vec![format!("{}'s {} lowered {}'s {}.", holder.name, self.name, opponent.name, stat)]
+            },
+            Effect::Immunity(status) => {
+                holder.state.immune.insert(status, 0);
+                vec![]
+            },
+            _ => vec![],
+        }
+    }
+
+    fn on_hit(&self, _: &OnionCharacter, alignment: &Alignment, damage: u32) -> u32 {
+        match self.effect {
+            Effect::Resist(resisted, percent) if resisted == *alignment => damage * (100 - percent.min(100)) / 100,
+            _ => damage,
+        }
+    }
+
+    fn on_end_turn(&self, holder: &mut OnionCharacter, _: &mut Field) -> States {
+        let max = holder.attributes.stats.health as i32;
+        match self.effect {
+            Effect::Regenerate(percent) if holder.state.health > 0 && holder.state.health < max => {
+                let health = holder.state.health;
+                holder.state.health = std::cmp::min(max, health + std::cmp::max(1, max * percent as i32 / 100));
+                vec![format!("{}'s {} restored {} health.", holder.name, self.name, holder.state.health - health)]
+            },
+            _ => vec![],
+        }
+    }
+
+    fn priority(&self, turn: u32) -> i32 {
+        match self.effect {
+            Effect::QuickStart if turn == 0 => 1,
+            _ => 0,
+        }
+    }
+}
+
+// the abilities every world has, which its species are rolled from
+pub fn default_abilities() -> Vec<OnionAbility> {
+    vec![
+        OnionAbility { name: "Thick Skin".into(), effect: Effect::Resist(Alignment::Rock, 20) },
+        OnionAbility { name: "Quick Start".into(), effect: Effect::QuickStart },
+        OnionAbility { name: "Regenerator".into(), effect: Effect::Regenerate(5) },
+        OnionAbility { name: "Intimidate".into(), effect: Effect::Intimidate(Stat::Attack) },
+        OnionAbility { name: "Clotting".into(), effect: Effect::Immunity(Status::Bleed) },
+    ]
+}
+
+#[cfg(test)]
+mod abilities_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::OnionWorld;
+
+    fn fake_character() -> OnionCharacter {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut character = world.sample_at_level(50, &mut rng);
+        character.name = "fake".into();
+        character
+    }
+
+    fn ability(effect: Effect) -> OnionAbility {
+        OnionAbility { name: "Fake".into(), effect }
+    }
+
+    #[test]
+    fn resist_test() {
+        let holder = fake_character();
+        let thick_skin = ability(Effect::Resist(Alignment::Rock, 20));
+        assert_eq!(thick_skin.on_hit(&holder, &Alignment::Rock, 100), 80);
+        assert_eq!(thick_skin.on_hit(&holder, &Alignment::Paper, 100), 100);
+    }
+
+    #[test]
+    fn quick_start_test() {
+        let quick_start = ability(Effect::QuickStart);
+        assert_eq!(quick_start.priority(0), 1);
+        assert_eq!(quick_start.priority(1), 0);
+        assert_eq!(ability(Effect::Regenerate(5)).priority(0), 0);
+    }
+
+    #[test]
+    fn regenerate_test() {
+        let mut holder = fake_character();
+        let regenerator = ability(Effect::Regenerate(100));
+        assert!(regenerator.on_end_turn(&mut holder, &mut Field::default()).is_empty());
+
+        holder.state.health = 1;
+        assert_eq!(regenerator.on_end_turn(&mut holder, &mut Field::default()).len(), 1);
+        assert_eq!(holder.state.health, holder.attributes.stats.health as i32);
+
+        // fainted kaizo stay fainted
+        holder.state.health = 0;
+        assert!(regenerator.on_end_turn(&mut holder, &mut Field::default()).is_empty());
+        assert_eq!(holder.state.health, 0);
+    }
+
+    #[test]
+    fn battle_start_test() {
+        let mut holder = fake_character();
+        let mut opponent = fake_character();
+        let logs = ability(Effect::Intimidate(Stat::Attack)).on_battle_start(&mut holder, &mut opponent, &mut Field::default());
+        assert_eq!(logs, vec!["fake's Fake lowered fake's attack."]);
+        assert_eq!(*opponent.state.stages.get(Stat::Attack), -1);
+
+        ability(Effect::Immunity(Status::Bleed)).on_battle_start(&mut holder, &mut opponent, &mut Field::default());
+        assert!(holder.state.immune.contains_key(&Status::Bleed));
+    }
+}
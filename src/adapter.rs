@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+
+use crate::core::ActionId;
+use crate::onion::OnionBattleState;
+use crate::session::{GameSession, SessionError};
+
+// what a frontend can ask for during a frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    StartBattle,
+    Choose(usize),
+    Flee,
+    // re-rolls the enemy the next battle is against
+    Mulligan,
+    // replaces the move in a slot with the one waiting to be learned, or gives up on it
+    Learn(Option<usize>),
+}
+
+// what happened because of the inputs, in order
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Log(String),
+    BattleStarted,
+    BattleEnded(OnionBattleState),
+    // the next enemy was re-rolled, with this many mulligans left
+    Rerolled(u32),
+    // the kaizo needs to forget a move to learn this one; answered with `Input::Learn`
+    WantsToLearn(ActionId),
+    Rejected(SessionError),
+    // sent again before the last one was resolved, e.g. by a double click
+    Dropped(Input),
+}
+
+// turns away inputs that arrive while the last one is still being resolved or shown, so one that
+// was made against what's now stale state doesn't go through too
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputLock {
+    locked: bool,
+}
+
+impl InputLock {
+    // whether the input can go ahead; if it can, the lock is held until it's released
+    pub fn acquire(&mut self) -> bool {
+        !std::mem::replace(&mut self.locked, true)
+    }
+
+    // once the result of the input is out, e.g. drawn
+    pub fn release(&mut self) {
+        self.locked = false;
+    }
+
+    pub fn is_locked(&self) -> bool { self.locked }
+}
+
+impl Input {
+    // inputs that answer what the player was just shown, so only one goes through a frame
+    fn locks(&self) -> bool {
+        matches!(self, Input::Choose(_) | Input::Learn(_))
+    }
+}
+
+// wraps a session for game loops (native windows, engines like bevy): inputs are queued whenever
+// they arrive, applied once per frame by `update`, and the results are drained with `poll`
+pub struct GameLoop {
+    session: GameSession,
+    inputs: VecDeque<Input>,
+    events: VecDeque<Event>,
+    lock: InputLock,
+}
+
+impl GameLoop {
+    pub fn new(session: GameSession) -> GameLoop {
+        GameLoop { session, inputs: VecDeque::new(), events: VecDeque::new(), lock: InputLock::default() }
+    }
+
+    // for drawing; changes go through `send`
+    pub fn session(&self) -> &GameSession { &self.session }
+
+    pub fn send(&mut self, input: Input) {
+        self.inputs.push_back(input);
+    }
+
+    pub fn update(&mut self) {
+        while let Some(input) = self.inputs.pop_front() {
+            if input.locks() && !self.lock.acquire() {
+                self.events.push_back(Event::Dropped(input));
+                continue;
+            }
+            // the logs, then whatever the input changed
+            let result = match input {
+                Input::StartBattle => self.session.start_battle().map(|logs| (logs, Some(Event::BattleStarted))),
+                Input::Choose(slot) => self.session.choose(slot).map(|(state, logs)| match state {
+                    OnionBattleState::InProcess => (logs, None),
+                    state => (logs, Some(Event::BattleEnded(state))),
+                }),
+                Input::Flee => self.session.flee().map(|logs| (logs, Some(Event::BattleEnded(OnionBattleState::Fled)))),
+                Input::Mulligan => self.session.mulligan().map(|logs| (logs, Some(Event::Rerolled(self.session.mulligans())))),
+                Input::Learn(slot) => self.session.learn(slot).map(|logs| (logs, None)),
+            };
+            match result {
+                Ok((logs, event)) => {
+                    self.events.extend(logs.into_iter().map(Event::Log));
+                    self.events.extend(event);
+                    // moves are asked about one at a time, once the battle is over
+                    if let Input::Choose(_) | Input::Learn(_) = input {
+                        self.events.extend(self.session.learning().map(Event::WantsToLearn));
+                    }
+                },
+                Err(error) => self.events.push_back(Event::Rejected(error)),
+            }
+        }
+        // the frame is drawn with everything resolved, so the next one can take new input
+        self.lock.release();
+    }
+
+    pub fn poll(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod adapter_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::OnionWorld;
+
+    fn fake_loop() -> GameLoop {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let player = world.sample_at_level(5, &mut rng);
+        GameLoop::new(GameSession::new(world, player, crate::seed::Seed(0)))
+    }
+
+    fn drain(game: &mut GameLoop) -> Vec<Event> {
+        std::iter::from_fn(|| game.poll()).collect()
+    }
+
+    #[test]
+    fn update_test() {
+        let mut game = fake_loop();
+        game.send(Input::Choose(0));
+        game.send(Input::StartBattle);
+        // nothing happens until the frame is updated
+        assert_eq!(game.poll(), None);
+        assert!(game.session().battle().is_none());
+
+        game.update();
+        let events = drain(&mut game);
+        assert_eq!(events[0], Event::Rejected(SessionError::NoBattle));
+        assert!(matches!(events[1], Event::Log(_)));
+        assert_eq!(events.last(), Some(&Event::BattleStarted));
+        assert!(game.session().battle().is_some());
+
+        game.send(Input::Flee);
+        game.update();
+        let events = drain(&mut game);
+        assert_eq!(events.last(), Some(&Event::BattleEnded(OnionBattleState::Fled)));
+        assert!(game.session().battle().is_none());
+
+        game.send(Input::Learn(None));
+        game.update();
+        assert_eq!(game.poll(), Some(Event::Rejected(SessionError::NothingToLearn)));
+
+        game.send(Input::Mulligan);
+        game.update();
+        let events = drain(&mut game);
+        assert!(matches!(events[0], Event::Log(_)));
+        assert_eq!(events[1], Event::Rerolled(crate::session::MULLIGANS - 1));
+    }
+
+    #[test]
+    fn lock_test() {
+        let mut lock = InputLock::default();
+        assert!(lock.acquire());
+        assert!(lock.is_locked());
+        assert!(!lock.acquire());
+        lock.release();
+        assert!(lock.acquire());
+    }
+
+    #[test]
+    fn spam_test() {
+        let mut game = fake_loop();
+        game.send(Input::StartBattle);
+        game.update();
+        drain(&mut game);
+
+        // clicking a move over and over before the frame is drawn only plays one turn
+        for _ in 0..5 {
+            game.send(Input::Choose(0));
+        }
+        game.update();
+        let events = drain(&mut game);
+        assert_eq!(events.iter().filter(|event| **event == Event::Dropped(Input::Choose(0))).count(), 4);
+        assert_eq!(game.session().battle().unwrap().turn, 1);
+
+        // and the next frame takes input again
+        game.send(Input::Choose(0));
+        game.update();
+        let events = drain(&mut game);
+        assert!(!events.iter().any(|event| matches!(event, Event::Dropped(_))));
+        assert!(matches!(events[0], Event::Log(_)));
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+
+use rand::{RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+
+use crate::core::ActionId;
+use crate::onion::{ActionKind, ActionPool, Field, OnionBattle, OnionBattleState, OnionCharacter, SKIP_ID, Status};
+use crate::sim::play_turn;
+use crate::snapshot::{BattleSnapshot, Side, Simulator};
+
+// decides what a computer-controlled character does on its turn
+pub trait AiPolicy {
+    fn choose(
+        &self,
+        user: &OnionCharacter,
+        target: &OnionCharacter,
+        field: &Field,
+        actions: &ActionPool,
+        rng: &mut dyn RngCore,
+    ) -> ActionId;
+}
+
+// picks any known action with uses left
+pub struct Random;
+
+impl AiPolicy for Random {
+    fn choose(&self, user: &OnionCharacter, _: &OnionCharacter, _: &Field, actions: &ActionPool, rng: &mut dyn RngCore) -> ActionId {
+        actions.usable(user).collect::<Vec<_>>().choose(rng).copied().unwrap_or(SKIP_ID)
+    }
+}
+
+// picks the action that takes the most health off the target, found by trying each one on a snapshot
+// of the battle
+pub struct Greedy;
+
+impl AiPolicy for Greedy {
+    fn choose(&self, user: &OnionCharacter, target: &OnionCharacter, field: &Field, actions: &ActionPool, rng: &mut dyn RngCore) -> ActionId {
+        let snapshot = BattleSnapshot::new(user, target, field);
+        let mut simulator = Simulator::new(&snapshot);
+        actions.usable(user)
+            .min_by_key(|action| simulator.simulate_from_snapshot(&snapshot, Side::Player, &actions[*action]).enemy_state.health)
+            .unwrap_or_else(|| Random.choose(user, target, field, actions, rng))
+    }
+}
+
+// the actions the player has picked this run and how often, with the first turn of each battle
+// kept apart since that's where habits show the most
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Habits {
+    opening: BTreeMap<ActionId, u32>,
+    later: BTreeMap<ActionId, u32>,
+}
+
+impl Habits {
+    pub fn record(&mut self, action: ActionId, opening: bool) {
+        let counts = if opening { &mut self.opening } else { &mut self.later };
+        *counts.entry(action).or_insert(0) += 1;
+    }
+
+    // the usable action the player has picked the most in this spot, if any of them have been
+    pub fn predict(&self, player: &OnionCharacter, actions: &ActionPool, opening: bool) -> Option<ActionId> {
+        let counts = if opening { &self.opening } else { &self.later };
+        actions.usable(player)
+            .filter_map(|action| counts.get(&action).map(|count| (action, *count)))
+            .max_by_key(|(_, count)| *count)
+            .map(|(action, _)| action)
+    }
+}
+
+// a move is a threat when it would take at least a third of the health left
+static THREAT_SHARE: i32 = 3;
+
+// plays like greedy unless it can't finish the target this turn, then defends against a move that
+// would knock it out, or stuns a target with a move that would hurt
+pub struct Tactical;
+
+impl AiPolicy for Tactical {
+    fn choose(&self, user: &OnionCharacter, target: &OnionCharacter, field: &Field, actions: &ActionPool, rng: &mut dyn RngCore) -> ActionId {
+        let snapshot = BattleSnapshot::new(user, target, field);
+        let mut simulator = Simulator::new(&snapshot);
+        let finishes = actions.usable(user)
+            .any(|action| simulator.simulate_from_snapshot(&snapshot, Side::Player, &actions[action]).enemy_state.health <= 0);
+        if !finishes {
+            // the most the target could take off with one move
+            let threat = actions.usable(target)
+                .map(|action| user.state.health - simulator.simulate_from_snapshot(&snapshot, Side::Enemy, &actions[action]).player_state.health)
+                .max()
+                .unwrap_or(0);
+            let usable = |kind| actions.usable(user).find(|action| actions.kind(*action) == kind);
+            if threat >= user.state.health {
+                if let Some(defend) = usable(ActionKind::Defend) {
+                    return defend;
+                }
+            }
+            // stuns don't stack on a stunned or bleeding target
+            let stunnable = !target.state.status.contains_key(&Status::Stun) && !target.state.status.contains_key(&Status::Bleed);
+            if stunnable && threat * THREAT_SHARE >= user.state.health {
+                if let Some(stun) = usable(ActionKind::Stun) {
+                    return stun;
+                }
+            }
+        }
+        Greedy.choose(user, target, field, actions, rng)
+    }
+}
+
+// plays like greedy, but guesses the player's move from their habits and defends against the ones
+// that are a threat
+pub struct Adaptive<'a> {
+    pub habits: &'a Habits,
+    // whether this is the first turn of the battle
+    pub opening: bool,
+}
+
+impl AiPolicy for Adaptive<'_> {
+    fn choose(&self, user: &OnionCharacter, target: &OnionCharacter, field: &Field, actions: &ActionPool, rng: &mut dyn RngCore) -> ActionId {
+        let defend = actions.usable(user).find(|action| actions.kind(*action) == ActionKind::Defend);
+        if let (Some(defend), Some(predicted)) = (defend, self.habits.predict(target, actions, self.opening)) {
+            let snapshot = BattleSnapshot::new(user, target, field);
+            let hit = Simulator::new(&snapshot).simulate_from_snapshot(&snapshot, Side::Enemy, &actions[predicted]);
+            if (user.state.health - hit.player_state.health) * THREAT_SHARE >= user.state.health {
+                return defend;
+            }
+        }
+        Greedy.choose(user, target, field, actions, rng)
+    }
+}
+
+// how many turns ahead kaizo looks
+pub static KAIZO_DEPTH: u32 = 2;
+
+// plays `depth` whole turns ahead on copies of the battle and picks the move that does best on
+// average over every answer the target has, so it finds moves that only pay off later, like ones
+// that get through a target that keeps defending
+pub struct Expectimax {
+    pub depth: u32,
+}
+
+impl Expectimax {
+    // the best the user (the enemy side of `battle`) can expect with `depth` turns left
+    fn best(&self, battle: &OnionBattle, actions: &ActionPool, depth: u32, rng: &mut StdRng) -> f64 {
+        if depth == 0 {
+            return evaluate(battle);
+        }
+        let moves: Vec<ActionId> = actions.usable(&battle.enemy).collect();
+        if moves.is_empty() {
+            return self.expected(battle, SKIP_ID, actions, depth, rng);
+        }
+        moves.into_iter()
+            .map(|action| self.expected(battle, action, actions, depth, rng))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    // the average over the target's answers to `action`, each as likely as the others
+    fn expected(&self, battle: &OnionBattle, action: ActionId, actions: &ActionPool, depth: u32, rng: &mut StdRng) -> f64 {
+        let mut answers: Vec<ActionId> = actions.usable(&battle.player).collect();
+        if answers.is_empty() {
+            answers.push(SKIP_ID);
+        }
+        let total: f64 = answers.iter()
+            .map(|answer| {
+                let mut next = battle.clone();
+                match play_turn(&mut next, *answer, action, actions, rng).0 {
+                    OnionBattleState::Victory => -1.0,
+                    OnionBattleState::Defeat => 1.0,
+                    OnionBattleState::Escaped | OnionBattleState::Fled | OnionBattleState::Draw => 0.0,
+                    OnionBattleState::InProcess => self.best(&next, actions, depth - 1, rng),
+                }
+            })
+            .sum();
+        total / answers.len() as f64
+    }
+}
+
+// how good the battle looks for its enemy side, between -1 and 1
+fn evaluate(battle: &OnionBattle) -> f64 {
+    let health = |character: &OnionCharacter| character.state.health as f64 / character.attributes.stats.health.max(1) as f64;
+    health(&battle.enemy) - health(&battle.player)
+}
+
+impl AiPolicy for Expectimax {
+    fn choose(&self, user: &OnionCharacter, target: &OnionCharacter, field: &Field, actions: &ActionPool, rng: &mut dyn RngCore) -> ActionId {
+        let mut battle = OnionBattle::new(target.clone(), user.clone());
+        battle.field = field.clone();
+        // the search rolls its own dice, so looking ahead doesn't change how the real battle rolls
+        let mut search = StdRng::seed_from_u64(rng.next_u64());
+        let mut best = None;
+        for action in actions.usable(user) {
+            let value = self.expected(&battle, action, actions, self.depth.max(1), &mut search);
+            if best.is_none_or(|(_, best)| value > best) {
+                best = Some((action, value));
+            }
+        }
+        best.map(|(action, _)| action).unwrap_or(SKIP_ID)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty { Easy, Normal, Hard, Adaptive, Kaizo }
+
+// the masters get harder as you beat them: the difficulty from each number of wins on
+static TIERS: [(u32, Difficulty); 3] = [(0, Difficulty::Easy), (5, Difficulty::Normal), (15, Difficulty::Hard)];
+
+impl Difficulty {
+    pub fn for_wins(wins: u32) -> Difficulty {
+        TIERS.iter().rev().find(|(from, _)| wins >= *from).map(|(_, difficulty)| *difficulty).unwrap_or(Difficulty::Easy)
+    }
+
+    // `habits` are the player's this run, for the policies that learn from them
+    pub fn policy<'a>(&self, habits: &'a Habits, opening: bool) -> Box<dyn AiPolicy + 'a> {
+        match self {
+            Difficulty::Easy => Box::new(Random),
+            Difficulty::Normal => Box::new(Greedy),
+            Difficulty::Hard => Box::new(Tactical),
+            Difficulty::Adaptive => Box::new(Adaptive { habits, opening }),
+            Difficulty::Kaizo => Box::new(Expectimax { depth: KAIZO_DEPTH }),
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Adaptive,
+            Difficulty::Adaptive => Difficulty::Kaizo,
+            Difficulty::Kaizo => Difficulty::Easy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod ai_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::core::{Character, Stats};
+    use crate::onion::{Alignment, Attack, testing};
+
+    fn fake_character(actions: Vec<ActionId>) -> OnionCharacter {
+        let mut character = Character::from_species_and_actions(testing::fake_species_with_bst(400), actions);
+        character.attributes.level = 10;
+        character.attributes.stats = Stats::from_values(100, 10, 10, 10, 10, 10);
+        character.refresh();
+        character
+    }
+
+    fn fake_pool() -> ActionPool {
+        ActionPool::with_attacks(vec![
+            Attack::new("weak", 10, Alignment::Rock, 0),
+            Attack::new("strong", 100, Alignment::Rock, 0),
+        ])
+    }
+
+    #[test]
+    fn random_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let pool = fake_pool();
+        let user = fake_character(vec![0, 1]);
+        let target = fake_character(vec![]);
+
+        for _ in 0..10 {
+            let action = Random.choose(&user, &target, &Field::default(), &pool, &mut rng);
+            assert!(user.attributes.actions.contains(&action));
+        }
+
+        let user = fake_character(vec![]);
+        assert_eq!(Random.choose(&user, &target, &Field::default(), &pool, &mut rng), SKIP_ID);
+    }
+
+    #[test]
+    fn greedy_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let pool = fake_pool();
+        let user = fake_character(vec![0, 1]);
+        let target = fake_character(vec![]);
+
+        assert_eq!(Greedy.choose(&user, &target, &Field::default(), &pool, &mut rng), 1);
+
+        let user = fake_character(vec![1, 0]);
+        assert_eq!(Greedy.choose(&user, &target, &Field::default(), &pool, &mut rng), 1);
+    }
+
+    #[test]
+    fn tactical_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let pool = fake_pool();
+        let kind = |kind| pool.ids().find(|action| pool.kind(*action) == kind).unwrap();
+        let (defend, stun) = (kind(ActionKind::Defend), kind(ActionKind::Stun));
+        let mut user = fake_character(vec![0, 1, defend, stun]);
+        let mut target = fake_character(vec![0, 1]);
+        let mut choose = |user: &OnionCharacter, target: &OnionCharacter| Tactical.choose(user, target, &Field::default(), &pool, &mut rng);
+
+        // the strong attack takes 20
+        user.state.health = 15;
+        assert_eq!(choose(&user, &target), defend);
+        user.state.health = 50;
+        assert_eq!(choose(&user, &target), stun);
+        user.state.health = 100;
+        assert_eq!(choose(&user, &target), 1);
+
+        user.state.health = 50;
+        target.state.status.insert(Status::Stun, 1);
+        assert_eq!(choose(&user, &target), 1);
+        // finishing the target off beats playing safe
+        user.state.health = 15;
+        target.state.health = 1;
+        assert_ne!(choose(&user, &target), defend);
+    }
+
+    #[test]
+    fn expectimax_test() {
+        let pool = fake_pool();
+        let user = fake_character(vec![0, 1]);
+        let target = fake_character(vec![0, 1]);
+        let choose = |depth, target: &OnionCharacter, seed| {
+            Expectimax { depth }.choose(&user, target, &Field::default(), &pool, &mut StdRng::seed_from_u64(seed))
+        };
+
+        assert_eq!(choose(1, &target, 0), 1);
+        assert_eq!(choose(2, &target, 0), 1);
+        // the same seed searches the same way
+        assert_eq!(choose(2, &target, 3), choose(2, &target, 3));
+        // nothing to pick
+        assert_eq!(Expectimax { depth: 1 }.choose(&fake_character(vec![]), &target, &Field::default(), &pool, &mut StdRng::seed_from_u64(0)), SKIP_ID);
+    }
+
+    #[test]
+    fn for_wins_test() {
+        assert_eq!(Difficulty::for_wins(0), Difficulty::Easy);
+        assert_eq!(Difficulty::for_wins(5), Difficulty::Normal);
+        assert_eq!(Difficulty::for_wins(14), Difficulty::Normal);
+        assert_eq!(Difficulty::for_wins(100), Difficulty::Hard);
+    }
+
+    #[test]
+    fn adaptive_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let pool = fake_pool();
+        let defend = pool.ids().find(|action| pool.kind(*action) == ActionKind::Defend).unwrap();
+        let mut user = fake_character(vec![0, 1, defend]);
+        // hurt enough that the strong attack takes over a third of what's left, and the weak one doesn't
+        user.state.health = 50;
+        let target = fake_character(vec![0, 1]);
+
+        let mut habits = Habits::default();
+        habits.record(1, true);
+        habits.record(1, true);
+        habits.record(0, true);
+        habits.record(0, false);
+        assert_eq!(habits.predict(&target, &pool, true), Some(1));
+        assert_eq!(habits.predict(&target, &pool, false), Some(0));
+        assert_eq!(habits.predict(&fake_character(vec![2]), &pool, true), None);
+
+        // always opening with the strong attack gets defended against, the weak one doesn't
+        let adaptive = Adaptive { habits: &habits, opening: true };
+        assert_eq!(adaptive.choose(&user, &target, &Field::default(), &pool, &mut rng), defend);
+        let adaptive = Adaptive { habits: &habits, opening: false };
+        assert_eq!(adaptive.choose(&user, &target, &Field::default(), &pool, &mut rng), 1);
+        // with no habits it plays like greedy
+        let habits = Habits::default();
+        let adaptive = Adaptive { habits: &habits, opening: true };
+        assert_eq!(adaptive.choose(&user, &target, &Field::default(), &pool, &mut rng), 1);
+    }
+}
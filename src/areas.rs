@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{DayCycle, TimeOfDay};
+use crate::core::{Name, Rarity, Species};
+use crate::onion::Alignment;
+
+// generated worlds' areas, in the order they're reached: the name, the levels kaizo are met at there
+// and the slice of the bst range its species come from. neighbouring slices overlap a little, so
+// some kaizo can be met in either
+type Biome = (&'static str, (u32, u32), (u32, u32));
+
+static BIOMES: [Biome; 4] = [
+    ("Meadow", (2, 8), (200, 340)),
+    ("Forest", (8, 18), (310, 460)),
+    ("Caves", (18, 30), (430, 580)),
+    ("Summit", (30, 45), (550, 700)),
+];
+
+// how often a generated world's species of each rarity are met, compared to the rest of their area
+pub fn spawn_weight(rarity: Rarity) -> u32 {
+    match rarity {
+        Rarity::Common => 10,
+        Rarity::Uncommon => 5,
+        Rarity::Rare => 2,
+        Rarity::Legendary => 1,
+    }
+}
+
+// somewhere to look for wild kaizo, e.g.
+// `{ "name": "Meadow", "levels": [2, 8], "encounters": [["Pebble", 10], ["Scrap", 1]] }`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Area {
+    pub name: String,
+    // the levels kaizo are met at; a party can search the area once its lead is at the lowest of them
+    pub levels: (u32, u32),
+    // the species met there, each with how often it's met compared to the rest
+    pub encounters: Vec<(Name, u32)>,
+}
+
+impl Area {
+    pub fn weight(&self, species: &Species<Alignment>) -> u32 {
+        self.encounters.iter()
+            .find(|(name, _)| name.to_lowercase() == species.name.to_lowercase())
+            .map_or(0, |(_, weight)| *weight)
+    }
+
+    // the species that can be met there at that time, each listed as many times as it's weighted,
+    // counting the cycle's weights too. an area of only night creatures still has something to meet
+    // by day
+    pub fn table<'a>(&self, species: &'a [Arc<Species<Alignment>>], cycle: &DayCycle, time: TimeOfDay) -> Vec<&'a Arc<Species<Alignment>>> {
+        let table = |cycle_weight: &dyn Fn(&Species<Alignment>) -> u32| -> Vec<&'a Arc<Species<Alignment>>> {
+            species.iter()
+                .flat_map(|species| std::iter::repeat_n(species, (self.weight(species) * cycle_weight(species)) as usize))
+                .collect()
+        };
+        let timed = table(&|species| cycle.weight(species, time));
+        if timed.is_empty() { table(&|_| 1) } else { timed }
+    }
+}
+
+// the furthest area a party led by a kaizo at that level can search; the first one before the
+// lead is strong enough for any
+pub fn area_at(areas: &[Area], level: u32) -> Option<&Area> {
+    areas.iter().rev().find(|area| area.levels.0 <= level).or(areas.first())
+}
+
+// splits a generated world's species into its biomes by bst, weighted by their rarity
+pub fn generate_areas(species: &[Arc<Species<Alignment>>]) -> Vec<Area> {
+    BIOMES.iter()
+        .map(|(name, levels, (lowest, highest))| Area {
+            name: name.to_string(),
+            levels: *levels,
+            encounters: species.iter()
+                .filter(|species| (*lowest..*highest).contains(&species.bst))
+                .map(|species| (species.name.clone(), spawn_weight(species.rarity)))
+                .collect(),
+        })
+        .filter(|area| !area.encounters.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod areas_tests {
+    use super::*;
+
+    use crate::core::Rarity;
+    use crate::onion::testing;
+
+    fn species(name: &str, bst: u32, rarity: Rarity) -> Arc<Species<Alignment>> {
+        Arc::new(Species { name: name.into(), rarity, ..testing::fake_species_with_bst(bst) })
+    }
+
+    #[test]
+    fn generate_test() {
+        let species = vec![
+            species("Pebble", 200, Rarity::Common),
+            species("Stone", 330, Rarity::Common),
+            species("Boulder", 455, Rarity::Uncommon),
+            species("Mountain", 690, Rarity::Legendary),
+        ];
+        let areas = generate_areas(&species);
+        let names: Vec<&str> = areas.iter().map(|area| area.name.as_str()).collect();
+        assert_eq!(names, vec!["Meadow", "Forest", "Caves", "Summit"]);
+        assert_eq!(areas[0].encounters, vec![("Pebble".into(), 10), ("Stone".into(), 10)]);
+        // the stronger kaizo in an area are the rarer ones
+        assert!(areas[1].weight(&species[1]) > areas[1].weight(&species[2]));
+        assert_eq!(areas[3].weight(&species[3]), 1);
+        assert_eq!(areas[0].weight(&species[3]), 0);
+
+        // areas nothing would be met in are left out
+        assert_eq!(generate_areas(&species[..1]).len(), 1);
+    }
+
+    #[test]
+    fn area_at_test() {
+        let areas = generate_areas(&[species("Pebble", 330, Rarity::Common), species("Boulder", 600, Rarity::Rare)]);
+        assert_eq!(area_at(&areas, 1).unwrap().name, "Meadow");
+        assert_eq!(area_at(&areas, 10).unwrap().name, "Forest");
+        assert_eq!(area_at(&areas, 100).unwrap().name, "Summit");
+        assert!(area_at(&[], 10).is_none());
+    }
+
+    #[test]
+    fn table_test() {
+        let species = vec![species("Pebble", 300, Rarity::Common), species("Moth", 300, Rarity::Common), species("Boulder", 600, Rarity::Rare)];
+        let area = Area { name: "Meadow".to_string(), levels: (2, 8), encounters: vec![("pebble".into(), 3), ("Moth".into(), 1)] };
+        let table = area.table(&species, &DayCycle::default(), TimeOfDay::Day);
+        assert_eq!(table.iter().map(|species| &*species.name).collect::<Vec<_>>(), vec!["Pebble", "Pebble", "Pebble", "Moth"]);
+
+        let cycle = DayCycle { nocturnal: vec!["Pebble".into(), "Moth".into()], ..DayCycle::default() };
+        assert_eq!(area.table(&species, &cycle, TimeOfDay::Day).len(), 4);
+        let cycle = DayCycle { nocturnal: vec!["Pebble".into()], ..DayCycle::default() };
+        assert_eq!(area.table(&species, &cycle, TimeOfDay::Day).len(), 1);
+    }
+}
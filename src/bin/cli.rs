@@ -0,0 +1,102 @@
+// a terminal frontend: an endless string of battles against random kaizo, one move per line of input.
+// pass a seed to replay a run: `cargo run --bin cli -- <seed>`
+use std::io::{self, BufRead, Write};
+
+use rand::distributions::{Distribution, Standard};
+
+use kaizo_quest::onion::{OnionBattleState, OnionWorld};
+use kaizo_quest::render::text::frame;
+use kaizo_quest::seed::Seed;
+use kaizo_quest::session::GameSession;
+
+fn main() {
+    let seed = match std::env::args().nth(1).map(|arg| arg.parse::<Seed>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(error)) => {
+            eprintln!("{}", error);
+            return;
+        },
+        None => Seed::random(),
+    };
+    println!("Seed: {}", seed);
+    let mut rng = seed.rng();
+    let world: OnionWorld = Standard.sample(&mut rng);
+    let player = world.sample_at_level(5, &mut rng);
+    let mut session = GameSession::new(world, player, seed);
+    println!("World: {}", session.world_id());
+    let mut lines = io::stdin().lock().lines();
+
+    loop {
+        // the next enemy can be re-rolled while there are mulligans left
+        while session.mulligans() > 0 {
+            let upcoming = session.upcoming();
+            print!("Next up: {} Lv{}. Re-roll it ({} left)? [y/N] ", upcoming.name, upcoming.attributes.level, session.mulligans());
+            let _ = io::stdout().flush();
+            match lines.next() {
+                Some(Ok(line)) if line.trim() == "q" => return,
+                Some(Ok(line)) if line.trim().eq_ignore_ascii_case("y") => {
+                    for log in session.mulligan().unwrap() {
+                        println!("> {}", log);
+                    }
+                },
+                Some(Ok(_)) => break,
+                _ => return,
+            }
+        }
+
+        let mut logs = session.start_battle().unwrap();
+        let state = loop {
+            let battle = session.battle().unwrap();
+            println!("{}\n", frame(battle, &logs));
+            for (index, action) in battle.player.attributes.actions.iter().enumerate() {
+                println!("{}) {}", index + 1, session.world().actions[*action].name());
+            }
+            print!("Move? ");
+            let _ = io::stdout().flush();
+
+            let line = match lines.next() {
+                Some(Ok(line)) if line.trim() != "q" => line,
+                _ => return,
+            };
+            let slot = line.trim().parse::<usize>().unwrap_or(0).wrapping_sub(1);
+            match session.choose(slot) {
+                Ok((OnionBattleState::InProcess, turn)) => logs = turn,
+                Ok((state, turn)) => {
+                    logs = turn;
+                    break state;
+                },
+                Err(error) => logs = vec![error.to_string()],
+            }
+        };
+        for log in logs {
+            println!("> {}", log);
+        }
+        println!();
+
+        if state == OnionBattleState::Defeat {
+            println!("Game over after {}.", session.result());
+            return;
+        }
+
+        while let Some(action) = session.learning() {
+            println!("Forget a move to learn {}?", session.world().actions[action].name());
+            for (index, known) in session.player().attributes.actions.iter().enumerate() {
+                println!("{}) {}", index + 1, session.world().actions[*known].name());
+            }
+            print!("Move to forget (anything else keeps them all)? ");
+            let _ = io::stdout().flush();
+
+            let line = match lines.next() {
+                Some(Ok(line)) if line.trim() != "q" => line,
+                _ => return,
+            };
+            let slot = line.trim().parse::<usize>().ok()
+                .and_then(|slot| slot.checked_sub(1))
+                .filter(|slot| *slot < session.player().attributes.actions.len());
+            for log in session.learn(slot).unwrap() {
+                println!("> {}", log);
+            }
+            println!();
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// correspondence play in the terminal: two people share a seed, one plays as the host and one as
+// the guest, and every turn each sends the other the code it prints.
+//   cargo run --bin correspond -- <seed> <host|guest>
+use std::io::{self, BufRead, Write};
+
+use kaizo_quest::correspondence::Correspondence;
+use kaizo_quest::onion::OnionBattleState;
+use kaizo_quest::render::text::frame;
+use kaizo_quest::seed::Seed;
+use kaizo_quest::snapshot::Side;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let seed = match args.next().map(|arg| arg.parse::<Seed>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(error)) => return eprintln!("{}", error),
+        None => return eprintln!("usage: correspond <seed> <host|guest>"),
+    };
+    let side = match args.next().as_deref() {
+        Some("host") => Side::Player,
+        Some("guest") => Side::Enemy,
+        _ => return eprintln!("usage: correspond <seed> <host|guest>"),
+    };
+    let (world, mut game) = Correspondence::from_seed(seed, side);
+    let mine = match side {
+        Side::Player => &game.battle.player,
+        Side::Enemy => &game.battle.enemy,
+    };
+    println!("Seed: {}. You are {}.", seed, mine.name);
+    let mut lines = io::stdin().lock().lines();
+    let mut logs = Vec::new();
+
+    while game.state == OnionBattleState::InProcess {
+        println!("{}\n", frame(&game.battle, &logs));
+        let actions = match side {
+            Side::Player => game.battle.player.attributes.actions.clone(),
+            Side::Enemy => game.battle.enemy.attributes.actions.clone(),
+        };
+        for (index, action) in actions.iter().enumerate() {
+            println!("{}) {}", index + 1, world.actions[*action].name());
+        }
+        print!("Move? ");
+        let _ = io::stdout().flush();
+        let line = match lines.next() {
+            Some(Ok(line)) if line.trim() != "q" => line,
+            _ => return,
+        };
+        let action = match line.trim().parse::<usize>().ok().and_then(|slot| actions.get(slot.wrapping_sub(1))) {
+            Some(action) => *action,
+            None => continue,
+        };
+        let code = match game.choose(action, &world.actions) {
+            Ok((code, _)) => code,
+            Err(error) => return eprintln!("{}", error),
+        };
+        println!("Send this code: {}", code);
+
+        logs = loop {
+            print!("Their code? ");
+            let _ = io::stdout().flush();
+            let line = match lines.next() {
+                Some(Ok(line)) if line.trim() != "q" => line,
+                _ => return,
+            };
+            match game.import(&line, &world.actions) {
+                Ok(Some(logs)) => break logs,
+                Ok(None) => (),
+                Err(error) => println!("{}", error),
+            }
+        };
+    }
+    for log in logs {
+        println!("> {}", log);
+    }
+    let won = matches!((game.state, side), (OnionBattleState::Victory, Side::Player) | (OnionBattleState::Defeat, Side::Enemy));
+    println!("{}", if won { "You won!" } else { "You lost." });
+}
@@ -0,0 +1,102 @@
+// plays out lots of ai against ai battles at a few levels and prints how each species did as csv,
+// for balancing content:
+//   cargo run --bin simulate --no-default-features --features world -- [battles] [world.json]
+// with no world file a random one is generated; pass `--seed <seed>` to pick which
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+
+use rand::distributions::{Distribution, Standard};
+
+use kaizo_quest::ai::Greedy;
+use kaizo_quest::content::load_world;
+use kaizo_quest::core::Species;
+use kaizo_quest::onion::{Alignment, OnionBattle, OnionWorld};
+use kaizo_quest::seed::Seed;
+use kaizo_quest::sim::simulate;
+use kaizo_quest::snapshot::Side;
+
+// the levels battles are played at; both sides are always the same level
+static BRACKETS: [u32; 4] = [5, 15, 30, 50];
+static DEFAULT_BATTLES: u32 = 1000;
+
+// how one species did at one level
+#[derive(Default)]
+struct Record {
+    battles: u32,
+    wins: u32,
+    // the turns its wins took
+    turns: u32,
+}
+
+fn main() {
+    let mut battles = DEFAULT_BATTLES;
+    let mut path = None;
+    let mut seed = Seed::random();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            match args.next().map(|arg| arg.parse::<Seed>()) {
+                Some(Ok(parsed)) => seed = parsed,
+                Some(Err(error)) => return eprintln!("{}", error),
+                None => return eprintln!("--seed needs a seed"),
+            }
+        } else if let Ok(parsed) = arg.parse::<u32>() {
+            battles = parsed;
+        } else {
+            path = Some(arg);
+        }
+    }
+    // csv goes to stdout, so anything else goes to stderr
+    eprintln!("Seed: {}", seed);
+    let mut rng = seed.rng();
+    let world: OnionWorld = match path {
+        Some(path) => {
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(error) => return eprintln!("{}: {}", path, error),
+            };
+            match load_world(&text) {
+                Ok(world) => world,
+                Err(errors) => {
+                    for error in errors {
+                        eprintln!("{}", error);
+                    }
+                    return;
+                },
+            }
+        },
+        None => Standard.sample(&mut rng),
+    };
+
+    // generated worlds reuse names, so species are told apart by where they are in the dex
+    let dex = |species: &Arc<Species<Alignment>>| world.species().iter().position(|other| Arc::ptr_eq(other, species)).unwrap();
+    println!("level,dex,species,battles,wins,win_rate,turns_to_kill");
+    for level in BRACKETS {
+        let mut records: BTreeMap<usize, Record> = BTreeMap::new();
+        for _ in 0..battles {
+            let battle = OnionBattle::new(world.sample_at_level(level, &mut rng), world.sample_at_level(level, &mut rng));
+            let (player, enemy) = (dex(&battle.player.species), dex(&battle.enemy.species));
+            let result = simulate(battle, &Greedy, &Greedy, &world.actions, &mut rng);
+            for (index, side) in [(player, Side::Player), (enemy, Side::Enemy)] {
+                let record = records.entry(index).or_default();
+                record.battles += 1;
+                if result.winner == Some(side) {
+                    record.wins += 1;
+                    record.turns += result.turns;
+                }
+            }
+        }
+        for (index, record) in records {
+            // species that never won never killed anything, so they have no time to kill
+            let turns_to_kill = match record.wins {
+                0 => String::new(),
+                wins => format!("{:.2}", record.turns as f64 / wins as f64),
+            };
+            println!(
+                "{},{},{},{},{},{:.3},{}",
+                level, index, world.species()[index].name, record.battles, record.wins, record.wins as f64 / record.battles as f64, turns_to_kill
+            );
+        }
+    }
+}
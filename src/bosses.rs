@@ -0,0 +1,197 @@
+use rand::{Rng, RngCore};
+use rand::distributions::Distribution;
+use serde::{Deserialize, Serialize};
+
+use crate::config::boost_stats;
+use crate::core::{ActionId, Actions, Character, Name, States};
+use crate::encounters::EncounterKind;
+use crate::onion::{ActionKind, ActionPool, OnionBattle, OnionCharacter, OnionWorld, set_level};
+use crate::rules::BattleHook;
+
+// how much stronger a boss's stats are than another kaizo's at its level, in percent
+pub static BOSS_SCALING: u32 = 30;
+// a boss blocks the way every this many wins outside of the gauntlet
+pub static BOSS_EVERY_WINS: u32 = 10;
+// generated bosses change their moves once they're down to this percent of their health
+static GENERATED_PHASE: u32 = 50;
+
+// a change of moves partway through a boss battle, e.g. `{ "below": 50, "actions": [4, 7] }`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Phase {
+    // the percent of its max health the boss has to be under for the phase to start
+    pub below: u32,
+    // ids into the world's action pool
+    pub actions: Actions,
+}
+
+// a boss as a world lists it, e.g.
+// `{ "species": "Pebble", "actions": [0, 1], "phases": [{ "below": 50, "actions": [2, 3] }] }`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Boss {
+    pub species: Name,
+    // rolled like any kaizo's moves when there aren't any
+    #[serde(default)]
+    pub actions: Actions,
+    // in the order they start, highest health first
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+}
+
+pub fn boss_due(wins: u32) -> bool {
+    wins > 0 && wins.is_multiple_of(BOSS_EVERY_WINS)
+}
+
+// the move a boss always has, so it can't be outsped into the ground: a priority attack if the pool
+// has any, and otherwise defending, which always goes first. random worlds don't roll priority attacks
+pub fn priority_move<R: Rng + ?Sized>(actions: &ActionPool, rng: &mut R) -> Option<ActionId> {
+    let fast: Vec<ActionId> = actions.ids().filter(|id| actions[*id].priority() > 0).collect();
+    let attacks: Vec<ActionId> = fast.iter().copied().filter(|id| actions.kind(*id) != ActionKind::Defend).collect();
+    let pick = if attacks.is_empty() { fast } else { attacks };
+    (!pick.is_empty()).then(|| pick[rng.gen_range(0..pick.len())])
+}
+
+// makes sure a moveset has the move, in place of its last one when it's full
+fn with_move(mut actions: Actions, action: Option<ActionId>) -> Actions {
+    if let Some(action) = action.filter(|action| !actions.contains(action)) {
+        if actions.len() >= 4 {
+            actions.pop();
+        }
+        actions.push(action);
+    }
+    actions
+}
+
+// one of the world's own bosses if it lists any, otherwise a kaizo from the world with a second set
+// of moves for when it's hurt. either way it's stronger than its level and has a priority move
+pub fn generate<R: Rng + ?Sized>(world: &OnionWorld, level: u32, rng: &mut R) -> (OnionCharacter, Vec<Phase>) {
+    let listed = (!world.bosses.is_empty())
+        .then(|| &world.bosses[rng.gen_range(0..world.bosses.len())])
+        .and_then(|boss| Some((boss, world.species().iter().find(|species| species.name.to_lowercase() == boss.species.to_lowercase())?)));
+    let (mut boss, phases) = match listed {
+        Some((boss, species)) => {
+            let mut character = Character::from_shared_species(species.clone());
+            character.attributes.actions = if boss.actions.is_empty() { world.sample_actions(rng) } else { boss.actions.clone() };
+            (character, boss.phases.clone())
+        },
+        None => {
+            let character = world.sample(rng);
+            (character, vec![Phase { below: GENERATED_PHASE, actions: world.sample_actions(rng) }])
+        },
+    };
+    let fast = priority_move(&world.actions, rng);
+    boss.attributes.actions = with_move(boss.attributes.actions, fast);
+    let phases = phases.into_iter().map(|phase| Phase { actions: with_move(phase.actions, fast), ..phase }).collect();
+    boss.name = format!("Boss {}", boss.name).into();
+    set_level(&mut boss, level);
+    boost_stats(&mut boss, BOSS_SCALING);
+    (boss, phases)
+}
+
+// sets the battle up as a boss battle, with the boss's phases still to come
+pub fn start(battle: &mut OnionBattle, phases: Vec<Phase>) {
+    battle.kind = EncounterKind::Boss;
+    battle.phases = phases;
+}
+
+// swaps the boss's moves out once it's hurt enough for its next phase
+pub struct Phases;
+
+impl BattleHook for Phases {
+    fn name(&self) -> String { "Phases".to_string() }
+
+    fn on_turn_end(&self, battle: &mut OnionBattle, _: &mut dyn RngCore) -> States {
+        let enemy = &mut battle.enemy;
+        let percent = enemy.state.health.max(0) as u32 * 100 / enemy.attributes.stats.health.max(1);
+        match battle.phases.first() {
+            Some(phase) if percent < phase.below => {
+                let phase = battle.phases.remove(0);
+                enemy.attributes.actions = phase.actions;
+                vec![format!("{} is changing its tactics!", enemy.name)]
+            },
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod bosses_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::encounters::end_turn;
+    use crate::onion::testing;
+
+    #[test]
+    fn generate_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let (boss, phases) = generate(&world, 20, &mut rng);
+        let mut plain = Character::from_shared_species(boss.species.clone());
+        plain.attributes.nature = boss.attributes.nature;
+        plain.attributes.ivs = boss.attributes.ivs;
+        set_level(&mut plain, 20);
+        assert!(boss.name.starts_with("Boss "));
+        assert_eq!(boss.attributes.level, 20);
+        assert_eq!(boss.attributes.stats.health, plain.attributes.stats.health * (100 + BOSS_SCALING) / 100);
+        assert_eq!(boss.state.health, boss.attributes.stats.health as i32);
+        // it keeps its priority move through every phase
+        let fast = boss.attributes.actions.iter().find(|action| world.actions[**action].priority() > 0).unwrap();
+        assert_eq!(phases.len(), 1);
+        assert!(phases[0].actions.contains(fast));
+    }
+
+    #[test]
+    fn listed_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut world: OnionWorld = Standard.sample(&mut rng);
+        let species = world.species()[3].name.clone();
+        world.bosses = vec![Boss { species: species.clone(), actions: vec![0, 1], phases: vec![] }];
+        let (boss, phases) = generate(&world, 10, &mut rng);
+        assert_eq!(boss.species.name, species);
+        assert_eq!(&boss.attributes.actions[..2], &[0, 1]);
+        assert_eq!(boss.attributes.actions.len(), 3);
+        assert!(phases.is_empty());
+    }
+
+    #[test]
+    fn phases_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut battle = OnionBattle::new(testing::fake_character(), testing::fake_character());
+        battle.enemy.attributes.stats.health = 100;
+        battle.enemy.refresh();
+        battle.enemy.attributes.actions = vec![0];
+        start(&mut battle, vec![Phase { below: 50, actions: vec![1, 2] }, Phase { below: 20, actions: vec![3] }]);
+
+        battle.enemy.state.health = 50;
+        assert!(Phases.on_turn_end(&mut battle, &mut rng).is_empty());
+        battle.enemy.state.health = 10;
+        assert_eq!(Phases.on_turn_end(&mut battle, &mut rng), vec!["fake is changing its tactics!"]);
+        assert_eq!(battle.enemy.attributes.actions, vec![1, 2]);
+        // one phase a turn, even if it's already hurt enough for the next
+        assert_eq!(Phases.on_turn_end(&mut battle, &mut rng).len(), 1);
+        assert_eq!(battle.enemy.attributes.actions, vec![3]);
+        assert!(battle.phases.is_empty());
+
+        // bosses are played with it
+        let mut battle = OnionBattle::new(testing::fake_character(), testing::fake_character());
+        start(&mut battle, vec![Phase { below: 101, actions: vec![5] }]);
+        battle.player.attributes.stats.health = 40;
+        battle.player.refresh();
+        battle.enemy.attributes.stats.health = 40;
+        battle.enemy.refresh();
+        assert!(end_turn(&mut battle, &mut rng).1.contains(&"fake is changing its tactics!".to_string()));
+    }
+
+    #[test]
+    fn boss_due_test() {
+        assert!(!boss_due(0));
+        assert!(!boss_due(BOSS_EVERY_WINS - 1));
+        assert!(boss_due(BOSS_EVERY_WINS));
+        assert!(boss_due(BOSS_EVERY_WINS * 3));
+    }
+}
@@ -0,0 +1,141 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{ActionId, Character, NATURES, Stats};
+use crate::ivs;
+use crate::onion::{OnionCharacter, OnionWorld, set_level};
+use crate::roster::Member;
+use crate::team::MAX_ACTIONS;
+
+// kaizo have to be at least this grown to breed
+pub static BREEDING_LEVEL: u32 = 10;
+// the highest a gene can go. a hatchling's genes are added onto its stats, and are what it passes
+// on to its own eggs
+pub static MAX_GENE: u32 = 15;
+// how many battles have to be won for an egg to hatch
+pub static HATCH_BATTLES: u32 = 5;
+// how many eggs the roster can keep warm at once
+pub static MAX_EGGS: usize = 3;
+
+// a level 1 kaizo waiting to join the roster
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Egg {
+    pub character: OnionCharacter,
+    pub genes: Stats<u32>,
+    // won battles left until it hatches
+    pub battles: u32,
+}
+
+// there are no species lines, so kaizo are compatible when their species share an alignment
+pub fn compatible(first: &OnionCharacter, second: &OnionCharacter) -> bool {
+    let alignments = second.species.alignments();
+    first.attributes.level >= BREEDING_LEVEL && second.attributes.level >= BREEDING_LEVEL
+        && first.species.alignments().iter().any(|alignment| alignments.contains(alignment))
+}
+
+// an egg of either parent's species. it starts with moves like a wild kaizo's, plus one of its
+// parents' moves that its species would never learn on its own
+pub fn breed<R: Rng + ?Sized>(first: &Member, second: &Member, world: &OnionWorld, rng: &mut R) -> Option<Egg> {
+    if !compatible(&first.character, &second.character) {
+        return None;
+    }
+    let species = if rng.gen_bool(0.5) { &first.character.species } else { &second.character.species };
+    let mut character = Character::from_shared_species(species.clone());
+    character.attributes.actions = world.sample_actions(rng);
+    character.attributes.nature = *NATURES.choose(rng).unwrap();
+    character.attributes.ivs = ivs::roll(rng);
+    set_level(&mut character, 1);
+
+    let egg_moves: Vec<ActionId> = first.character.attributes.actions.iter()
+        .chain(&second.character.attributes.actions)
+        .filter(|action| !species.learnset.iter().any(|(_, learned)| learned == *action))
+        .filter(|action| !character.attributes.actions.contains(action))
+        .copied()
+        .collect();
+    if let Some(action) = egg_moves.choose(rng) {
+        character.attributes.actions.truncate(MAX_ACTIONS - 1);
+        character.attributes.actions.push(*action);
+    }
+
+    let genes = inherit(&first.genes, &second.genes, rng);
+    character.attributes.bonus = genes;
+    character.recalculate_stats();
+    character.refresh();
+    Some(Egg { character, genes, battles: HATCH_BATTLES })
+}
+
+// each gene comes from one of the parents half of the time, and is rolled fresh otherwise
+fn inherit<R: Rng + ?Sized>(first: &Stats<u32>, second: &Stats<u32>, rng: &mut R) -> Stats<u32> {
+    Vec::from(first).into_iter()
+        .zip(Vec::from(second))
+        .map(|(first, second)| if rng.gen_bool(0.5) {
+            *[first, second].choose(rng).unwrap()
+        } else {
+            rng.gen_range(0..=MAX_GENE)
+        })
+        .collect::<Vec<u32>>()
+        .into()
+}
+
+#[cfg(test)]
+mod breeding_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::core::SCALING_FACTOR;
+
+    use num_traits::identities::Zero;
+
+    fn parents(world: &OnionWorld, rng: &mut StdRng) -> (Member, Member) {
+        let character = world.sample_at_level(BREEDING_LEVEL, rng);
+        let mut second = character.clone();
+        second.attributes.actions = world.sample_actions(rng);
+        (Member { character, recruited: 0, genes: Stats::zero() }, Member { character: second, recruited: 1, genes: Stats::zero() })
+    }
+
+    #[test]
+    fn compatible_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let (mut first, second) = parents(&world, &mut rng);
+        assert!(compatible(&first.character, &second.character));
+
+        first.character.attributes.level = BREEDING_LEVEL - 1;
+        assert!(!compatible(&first.character, &second.character));
+        assert!(breed(&first, &second, &world, &mut rng).is_none());
+
+        let other = world.species().iter()
+            .find(|species| !species.alignments().iter().any(|alignment| second.character.species.alignments().contains(alignment)))
+            .unwrap();
+        let mut stranger = world.sample_at_level(BREEDING_LEVEL, &mut rng);
+        stranger.species = other.clone();
+        assert!(!compatible(&stranger, &second.character));
+    }
+
+    #[test]
+    fn breed_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let (mut first, second) = parents(&world, &mut rng);
+        first.genes = Stats::from_values(MAX_GENE, MAX_GENE, MAX_GENE, MAX_GENE, MAX_GENE, MAX_GENE);
+        for _ in 0..20 {
+            let egg = breed(&first, &second, &world, &mut rng).unwrap();
+            let character = &egg.character;
+            assert_eq!(character.attributes.level, 1);
+            assert_eq!(egg.battles, HATCH_BATTLES);
+            assert!(Vec::from(&egg.genes).iter().all(|gene| *gene <= MAX_GENE));
+            assert_eq!(character.attributes.bonus, egg.genes);
+            assert_eq!(character.attributes.stats, character.scaled_stats(SCALING_FACTOR));
+            assert_eq!(character.state.health, character.attributes.stats.health as i32);
+            assert!(character.attributes.actions.len() <= MAX_ACTIONS);
+            // the last move is the egg move whenever the parents had one to give
+            let last = *character.attributes.actions.last().unwrap();
+            let parental = first.character.attributes.actions.contains(&last) || second.character.attributes.actions.contains(&last);
+            assert!(!parental || !character.species.learnset.iter().any(|(_, learned)| *learned == last));
+        }
+    }
+}
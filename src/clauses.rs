@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::onion::{ActionKind, ActionPool, OnionCharacter};
+
+// competitive restrictions on teams and battles
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Clause {
+    // a team may only bring one stun move between all of its members
+    StunLimit,
+    // a character that is already stunned can't be stunned again
+    Stun,
+    // a team may only bring one of each species
+    Species,
+    // no one-hit KO moves on a team, and any that get into a battle fail
+    Ohko,
+}
+
+pub static CLAUSES: [Clause; 4] = [Clause::StunLimit, Clause::Stun, Clause::Species, Clause::Ohko];
+// what battles between two people are played under
+pub static PVP_CLAUSES: [Clause; 1] = [Clause::Ohko];
+
+impl Clause {
+    pub fn description(&self) -> String {
+        match self {
+            Clause::StunLimit => "Only one stun move per team.",
+            Clause::Stun => "Stunned kaizo can't be stunned again.",
+            Clause::Species => "Only one of each species per team.",
+            Clause::Ohko => "No one-hit KO moves.",
+        }.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClauseViolation {
+    pub clause: Clause,
+    pub message: String,
+}
+
+impl fmt::Display for ClauseViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} clause: {}", self.clause, self.message)
+    }
+}
+
+// checks a team before it is allowed into a battle; the battle-time clauses are enforced by the field
+pub fn validate_team(clauses: &[Clause], team: &[OnionCharacter], actions: &ActionPool) -> Result<(), Vec<ClauseViolation>> {
+    let mut violations = Vec::new();
+    for clause in clauses {
+        match clause {
+            Clause::StunLimit => {
+                let stuns = team.iter()
+                    .flat_map(|character| character.attributes.actions.iter())
+                    .filter(|action| actions.kind(**action) == ActionKind::Stun)
+                    .count();
+                if stuns > 1 {
+                    violations.push(ClauseViolation {
+                        clause: *clause,
+                        message: format!("the team has {} stun moves.", stuns),
+                    });
+                }
+            },
+            Clause::Species => {
+                let mut seen = BTreeSet::new();
+                team.iter()
+                    .filter(|character| !seen.insert(character.species.name.clone()))
+                    .for_each(|character| violations.push(ClauseViolation {
+                        clause: *clause,
+                        message: format!("{} is on the team more than once.", character.species.name),
+                    }));
+            },
+            Clause::Ohko => {
+                team.iter()
+                    .filter(|character| character.attributes.actions.iter().any(|action| actions.kind(*action) == ActionKind::Ohko))
+                    .for_each(|character| violations.push(ClauseViolation {
+                        clause: *clause,
+                        message: format!("{} knows a one-hit KO move.", character.name),
+                    }));
+            },
+            Clause::Stun => (),
+        }
+    }
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
+#[cfg(test)]
+mod clause_tests {
+    use super::*;
+
+    use crate::core::{ActionId, Character, Species};
+    use crate::onion::{Alignment, Attack, OhkoAttack, OnionAction, testing};
+
+    fn fake_character(species: &str, actions: Vec<ActionId>) -> OnionCharacter {
+        Character::from_species_and_actions(Species { name: species.into(), ..testing::fake_species_with_bst(400) }, actions)
+    }
+
+    fn fake_pool() -> ActionPool {
+        ActionPool::with_attacks(vec![Attack::new("fake", 10, Alignment::Rock, 0)])
+    }
+
+    fn stun(pool: &ActionPool) -> ActionId {
+        (0..).find(|id| pool.kind(*id) == ActionKind::Stun).unwrap()
+    }
+
+    #[test]
+    fn no_clauses_test() {
+        let pool = fake_pool();
+        let team = vec![fake_character("a", vec![stun(&pool)]), fake_character("a", vec![stun(&pool)])];
+        assert_eq!(validate_team(&[], &team, &pool), Ok(()));
+    }
+
+    #[test]
+    fn stun_limit_test() {
+        let pool = fake_pool();
+        let team = vec![fake_character("a", vec![0, stun(&pool)]), fake_character("b", vec![0])];
+        assert_eq!(validate_team(&[Clause::StunLimit], &team, &pool), Ok(()));
+
+        let team = vec![fake_character("a", vec![0, stun(&pool)]), fake_character("b", vec![stun(&pool)])];
+        let violations = validate_team(&[Clause::StunLimit], &team, &pool).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].clause, Clause::StunLimit);
+    }
+
+    #[test]
+    fn species_clause_test() {
+        let pool = fake_pool();
+        let team = vec![fake_character("a", vec![0]), fake_character("b", vec![0])];
+        assert_eq!(validate_team(&[Clause::Species], &team, &pool), Ok(()));
+
+        let team = vec![fake_character("a", vec![0]), fake_character("b", vec![0]), fake_character("a", vec![0])];
+        let violations = validate_team(&CLAUSES, &team, &pool).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].clause, Clause::Species);
+    }
+
+    #[test]
+    fn ohko_clause_test() {
+        let pool = fake_pool().with_actions(vec![OnionAction::Ohko(OhkoAttack::new("fissure", Alignment::Rock))]);
+        let ohko = pool.ids().find(|id| pool.kind(*id) == ActionKind::Ohko).unwrap();
+        let team = vec![fake_character("a", vec![0]), fake_character("b", vec![0, ohko])];
+        assert_eq!(validate_team(&[], &team, &pool), Ok(()));
+
+        let violations = validate_team(&[Clause::Ohko], &team, &pool).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].clause, Clause::Ohko);
+    }
+}
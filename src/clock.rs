@@ -0,0 +1,173 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Name, Species};
+use crate::onion::Alignment;
+
+// on a 24 hour clock, for a clock that follows real time
+static DUSK: u32 = 20;
+static DAWN: u32 = 6;
+
+static DEFAULT_LENGTH: u32 = 5;
+static DEFAULT_NIGHT_WEIGHT: u32 = 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TimeOfDay { Day, Night }
+
+impl TimeOfDay {
+    pub fn from_hour(hour: u32) -> TimeOfDay {
+        if (DAWN..DUSK).contains(&(hour % 24)) { TimeOfDay::Day } else { TimeOfDay::Night }
+    }
+
+    // the resource it's shown with
+    pub fn icon(&self) -> &'static str {
+        match self {
+            TimeOfDay::Day => "day",
+            TimeOfDay::Night => "night",
+        }
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TimeOfDay::Day => "Day",
+            TimeOfDay::Night => "Night",
+        })
+    }
+}
+
+fn default_length() -> u32 { DEFAULT_LENGTH }
+
+fn default_night_weight() -> u32 { DEFAULT_NIGHT_WEIGHT }
+
+// how a world's days go and what changes at night, e.g.
+// `{ "length": 3, "nocturnal": ["Moth"], "night_alignments": ["Scissors"] }`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DayCycle {
+    // how many battles a day lasts, and then a night; 0 if it's always day
+    #[serde(default = "default_length")]
+    pub length: u32,
+    // species that only come out at night
+    #[serde(default)]
+    pub nocturnal: Vec<Name>,
+    // species with any of these alignments are met `night_weight` times as often at night
+    #[serde(default)]
+    pub night_alignments: Vec<Alignment>,
+    #[serde(default = "default_night_weight")]
+    pub night_weight: u32,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        DayCycle { length: DEFAULT_LENGTH, nocturnal: Vec::new(), night_alignments: Vec::new(), night_weight: DEFAULT_NIGHT_WEIGHT }
+    }
+}
+
+impl DayCycle {
+    fn nocturnal(&self, species: &Species<Alignment>) -> bool {
+        self.nocturnal.iter().any(|name| name.to_lowercase() == species.name.to_lowercase())
+    }
+
+    // how often a species is met at that time, compared to one nothing changes for at night
+    pub fn weight(&self, species: &Species<Alignment>, time: TimeOfDay) -> u32 {
+        match time {
+            TimeOfDay::Day if self.nocturnal(species) => 0,
+            TimeOfDay::Day => 1,
+            TimeOfDay::Night if species.alignments().iter().any(|alignment| self.night_alignments.contains(alignment)) => self.night_weight,
+            TimeOfDay::Night => 1,
+        }
+    }
+
+    // the species that can be met at that time, each listed as many times as it's weighted. the
+    // same as `species` when nothing changes at night, so worlds without a cycle roll the same
+    pub fn table<'a>(&self, species: &'a [Arc<Species<Alignment>>], time: TimeOfDay) -> Vec<&'a Arc<Species<Alignment>>> {
+        let table: Vec<&Arc<Species<Alignment>>> = species.iter()
+            .flat_map(|species| std::iter::repeat_n(species, self.weight(species, time) as usize))
+            .collect();
+        // a world of only night creatures still has something to meet by day
+        if table.is_empty() { species.iter().collect() } else { table }
+    }
+}
+
+// how far a run's days have gone, counted in battles. a clock can follow real time instead, when
+// whoever's driving it can tell the time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Clock {
+    pub battles: u32,
+    #[serde(default)]
+    pub real_time: bool,
+}
+
+impl Clock {
+    // `hour` is the real hour, if it's known
+    pub fn time(&self, cycle: &DayCycle, hour: Option<u32>) -> TimeOfDay {
+        match hour.filter(|_| self.real_time) {
+            Some(hour) => TimeOfDay::from_hour(hour),
+            None if cycle.length > 0 && (self.battles / cycle.length) % 2 == 1 => TimeOfDay::Night,
+            None => TimeOfDay::Day,
+        }
+    }
+
+    // counts a battle, and says so when that turned day into night or back
+    pub fn tick(&mut self, cycle: &DayCycle) -> Option<String> {
+        let before = self.time(cycle, None);
+        self.battles += 1;
+        match self.time(cycle, None) {
+            _ if self.real_time => None,
+            time if time == before => None,
+            TimeOfDay::Night => Some("Night fell.".to_string()),
+            TimeOfDay::Day => Some("The sun came up.".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+
+    use crate::onion::testing;
+
+    fn species(name: &str, alignment: Alignment) -> Arc<Species<Alignment>> {
+        Arc::new(Species { name: name.into(), alignment, ..testing::fake_species_with_bst(300) })
+    }
+
+    #[test]
+    fn clock_test() {
+        let cycle = DayCycle { length: 2, ..DayCycle::default() };
+        let mut clock = Clock::default();
+        assert_eq!(clock.tick(&cycle), None);
+        assert_eq!(clock.tick(&cycle), Some("Night fell.".to_string()));
+        assert_eq!(clock.time(&cycle, None), TimeOfDay::Night);
+        // the real hour only counts once the clock follows it
+        assert_eq!(clock.time(&cycle, Some(12)), TimeOfDay::Night);
+        clock.tick(&cycle);
+        assert_eq!(clock.tick(&cycle), Some("The sun came up.".to_string()));
+
+        clock.real_time = true;
+        assert_eq!(clock.time(&cycle, Some(12)), TimeOfDay::Day);
+        assert_eq!(clock.time(&cycle, Some(23)), TimeOfDay::Night);
+        assert_eq!(clock.time(&cycle, Some(3)), TimeOfDay::Night);
+        assert_eq!(clock.tick(&cycle), None);
+
+        let endless = DayCycle { length: 0, ..DayCycle::default() };
+        assert_eq!(Clock { battles: 7, real_time: false }.time(&endless, None), TimeOfDay::Day);
+    }
+
+    #[test]
+    fn table_test() {
+        let all = vec![species("Pebble", Alignment::Rock), species("Moth", Alignment::Paper), species("Shears", Alignment::Scissors)];
+        let cycle = DayCycle { nocturnal: vec!["moth".into()], night_alignments: vec![Alignment::Scissors], ..DayCycle::default() };
+        let names = |time| cycle.table(&all, time).iter().map(|species| species.name.to_string()).collect::<Vec<_>>();
+        assert_eq!(names(TimeOfDay::Day), vec!["Pebble", "Shears"]);
+        assert_eq!(names(TimeOfDay::Night), vec!["Pebble", "Moth", "Shears", "Shears"]);
+
+        // without anything set, the table is just the species
+        assert_eq!(DayCycle::default().table(&all, TimeOfDay::Night).len(), all.len());
+        let owls = DayCycle { nocturnal: vec!["Pebble".into(), "Moth".into(), "Shears".into()], ..DayCycle::default() };
+        assert_eq!(owls.table(&all, TimeOfDay::Day).len(), all.len());
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{States, Stats};
+use crate::onion::{OnionCharacter, settle};
+use crate::roster::Roster;
+use crate::share::LEVEL_CAPS;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Preset { Casual, Classic, Kaizo }
+
+pub static PRESETS: [Preset; 3] = [Preset::Casual, Preset::Classic, Preset::Kaizo];
+
+// how much stronger enemies are on kaizo, in percent
+pub static KAIZO_ENEMY_BOOST: u32 = 10;
+
+impl Preset {
+    pub fn description(&self) -> String {
+        match self {
+            Preset::Casual => "Your party is healed after every battle, even the ones you lose.",
+            Preset::Classic => "Only resting heals your party, and losing a battle off the map starts you over with a new kaizo.",
+            Preset::Kaizo => "No fleeing, tougher enemies, and kaizo that faint are gone for good.",
+        }.to_string()
+    }
+}
+
+// the rules of the game itself, picked on the title screen and kept for the whole run; unlike a
+// ruleset these don't change how a battle plays out, only what happens around it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameConfig {
+    // losing a battle off the map keeps the party instead of starting over
+    pub heal_after_defeat: bool,
+    // the party is healed after every battle, instead of only when it rests
+    #[serde(default)]
+    pub heal_between_battles: bool,
+    pub flee: bool,
+    // in percent, on top of every enemy stat
+    pub enemy_boost: u32,
+    // kaizo that faint leave the roster once the battle is over
+    pub permadeath: bool,
+    // the highest level kaizo can reach by winning; experience past it turns into money
+    #[serde(default)]
+    pub level_cap: Option<u32>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig::preset(Preset::Classic)
+    }
+}
+
+impl GameConfig {
+    pub fn preset(preset: Preset) -> GameConfig {
+        match preset {
+            Preset::Casual => GameConfig { heal_after_defeat: true, heal_between_battles: true, flee: true, enemy_boost: 0, permadeath: false, level_cap: None },
+            Preset::Classic => GameConfig { heal_after_defeat: false, heal_between_battles: false, flee: true, enemy_boost: 0, permadeath: false, level_cap: None },
+            Preset::Kaizo => GameConfig { heal_after_defeat: false, heal_between_battles: false, flee: false, enemy_boost: KAIZO_ENEMY_BOOST, permadeath: true, level_cap: None },
+        }
+    }
+
+    // the preset this matches, if it hasn't been changed since, e.g. by toggling permadeath
+    pub fn matching(&self) -> Option<Preset> {
+        PRESETS.iter().copied().find(|preset| GameConfig::preset(*preset) == *self)
+    }
+
+    // raises every stat of an enemy that's about to be battled
+    pub fn boost(&self, enemy: &mut OnionCharacter) {
+        if self.enemy_boost == 0 {
+            return;
+        }
+        boost_stats(enemy, self.enemy_boost);
+    }
+
+    // the next cap on the title screen's list, wrapping back around to none
+    pub fn next_level_cap(&self) -> Option<u32> {
+        let index = LEVEL_CAPS.iter().position(|cap| *cap == self.level_cap).map_or(0, |index| index + 1);
+        LEVEL_CAPS[index % LEVEL_CAPS.len()]
+    }
+
+    // tidies the party up once a battle is over, healing it too on casual
+    pub fn after_battle(&self, roster: &mut Roster) {
+        if self.heal_between_battles {
+            roster.refresh();
+        } else {
+            roster.settle();
+        }
+    }
+
+    // the same for a lone kaizo
+    pub fn recover(&self, character: &mut OnionCharacter) {
+        if self.heal_between_battles {
+            character.refresh();
+        } else {
+            settle(character);
+        }
+    }
+
+    // takes the party members that fainted off the roster under permadeath
+    pub fn bury(&self, roster: &mut Roster) -> States {
+        if !self.permadeath {
+            return vec![];
+        }
+        roster.bury().iter().map(|character| format!("{} is gone for good.", character.name)).collect()
+    }
+}
+
+// raises every stat by `percent`, and heals up to the new max
+pub fn boost_stats(character: &mut OnionCharacter, percent: u32) {
+    let stats: Vec<u32> = (&character.attributes.stats).into();
+    character.attributes.bonus += Stats::from(stats.iter().map(|stat| stat * percent / 100).collect::<Vec<_>>());
+    character.recalculate_stats();
+    character.state.health = character.attributes.stats.health as i32;
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::OnionWorld;
+
+    #[test]
+    fn preset_test() {
+        assert_eq!(GameConfig::default().matching(), Some(Preset::Classic));
+        for preset in PRESETS {
+            assert_eq!(GameConfig::preset(preset).matching(), Some(preset));
+        }
+        let config = GameConfig { permadeath: true, ..GameConfig::preset(Preset::Casual) };
+        assert_eq!(config.matching(), None);
+        let config = GameConfig { level_cap: Some(20), ..GameConfig::default() };
+        assert_eq!(config.matching(), None);
+        assert_eq!(config.next_level_cap(), LEVEL_CAPS[2]);
+        assert_eq!(GameConfig { level_cap: LEVEL_CAPS[LEVEL_CAPS.len() - 1], ..config }.next_level_cap(), None);
+    }
+
+    #[test]
+    fn boost_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let enemy = world.sample_at_level(50, &mut rng);
+
+        let mut boosted = enemy.clone();
+        GameConfig::default().boost(&mut boosted);
+        assert_eq!(boosted, enemy);
+
+        GameConfig::preset(Preset::Kaizo).boost(&mut boosted);
+        assert_eq!(boosted.attributes.stats.attack, enemy.attributes.stats.attack * 110 / 100);
+        assert_eq!(boosted.state.health, boosted.attributes.stats.health as i32);
+        assert!(boosted.attributes.stats.health > enemy.attributes.stats.health);
+    }
+
+    #[test]
+    fn after_battle_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut roster = Roster::with_lead(world.sample_at_level(5, &mut rng));
+        let health = roster.lead().state.health;
+        let mut hurt = roster.lead().clone();
+        hurt.state.health = 1;
+        roster.set(0, hurt);
+
+        GameConfig::default().after_battle(&mut roster);
+        assert_eq!(roster.lead().state.health, 1);
+        GameConfig::preset(Preset::Casual).after_battle(&mut roster);
+        assert_eq!(roster.lead().state.health, health);
+    }
+
+    #[test]
+    fn bury_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut roster = Roster::with_lead(world.sample_at_level(5, &mut rng));
+        roster.recruit(world.sample_at_level(5, &mut rng));
+        let mut fainted = roster.lead().clone();
+        fainted.state.health = 0;
+        roster.set(0, fainted);
+
+        assert!(GameConfig::default().bury(&mut roster).is_empty());
+        assert_eq!(roster.party().len(), 2);
+        let logs = GameConfig::preset(Preset::Kaizo).bury(&mut roster);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(roster.party().len(), 1);
+    }
+}
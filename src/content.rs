@@ -0,0 +1,644 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::areas::Area;
+use crate::bosses::Boss;
+use crate::clock::DayCycle;
+use crate::core::{Action, BASE_ACCURACY, Category, DEFAULT_PP, Name, Species, Stats};
+use crate::abilities::default_abilities;
+use crate::items::{Effect, OnionItem, default_items};
+use crate::migrate::{SaveFile, Schema};
+use crate::onion::{ALIGNMENTS, ActionPool, Alignment, Attack, DEFAULT_DRAIN, DrainAttack, OnionAction, OnionWorld, Sacrifice, TypeChart};
+
+// a world as a modder writes it: the species, the attacks that go into the action pool (the
+// non-attack actions are always there, after the attacks) and optionally a type chart, the items
+// the bag can hold, how its days and nights go, the areas wild kaizo are met in, its bosses, and attacks that
+// drain the target's health or knock out the user, which come after the rest of the pool. last come any other actions, described by
+// their kind the way saves describe them. a species' learnset pairs levels with ids into that
+// pool, and its optional ability is an id into the world's abilities. the chart is square, a
+// row of effectiveness in tenths for each attacking alignment, and brings in as many alignments as
+// it has rows, in order from Rock, Paper, Scissors (up to all of `ALIGNMENTS`)
+//
+//     {
+//         "species": [
+//             { "name": "Pebble", "bst": 300, "alignment": "Rock", "secondary": "Paper",
+//               "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 },
+//               "learnset": [[10, 1]], "ability": 0 }
+//         ],
+//         "attacks": [{ "name": "Tackle", "power": 40, "alignment": "Rock" },
+//                     { "name": "Flash", "power": 40, "alignment": "Paper", "category": "Special" }],
+//         "chart": [[10, 5, 20], [20, 10, 5], [5, 20, 10]],
+//         "items": [{ "name": "Potion", "effect": { "Heal": 20 } }, { "name": "Revive", "effect": { "Revive": 50 } }],
+//         "cycle": { "length": 5, "nocturnal": ["Pebble"], "night_alignments": ["Paper"] },
+//         "areas": [{ "name": "Meadow", "levels": [2, 8], "encounters": [["Pebble", 10]] }],
+//         "bosses": [{ "species": "Pebble", "actions": [0], "phases": [{ "below": 50, "actions": [1] }] }],
+//         "drains": [{ "name": "Leech", "power": 30, "alignment": "Paper" }],
+//         "sacrifices": [{ "name": "Landslide", "power": 250, "alignment": "Rock" }],
+//         "actions": [{ "kind": "Stun", "name": "Hypnosis" }, { "kind": "Recoil", "name": "Ram", "power": 90, "alignment": "Rock", "recoil": 3 }]
+//     }
+pub mod id;
+pub mod pack;
+pub mod patch;
+pub mod remote;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorldDefinition {
+    pub species: Vec<Species<Alignment>>,
+    pub attacks: Vec<AttackDefinition>,
+    #[serde(default)]
+    pub chart: Option<Vec<Vec<u32>>>,
+    // the default items when there aren't any; left out when saved, so worlds from before there
+    // were items keep their ids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<OnionItem>>,
+    // days and nights, and what comes out at night; left out like the items when it's the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<DayCycle>,
+    // without any, every species is met everywhere
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub areas: Vec<Area>,
+    // without any, bosses are rolled from the species
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bosses: Vec<Boss>,
+    // left out when there aren't any, for the same reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drains: Vec<AttackDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sacrifices: Vec<AttackDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<OnionAction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttackDefinition {
+    pub name: String,
+    pub power: u32,
+    pub alignment: Alignment,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub high_crit: bool,
+    // a percentage
+    #[serde(default = "base_accuracy")]
+    pub accuracy: u32,
+    #[serde(default)]
+    pub category: Category,
+    // uses per battle
+    #[serde(default = "default_pp")]
+    pub pp: u32,
+}
+
+// untagged world files are all version 1
+pub static WORLD_SCHEMA: Schema = Schema { version: 1, migrations: &[] };
+
+fn base_accuracy() -> u32 { BASE_ACCURACY }
+
+fn default_pp() -> u32 { DEFAULT_PP }
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContentError {
+    // the text isn't a world definition at all
+    Parse { line: usize, column: usize, message: String },
+    NoSpecies,
+    NoAttacks,
+    UnnamedSpecies(usize),
+    UnnamedAttack(usize),
+    DuplicateSpecies(String),
+    DuplicateAttack(String),
+    UnnamedItem(usize),
+    DuplicateItem(String),
+    // items that heal or revive with nothing
+    InvalidItem(String),
+    // species whose bst is zero or whose stat split is negative or empty
+    InvalidStats(String),
+    // attacks that can never hit
+    InvalidAccuracy(String),
+    // attacks that can never be used
+    InvalidPp(String),
+    // species that learn actions that aren't in the pool
+    InvalidLearnset(String),
+    // species with an ability that isn't one of the world's
+    InvalidAbility(String),
+    // species and attacks with an alignment the chart doesn't have a row for
+    InvalidAlignment(String),
+    InvalidChart,
+    // nocturnal species that aren't in the world, or night alignments that aren't in the chart
+    InvalidCycle(String),
+    // areas with nothing to meet, levels the wrong way round or species that aren't in the world
+    InvalidArea(String),
+    // bosses of species that aren't in the world, with actions that aren't either, or with phases
+    // that start above full health
+    InvalidBoss(String),
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContentError::Parse { line, column, message } => write!(f, "Line {}, column {}: {}", line, column, message),
+            ContentError::NoSpecies => write!(f, "The world needs at least one species."),
+            ContentError::NoAttacks => write!(f, "The world needs at least one attack."),
+            ContentError::UnnamedSpecies(index) => write!(f, "Species #{} needs a name.", index + 1),
+            ContentError::UnnamedAttack(index) => write!(f, "Attack #{} needs a name.", index + 1),
+            ContentError::DuplicateSpecies(name) => write!(f, "There is more than one species called \"{}\".", name),
+            ContentError::DuplicateAttack(name) => write!(f, "There is more than one attack called \"{}\".", name),
+            ContentError::UnnamedItem(index) => write!(f, "Item #{} needs a name.", index + 1),
+            ContentError::DuplicateItem(name) => write!(f, "There is more than one item called \"{}\".", name),
+            ContentError::InvalidItem(name) => write!(f, "{} needs to heal at least 1 health, or revive with between 1% and 100%.", name),
+            ContentError::InvalidStats(name) => write!(f, "{} needs a positive BST and stats that are positive and add up to more than 0.", name),
+            ContentError::InvalidAccuracy(name) => write!(f, "{} needs an accuracy above 0.", name),
+            ContentError::InvalidPp(name) => write!(f, "{} needs at least 1 PP.", name),
+            ContentError::InvalidLearnset(name) => write!(f, "{} learns an action that isn't in the world.", name),
+            ContentError::InvalidAbility(name) => write!(f, "{} has an ability that isn't in the world.", name),
+            ContentError::InvalidAlignment(name) => write!(f, "{}'s alignment isn't in the type chart.", name),
+            ContentError::InvalidChart => write!(f, "The type chart needs as many columns as rows, and between 1 and {} of them.", ALIGNMENTS.len()),
+            ContentError::InvalidCycle(name) => write!(f, "The day cycle's {} isn't in the world.", name),
+            ContentError::InvalidArea(name) => write!(f, "{} needs species from the world with a weight above 0, and its lowest level first.", name),
+            ContentError::InvalidBoss(name) => write!(f, "The boss {} needs to be a species from the world, use actions from the world and change phases at 100% health or below.", name),
+        }
+    }
+}
+
+fn valid_stats(stats: &Stats<f64>) -> bool {
+    let stats: Vec<f64> = stats.into();
+    stats.iter().all(|stat| stat.is_finite() && *stat >= 0.0) && stats.iter().sum::<f64>() > 0.0
+}
+
+impl From<&Attack> for AttackDefinition {
+    fn from(attack: &Attack) -> Self {
+        AttackDefinition {
+            name: attack.name().to_string(),
+            power: attack.power(),
+            alignment: attack.alignment(),
+            priority: attack.priority(),
+            high_crit: attack.high_crit(),
+            accuracy: attack.accuracy(),
+            category: attack.category(),
+            pp: attack.pp(),
+        }
+    }
+}
+
+impl From<&AttackDefinition> for Attack {
+    fn from(attack: &AttackDefinition) -> Self {
+        let built = Attack::new(&attack.name, attack.power, attack.alignment, attack.priority)
+            .with_accuracy(attack.accuracy)
+            .with_category(attack.category)
+            .with_pp(attack.pp);
+        if attack.high_crit { built.with_high_crit() } else { built }
+    }
+}
+
+impl WorldDefinition {
+    // every attack the definition lists, whatever it does on top of the damage
+    fn all_attacks(&self) -> impl Iterator<Item = &AttackDefinition> {
+        self.attacks.iter().chain(&self.drains).chain(&self.sacrifices)
+    }
+
+    // the definition of an existing world, e.g. a generated one, so it can be edited or patched
+    // TODO: generated worlds pad their action pools and roll multi-hit, recoil, charging and
+    //       recharging attacks, which are lost here, like actions a definition listed by kind.
+    //       they'd have to go into `actions` without moving the drains and sacrifices' ids
+    pub fn from_world(world: &OnionWorld) -> WorldDefinition {
+        WorldDefinition {
+            species: world.species().iter().map(|species| species.as_ref().clone()).collect(),
+            attacks: world.actions.attacks().map(AttackDefinition::from).collect(),
+            chart: Some(world.chart().rows().to_vec()),
+            items: (world.items != default_items()).then(|| world.items.clone()),
+            cycle: (world.cycle != DayCycle::default()).then(|| world.cycle.clone()),
+            areas: world.areas.clone(),
+            bosses: world.bosses.clone(),
+            drains: world.actions.drains().map(|drain| AttackDefinition::from(drain.attack())).collect(),
+            sacrifices: world.actions.sacrifices().map(|sacrifice| AttackDefinition::from(sacrifice.attack())).collect(),
+            actions: vec![],
+        }
+    }
+
+    // either a definition as it is, or one tagged with `{ "version": 1, "payload": { ... } }`
+    pub fn from_json(text: &str) -> Result<WorldDefinition, ContentError> {
+        let parse_error = |error: serde_json::Error| ContentError::Parse {
+            line: error.line(),
+            column: error.column(),
+            message: error.to_string(),
+        };
+        let json: serde_json::Value = serde_json::from_str(text).map_err(parse_error)?;
+        match SaveFile::read(json) {
+            Ok(file) => WORLD_SCHEMA.migrate(file.version, file.payload)
+                .map_err(|error| ContentError::Parse { line: 0, column: 0, message: error.to_string() }),
+            // parsed again from the text so mistakes in it are pointed out by line
+            Err(_) => serde_json::from_str(text).map_err(parse_error),
+        }
+    }
+
+    // the same definition written as RON, e.g. `(species: [(name: "Pebble", ...)], attacks: [...])`
+    pub fn from_ron(text: &str) -> Result<WorldDefinition, ContentError> {
+        ron::from_str(text).map_err(|error| ContentError::Parse {
+            line: error.position.line,
+            column: error.position.col,
+            message: error.code.to_string(),
+        })
+    }
+
+    // every problem with the definition, so a modder can fix them all in one go
+    pub fn validate(&self) -> Result<(), Vec<ContentError>> {
+        let mut errors = Vec::new();
+        if self.species.is_empty() {
+            errors.push(ContentError::NoSpecies);
+        }
+        if self.attacks.is_empty() {
+            errors.push(ContentError::NoAttacks);
+        }
+        let actions = self.all_attacks().count() + self.actions.len() + ActionPool::with_attacks(Vec::new()).ids().len();
+        let mut seen = BTreeSet::new();
+        for (index, species) in self.species.iter().enumerate() {
+            if species.name.trim().is_empty() {
+                errors.push(ContentError::UnnamedSpecies(index));
+            } else if !seen.insert(species.name.to_lowercase()) {
+                errors.push(ContentError::DuplicateSpecies(species.name.to_string()));
+            }
+            if species.bst == 0 || !valid_stats(&species.stats) {
+                errors.push(ContentError::InvalidStats(species.name.to_string()));
+            }
+            if species.learnset.iter().any(|(_, action)| *action >= actions) {
+                errors.push(ContentError::InvalidLearnset(species.name.to_string()));
+            }
+            // TODO: definitions can't list their own abilities yet, so these are the default ones
+            if species.ability.is_some_and(|ability| ability >= default_abilities().len()) {
+                errors.push(ContentError::InvalidAbility(species.name.to_string()));
+            }
+        }
+        // names are compared loosely because team texts look them up that way
+        let mut seen = BTreeSet::new();
+        for (index, attack) in self.all_attacks().enumerate() {
+            if attack.name.trim().is_empty() {
+                errors.push(ContentError::UnnamedAttack(index));
+            } else if !seen.insert(attack.name.to_lowercase()) {
+                errors.push(ContentError::DuplicateAttack(attack.name.clone()));
+            }
+            if attack.accuracy == 0 {
+                errors.push(ContentError::InvalidAccuracy(attack.name.clone()));
+            }
+            if attack.pp == 0 {
+                errors.push(ContentError::InvalidPp(attack.name.clone()));
+            }
+        }
+        // actions listed by kind go by the same rules, numbered on from the attacks
+        let listed = self.all_attacks().count();
+        for (index, action) in self.actions.iter().map(OnionAction::action).enumerate() {
+            if action.name().trim().is_empty() {
+                errors.push(ContentError::UnnamedAttack(listed + index));
+            } else if !seen.insert(action.name().to_lowercase()) {
+                errors.push(ContentError::DuplicateAttack(action.name().to_string()));
+            }
+            if action.accuracy() == 0 {
+                errors.push(ContentError::InvalidAccuracy(action.name().to_string()));
+            }
+            if action.pp() == 0 {
+                errors.push(ContentError::InvalidPp(action.name().to_string()));
+            }
+        }
+        let mut seen = BTreeSet::new();
+        for (index, item) in self.items.iter().flatten().enumerate() {
+            if item.name.trim().is_empty() {
+                errors.push(ContentError::UnnamedItem(index));
+            } else if !seen.insert(item.name.to_lowercase()) {
+                errors.push(ContentError::DuplicateItem(item.name.to_string()));
+            }
+            if matches!(item.effect, Effect::Heal(0)) || matches!(item.effect, Effect::Revive(percent) if !(1..=100).contains(&percent)) {
+                errors.push(ContentError::InvalidItem(item.name.to_string()));
+            }
+        }
+        // without a chart the world has the default three alignments
+        let alignments = match &self.chart {
+            Some(chart) => match TypeChart::from_rows(chart.clone()) {
+                Some(chart) => chart.alignments(),
+                None => {
+                    errors.push(ContentError::InvalidChart);
+                    &ALIGNMENTS[..]
+                },
+            },
+            None => TypeChart::default().alignments(),
+        };
+        for species in &self.species {
+            if !species.alignments().iter().all(|alignment| alignments.contains(alignment)) {
+                errors.push(ContentError::InvalidAlignment(species.name.to_string()));
+            }
+        }
+        for attack in self.all_attacks() {
+            if !alignments.contains(&attack.alignment) {
+                errors.push(ContentError::InvalidAlignment(attack.name.clone()));
+            }
+        }
+        for action in &self.actions {
+            if action.alignment().is_some_and(|alignment| !alignments.contains(&alignment)) {
+                errors.push(ContentError::InvalidAlignment(action.action().name().to_string()));
+            }
+        }
+        if let Some(cycle) = &self.cycle {
+            for name in &cycle.nocturnal {
+                if !self.species.iter().any(|species| species.name.to_lowercase() == name.to_lowercase()) {
+                    errors.push(ContentError::InvalidCycle(name.to_string()));
+                }
+            }
+            for alignment in &cycle.night_alignments {
+                if !alignments.contains(alignment) {
+                    errors.push(ContentError::InvalidCycle(format!("{:?}", alignment)));
+                }
+            }
+        }
+        for area in &self.areas {
+            let known = |name: &Name| self.species.iter().any(|species| species.name.to_lowercase() == name.to_lowercase());
+            if area.levels.0 > area.levels.1
+                || area.encounters.iter().all(|(_, weight)| *weight == 0)
+                || !area.encounters.iter().all(|(name, _)| known(name)) {
+                errors.push(ContentError::InvalidArea(area.name.clone()));
+            }
+        }
+        for boss in &self.bosses {
+            let movesets = std::iter::once(&boss.actions).chain(boss.phases.iter().map(|phase| &phase.actions));
+            if !self.species.iter().any(|species| species.name.to_lowercase() == boss.species.to_lowercase())
+                || movesets.flatten().any(|action| *action >= actions)
+                || boss.phases.iter().any(|phase| phase.below > 100) {
+                errors.push(ContentError::InvalidBoss(boss.species.to_string()));
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    pub fn build(&self) -> Result<OnionWorld, Vec<ContentError>> {
+        self.validate()?;
+        let attacks = self.attacks.iter().map(Attack::from).collect();
+        let drains = self.drains.iter().map(|drain| DrainAttack::new(drain.into(), DEFAULT_DRAIN)).collect();
+        let sacrifices = self.sacrifices.iter().map(|sacrifice| Sacrifice::new(sacrifice.into())).collect();
+        let chart = match &self.chart {
+            Some(chart) => TypeChart::from_rows(chart.clone()).ok_or_else(|| vec![ContentError::InvalidChart])?,
+            None => TypeChart::default(),
+        };
+        let actions = ActionPool::with_attacks(attacks).with_specials(drains, sacrifices).with_actions(self.actions.clone());
+        let world = OnionWorld::new(self.species.clone(), actions, chart)
+            .with_cycle(self.cycle.clone().unwrap_or_default())
+            .with_areas(self.areas.clone())
+            .with_bosses(self.bosses.clone());
+        Ok(match &self.items {
+            Some(items) => world.with_items(items.clone()),
+            None => world,
+        })
+    }
+}
+
+pub fn load_world(text: &str) -> Result<OnionWorld, Vec<ContentError>> {
+    WorldDefinition::from_json(text).map_err(|error| vec![error])?.build()
+}
+
+#[cfg(test)]
+mod content_tests {
+    use super::*;
+
+    use crate::onion::ActionKind;
+
+    static WORLD: &str = r#"{
+        "species": [
+            { "name": "Pebble", "bst": 300, "alignment": "Rock",
+              "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 } },
+            { "name": "Scrap", "bst": 400, "alignment": "Paper",
+              "stats": { "health": 0.4, "attack": 0.2, "defense": 0.2, "special_attack": 0.2, "special_defense": 0.2, "speed": 0.2 },
+              "learnset": [[10, 1], [5, 2]] }
+        ],
+        "attacks": [
+            { "name": "Tackle", "power": 40, "alignment": "Rock" },
+            { "name": "Quick Fold", "power": 20, "alignment": "Paper", "priority": 1, "high_crit": true, "accuracy": 90,
+              "category": "Special" }
+        ]
+    }"#;
+
+    #[test]
+    fn load_world_test() {
+        let world = load_world(WORLD).unwrap();
+        assert_eq!(world.species().len(), 2);
+        assert_eq!(&*world.species()[1].name, "Scrap");
+        assert!(world.species()[0].learnset.is_empty());
+        // learned in level order, whatever order they're listed in
+        assert_eq!(world.species()[1].learned_between(0, 10), vec![2, 1]);
+        assert_eq!(world.species()[1].learned_between(5, 20), vec![1]);
+        assert_eq!(world.actions[0].name(), "Tackle");
+        assert_eq!(world.actions[1].priority(), 1);
+        assert_eq!(world.actions[0].priority(), 0);
+        assert!(world.actions[1].description().contains("High critical hit ratio."));
+        assert!(!world.actions[0].description().contains("High critical hit ratio."));
+        assert_eq!(world.actions[0].accuracy(), 100);
+        assert_eq!(world.actions[1].accuracy(), 90);
+        assert_eq!(world.actions[0].category(), Category::Physical);
+        assert_eq!(world.actions[1].category(), Category::Special);
+        assert!(world.actions[1].description().contains("90% accurate."));
+        assert_eq!(world.chart(), &TypeChart::default());
+    }
+
+    #[test]
+    fn chart_test() {
+        let text = WORLD.replacen("\"attacks\"", "\"chart\": [[10, 10, 10], [10, 10, 10], [10, 10, 10]], \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        assert_eq!(world.chart().effectiveness(Alignment::Rock, Alignment::Scissors), 10);
+
+        let text = WORLD.replacen("\"attacks\"", "\"chart\": [[10, 10, 10], [10, 10]], \"attacks\"", 1);
+        assert_eq!(load_world(&text).err(), Some(vec![ContentError::InvalidChart]));
+
+        // bigger charts bring in more alignments, with immunities
+        let mut rows = vec![vec![10; 6]; 6];
+        rows[0][5] = 0;
+        let mut definition = WorldDefinition::from_json(WORLD).unwrap();
+        definition.chart = Some(rows);
+        definition.species[0].alignment = Alignment::Fire;
+        let world = definition.build().unwrap();
+        assert_eq!(world.chart().alignments().len(), 6);
+        assert_eq!(world.chart().effectiveness(Alignment::Rock, Alignment::Fire), 0);
+
+        // and smaller ones leave some out
+        definition.chart = Some(vec![vec![10; 2]; 2]);
+        definition.species[0].alignment = Alignment::Scissors;
+        assert_eq!(definition.validate(), Err(vec![ContentError::InvalidAlignment(definition.species[0].name.to_string())]));
+        // secondary alignments too
+        definition.species[0].alignment = Alignment::Rock;
+        definition.species[0].secondary = Some(Alignment::Scissors);
+        assert_eq!(definition.validate(), Err(vec![ContentError::InvalidAlignment(definition.species[0].name.to_string())]));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let errors = load_world("{\n  \"species\": 3\n}").err().unwrap();
+        assert!(matches!(errors[..], [ContentError::Parse { line: 2, .. }]));
+
+        let errors = load_world(&WORLD.replace("\"power\": 40", "\"power\": 40, \"color\": \"grey\"")).err().unwrap();
+        assert!(errors[0].to_string().contains("color"));
+    }
+
+    #[test]
+    fn versioned_test() {
+        let tagged = format!("{{ \"version\": 1, \"payload\": {} }}", WORLD);
+        assert_eq!(WorldDefinition::from_json(&tagged), WorldDefinition::from_json(WORLD));
+        let newer = format!("{{ \"version\": {}, \"payload\": {} }}", WORLD_SCHEMA.version + 1, WORLD);
+        assert!(matches!(WorldDefinition::from_json(&newer), Err(ContentError::Parse { line: 0, .. })));
+    }
+
+    #[test]
+    fn validate_test() {
+        let mut definition = WorldDefinition::from_json(WORLD).unwrap();
+        assert_eq!(definition.validate(), Ok(()));
+
+        definition.species[1].name = "pebble".into();
+        definition.species[0].bst = 0;
+        definition.species[1].stats.attack = -1.0;
+        definition.attacks[1].name = " ".to_string();
+        definition.attacks[0].accuracy = 0;
+        definition.attacks[0].pp = 0;
+        definition.species[0].learnset.push((10, 100));
+        definition.species[0].ability = Some(100);
+        assert_eq!(definition.validate(), Err(vec![
+            ContentError::InvalidStats("Pebble".to_string()),
+            ContentError::InvalidLearnset("Pebble".to_string()),
+            ContentError::InvalidAbility("Pebble".to_string()),
+            ContentError::DuplicateSpecies("pebble".to_string()),
+            ContentError::InvalidStats("pebble".to_string()),
+            ContentError::InvalidAccuracy("Tackle".to_string()),
+            ContentError::InvalidPp("Tackle".to_string()),
+            ContentError::UnnamedAttack(1),
+        ]));
+
+        let definition = WorldDefinition { species: vec![], attacks: vec![], chart: None, items: None, cycle: None, areas: vec![], bosses: vec![], drains: vec![], sacrifices: vec![], actions: vec![] };
+        assert_eq!(definition.build().err(), Some(vec![ContentError::NoSpecies, ContentError::NoAttacks]));
+    }
+
+    #[test]
+    fn cycle_test() {
+        let world = load_world(WORLD).unwrap();
+        assert_eq!(world.cycle, DayCycle::default());
+        assert_eq!(WorldDefinition::from_world(&world).cycle, None);
+
+        let text = WORLD.replacen("\"attacks\"", "\"cycle\": { \"length\": 3, \"nocturnal\": [\"Scrap\"] }, \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        assert_eq!(world.cycle.length, 3);
+        assert_eq!(world.cycle.nocturnal, vec!["Scrap".into()]);
+        assert_eq!(WorldDefinition::from_world(&world).cycle, Some(world.cycle.clone()));
+
+        let text = WORLD.replacen("\"attacks\"", "\"cycle\": { \"nocturnal\": [\"Moth\"], \"night_alignments\": [\"Fire\"] }, \"attacks\"", 1);
+        assert_eq!(load_world(&text).err(), Some(vec![
+            ContentError::InvalidCycle("Moth".to_string()),
+            ContentError::InvalidCycle("Fire".to_string()),
+        ]));
+    }
+
+    #[test]
+    fn areas_test() {
+        let text = WORLD.replacen("\"attacks\"", "\"areas\": [{ \"name\": \"Meadow\", \"levels\": [2, 8], \"encounters\": [[\"pebble\", 3]] }], \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        assert_eq!(world.area(1).unwrap().weight(&world.species()[0]), 3);
+        assert_eq!(WorldDefinition::from_world(&world).areas, world.areas);
+
+        let text = WORLD.replacen("\"attacks\"", "\"areas\": [{ \"name\": \"Caves\", \"levels\": [8, 2], \"encounters\": [[\"Moth\", 3]] }], \"attacks\"", 1);
+        assert_eq!(load_world(&text).err(), Some(vec![ContentError::InvalidArea("Caves".to_string())]));
+    }
+
+    #[test]
+    fn bosses_test() {
+        let text = WORLD.replacen("\"attacks\"", "\"bosses\": [{ \"species\": \"pebble\", \"actions\": [0], \"phases\": [{ \"below\": 50, \"actions\": [1] }] }], \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        assert_eq!(world.bosses[0].phases[0].actions, vec![1]);
+        assert_eq!(WorldDefinition::from_world(&world).bosses, world.bosses);
+
+        let text = WORLD.replacen("\"attacks\"", "\"bosses\": [{ \"species\": \"Pebble\", \"phases\": [{ \"below\": 150, \"actions\": [1] }] }, { \"species\": \"Moth\" }], \"attacks\"", 1);
+        assert_eq!(load_world(&text).err(), Some(vec![
+            ContentError::InvalidBoss("Pebble".to_string()),
+            ContentError::InvalidBoss("Moth".to_string()),
+        ]));
+    }
+
+    #[test]
+    fn items_test() {
+        // worlds that don't list items get the default ones
+        let world = load_world(WORLD).unwrap();
+        assert_eq!(world.items, default_items());
+        assert_eq!(WorldDefinition::from_world(&world).items, None);
+
+        let text = WORLD.replacen("\"attacks\"", "\"items\": [{ \"name\": \"Elixir\", \"effect\": { \"Heal\": 50 } }], \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        assert_eq!(world.items, vec![OnionItem { name: "Elixir".into(), effect: Effect::Heal(50) }]);
+        assert_eq!(WorldDefinition::from_world(&world).items, Some(world.items.clone()));
+
+        let mut definition = WorldDefinition::from_json(WORLD).unwrap();
+        definition.items = Some(vec![
+            OnionItem { name: "Elixir".into(), effect: Effect::Heal(0) },
+            OnionItem { name: "elixir".into(), effect: Effect::Revive(150) },
+            OnionItem { name: "".into(), effect: Effect::Cure },
+        ]);
+        assert_eq!(definition.validate(), Err(vec![
+            ContentError::InvalidItem("Elixir".to_string()),
+            ContentError::DuplicateItem("elixir".to_string()),
+            ContentError::InvalidItem("elixir".to_string()),
+            ContentError::UnnamedItem(2),
+        ]));
+    }
+
+    #[test]
+    fn specials_test() {
+        let text = WORLD.replacen("\"attacks\"", "\"drains\": [{ \"name\": \"Leech\", \"power\": 30, \"alignment\": \"Paper\" }], \
+            \"sacrifices\": [{ \"name\": \"Landslide\", \"power\": 250, \"alignment\": \"Rock\" }], \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        // after the attacks and the fixed actions
+        let (leech, landslide) = (world.actions.ids().len() - 2, world.actions.ids().len() - 1);
+        assert_eq!(world.actions.kind(leech), ActionKind::Drain);
+        assert_eq!(world.actions[leech].name(), "Leech");
+        assert_eq!(world.actions.drains().next().unwrap().percent(), DEFAULT_DRAIN);
+        assert_eq!(world.actions.kind(landslide), ActionKind::Sacrifice);
+        let definition = WorldDefinition::from_world(&world);
+        let listed = WorldDefinition::from_json(&text).unwrap();
+        assert_eq!((definition.drains, definition.sacrifices), (listed.drains, listed.sacrifices));
+
+        // they're checked like any other attack, and can be learned
+        let mut definition = WorldDefinition::from_json(&text).unwrap();
+        definition.species[0].learnset.push((5, landslide));
+        assert_eq!(definition.validate(), Ok(()));
+        definition.sacrifices[0].name = "tackle".to_string();
+        definition.drains[0].alignment = Alignment::Fire;
+        assert_eq!(definition.validate(), Err(vec![
+            ContentError::DuplicateAttack("tackle".to_string()),
+            ContentError::InvalidAlignment("Leech".to_string()),
+        ]));
+    }
+
+    #[test]
+    fn actions_test() {
+        let text = WORLD.replacen("\"attacks\"", "\"actions\": [{ \"kind\": \"Stun\", \"name\": \"Hypnosis\" }, \
+            { \"kind\": \"Recoil\", \"name\": \"Ram\", \"power\": 90, \"alignment\": \"Rock\", \"recoil\": 3 }], \"attacks\"", 1);
+        let world = load_world(&text).unwrap();
+        // after everything else
+        let (hypnosis, ram) = (world.actions.ids().len() - 2, world.actions.ids().len() - 1);
+        assert_eq!((world.actions.kind(hypnosis), world.actions.kind(ram)), (ActionKind::Stun, ActionKind::Recoil));
+        assert_eq!(world.actions[ram].name(), "Ram");
+
+        // they're checked like the attacks
+        let mut definition = WorldDefinition::from_json(&text).unwrap();
+        definition.species[0].learnset.push((5, ram));
+        assert_eq!(definition.validate(), Ok(()));
+        definition.actions[1] = OnionAction::Attack(Attack::new("Tackle", 90, Alignment::Fire, 0).with_pp(0));
+        assert_eq!(definition.validate(), Err(vec![
+            ContentError::DuplicateAttack("Tackle".to_string()),
+            ContentError::InvalidPp("Tackle".to_string()),
+            ContentError::InvalidAlignment("Tackle".to_string()),
+        ]));
+    }
+
+    #[test]
+    fn from_world_test() {
+        let world = load_world(WORLD).unwrap();
+        let definition = WorldDefinition::from_world(&world);
+        assert_eq!(definition.species.len(), 2);
+        assert_eq!(definition.attacks, WorldDefinition::from_json(WORLD).unwrap().attacks);
+        assert_eq!(definition.chart, Some(TypeChart::default().rows().to_vec()));
+
+        let rebuilt = definition.build().unwrap();
+        assert_eq!(rebuilt.species(), world.species());
+        assert_eq!(WorldDefinition::from_world(&rebuilt), definition);
+    }
+}
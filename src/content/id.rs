@@ -0,0 +1,110 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::DayCycle;
+use crate::content::WorldDefinition;
+use crate::items::default_items;
+use crate::onion::{OnionWorld, TypeChart};
+
+// fnv-1a, since it's tiny and, unlike std's hashers, promised to give the same answer everywhere
+static FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+static FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+// names a world by its content, so results from two runs are only compared when they were played
+// with the same species, attacks and chart
+// TODO: this is a checksum, not a signature; anyone can claim any id for a result
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct WorldId(pub u64);
+
+impl WorldId {
+    pub fn of(world: &OnionWorld) -> WorldId {
+        WorldDefinition::from_world(world).id()
+    }
+}
+
+impl fmt::Display for WorldId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl WorldDefinition {
+    // the order species, attacks, items and an area's encounters are listed in doesn't change the id, and neither does
+    // leaving out the default chart, items or cycle. the chart's rows and columns do keep their order,
+    // since they line up with the alignments
+    pub fn id(&self) -> WorldId {
+        let mut canonical = self.clone();
+        canonical.species.sort_by_key(|species| species.name.to_lowercase());
+        canonical.attacks.sort_by_key(|attack| attack.name.to_lowercase());
+        canonical.drains.sort_by_key(|attack| attack.name.to_lowercase());
+        canonical.sacrifices.sort_by_key(|attack| attack.name.to_lowercase());
+        canonical.actions.sort_by_key(|action| action.action().name().to_lowercase());
+        canonical.chart.get_or_insert_with(|| TypeChart::default().rows().to_vec());
+        if canonical.items.as_ref() == Some(&default_items()) {
+            canonical.items = None;
+        }
+        if canonical.cycle == Some(DayCycle::default()) {
+            canonical.cycle = None;
+        }
+        for area in &mut canonical.areas {
+            area.encounters.sort_by_key(|(name, _)| name.to_lowercase());
+        }
+        if let Some(items) = &mut canonical.items {
+            items.sort_by_key(|item| item.name.to_lowercase());
+        }
+        // the definition is all plain data, so this can't fail
+        WorldId(fnv1a(&serde_json::to_vec(&canonical).unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod id_tests {
+    use super::*;
+
+    static WORLD: &str = r#"{
+        "species": [
+            { "name": "Pebble", "bst": 300, "alignment": "Rock",
+              "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 } },
+            { "name": "Scrap", "bst": 400, "alignment": "Paper",
+              "stats": { "health": 0.4, "attack": 0.2, "defense": 0.2, "special_attack": 0.2, "special_defense": 0.2, "speed": 0.2 } }
+        ],
+        "attacks": [
+            { "name": "Tackle", "power": 40, "alignment": "Rock" },
+            { "name": "Fold", "power": 20, "alignment": "Paper" }
+        ]
+    }"#;
+
+    #[test]
+    fn id_test() {
+        let definition = WorldDefinition::from_json(WORLD).unwrap();
+        let id = definition.id();
+        assert_eq!(id, WorldDefinition::from_json(WORLD).unwrap().id());
+        assert_eq!(id.to_string().len(), 16);
+
+        // reordering and spelling out the default chart are the same world
+        let mut reordered = definition.clone();
+        reordered.species.reverse();
+        reordered.attacks.reverse();
+        reordered.chart = Some(TypeChart::default().rows().to_vec());
+        reordered.items = Some(default_items());
+        assert_eq!(reordered.id(), id);
+        // and so is the world built from it
+        assert_eq!(WorldId::of(&definition.build().unwrap()), id);
+
+        // any change to the content isn't
+        let mut stronger = definition.clone();
+        stronger.attacks[0].power += 1;
+        assert_ne!(stronger.id(), id);
+        let mut fewer = definition.clone();
+        fewer.items = Some(default_items()[1..].to_vec());
+        assert_ne!(fewer.id(), id);
+        let mut swapped = definition;
+        swapped.chart = Some(TypeChart::default().inverse().rows().to_vec());
+        assert_ne!(swapped.id(), id);
+    }
+}
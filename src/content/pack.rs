@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "web")]
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::content::{ContentError, WorldDefinition};
+use crate::content::patch::{MergeConflict, WorldPatch, merge};
+use crate::core::ActionId;
+use crate::onion::{OnionCharacter, OnionWorld};
+use crate::team::Team;
+
+#[cfg(feature = "web")]
+static PACKS_KEY: &str = "kaizo-quest.packs";
+
+// an expansion laid over the base world: new species and attacks, or changes to the ones there.
+// packs are applied in order, so later packs win where they agree with earlier ones
+// TODO: packs can't add areas since there aren't any yet
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContentPack {
+    pub name: String,
+    pub enabled: bool,
+    pub patch: WorldPatch,
+}
+
+impl ContentPack {
+    pub fn from_json(name: &str, text: &str) -> Result<ContentPack, ContentError> {
+        Ok(ContentPack { name: name.to_string(), enabled: true, patch: WorldPatch::from_json(text)? })
+    }
+}
+
+// the enabled packs over the base, validated as a whole. conflicts are numbered by the packs'
+// places in `packs`, disabled ones included
+pub fn compose(base: &WorldDefinition, packs: &[ContentPack]) -> Result<WorldDefinition, Vec<MergeConflict>> {
+    let enabled: Vec<usize> = packs.iter().enumerate().filter(|(_, pack)| pack.enabled).map(|(index, _)| index).collect();
+    let patches: Vec<WorldPatch> = enabled.iter().map(|index| packs[*index].patch.clone()).collect();
+    merge(base, &patches).map_err(|conflicts| conflicts.into_iter()
+        .map(|conflict| conflict.renumber(|patch| enabled[patch]))
+        .collect())
+}
+
+pub fn compose_world(base: &OnionWorld, packs: &[ContentPack]) -> Result<OnionWorld, Vec<MergeConflict>> {
+    compose(&WorldDefinition::from_world(base), packs)?
+        .build()
+        .map_err(|errors| errors.into_iter().map(MergeConflict::Invalid).collect())
+}
+
+// where one world's species and actions are in another, matched by name, so characters and teams
+// made in the old world still mean the same thing in the new one
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdMap {
+    species: Vec<Option<usize>>,
+    actions: BTreeMap<ActionId, ActionId>,
+}
+
+impl IdMap {
+    pub fn between(old: &OnionWorld, new: &OnionWorld) -> IdMap {
+        let species = old.species().iter()
+            .map(|old| new.species().iter().position(|new| new.name.eq_ignore_ascii_case(&old.name)))
+            .collect();
+        let mut names = BTreeMap::new();
+        for id in new.actions.ids().rev() {
+            names.insert(new.actions[id].name().to_lowercase(), id);
+        }
+        let actions = old.actions.ids()
+            .filter_map(|id| Some((id, *names.get(&old.actions[id].name().to_lowercase())?)))
+            .collect();
+        IdMap { species, actions }
+    }
+
+    pub fn species(&self, old: usize) -> Option<usize> {
+        self.species.get(old).copied().flatten()
+    }
+
+    pub fn action(&self, old: ActionId) -> Option<ActionId> {
+        self.actions.get(&old).copied()
+    }
+
+    // moves that are gone are forgotten. the character takes on the new world's version of its
+    // species if there is one, and otherwise keeps its own copy
+    // TODO: stats aren't recalculated when a pack rebalances the species
+    pub fn remap_character(&self, new: &OnionWorld, character: &mut OnionCharacter) {
+        character.attributes.actions = character.attributes.actions.iter().filter_map(|action| self.action(*action)).collect();
+        if let Some(species) = new.species().iter().find(|species| species.name.eq_ignore_ascii_case(&character.species.name)) {
+            if character.name == character.species.name {
+                character.name = species.name.clone();
+            }
+            character.species = species.clone();
+        }
+    }
+
+    // members whose species is gone are dropped
+    pub fn remap_team(&self, team: &mut Team) {
+        team.members.retain_mut(|member| match self.species(member.species) {
+            Some(species) => {
+                member.species = species;
+                member.actions = member.actions.iter().filter_map(|action| self.action(*action)).collect();
+                true
+            },
+            None => false,
+        });
+    }
+}
+
+#[cfg(feature = "web")]
+pub fn load_packs() -> Vec<ContentPack> {
+    LocalStorage::get(PACKS_KEY).unwrap_or_default()
+}
+
+#[cfg(feature = "web")]
+pub fn store_packs(packs: &[ContentPack]) {
+    let _ = LocalStorage::set(PACKS_KEY, packs);
+}
+
+#[cfg(test)]
+mod pack_tests {
+    use super::*;
+
+    use crate::content::load_world;
+    use crate::content::patch::Entry;
+    use crate::team::TeamMember;
+
+    static BASE: &str = r#"{
+        "species": [
+            { "name": "Pebble", "bst": 300, "alignment": "Rock",
+              "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 } },
+            { "name": "Scrap", "bst": 400, "alignment": "Paper",
+              "stats": { "health": 0.4, "attack": 0.2, "defense": 0.2, "special_attack": 0.2, "special_defense": 0.2, "speed": 0.2 } }
+        ],
+        "attacks": [
+            { "name": "Tackle", "power": 40, "alignment": "Rock" },
+            { "name": "Fold", "power": 20, "alignment": "Paper" }
+        ]
+    }"#;
+
+    static EXPANSION: &str = r#"{
+        "species": [{ "name": "Shard", "bst": 500, "alignment": "Scissors",
+                      "stats": { "health": 0.2, "attack": 0.4, "defense": 0.2, "special_attack": 0.2, "special_defense": 0.2, "speed": 0.2 } }],
+        "attacks": [{ "name": "Snip", "power": 60, "alignment": "Scissors" }],
+        "remove_attacks": ["Fold"]
+    }"#;
+
+    fn base() -> OnionWorld {
+        load_world(BASE).unwrap()
+    }
+
+    fn action(world: &OnionWorld, name: &str) -> ActionId {
+        world.actions.ids().find(|id| world.actions[*id].name() == name).unwrap()
+    }
+
+    #[test]
+    fn compose_test() {
+        let base = base();
+        let expansion = ContentPack::from_json("Expansion", EXPANSION).unwrap();
+        let world = compose_world(&base, std::slice::from_ref(&expansion)).unwrap();
+        assert_eq!(world.species().len(), 3);
+        assert_eq!(world.actions.attacks().count(), 2);
+        assert_eq!(world.actions[1].name(), "Snip");
+
+        // disabled packs are skipped
+        let disabled = ContentPack { enabled: false, ..expansion.clone() };
+        let world = compose_world(&base, std::slice::from_ref(&disabled)).unwrap();
+        assert_eq!(world.species(), base.species());
+
+        // and conflicts are numbered by where the pack is in the list
+        let other = ContentPack::from_json("Other", r#"{ "remove_species": ["Ghost"] }"#).unwrap();
+        assert_eq!(compose_world(&base, &[disabled, expansion, other]).err(), Some(vec![
+            MergeConflict::Missing { entry: Entry::Species, name: "Ghost".to_string(), patch: 2 },
+        ]));
+    }
+
+    #[test]
+    fn remap_test() {
+        let old = base();
+        let new = compose_world(&old, &[ContentPack::from_json("Expansion", EXPANSION).unwrap()]).unwrap();
+        let map = IdMap::between(&old, &new);
+        assert_eq!(map.species(0), Some(0));
+        assert_eq!(map.action(action(&old, "Tackle")), Some(action(&new, "Tackle")));
+        assert_eq!(map.action(action(&old, "Fold")), None);
+        // the built in actions are matched up too
+        assert_eq!(map.action(action(&old, "Block")), Some(action(&new, "Block")));
+
+        let mut character = OnionCharacter::from_species(old.species()[1].as_ref().clone());
+        character.attributes.actions = vec![action(&old, "Fold"), action(&old, "Block")];
+        map.remap_character(&new, &mut character);
+        assert_eq!(character.attributes.actions, vec![action(&new, "Block")]);
+        assert!(std::sync::Arc::ptr_eq(&character.species, &new.species()[1]));
+
+        let mut team = Team {
+            name: "Team".to_string(),
+            members: vec![TeamMember { species: 1, level: 5, actions: vec![action(&old, "Tackle")] }],
+        };
+        map.remap_team(&mut team);
+        assert_eq!(team.members[0].actions, vec![action(&new, "Tackle")]);
+
+        // species that are gone take their members with them
+        let smaller = compose_world(&new, &[ContentPack::from_json("Cut", r#"{ "remove_species": ["Scrap"] }"#).unwrap()]).unwrap();
+        IdMap::between(&new, &smaller).remap_team(&mut team);
+        assert!(team.members.is_empty());
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::content::{AttackDefinition, ContentError, WorldDefinition};
+use crate::core::Species;
+use crate::onion::{Alignment, TypeChart};
+
+// a "patch mod": changes laid over a base world (an official one, or one exported from a generated
+// world) so a mod doesn't have to copy all of it. entries are matched by name, loosely, the same
+// way validation compares them
+// TODO: items can't be patched yet; a mod that changes them has to be a whole world
+//
+//     {
+//         "species": [{ "name": "Pebble", "bst": 350, "alignment": "Rock",
+//                       "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 } }],
+//         "remove_attacks": ["Quick Fold"]
+//     }
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorldPatch {
+    // added to the world, or replacing the entry with the same name
+    #[serde(default)]
+    pub species: Vec<Species<Alignment>>,
+    #[serde(default)]
+    pub attacks: Vec<AttackDefinition>,
+    #[serde(default)]
+    pub remove_species: Vec<String>,
+    #[serde(default)]
+    pub remove_attacks: Vec<String>,
+    #[serde(default)]
+    pub chart: Option<Vec<Vec<u32>>>,
+}
+
+impl WorldPatch {
+    pub fn from_json(text: &str) -> Result<WorldPatch, ContentError> {
+        serde_json::from_str(text).map_err(|error| ContentError::Parse {
+            line: error.line(),
+            column: error.column(),
+            message: error.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Entry { Species, Attack }
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Entry::Species => write!(f, "species"),
+            Entry::Attack => write!(f, "attack"),
+        }
+    }
+}
+
+trait Named {
+    fn name(&self) -> &str;
+
+    fn key(&self) -> String { self.name().to_lowercase() }
+}
+
+impl Named for Species<Alignment> {
+    fn name(&self) -> &str { &self.name }
+}
+
+impl Named for AttackDefinition {
+    fn name(&self) -> &str { &self.name }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change<T> {
+    Added(T),
+    Removed(T),
+    Changed { before: T, after: T },
+}
+
+// what it takes to get from one world to another
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorldDiff {
+    pub species: Vec<Change<Species<Alignment>>>,
+    pub attacks: Vec<Change<AttackDefinition>>,
+    pub chart: Option<Change<Vec<Vec<u32>>>>,
+}
+
+fn diff_entries<T: Named + Clone + PartialEq>(before: &[T], after: &[T]) -> Vec<Change<T>> {
+    let mut changes = Vec::new();
+    for old in before {
+        match after.iter().find(|new| new.key() == old.key()) {
+            Some(new) if new != old => changes.push(Change::Changed { before: old.clone(), after: new.clone() }),
+            Some(_) => (),
+            None => changes.push(Change::Removed(old.clone())),
+        }
+    }
+    for new in after.iter().filter(|new| !before.iter().any(|old| old.key() == new.key())) {
+        changes.push(Change::Added(new.clone()));
+    }
+    changes
+}
+
+fn chart_rows(definition: &WorldDefinition) -> Vec<Vec<u32>> {
+    definition.chart.clone().unwrap_or_else(|| TypeChart::default().rows().to_vec())
+}
+
+// removals and changes come in the order of `before`, then additions in the order of `after`
+pub fn diff(before: &WorldDefinition, after: &WorldDefinition) -> WorldDiff {
+    let (old_chart, new_chart) = (chart_rows(before), chart_rows(after));
+    WorldDiff {
+        species: diff_entries(&before.species, &after.species),
+        attacks: diff_entries(&before.attacks, &after.attacks),
+        chart: if old_chart == new_chart { None } else { Some(Change::Changed { before: old_chart, after: new_chart }) },
+    }
+}
+
+impl WorldDiff {
+    pub fn is_empty(&self) -> bool {
+        self.species.is_empty() && self.attacks.is_empty() && self.chart.is_none()
+    }
+
+    // the patch that makes the same changes, to share them as a mod
+    pub fn to_patch(&self) -> WorldPatch {
+        fn split<T: Named + Clone>(changes: &[Change<T>]) -> (Vec<T>, Vec<String>) {
+            let mut set = Vec::new();
+            let mut removed = Vec::new();
+            for change in changes {
+                match change {
+                    Change::Added(entry) | Change::Changed { after: entry, .. } => set.push(entry.clone()),
+                    Change::Removed(entry) => removed.push(entry.name().to_string()),
+                }
+            }
+            (set, removed)
+        }
+        let (species, remove_species) = split(&self.species);
+        let (attacks, remove_attacks) = split(&self.attacks);
+        WorldPatch {
+            species,
+            attacks,
+            remove_species,
+            remove_attacks,
+            chart: match &self.chart {
+                Some(Change::Changed { after, .. } | Change::Added(after)) => Some(after.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+// one line per change, for showing in the editor
+impl fmt::Display for WorldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_changes<T: Named>(f: &mut fmt::Formatter, entry: Entry, changes: &[Change<T>]) -> fmt::Result {
+            for change in changes {
+                match change {
+                    Change::Added(new) => writeln!(f, "+ {} {}", entry, new.name())?,
+                    Change::Removed(old) => writeln!(f, "- {} {}", entry, old.name())?,
+                    Change::Changed { after, .. } => writeln!(f, "~ {} {}", entry, after.name())?,
+                }
+            }
+            Ok(())
+        }
+        write_changes(f, Entry::Species, &self.species)?;
+        write_changes(f, Entry::Attack, &self.attacks)?;
+        if self.chart.is_some() {
+            writeln!(f, "~ type chart")?;
+        }
+        Ok(())
+    }
+}
+
+// patches are numbered from 0 in the order they were given to `merge`
+#[derive(Clone, Debug, PartialEq)]
+pub enum MergeConflict {
+    // a patch removes something the world doesn't have
+    Missing { entry: Entry, name: String, patch: usize },
+    // a patch both removes and sets the same entry
+    RemovedAndSet { entry: Entry, name: String, patch: usize },
+    // two patches leave the same entry different ways
+    Overlap { entry: Entry, name: String, first: usize, second: usize },
+    ChartOverlap { first: usize, second: usize },
+    // the merged world isn't a valid world
+    Invalid(ContentError),
+}
+
+impl MergeConflict {
+    // for callers that only merged some of their patches
+    pub fn renumber(self, number: impl Fn(usize) -> usize) -> MergeConflict {
+        match self {
+            MergeConflict::Missing { entry, name, patch } => MergeConflict::Missing { entry, name, patch: number(patch) },
+            MergeConflict::RemovedAndSet { entry, name, patch } => MergeConflict::RemovedAndSet { entry, name, patch: number(patch) },
+            MergeConflict::Overlap { entry, name, first, second } =>
+                MergeConflict::Overlap { entry, name, first: number(first), second: number(second) },
+            MergeConflict::ChartOverlap { first, second } => MergeConflict::ChartOverlap { first: number(first), second: number(second) },
+            MergeConflict::Invalid(error) => MergeConflict::Invalid(error),
+        }
+    }
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeConflict::Missing { entry, name, patch } =>
+                write!(f, "Patch #{} removes the {} \"{}\", which isn't in the world.", patch + 1, entry, name),
+            MergeConflict::RemovedAndSet { entry, name, patch } =>
+                write!(f, "Patch #{} both removes and changes the {} \"{}\".", patch + 1, entry, name),
+            MergeConflict::Overlap { entry, name, first, second } =>
+                write!(f, "Patches #{} and #{} change the {} \"{}\" differently.", first + 1, second + 1, entry, name),
+            MergeConflict::ChartOverlap { first, second } =>
+                write!(f, "Patches #{} and #{} change the type chart differently.", first + 1, second + 1),
+            MergeConflict::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// what each patch left each entry as so far (None for removed), to catch patches that disagree
+type Touched<T> = BTreeMap<String, (usize, Option<T>)>;
+
+fn touch<T: PartialEq>(touched: &mut Touched<T>, entry: Entry, name: &str, patch: usize, result: Option<T>, conflicts: &mut Vec<MergeConflict>) {
+    let key = name.to_lowercase();
+    match touched.get(&key) {
+        Some((first, earlier)) if *first != patch && *earlier != result => conflicts.push(MergeConflict::Overlap {
+            entry,
+            name: name.to_string(),
+            first: *first,
+            second: patch,
+        }),
+        _ => { touched.insert(key, (patch, result)); },
+    }
+}
+
+fn apply<T: Named + Clone + PartialEq>(
+    entries: &mut Vec<T>,
+    entry: Entry,
+    patch: usize,
+    set: &[T],
+    remove: &[String],
+    touched: &mut Touched<T>,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    for name in remove {
+        let key = name.to_lowercase();
+        if set.iter().any(|new| new.key() == key) {
+            conflicts.push(MergeConflict::RemovedAndSet { entry, name: name.clone(), patch });
+        } else if let Some(index) = entries.iter().position(|old| old.key() == key) {
+            entries.remove(index);
+            touch(touched, entry, name, patch, None, conflicts);
+        } else if !matches!(touched.get(&key), Some((_, None))) {
+            conflicts.push(MergeConflict::Missing { entry, name: name.clone(), patch });
+        }
+    }
+    for new in set {
+        match entries.iter_mut().find(|old| old.key() == new.key()) {
+            Some(old) => *old = new.clone(),
+            None => entries.push(new.clone()),
+        }
+        touch(touched, entry, new.name(), patch, Some(new.clone()), conflicts);
+    }
+}
+
+// lays the patches over the base in order. patches that only agree with each other are fine;
+// everything that conflicts is reported, along with anything wrong with the merged world
+pub fn merge(base: &WorldDefinition, patches: &[WorldPatch]) -> Result<WorldDefinition, Vec<MergeConflict>> {
+    let mut world = base.clone();
+    let mut conflicts = Vec::new();
+    let mut species = Touched::new();
+    let mut attacks = Touched::new();
+    let mut chart: Option<(usize, &Vec<Vec<u32>>)> = None;
+    for (index, patch) in patches.iter().enumerate() {
+        apply(&mut world.species, Entry::Species, index, &patch.species, &patch.remove_species, &mut species, &mut conflicts);
+        apply(&mut world.attacks, Entry::Attack, index, &patch.attacks, &patch.remove_attacks, &mut attacks, &mut conflicts);
+        if let Some(rows) = &patch.chart {
+            match chart {
+                Some((first, earlier)) if earlier != rows => conflicts.push(MergeConflict::ChartOverlap { first, second: index }),
+                _ => chart = Some((index, rows)),
+            }
+            world.chart = Some(rows.clone());
+        }
+    }
+    if let Err(errors) = world.validate() {
+        conflicts.extend(errors.into_iter().map(MergeConflict::Invalid));
+    }
+    if conflicts.is_empty() { Ok(world) } else { Err(conflicts) }
+}
+
+#[cfg(test)]
+mod patch_tests {
+    use super::*;
+
+    use crate::core::Category;
+    use crate::onion::testing;
+
+    fn species(name: &str, bst: u32) -> Species<Alignment> {
+        Species { name: name.into(), ..testing::fake_species_with_bst(bst) }
+    }
+
+    fn attack(name: &str, power: u32) -> AttackDefinition {
+        AttackDefinition { name: name.to_string(), power, alignment: Alignment::Rock, priority: 0, high_crit: false, accuracy: 100, category: Category::Physical, pp: 20 }
+    }
+
+    fn base() -> WorldDefinition {
+        WorldDefinition {
+            species: vec![species("Pebble", 300), species("Scrap", 400)],
+            attacks: vec![attack("Tackle", 40), attack("Fold", 20)],
+            chart: None,
+            items: None,
+            cycle: None,
+            areas: vec![],
+            bosses: vec![],
+            drains: vec![],
+            sacrifices: vec![],
+            actions: vec![],
+        }
+    }
+
+    #[test]
+    fn diff_test() {
+        let before = base();
+        assert!(diff(&before, &before).is_empty());
+
+        let mut after = base();
+        after.species[0].bst = 350;
+        after.species.push(species("Shard", 500));
+        after.attacks.remove(1);
+        after.chart = Some(TypeChart::default().inverse().rows().to_vec());
+        let changes = diff(&before, &after);
+        assert_eq!(changes.species, vec![
+            Change::Changed { before: species("Pebble", 300), after: species("Pebble", 350) },
+            Change::Added(species("Shard", 500)),
+        ]);
+        assert_eq!(changes.attacks, vec![Change::Removed(attack("Fold", 20))]);
+        assert!(changes.chart.is_some());
+        assert_eq!(changes.to_string(), "~ species Pebble\n+ species Shard\n- attack Fold\n~ type chart\n");
+
+        // a default chart written out is the same chart
+        after.chart = Some(TypeChart::default().rows().to_vec());
+        assert_eq!(diff(&before, &after).chart, None);
+    }
+
+    #[test]
+    fn merge_test() {
+        let patch = WorldPatch::from_json(r#"{
+            "species": [{ "name": "pebble", "bst": 350, "alignment": "Rock",
+                          "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 } }],
+            "attacks": [{ "name": "Slam", "power": 80, "alignment": "Rock" }],
+            "remove_attacks": ["FOLD"]
+        }"#).unwrap();
+        let merged = merge(&base(), &[patch]).unwrap();
+        assert_eq!(merged.species, vec![species("pebble", 350), species("Scrap", 400)]);
+        assert_eq!(merged.attacks, vec![attack("Tackle", 40), attack("Slam", 80)]);
+        assert_eq!(merge(&base(), &[]), Ok(base()));
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut after = base();
+        after.species.remove(0);
+        after.attacks[0].power = 60;
+        after.attacks.push(attack("Slam", 80));
+        let patch = diff(&base(), &after).to_patch();
+        assert_eq!(merge(&base(), &[patch]), Ok(after));
+    }
+
+    #[test]
+    fn conflict_test() {
+        let buff = WorldPatch { species: vec![species("Scrap", 450)], ..WorldPatch::default() };
+        let nerf = WorldPatch { species: vec![species("Scrap", 350)], ..WorldPatch::default() };
+        let remove = WorldPatch { remove_species: vec!["Scrap".to_string()], ..WorldPatch::default() };
+        assert_eq!(merge(&base(), &[buff.clone(), nerf.clone()]), Err(vec![
+            MergeConflict::Overlap { entry: Entry::Species, name: "Scrap".to_string(), first: 0, second: 1 },
+        ]));
+        assert_eq!(merge(&base(), &[buff.clone(), remove.clone()]).err().map(|conflicts| conflicts.len()), Some(1));
+        // patches that agree don't conflict, even removals
+        assert!(merge(&base(), &[buff.clone(), buff]).is_ok());
+        assert!(merge(&base(), &[remove.clone(), remove]).is_ok());
+
+        let patch = WorldPatch {
+            species: vec![species("Pebble", 0)],
+            remove_species: vec!["Pebble".to_string(), "Ghost".to_string()],
+            remove_attacks: vec!["Tackle".to_string(), "Fold".to_string()],
+            ..WorldPatch::default()
+        };
+        let conflicts = merge(&base(), &[patch]).err().unwrap();
+        assert_eq!(conflicts, vec![
+            MergeConflict::RemovedAndSet { entry: Entry::Species, name: "Pebble".to_string(), patch: 0 },
+            MergeConflict::Missing { entry: Entry::Species, name: "Ghost".to_string(), patch: 0 },
+            MergeConflict::Invalid(ContentError::NoAttacks),
+            MergeConflict::Invalid(ContentError::InvalidStats("Pebble".to_string())),
+        ]);
+        assert_eq!(conflicts[1].to_string(), "Patch #1 removes the species \"Ghost\", which isn't in the world.");
+
+        let inverse = WorldPatch { chart: Some(TypeChart::default().inverse().rows().to_vec()), ..WorldPatch::default() };
+        let flat = WorldPatch { chart: Some(vec![vec![10; 3]; 3]), ..WorldPatch::default() };
+        assert_eq!(merge(&base(), &[inverse, flat]), Err(vec![MergeConflict::ChartOverlap { first: 0, second: 1 }]));
+    }
+}
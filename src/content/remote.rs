@@ -0,0 +1,93 @@
+#[cfg(feature = "web")]
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::content::{ContentError, WorldDefinition};
+#[cfg(feature = "web")]
+use crate::net::fetch_text;
+use crate::onion::OnionWorld;
+
+#[cfg(feature = "web")]
+static REMOTE_WORLD_KEY: &str = "kaizo-quest.world";
+
+// a world someone shared online. it's kept once it's downloaded, so new runs can start in it
+// without fetching it again
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RemoteWorld {
+    pub url: String,
+    pub definition: WorldDefinition,
+}
+
+impl RemoteWorld {
+    // urls ending in .ron are read as RON and everything else as JSON
+    pub fn parse(url: &str, text: &str) -> Result<RemoteWorld, Vec<ContentError>> {
+        let url = url.trim();
+        let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+        let definition = if path.ends_with(".ron") {
+            WorldDefinition::from_ron(text)
+        } else {
+            WorldDefinition::from_json(text)
+        }.map_err(|error| vec![error])?;
+        definition.validate()?;
+        Ok(RemoteWorld { url: url.to_string(), definition })
+    }
+
+    pub fn build(&self) -> Result<OnionWorld, Vec<ContentError>> {
+        self.definition.build()
+    }
+}
+
+#[cfg(feature = "web")]
+pub async fn fetch_world(url: &str) -> Result<RemoteWorld, Vec<String>> {
+    let text = fetch_text(url.trim()).await.map_err(|error| vec![error])?;
+    RemoteWorld::parse(url, &text).map_err(|errors| errors.iter().map(|error| error.to_string()).collect())
+}
+
+#[cfg(feature = "web")]
+pub fn load_remote_world() -> Option<RemoteWorld> {
+    LocalStorage::get(REMOTE_WORLD_KEY).ok()
+}
+
+#[cfg(feature = "web")]
+pub fn store_remote_world(world: &RemoteWorld) {
+    let _ = LocalStorage::set(REMOTE_WORLD_KEY, world);
+}
+
+// new runs go back to generating their worlds
+#[cfg(feature = "web")]
+pub fn clear_remote_world() {
+    LocalStorage::delete(REMOTE_WORLD_KEY);
+}
+
+#[cfg(test)]
+mod remote_tests {
+    use super::*;
+
+    static JSON: &str = r#"{
+        "species": [{ "name": "Pebble", "bst": 300, "alignment": "Rock",
+                      "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 } }],
+        "attacks": [{ "name": "Tackle", "power": 40, "alignment": "Rock" }]
+    }"#;
+
+    static RON: &str = r#"(
+        species: [(name: "Pebble", bst: 300, alignment: Rock,
+                   stats: (health: 0.25, attack: 0.25, defense: 0.25, special_attack: 0.25, special_defense: 0.25, speed: 0.25))],
+        attacks: [(name: "Tackle", power: 40, alignment: Rock)],
+    )"#;
+
+    #[test]
+    fn parse_test() {
+        let json = RemoteWorld::parse("https://example.com/world.json", JSON).unwrap();
+        let ron = RemoteWorld::parse(" https://example.com/world.RON?v=2 ", RON).unwrap();
+        assert_eq!(json.definition, ron.definition);
+        assert_eq!(ron.url, "https://example.com/world.RON?v=2");
+        assert_eq!(ron.build().unwrap().species().len(), 1);
+
+        // anything that isn't .ron is read as json
+        assert!(matches!(RemoteWorld::parse("https://example.com/world", RON).unwrap_err()[..], [ContentError::Parse { .. }]));
+        assert_eq!(
+            RemoteWorld::parse("world.json", &JSON.replace("\"bst\": 300", "\"bst\": 0")).unwrap_err(),
+            vec![ContentError::InvalidStats("Pebble".to_string())],
+        );
+    }
+}
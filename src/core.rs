@@ -1,42 +1,50 @@
-use std::cmp::{Eq, PartialEq};
-use std::collections::HashMap;
-use std::fmt::Debug;
-use std::hash::Hash;
-use std::ops::{Add, AddAssign};
+// nothing in here needs more than core and alloc
+// TODO: mark the crate no_std when only `core` is enabled
+use core::cmp::{Eq, PartialEq};
+use core::fmt::{self, Debug};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, RangeInclusive};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
 
 use num_traits::identities::Zero;
+#[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Stats<T> {
     pub health: T,
     pub attack: T,
     pub defense: T,
+    pub special_attack: T,
+    pub special_defense: T,
     pub speed: T,
 }
 
-// convenience methods for adjusting stats
-// TODO: this implementation only handles "physical" stats
+// convenience methods for adjusting stats; as a list they're in the order they're declared
 impl <T> From<Vec<T>> for Stats<T> where T: Clone + Copy {
     fn from(stats: Vec<T>) -> Self {
         Stats {
-            health: *stats.get(0).unwrap(),
+            health: *stats.first().unwrap(),
             attack: *stats.get(1).unwrap(),
             defense: *stats.get(2).unwrap(),
-            speed: *stats.get(3).unwrap(),
+            special_attack: *stats.get(3).unwrap(),
+            special_defense: *stats.get(4).unwrap(),
+            speed: *stats.get(5).unwrap(),
         }
     }
 }
 
 impl <T: Copy> From<Stats<T>> for Vec<T> {
     fn from(stats: Stats<T>) -> Self {
-        vec![stats.health, stats.attack, stats.defense, stats.speed]
+        Vec::from(&stats)
     }
 }
 
 impl <T: Copy> From<&Stats<T>> for Vec<T> {
     fn from(stats: &Stats<T>) -> Self {
-        vec![stats.health, stats.attack, stats.defense, stats.speed]
+        vec![stats.health, stats.attack, stats.defense, stats.special_attack, stats.special_defense, stats.speed]
     }
 }
 
@@ -48,6 +56,8 @@ impl <T: Add<T, Output = T>> Add<Stats<T>> for Stats<T> {
             health: self.health + other.health,
             attack: self.attack + other.attack,
             defense: self.defense + other.defense,
+            special_attack: self.special_attack + other.special_attack,
+            special_defense: self.special_defense + other.special_defense,
             speed: self.speed + other.speed,
         }
     }
@@ -58,6 +68,8 @@ impl <T: AddAssign> AddAssign for Stats<T> {
         self.health += other.health;
         self.attack += other.attack;
         self.defense += other.defense;
+        self.special_attack += other.special_attack;
+        self.special_defense += other.special_defense;
         self.speed += other.speed;
     }
 }
@@ -68,13 +80,16 @@ impl <T: Zero + PartialEq> Zero for Stats<T> {
             health: T::zero(),
             attack: T::zero(),
             defense: T::zero(),
+            special_attack: T::zero(),
+            special_defense: T::zero(),
             speed: T::zero(),
         }
     }
 
     fn is_zero(&self) -> bool {
         let zero = T::zero();
-        self.health == zero && self.attack == zero && self.defense == zero && self.speed == zero
+        self.health == zero && self.attack == zero && self.defense == zero &&
+            self.special_attack == zero && self.special_defense == zero && self.speed == zero
     }
 
     fn set_zero(&mut self) {
@@ -83,25 +98,169 @@ impl <T: Zero + PartialEq> Zero for Stats<T> {
 }
 
 impl <T> Stats<T> {
-    pub fn from_values(health: T, attack: T, defense: T, speed: T) -> Stats<T> {
+    pub fn from_values(health: T, attack: T, defense: T, special_attack: T, special_defense: T, speed: T) -> Stats<T> {
         Stats {
             health,
             attack,
             defense,
+            special_attack,
+            special_defense,
             speed,
         }
     }
 }
 
+// the stats that can be raised and lowered during a battle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Stat { Attack, Defense, SpecialAttack, SpecialDefense, Speed }
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Stat::Attack => "attack",
+            Stat::Defense => "defense",
+            Stat::SpecialAttack => "special attack",
+            Stat::SpecialDefense => "special defense",
+            Stat::Speed => "speed",
+        })
+    }
+}
+
+impl <T> Stats<T> {
+    pub fn get(&self, stat: Stat) -> &T {
+        match stat {
+            Stat::Attack => &self.attack,
+            Stat::Defense => &self.defense,
+            Stat::SpecialAttack => &self.special_attack,
+            Stat::SpecialDefense => &self.special_defense,
+            Stat::Speed => &self.speed,
+        }
+    }
+
+    pub fn get_mut(&mut self, stat: Stat) -> &mut T {
+        match stat {
+            Stat::Attack => &mut self.attack,
+            Stat::Defense => &mut self.defense,
+            Stat::SpecialAttack => &mut self.special_attack,
+            Stat::SpecialDefense => &mut self.special_defense,
+            Stat::Speed => &mut self.speed,
+        }
+    }
+}
+
+// how many stages a stat can be raised or lowered
+pub const MAX_STAGE: i32 = 6;
+
+// each stage up adds half of the stat again, and each stage down takes the same share away, so +6
+// is 4x and -6 is 1/4
+pub fn apply_stage(value: u32, stage: i32) -> u32 {
+    let stage = stage.clamp(-MAX_STAGE, MAX_STAGE);
+    if stage >= 0 {
+        value * (2 + stage as u32) / 2
+    } else {
+        value * 2 / (2 + stage.unsigned_abs())
+    }
+}
+
+// how much a nature raises one stat and lowers another, in percent
+pub const NATURE_BIAS: u32 = 10;
+
+// a character's personality, rolled for it rather than its species. most raise one stat and lower
+// another; the five that would raise and lower the same stat leave them all alone
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Nature {
+    #[default]
+    Hardy, Lonely, Brave, Adamant, Naughty,
+    Bold, Docile, Relaxed, Impish, Lax,
+    Timid, Hasty, Serious, Jolly, Naive,
+    Modest, Mild, Quiet, Bashful, Rash,
+    Calm, Gentle, Sassy, Careful, Quirky,
+}
+
+// laid out in rows by the stat that's raised and columns by the one that's lowered
+pub const NATURES: [Nature; 25] = [
+    Nature::Hardy, Nature::Lonely, Nature::Brave, Nature::Adamant, Nature::Naughty,
+    Nature::Bold, Nature::Docile, Nature::Relaxed, Nature::Impish, Nature::Lax,
+    Nature::Timid, Nature::Hasty, Nature::Serious, Nature::Jolly, Nature::Naive,
+    Nature::Modest, Nature::Mild, Nature::Quiet, Nature::Bashful, Nature::Rash,
+    Nature::Calm, Nature::Gentle, Nature::Sassy, Nature::Careful, Nature::Quirky,
+];
+
+const NATURE_STATS: [Stat; 5] = [Stat::Attack, Stat::Defense, Stat::Speed, Stat::SpecialAttack, Stat::SpecialDefense];
+
+impl Nature {
+    // the stat raised and the stat lowered, if they're different
+    pub fn bias(&self) -> Option<(Stat, Stat)> {
+        let index = NATURES.iter().position(|nature| nature == self).unwrap();
+        let (up, down) = (NATURE_STATS[index / 5], NATURE_STATS[index % 5]);
+        (up != down).then_some((up, down))
+    }
+
+    pub fn apply(&self, mut stats: Stats<u32>) -> Stats<u32> {
+        if let Some((up, down)) = self.bias() {
+            *stats.get_mut(up) = *stats.get(up) * (100 + NATURE_BIAS) / 100;
+            *stats.get_mut(down) = *stats.get(down) * (100 - NATURE_BIAS) / 100;
+        }
+        stats
+    }
+
+    pub fn description(&self) -> String {
+        match self.bias() {
+            Some((up, down)) => format!("{:?} nature: +{}% {}, -{}% {}.", self, NATURE_BIAS, up, NATURE_BIAS, down),
+            None => format!("{:?} nature: no stat is raised or lowered.", self),
+        }
+    }
+}
+
+impl Stats<u32> {
+    pub fn saturating_sub(&self, other: &Stats<u32>) -> Stats<u32> {
+        Vec::from(self).into_iter()
+            .zip(Vec::from(other))
+            .map(|(stat, other)| stat.saturating_sub(other))
+            .collect::<Vec<u32>>()
+            .into()
+    }
+}
+
+// a level's worth of stats, spread over the species' stats
+pub const SCALING_FACTOR: u32 = 100;
+// every this much effort in a stat adds 1 to it for each level
+pub const EFFORT_PER_POINT: u32 = 8;
+
+pub trait Scale {
+    fn scale(&self, a: u32) -> Stats<u32>;
+}
+
+impl Scale for Stats<f64> {
+    // linearly scales floats to have a total sum equal to some integer; there may be a rounding error
+    fn scale(&self, a: u32) -> Stats<u32> {
+        let x: Vec<f64> = self.into();
+        let z: f64 = x.iter().sum();
+        x.iter().map(|x| a as f64 * *x / z).map(|x| x as u32).collect::<Vec<u32>>().into()
+    }
+}
+
+// raises each of the species' stats by its individual value, in percent. the total goes up along
+// with them, so better values mean more stats overall, not just different ones
+fn perturb(stats: &Stats<f64>, ivs: &Stats<u32>) -> Stats<f64> {
+    Vec::from(stats).into_iter()
+        .zip(Vec::from(ivs))
+        .map(|(stat, iv)| stat * (100 + iv) as f64 / 100.0)
+        .collect::<Vec<f64>>()
+        .into()
+}
+
 #[cfg(test)]
 mod stats_tests {
     use super::*;
 
     fn zero_test<T: Add + Zero + Debug + PartialEq>() {
         let mut zero: Stats<T> = Stats::zero();
-        assert_eq!(zero.is_zero(), true);
+        assert!(zero.is_zero());
         zero.set_zero();
-        assert_eq!(zero.is_zero(), true);
+        assert!(zero.is_zero());
     }
 
     #[test]
@@ -112,72 +271,491 @@ mod stats_tests {
 
     #[test]
     fn add_test() {
-        let mut stats = Stats { health: 1, attack: 1, defense: 1, speed: 1 };
+        let mut stats = Stats::from_values(1, 1, 1, 1, 1, 1);
         assert_eq!(stats + Stats::zero(), stats);
-        let stats2 = Stats { health: 0, attack: 1, defense: 2, speed: 3 };
-        assert_eq!(stats + stats, Stats { health: 2, attack: 2, defense: 2, speed: 2 });
+        let stats2 = Stats::from_values(0, 1, 2, 3, 4, 5);
+        assert_eq!(stats + stats, Stats::from_values(2, 2, 2, 2, 2, 2));
         stats += stats2;
-        assert_eq!(stats, Stats { health: 1, attack: 2, defense: 3, speed: 4 });
+        assert_eq!(stats, Stats::from_values(1, 2, 3, 4, 5, 6));
+    }
+
+    #[test]
+    fn vec_test() {
+        let stats = Stats::from_values(1, 2, 3, 4, 5, 6);
+        let values: Vec<u32> = stats.into();
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(Stats::from(values), stats);
+    }
+
+    #[test]
+    fn nature_test() {
+        let stats = Stats::from_values(100, 100, 100, 100, 100, 100);
+        assert_eq!(Nature::Adamant.bias(), Some((Stat::Attack, Stat::SpecialAttack)));
+        assert_eq!(Nature::Adamant.apply(stats), Stats::from_values(100, 110, 100, 90, 100, 100));
+        assert_eq!(Nature::Timid.apply(stats), Stats::from_values(100, 90, 100, 100, 100, 110));
+        // the neutral natures are the diagonal
+        let neutral: Vec<Nature> = NATURES.iter().copied().filter(|nature| nature.bias().is_none()).collect();
+        assert_eq!(neutral, vec![Nature::Hardy, Nature::Docile, Nature::Serious, Nature::Bashful, Nature::Quirky]);
+        assert_eq!(Nature::Serious.apply(stats), stats);
+        assert_eq!(Nature::Modest.description(), "Modest nature: +10% special attack, -10% attack.");
+    }
+
+    #[test]
+    fn stage_test() {
+        assert_eq!(apply_stage(100, 0), 100);
+        assert_eq!(apply_stage(100, 1), 150);
+        assert_eq!(apply_stage(100, -1), 66);
+        assert_eq!(apply_stage(100, MAX_STAGE), 400);
+        assert_eq!(apply_stage(100, -MAX_STAGE), 25);
+        // past the limit is the same as at it
+        assert_eq!(apply_stage(100, 10), 400);
+
+        let mut stats = Stats::from_values(1, 2, 3, 4, 5, 6);
+        *stats.get_mut(Stat::SpecialDefense) += 1;
+        assert_eq!(*stats.get(Stat::SpecialDefense), 6);
     }
 }
 
+// names are shared rather than copied, since every character clone would otherwise copy its own
+pub type Name = Arc<str>;
+
+// TODO: This needs to be abstracted but then we will need to pipe forward generics
+pub type ActionId = usize;
+pub type Actions = Vec<ActionId>;
+
 // properties describing the character generally
-// TODO: it would be nice for this to have a notion of the actions the species would learn
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Species<A> {
-    pub name: String,
+    pub name: Name,
     pub bst: u32,
     pub stats: Stats<f64>,
     pub alignment: A,
+    // a second alignment: attacks of either get stab, and attacks against it count against both
+    #[cfg_attr(feature = "serde", serde(default = "Option::default"))]
+    pub secondary: Option<A>,
+    // the level each action is learned at
+    // TODO: these are ids into the world's action pool, so they shift if a pack adds attacks
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub learnset: Vec<(u32, ActionId)>,
+    // an id into the world's abilities, like the learnset's are into its actions
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ability: Option<AbilityId>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rarity: Rarity,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub growth: GrowthRate,
 }
 
-// TODO: This needs to be abstracted but then we will need to pipe forward generics
-pub type ActionId = usize;
-pub type Actions = Vec<ActionId>;
+// what the first level takes, on every curve; the ones after it take more, by how steep the curve is
+pub const EXPERIENCE_TO_LEVEL: u32 = 100;
+
+// how much experience each of a species' levels takes
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GrowthRate {
+    Fast,
+    #[default]
+    Medium,
+    Slow,
+    // cheap and expensive levels take turns, one on the fast curve and the next on the slow one
+    Erratic,
+}
+
+pub const GROWTH_RATES: [GrowthRate; 4] = [GrowthRate::Fast, GrowthRate::Medium, GrowthRate::Slow, GrowthRate::Erratic];
+
+impl GrowthRate {
+    // how steeply the experience a level takes climbs with the level
+    fn exponent(&self, level: u32) -> f64 {
+        match self {
+            GrowthRate::Fast => 0.1,
+            GrowthRate::Medium => 0.25,
+            GrowthRate::Slow => 0.4,
+            GrowthRate::Erratic if level.is_multiple_of(2) => 0.1,
+            GrowthRate::Erratic => 0.4,
+        }
+    }
+
+    // the experience it takes to get from `level` to the one after, the first level's times the
+    // level to the curve's exponent; never 0
+    pub fn experience_to_next_level(&self, level: u32) -> u32 {
+        let level = level.max(1);
+        (EXPERIENCE_TO_LEVEL as f64 * (level as f64).powf(self.exponent(level))) as u32
+    }
+
+    // the experience it takes to get from `from` up to `to`
+    pub fn experience_between(&self, from: u32, to: u32) -> u32 {
+        (from..to).map(|level| self.experience_to_next_level(level)).sum()
+    }
+}
+
+// how rarely a species turns up, which goes with how strong it is
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl <A> Species<A> {
+    // the primary alignment first, like `State::alignments`
+    pub fn alignments(&self) -> Vec<A> where A: Clone {
+        std::iter::once(self.alignment.clone()).chain(self.secondary.clone()).collect()
+    }
+
+    // the actions learned when growing from one level to a higher one, in the order they're learned
+    pub fn learned_between(&self, from: u32, to: u32) -> Actions {
+        let mut learned: Vec<(u32, ActionId)> = self.learnset.iter()
+            .copied()
+            .filter(|(level, _)| from < *level && *level <= to)
+            .collect();
+        learned.sort_by_key(|(level, _)| *level);
+        learned.into_iter().map(|(_, action)| action).collect()
+    }
+}
 
 // describes the fixed state in a battle
 // TODO: abstract the level + experience
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Attributes {
     pub level: u32,
     pub experience: u32,
     pub stats: Stats<u32>,
     pub actions: Actions,
+    // the uses left of each action. actions that haven't been used yet aren't in here, so this is
+    // empty whenever the character has been refreshed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pp: BTreeMap<ActionId, u32>,
+    // how each action has gone over every battle the character has fought
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub usage: BTreeMap<ActionId, ActionUsage>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nature: Nature,
+    // hidden values rolled for the character that make it a little better or worse than others of
+    // its species
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ivs: Stats<u32>,
+    // training from the kaizo the character has beaten, which adds to the stats for every level it has
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub effort: Stats<u32>,
+    // stats added on top of the formula, like a hatchling's genes or an upgrade from a draft; they
+    // don't grow with the level, but aren't lost to it either
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bonus: Stats<u32>,
+    // battles the character has fought to the end
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub battles: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActionUsage {
+    pub used: u32,
+    // went off and hit, rather than missing or being stunned out of
+    pub landed: u32,
+}
+
+impl ActionUsage {
+    pub fn landed_rate(&self) -> Option<f64> {
+        (self.used > 0).then(|| self.landed as f64 / self.used as f64)
+    }
+}
+
+// statuses are a small closed set, so they index a fixed array instead of being hashed. the index
+// is also the order statuses are shown, logged and saved in, whatever order they were gained in,
+// so replays and snapshot tests come out the same every run
+pub trait StatusKey: Copy + Eq {
+    // must be below `MAX_STATUSES`, and should follow the status' `Ord` if it has one
+    fn index(&self) -> usize;
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+pub const MAX_STATUSES: usize = 8;
+
+// a map from status to its counter (stun turns, bleed damage, ...) that is cheap to copy, since
+// characters are cloned on every render and every ai simulation
+#[derive(Clone, Copy, PartialEq)]
+pub struct StatusSet<S> {
+    values: [Option<i32>; MAX_STATUSES],
+    status: PhantomData<S>,
+}
+
+impl <S> Default for StatusSet<S> {
+    fn default() -> Self {
+        StatusSet { values: [None; MAX_STATUSES], status: PhantomData }
+    }
+}
+
+impl <S: StatusKey> StatusSet<S> {
+    pub fn new() -> StatusSet<S> { StatusSet::default() }
+
+    pub fn contains_key(&self, status: &S) -> bool { self.values[status.index()].is_some() }
+
+    pub fn get(&self, status: &S) -> Option<&i32> { self.values[status.index()].as_ref() }
+
+    pub fn get_mut(&mut self, status: &S) -> Option<&mut i32> { self.values[status.index()].as_mut() }
+
+    // the counter for a status, starting it at `value` if the character doesn't have it yet
+    pub fn get_or_insert(&mut self, status: S, value: i32) -> &mut i32 {
+        self.values[status.index()].get_or_insert(value)
+    }
+
+    pub fn insert(&mut self, status: S, value: i32) -> Option<i32> {
+        self.values[status.index()].replace(value)
+    }
+
+    pub fn remove(&mut self, status: &S) -> Option<i32> { self.values[status.index()].take() }
+
+    pub fn is_empty(&self) -> bool { self.values.iter().all(Option::is_none) }
+
+    pub fn len(&self) -> usize { self.values.iter().flatten().count() }
+
+    // in index order
+    pub fn iter(&self) -> impl Iterator<Item = (S, i32)> + '_ {
+        self.values.iter().enumerate()
+            .filter_map(|(index, value)| Some((S::from_index(index)?, (*value)?)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = S> + '_ { self.iter().map(|(status, _)| status) }
+}
+
+impl <S: StatusKey + Debug> Debug for StatusSet<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// stored as a map so saves look the same as when this was a HashMap, but always in index order
+#[cfg(feature = "serde")]
+impl <S: StatusKey + Serialize> Serialize for StatusSet<S> {
+    fn serialize<Z: serde::Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, S: StatusKey + Deserialize<'de>> Deserialize<'de> for StatusSet<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StatusSetVisitor<S>(PhantomData<S>);
+
+        impl <'de, S: StatusKey + Deserialize<'de>> serde::de::Visitor<'de> for StatusSetVisitor<S> {
+            type Value = StatusSet<S>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of statuses to counters")
+            }
+
+            fn visit_map<M: serde::de::MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+                let mut set = StatusSet::new();
+                while let Some((status, value)) = map.next_entry()? {
+                    set.insert(status, value);
+                }
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_map(StatusSetVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod status_set_tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    enum FakeStatus { A, B }
+
+    impl StatusKey for FakeStatus {
+        fn index(&self) -> usize { *self as usize }
+
+        fn from_index(index: usize) -> Option<Self> {
+            [FakeStatus::A, FakeStatus::B].get(index).copied()
+        }
+    }
+
+    #[test]
+    fn status_set_test() {
+        let mut set = StatusSet::new();
+        assert!(set.is_empty());
+        assert_eq!(set.insert(FakeStatus::B, 2), None);
+        assert_eq!(set.insert(FakeStatus::B, 3), Some(2));
+        assert!(set.contains_key(&FakeStatus::B));
+        assert!(!set.contains_key(&FakeStatus::A));
+
+        *set.get_or_insert(FakeStatus::A, 0) += 1;
+        *set.get_or_insert(FakeStatus::A, 0) += 1;
+        assert_eq!(set.get(&FakeStatus::A), Some(&2));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(FakeStatus::A, 2), (FakeStatus::B, 3)]);
+
+        assert_eq!(set.remove(&FakeStatus::A), Some(2));
+        assert_eq!(set.remove(&FakeStatus::A), None);
+        assert_eq!(set.keys().collect::<Vec<_>>(), vec![FakeStatus::B]);
+        assert_eq!(format!("{:?}", set), "{B: 3}");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn status_set_serde_test() {
+        let mut set = StatusSet::new();
+        set.insert(FakeStatus::B, 3);
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, r#"{"B":3}"#);
+        assert_eq!(serde_json::from_str::<StatusSet<FakeStatus>>(&json).unwrap(), set);
+    }
+
+    #[test]
+    fn order_test() {
+        let mut ab = StatusSet::new();
+        ab.insert(FakeStatus::A, 1);
+        ab.insert(FakeStatus::B, 2);
+        let mut ba = StatusSet::new();
+        ba.insert(FakeStatus::B, 2);
+        ba.insert(FakeStatus::A, 1);
+        assert_eq!(ab.keys().collect::<Vec<_>>(), ba.keys().collect::<Vec<_>>());
+        assert_eq!(format!("{:?}", ab), format!("{:?}", ba));
+        #[cfg(feature = "serde")]
+        assert_eq!(serde_json::to_string(&ab).unwrap(), serde_json::to_string(&ba).unwrap());
+    }
+}
+
+// how many times an action can be used in a battle unless it says otherwise
+pub const DEFAULT_PP: u32 = 20;
+
+// accuracy and evasion are percentages of this
+pub const BASE_ACCURACY: u32 = 100;
+
+#[cfg(feature = "serde")]
+fn base_accuracy() -> u32 { BASE_ACCURACY }
+
+// what a character is locked into by a move that takes two turns
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Pending {
+    // picked a move that charges up this turn
+    Charging(ActionId),
+    // charged up last turn, and lets the move loose this turn
+    Charged(ActionId),
+    // landed a move last turn that it has to sit this one out for
+    Recharge,
 }
 
 // describes the changing state within a battle
-// TODO: push status into a trait or function
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct State<A, S: Eq + Hash + PartialEq> {
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct State<A, S: StatusKey> {
     pub alignment: A,
+    #[cfg_attr(feature = "serde", serde(default = "Option::default"))]
+    pub secondary: Option<A>,
     pub health: i32,
-    pub status: HashMap<S, i32>,
+    pub status: StatusSet<S>,
+    #[cfg_attr(feature = "serde", serde(default = "base_accuracy"))]
+    pub accuracy: u32,
+    #[cfg_attr(feature = "serde", serde(default = "base_accuracy"))]
+    pub evasion: u32,
+    // how far each stat has been raised or lowered; health is never staged
+    #[cfg_attr(feature = "serde", serde(default = "Stats::zero"))]
+    pub stages: Stats<i32>,
+    // whether the last action it tried this turn went off and hit
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub landed: bool,
+    // statuses that can't be put on it, e.g. by an endless curse; only the counters' keys matter
+    #[cfg_attr(feature = "serde", serde(default = "StatusSet::new"))]
+    pub immune: StatusSet<S>,
+    // statuses it takes twice as much of, e.g. from a risk taken for a reward
+    #[cfg_attr(feature = "serde", serde(default = "StatusSet::new"))]
+    pub vulnerable: StatusSet<S>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pending: Option<Pending>,
+    // the physical damage it's taken so far this turn, for counters
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub taken: u32,
+}
+
+impl <A: Clone, S: StatusKey> State<A, S> {
+    // the primary alignment first
+    pub fn alignments(&self) -> Vec<A> {
+        std::iter::once(self.alignment.clone()).chain(self.secondary.clone()).collect()
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct Character<A, S: Eq + Hash + PartialEq> {
-    pub name: String,
-    pub species: Species<A>,
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Character<A, S: StatusKey> {
+    pub name: Name,
+    // shared, since species never change and characters get cloned a lot
+    pub species: Arc<Species<A>>,
     pub attributes: Attributes,
     pub state: State<A, S>,
 }
 
-impl <A: Clone, S: Eq + Hash + PartialEq> Character<A, S> {
+impl <A, S: StatusKey> Character<A, S> {
+    // the species' stats scaled to `a`, after the character's individual values have raised them
+    // and with its nature pulling them one way, plus what it's trained and its bonus
+    pub fn scaled_stats(&self, a: u32) -> Stats<u32> {
+        let total = |stats: &Stats<f64>| Vec::from(stats).iter().sum::<f64>();
+        let stats = perturb(&self.species.stats, &self.attributes.ivs);
+        let base = total(&self.species.stats);
+        let raised = if base > 0.0 { (a as f64 * total(&stats) / base) as u32 } else { a };
+        let effort: Vec<u32> = Vec::from(&self.attributes.effort).iter()
+            .map(|effort| effort * a / (SCALING_FACTOR * EFFORT_PER_POINT))
+            .collect();
+        self.attributes.nature.apply(stats.scale(raised)) + Stats::from(effort) + self.attributes.bonus
+    }
+
+    pub fn experience_to_next_level(&self) -> u32 {
+        self.species.growth.experience_to_next_level(self.attributes.level)
+    }
+
+    // works the stats out again from the level, so nothing outside of here has to
+    pub fn recalculate_stats(&mut self) {
+        self.attributes.stats = self.scaled_stats(self.attributes.level * SCALING_FACTOR);
+    }
+}
+
+impl <A: Clone, S: StatusKey> Character<A, S> {
     pub fn from_species(species: Species<A>) -> Character<A, S> {
+        Character::from_shared_species(Arc::new(species))
+    }
+
+    // for characters of a species that is already shared, like the ones in a world
+    pub fn from_shared_species(species: Arc<Species<A>>) -> Character<A, S> {
         let alignment = species.alignment.clone();
+        let secondary = species.secondary.clone();
         Character {
-            name: species.name.to_string(),
+            name: species.name.clone(),
             species,
             attributes: Attributes {
                 level: 0,
                 experience: 0,
                 stats: Stats::zero(),
                 actions: Vec::new(),
+                pp: BTreeMap::new(),
+                usage: BTreeMap::new(),
+                nature: Nature::default(),
+                ivs: Stats::zero(),
+                effort: Stats::zero(),
+                bonus: Stats::zero(),
+                battles: 0,
             },
             state: State {
                 alignment,
+                secondary,
                 health: 0,
-                status: HashMap::new(),
+                status: StatusSet::new(),
+                accuracy: BASE_ACCURACY,
+                evasion: BASE_ACCURACY,
+                stages: Stats::zero(),
+                landed: false,
+                immune: StatusSet::new(),
+                vulnerable: StatusSet::new(),
+                pending: None,
+                taken: 0,
             }
         }
     }
@@ -188,22 +766,119 @@ impl <A: Clone, S: Eq + Hash + PartialEq> Character<A, S> {
         character
     }
 
-    pub fn priority(&self) -> i32 { self.attributes.stats.speed as i32 }
+    pub fn priority(&self) -> i32 { self.stat(Stat::Speed) as i32 }
+
+    // the stat with its stage applied
+    pub fn stat(&self, stat: Stat) -> u32 {
+        apply_stage(*self.attributes.stats.get(stat), *self.state.stages.get(stat))
+    }
+
+    // `max` is how many uses the action has in a battle
+    pub fn pp_left(&self, action: ActionId, max: u32) -> u32 {
+        self.attributes.pp.get(&action).copied().unwrap_or(max)
+    }
+
+    // false if there were no uses left
+    pub fn spend_pp(&mut self, action: ActionId, max: u32) -> bool {
+        let left = self.attributes.pp.entry(action).or_insert(max);
+        if *left == 0 {
+            return false;
+        }
+        *left -= 1;
+        true
+    }
+
+    pub fn record_use(&mut self, action: ActionId, landed: bool) {
+        let usage = self.attributes.usage.entry(action).or_default();
+        usage.used += 1;
+        usage.landed += landed as u32;
+    }
+
+    // how many stages the stat actually moved, which is less than `change` near the limits
+    pub fn change_stage(&mut self, stat: Stat, change: i32) -> i32 {
+        let stage = self.state.stages.get_mut(stat);
+        let changed = (*stage + change).clamp(-MAX_STAGE, MAX_STAGE);
+        let moved = changed - *stage;
+        *stage = changed;
+        moved
+    }
 
     pub fn refresh(&mut self) {
         self.state.alignment = self.species.alignment.clone();
+        self.state.secondary = self.species.secondary.clone();
         self.state.health = self.attributes.stats.health as i32;
-        self.state.status = HashMap::new();
+        self.state.status = StatusSet::new();
+        self.state.accuracy = BASE_ACCURACY;
+        self.state.evasion = BASE_ACCURACY;
+        self.state.stages = Stats::zero();
+        self.state.landed = false;
+        self.state.immune = StatusSet::new();
+        self.state.vulnerable = StatusSet::new();
+        self.state.pending = None;
+        self.state.taken = 0;
+        self.attributes.pp.clear();
     }
 }
 
+// which of the user's and target's stats an action's damage is worked out from
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Category {
+    // attack against defense
+    #[default]
+    Physical,
+    // special attack against special defense
+    Special,
+}
+
 // TODO: This needs to be abstracted but then we will need to pipe forward generics
 // TODO: should be a list of states that can be applied sequentially
 pub type States = Vec<String>;
 
-pub trait Action<A, S: Eq + Hash + PartialEq> {
-    fn name(&self) -> String;
-    fn description(&self) -> String { self.name() }
+// `F` is whatever battle-wide state (the field) an action is allowed to see and change
+pub trait Action<A, S: StatusKey, F> {
+    fn name(&self) -> &str;
+    fn description(&self) -> String { self.name().to_string() }
     fn priority(&self) -> i32 { 0 }
-    fn act(&self, user: &mut Character<A, S>, target: &mut Character<A, S>) -> States;
+    // the percent chance to hit a target with normal evasion, before the user's accuracy
+    fn accuracy(&self) -> u32 { BASE_ACCURACY }
+    fn category(&self) -> Category { Category::Physical }
+    // how many times it can be used in a battle
+    fn pp(&self) -> u32 { DEFAULT_PP }
+    // how many times it hits in one use, at the least and at the most
+    fn hits(&self) -> RangeInclusive<u32> { 1..=1 }
+    // whether it takes a turn to charge up before it goes off
+    fn charges(&self) -> bool { false }
+    // whether the user has to sit out the turn after it lands
+    fn recharges(&self) -> bool { false }
+    // whether it knocks the target out outright, which its accuracy decides instead of its power
+    fn one_hit_ko(&self) -> bool { false }
+    fn act(&self, user: &mut Character<A, S>, target: &mut Character<A, S>, field: &mut F) -> States;
+}
+
+pub type ItemId = usize;
+
+// something used from the bag on your own side instead of acting; unlike an action it can be used
+// on any of your party, fainted or not, and not just whoever is battling
+pub trait Item<A, S: StatusKey> {
+    fn name(&self) -> &str;
+    fn description(&self) -> String { self.name().to_string() }
+    // whether it would do anything for the target, so it isn't used up for nothing
+    fn usable_on(&self, _target: &Character<A, S>) -> bool { true }
+    fn apply(&self, target: &mut Character<A, S>) -> States;
+}
+
+pub type AbilityId = usize;
+
+// a species' passive; it's never used, the battle calls into it at fixed points instead
+pub trait Ability<A, S: StatusKey, F> {
+    fn name(&self) -> &str;
+    fn description(&self) -> String { self.name().to_string() }
+    // called once both sides are known, before the first turn
+    fn on_battle_start(&self, _holder: &mut Character<A, S>, _opponent: &mut Character<A, S>, _field: &mut F) -> States { vec![] }
+    // the damage the holder takes from an attack of the alignment that would deal `damage`
+    fn on_hit(&self, _holder: &Character<A, S>, _alignment: &A, damage: u32) -> u32 { damage }
+    fn on_end_turn(&self, _holder: &mut Character<A, S>, _field: &mut F) -> States { vec![] }
+    // added to the priority of the holder's actions on a turn of the battle, counting from 0
+    fn priority(&self, _turn: u32) -> i32 { 0 }
 }
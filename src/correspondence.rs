@@ -0,0 +1,254 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::clauses::PVP_CLAUSES;
+use crate::content::id::fnv1a;
+use crate::core::{ActionId, States};
+use rand::distributions::{Distribution, Standard};
+
+use crate::onion::{ActionPool, OnionBattle, OnionBattleState, OnionCharacter, OnionWorld};
+use crate::rules::Ruleset;
+use crate::seed::{Seed, base36};
+use crate::sim::play_turn;
+use crate::snapshot::Side;
+
+// the level both kaizo are at when a battle is set up from just a seed
+pub static CORRESPONDENCE_LEVEL: u32 = 20;
+
+// what one side sends the other each turn: the move it picked and where it thinks the battle is,
+// written as three base 36 numbers, e.g. `0-3-1A2B3C`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TurnCode {
+    pub turn: u32,
+    pub action: ActionId,
+    // of the battle before the turn is played, so both ends can tell they haven't drifted apart
+    pub hash: u64,
+}
+
+impl fmt::Display for TurnCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}-{}", base36(self.turn as u64), base36(self.action as u64), base36(self.hash))
+    }
+}
+
+impl FromStr for TurnCode {
+    type Err = CorrespondenceError;
+
+    fn from_str(s: &str) -> Result<TurnCode, CorrespondenceError> {
+        let parts: Vec<u64> = s.trim()
+            .split('-')
+            .map(|part| u64::from_str_radix(part, 36))
+            .collect::<Result<_, _>>()
+            .map_err(|_| CorrespondenceError::Parse(s.to_string()))?;
+        match parts[..] {
+            [turn, action, hash] => Ok(TurnCode { turn: turn as u32, action: action as ActionId, hash }),
+            _ => Err(CorrespondenceError::Parse(s.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CorrespondenceError {
+    Parse(String),
+    // a code for a turn that isn't the one being played
+    WrongTurn { expected: u32, got: u32 },
+    // the other side's battle isn't the same as this one, so the turn can't be played the same
+    Desync,
+    // a move the other side's kaizo doesn't know
+    IllegalMove(ActionId),
+    // this side has already picked its move for the turn
+    AlreadyChosen,
+    Over,
+}
+
+impl fmt::Display for CorrespondenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CorrespondenceError::Parse(code) => write!(f, "\"{}\" is not a turn code.", code),
+            CorrespondenceError::WrongTurn { expected, got } => write!(f, "That code is for turn {}, but this is turn {}.", got + 1, expected + 1),
+            CorrespondenceError::Desync => write!(f, "That code is from a different battle, or one that has drifted apart from this one."),
+            CorrespondenceError::IllegalMove(action) => write!(f, "The other side picked move #{}, which their kaizo doesn't know.", action),
+            CorrespondenceError::AlreadyChosen => write!(f, "You have already picked a move this turn."),
+            CorrespondenceError::Over => write!(f, "The battle is over."),
+        }
+    }
+}
+
+// a battle between two people who trade turn codes instead of connecting. both ends start from the
+// same battle and seed and play every turn the same way once they have both moves, so nothing but
+// the codes ever needs to be sent
+pub struct Correspondence {
+    pub battle: OnionBattle,
+    seed: Seed,
+    pub turn: u32,
+    // which side of the battle this end picks for
+    pub side: Side,
+    chosen: Option<ActionId>,
+    received: Option<ActionId>,
+    pub state: OnionBattleState,
+}
+
+impl Correspondence {
+    pub fn new(battle: OnionBattle, seed: Seed, side: Side) -> Correspondence {
+        Correspondence { battle, seed, turn: 0, side, chosen: None, received: None, state: OnionBattleState::InProcess }
+    }
+
+    // the world and both kaizo are rolled from the seed, so sharing it is all it takes to start
+    pub fn from_seed(seed: Seed, side: Side) -> (OnionWorld, Correspondence) {
+        let mut rng = seed.rng();
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = OnionBattle::new(world.sample_at_level(CORRESPONDENCE_LEVEL, &mut rng), world.sample_at_level(CORRESPONDENCE_LEVEL, &mut rng));
+        let rules = Ruleset { clauses: PVP_CLAUSES.to_vec(), ..Ruleset::default() };
+        rules.start_battle(&mut battle, &world, &mut rng);
+        battle.start();
+        (world, Correspondence::new(battle, seed, side))
+    }
+
+    // the battle has no rng of its own, so each turn's rolls come from the seed and the turn
+    fn turn_seed(&self) -> Seed {
+        Seed(self.seed.0.wrapping_add(self.turn as u64))
+    }
+
+    pub fn hash(&self) -> u64 {
+        // the battle is all plain data, so this can't fail
+        fnv1a(&serde_json::to_vec(&self.battle).unwrap())
+    }
+
+    fn opponent(&self) -> &OnionCharacter {
+        match self.side {
+            Side::Player => &self.battle.enemy,
+            Side::Enemy => &self.battle.player,
+        }
+    }
+
+    // whether this side is waiting on the other's code
+    pub fn waiting(&self) -> bool {
+        self.chosen.is_some() && self.received.is_none()
+    }
+
+    // picks this side's move for the turn and hands back the code to send; the turn is played if
+    // the other side's code is already in
+    pub fn choose(&mut self, action: ActionId, actions: &ActionPool) -> Result<(TurnCode, Option<States>), CorrespondenceError> {
+        if self.state != OnionBattleState::InProcess {
+            return Err(CorrespondenceError::Over);
+        }
+        if self.chosen.is_some() {
+            return Err(CorrespondenceError::AlreadyChosen);
+        }
+        let code = TurnCode { turn: self.turn, action, hash: self.hash() };
+        self.chosen = Some(action);
+        Ok((code, self.advance(actions)))
+    }
+
+    // takes in the other side's code; the turn is played if this side has already picked
+    pub fn import(&mut self, code: &str, actions: &ActionPool) -> Result<Option<States>, CorrespondenceError> {
+        if self.state != OnionBattleState::InProcess {
+            return Err(CorrespondenceError::Over);
+        }
+        let code: TurnCode = code.parse()?;
+        if code.turn != self.turn {
+            return Err(CorrespondenceError::WrongTurn { expected: self.turn, got: code.turn });
+        }
+        if code.hash != self.hash() {
+            return Err(CorrespondenceError::Desync);
+        }
+        if !self.opponent().attributes.actions.contains(&code.action) {
+            return Err(CorrespondenceError::IllegalMove(code.action));
+        }
+        self.received = Some(code.action);
+        Ok(self.advance(actions))
+    }
+
+    fn advance(&mut self, actions: &ActionPool) -> Option<States> {
+        let (mine, theirs) = (self.chosen?, self.received?);
+        let (player_action, enemy_action) = match self.side {
+            Side::Player => (mine, theirs),
+            Side::Enemy => (theirs, mine),
+        };
+        let mut rng = self.turn_seed().rng();
+        let (state, logs) = play_turn(&mut self.battle, player_action, enemy_action, actions, &mut rng);
+        self.state = state;
+        self.turn += 1;
+        self.chosen = None;
+        self.received = None;
+        Some(logs)
+    }
+}
+
+#[cfg(test)]
+mod correspondence_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn fake_ends() -> (OnionWorld, Correspondence, Correspondence) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let battle = OnionBattle::new(world.sample_at_level(20, &mut rng), world.sample_at_level(20, &mut rng));
+        let host = Correspondence::new(battle.clone(), Seed(7), Side::Player);
+        let guest = Correspondence::new(battle, Seed(7), Side::Enemy);
+        (world, host, guest)
+    }
+
+    #[test]
+    fn code_test() {
+        let code = TurnCode { turn: 12, action: 3, hash: u64::MAX };
+        assert_eq!(code.to_string().parse::<TurnCode>(), Ok(code));
+        assert_eq!("0-3-1A2B".parse::<TurnCode>(), Ok(TurnCode { turn: 0, action: 3, hash: 36 * 36 * 36 + 10 * 36 * 36 + 2 * 36 + 11 }));
+        assert!("0-3".parse::<TurnCode>().is_err());
+        assert!("hello".parse::<TurnCode>().is_err());
+    }
+
+    #[test]
+    fn correspondence_test() {
+        let (world, mut host, mut guest) = fake_ends();
+        // both ends play the same turns from the same codes
+        while host.state == OnionBattleState::InProcess && host.turn < 50 {
+            let host_move = host.battle.player.attributes.actions[host.turn as usize % host.battle.player.attributes.actions.len()];
+            let guest_move = guest.battle.enemy.attributes.actions[0];
+            let (host_code, played) = host.choose(host_move, &world.actions).unwrap();
+            assert!(played.is_none());
+            assert!(host.waiting());
+            let (guest_code, guest_logs) = guest.choose(guest_move, &world.actions).unwrap();
+            let guest_logs = match guest_logs {
+                Some(logs) => logs,
+                None => guest.import(&host_code.to_string(), &world.actions).unwrap().unwrap(),
+            };
+            let host_logs = host.import(&guest_code.to_string(), &world.actions).unwrap().unwrap();
+            assert_eq!(host_logs, guest_logs);
+            assert_eq!(host.hash(), guest.hash());
+        }
+        assert_eq!(host.turn, guest.turn);
+    }
+
+    #[test]
+    fn from_seed_test() {
+        let (_, host) = Correspondence::from_seed(Seed(3), Side::Player);
+        let (_, guest) = Correspondence::from_seed(Seed(3), Side::Enemy);
+        assert_eq!(host.hash(), guest.hash());
+        let (_, other) = Correspondence::from_seed(Seed(4), Side::Enemy);
+        assert_ne!(host.hash(), other.hash());
+        // played without one-hit KOs
+        assert_eq!(host.battle.field.clauses, PVP_CLAUSES.to_vec());
+    }
+
+    #[test]
+    fn verify_test() {
+        let (world, mut host, mut guest) = fake_ends();
+        let action = guest.battle.enemy.attributes.actions[0];
+        let (code, _) = guest.choose(action, &world.actions).unwrap();
+        assert_eq!(
+            host.import(&TurnCode { turn: 1, ..code }.to_string(), &world.actions),
+            Err(CorrespondenceError::WrongTurn { expected: 0, got: 1 })
+        );
+        assert_eq!(host.import(&TurnCode { hash: code.hash + 1, ..code }.to_string(), &world.actions), Err(CorrespondenceError::Desync));
+        let unknown = world.actions.ids().find(|action| !guest.battle.enemy.attributes.actions.contains(action)).unwrap();
+        assert_eq!(host.import(&TurnCode { action: unknown, ..code }.to_string(), &world.actions), Err(CorrespondenceError::IllegalMove(unknown)));
+        assert_eq!(host.import(&code.to_string(), &world.actions), Ok(None));
+
+        let mine = host.battle.player.attributes.actions[0];
+        assert!(host.choose(mine, &world.actions).unwrap().1.is_some());
+        assert_eq!(host.turn, 1);
+    }
+}
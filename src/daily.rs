@@ -0,0 +1,122 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+
+use crate::clauses::CLAUSES;
+use crate::content::id::fnv1a;
+use crate::modifiers::Modifier;
+use crate::rules::Ruleset;
+use crate::seed::Seed;
+
+static MILLIS_PER_DAY: f64 = 86_400_000.0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Weekday { Monday, Tuesday, Wednesday, Thursday, Friday, Saturday, Sunday }
+
+static WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday, Weekday::Thursday, Weekday::Friday, Weekday::Saturday, Weekday::Sunday,
+];
+
+// the run everyone plays on the same day: its seed and its modifier of the day both come from the
+// date, so nobody has to share anything to race each other
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Daily {
+    // days since 1970-01-01, in utc so it's the same day everywhere
+    pub day: u64,
+}
+
+impl Daily {
+    // from a unix timestamp in milliseconds, like javascript's `Date.now()`
+    pub fn from_millis(millis: f64) -> Daily {
+        Daily { day: (millis / MILLIS_PER_DAY).floor().max(0.0) as u64 }
+    }
+
+    pub fn seed(&self) -> Seed {
+        Seed(fnv1a(&self.day.to_le_bytes()))
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a thursday
+        WEEKDAYS[((self.day + 3) % 7) as usize]
+    }
+
+    // the year, month and day
+    pub fn date(&self) -> (u64, u64, u64) {
+        // howard hinnant's `civil_from_days`, for days that can't be before the epoch
+        let z = self.day + 719_468;
+        let era = z / 146_097;
+        let day_of_era = z - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+
+    // inverse mondays and chaos fridays; every other day gets a clause picked by the date's seed
+    pub fn modifier(&self) -> Modifier {
+        match self.weekday() {
+            Weekday::Monday => Modifier::Inverse,
+            Weekday::Friday => Modifier::Chaos,
+            _ => Modifier::Clause(*CLAUSES.choose(&mut self.seed().rng()).unwrap()),
+        }
+    }
+
+    // turns on the day's modifier, so it's played with and recorded with the run's result like
+    // any other
+    pub fn apply(&self, rules: &mut Ruleset) {
+        match self.modifier() {
+            Modifier::Inverse => rules.inverse = true,
+            Modifier::Chaos => rules.chaos = true,
+            Modifier::Clause(clause) if !rules.clauses.contains(&clause) => rules.clauses.push(clause),
+            _ => (),
+        }
+    }
+}
+
+impl fmt::Display for Daily {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = self.date();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+#[cfg(test)]
+mod daily_tests {
+    use super::*;
+
+    use crate::modifiers::active;
+    use crate::ai::Difficulty;
+
+    #[test]
+    fn date_test() {
+        assert_eq!(Daily { day: 0 }.to_string(), "1970-01-01");
+        assert_eq!(Daily { day: 0 }.weekday(), Weekday::Thursday);
+        assert_eq!(Daily { day: 11_016 }.to_string(), "2000-02-29");
+        assert_eq!(Daily { day: 20_742 }.to_string(), "2026-10-16");
+        assert_eq!(Daily::from_millis(20_742.5 * MILLIS_PER_DAY), Daily { day: 20_742 });
+    }
+
+    #[test]
+    fn modifier_test() {
+        let monday = Daily { day: 20_738 };
+        assert_eq!(monday.weekday(), Weekday::Monday);
+        assert_eq!(monday.modifier(), Modifier::Inverse);
+        assert_eq!(Daily { day: 20_742 }.modifier(), Modifier::Chaos);
+        // the rest of the week is the same for everyone on the day, but changes from day to day
+        let tuesday = Daily { day: 20_739 };
+        assert_eq!(tuesday.modifier(), tuesday.modifier());
+        assert_ne!(tuesday.seed(), monday.seed());
+
+        // and it's recorded with the run like any other modifier
+        let mut rules = Ruleset::default();
+        monday.apply(&mut rules);
+        assert!(active(Difficulty::Normal, &rules).contains(&Modifier::Inverse));
+        tuesday.apply(&mut rules);
+        tuesday.apply(&mut rules);
+        assert_eq!(rules.clauses.len(), 1);
+        assert!(active(Difficulty::Normal, &rules).contains(&tuesday.modifier()));
+    }
+}
@@ -0,0 +1,28 @@
+// `trunk serve` copies resources over and rebuilds whenever they change, so a modder can keep the
+// world they're working on here
+pub static DEV_WORLD_URL: &str = "resources/world.json";
+// how long to wait between checks for a new definition
+pub static DEV_POLL_MILLIS: u32 = 1000;
+
+// remembers the last fetch, so the world is only reloaded (or the error only shown) when the file
+// changes
+#[derive(Clone, Debug, Default)]
+pub struct WorldWatcher {
+    last: Option<Result<String, String>>,
+}
+
+impl WorldWatcher {
+    // the new fetch, if it's different from the last one
+    pub fn changed(&mut self, fetched: Result<String, String>) -> Option<&Result<String, String>> {
+        if self.last.as_ref() == Some(&fetched) {
+            return None;
+        }
+        self.last = Some(fetched);
+        self.last.as_ref()
+    }
+
+    // the next fetch is reloaded even if it hasn't changed, e.g. after starting a new run
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
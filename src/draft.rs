@@ -0,0 +1,219 @@
+use std::fmt;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{ActionId, Actions, ItemId, Stat, States};
+use crate::gauntlet::treasure;
+use crate::items::Inventory;
+use crate::onion::{OnionCharacter, OnionWorld, learn_moves};
+
+// how many upgrades there are to pick from after a win
+pub static DRAFT_SIZE: usize = 3;
+// rolls that came up the same as an earlier offer are re-rolled at most this many times
+static REROLLS: usize = 10;
+
+static STATS: [Stat; 5] = [Stat::Attack, Stat::Defense, Stat::SpecialAttack, Stat::SpecialDefense, Stat::Speed];
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Rarity { Common, Uncommon, Rare }
+
+// how often each rarity comes up, out of 100
+static RARITY_WEIGHTS: [(Rarity, u32); 3] = [
+    (Rarity::Common, 60),
+    (Rarity::Uncommon, 30),
+    (Rarity::Rare, 10),
+];
+
+impl Rarity {
+    // how much bigger a boost of this rarity is than a common one
+    fn scale(&self) -> u32 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Uncommon => 2,
+            Rarity::Rare => 4,
+        }
+    }
+}
+
+impl fmt::Display for Rarity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Upgrade {
+    // raises one of the kaizo's stats for good
+    Boost(Stat, u32),
+    // more max health, and as much health again
+    Health(u32),
+    // a move from the world's pool that the kaizo doesn't know
+    Move(ActionId),
+    // some of one of the world's items, for the bag
+    Item(ItemId, u32),
+}
+
+// an upgrade on offer, and how lucky it was
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Offer {
+    pub upgrade: Upgrade,
+    pub rarity: Rarity,
+}
+
+fn roll_rarity<R: Rng + ?Sized>(rng: &mut R) -> Rarity {
+    let mut roll = rng.gen_range(0..RARITY_WEIGHTS.iter().map(|(_, weight)| weight).sum::<u32>());
+    for (rarity, weight) in RARITY_WEIGHTS {
+        if roll < weight {
+            return rarity;
+        }
+        roll -= weight;
+    }
+    Rarity::Common
+}
+
+// boosts are a share of what the kaizo already has, so they matter as much at every level
+fn boost(value: u32, rarity: Rarity) -> u32 {
+    std::cmp::max(1, value * 5 * rarity.scale() / 100)
+}
+
+fn roll_upgrade<R: Rng + ?Sized>(world: &OnionWorld, character: &OnionCharacter, rarity: Rarity, rng: &mut R) -> Upgrade {
+    // moves only come up uncommon or better, and are more health when there's nothing to learn
+    let kinds = if rarity == Rarity::Common { 3 } else { 4 };
+    match rng.gen_range(0..kinds) {
+        0 => {
+            let stat = STATS[rng.gen_range(0..STATS.len())];
+            Upgrade::Boost(stat, boost(*character.attributes.stats.get(stat), rarity))
+        },
+        1 => Upgrade::Health(boost(character.attributes.stats.health, rarity)),
+        2 if !world.items.is_empty() => Upgrade::Item(rng.gen_range(0..world.items.len()), rarity.scale()),
+        _ => match treasure(world, character, rng) {
+            Some(action) => Upgrade::Move(action),
+            None => Upgrade::Health(boost(character.attributes.stats.health, rarity)),
+        },
+    }
+}
+
+// the upgrades to pick one of after a win, rolled from the world's moves and items and sized for
+// the kaizo that won
+pub fn draft<R: Rng + ?Sized>(world: &OnionWorld, character: &OnionCharacter, rng: &mut R) -> Vec<Offer> {
+    let mut offers: Vec<Offer> = Vec::new();
+    while offers.len() < DRAFT_SIZE {
+        let rarity = roll_rarity(rng);
+        let mut upgrade = roll_upgrade(world, character, rarity, rng);
+        for _ in 0..REROLLS {
+            if !offers.iter().any(|offer| offer.upgrade == upgrade) {
+                break;
+            }
+            upgrade = roll_upgrade(world, character, rarity, rng);
+        }
+        offers.push(Offer { upgrade, rarity });
+    }
+    offers
+}
+
+impl Upgrade {
+    pub fn description(&self, world: &OnionWorld) -> String {
+        match self {
+            Upgrade::Boost(stat, amount) => format!("+{} {}.", amount, stat),
+            Upgrade::Health(amount) => format!("+{} max health.", amount),
+            Upgrade::Move(action) => format!("Learn {}.", world.actions[*action].name()),
+            Upgrade::Item(item, count) => format!("{} {} for the bag.", count, world.items[*item].name),
+        }
+    }
+
+    // hands back any moves that are waiting for the kaizo to forget one
+    pub fn apply(&self, character: &mut OnionCharacter, inventory: &mut Inventory, world: &OnionWorld) -> (States, Actions) {
+        match *self {
+            Upgrade::Boost(stat, amount) => {
+                *character.attributes.bonus.get_mut(stat) += amount;
+                character.recalculate_stats();
+                (vec![format!("{}'s {} rose by {}.", character.name, stat, amount)], vec![])
+            },
+            Upgrade::Health(amount) => {
+                character.attributes.bonus.health += amount;
+                character.recalculate_stats();
+                character.state.health += amount as i32;
+                (vec![format!("{}'s max health rose by {}.", character.name, amount)], vec![])
+            },
+            Upgrade::Move(action) => learn_moves(character, &[action], &world.actions),
+            Upgrade::Item(item, count) => {
+                inventory.add(item, count);
+                (vec![format!("Got {} {}.", count, world.items[item].name)], vec![])
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod draft_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::core::Stats;
+    use crate::onion::Experience;
+
+    fn fake_world() -> (OnionWorld, OnionCharacter, StdRng) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let character = world.sample_at_level(20, &mut rng);
+        (world, character, rng)
+    }
+
+    #[test]
+    fn draft_test() {
+        let (world, character, mut rng) = fake_world();
+        let mut rarities = Vec::new();
+        for _ in 0..100 {
+            let offers = draft(&world, &character, &mut rng);
+            assert_eq!(offers.len(), DRAFT_SIZE);
+            for (index, offer) in offers.iter().enumerate() {
+                assert!(!offers[..index].iter().any(|earlier| earlier.upgrade == offer.upgrade));
+                if let Upgrade::Move(action) = offer.upgrade {
+                    assert!(!character.attributes.actions.contains(&action));
+                    assert_ne!(offer.rarity, Rarity::Common);
+                }
+            }
+            rarities.extend(offers.iter().map(|offer| offer.rarity));
+        }
+        // rarer offers come up less
+        let count = |rarity| rarities.iter().filter(|other| **other == rarity).count();
+        assert!(count(Rarity::Common) > count(Rarity::Uncommon));
+        assert!(count(Rarity::Uncommon) > count(Rarity::Rare));
+        assert!(count(Rarity::Rare) > 0);
+    }
+
+    #[test]
+    fn apply_test() {
+        let (world, mut character, _) = fake_world();
+        let mut inventory = Inventory::default();
+        let attack = character.attributes.stats.attack;
+        Upgrade::Boost(Stat::Attack, 5).apply(&mut character, &mut inventory, &world);
+        assert_eq!(character.attributes.stats.attack, attack + 5);
+
+        let health = character.attributes.stats.health;
+        Upgrade::Health(10).apply(&mut character, &mut inventory, &world);
+        assert_eq!(character.attributes.stats.health, health + 10);
+        assert_eq!(character.state.health, (health + 10) as i32);
+
+        // the upgrades outlast the next level up
+        let level = character.attributes.level;
+        character.gain_experience(character.experience_to_next_level());
+        assert_eq!(character.attributes.level, level + 1);
+        let mut plain = character.clone();
+        plain.attributes.bonus = Stats::default();
+        plain.recalculate_stats();
+        assert_eq!(character.attributes.stats.attack, plain.attributes.stats.attack + 5);
+        assert_eq!(character.attributes.stats.health, plain.attributes.stats.health + 10);
+
+        Upgrade::Item(1, 2).apply(&mut character, &mut inventory, &world);
+        assert_eq!(inventory.count(1), 2);
+
+        let action = treasure(&world, &character, &mut StdRng::seed_from_u64(1)).unwrap();
+        let (_, waiting) = Upgrade::Move(action).apply(&mut character, &mut inventory, &world);
+        assert!(character.attributes.actions.contains(&action) || waiting == vec![action]);
+    }
+}
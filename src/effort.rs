@@ -0,0 +1,86 @@
+use crate::core::{States, Stats};
+use crate::onion::OnionCharacter;
+
+// effort a win earns, all of it in the beaten species' best stat
+pub static EFFORT_PER_WIN: u32 = 4;
+// the most effort a single stat can hold. what it adds to the stat is worked out with the rest of
+// them, in `Character::scaled_stats`
+pub static MAX_EFFORT: u32 = 64;
+
+static STAT_NAMES: [&str; 6] = ["health", "attack", "defense", "special attack", "special defense", "speed"];
+
+// the stat a species has the most of, as an index into its stats as a list; ties go to the first
+pub fn trained_stat(stats: &Stats<f64>) -> usize {
+    Vec::from(stats).iter()
+        .enumerate()
+        .fold((0, f64::MIN), |best, (index, stat)| if *stat > best.1 { (index, *stat) } else { best })
+        .0
+}
+
+// the victor trains whatever the beaten kaizo was best at, up to the cap
+pub fn train(victor: &mut OnionCharacter, defeated: &OnionCharacter) -> States {
+    let stat = trained_stat(&defeated.species.stats);
+    let mut effort: Vec<u32> = victor.attributes.effort.into();
+    if effort[stat] >= MAX_EFFORT {
+        return vec![];
+    }
+    effort[stat] = (effort[stat] + EFFORT_PER_WIN).min(MAX_EFFORT);
+    victor.attributes.effort = effort.into();
+    vec![format!("{} trained its {}.", victor.name, STAT_NAMES[stat])]
+}
+
+#[cfg(test)]
+mod effort_tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::core::Species;
+    use crate::core::{EFFORT_PER_POINT, SCALING_FACTOR};
+    use crate::onion::{Experience, OnionBattle, set_level, testing};
+
+    fn fake_character(level: u32) -> OnionCharacter {
+        let mut character = testing::fake_character_with_bst(400);
+        set_level(&mut character, level);
+        character
+    }
+
+    #[test]
+    fn train_test() {
+        assert_eq!(trained_stat(&Stats::from_values(0.1, 0.2, 0.3, 0.3, 0.0, 0.1)), 2);
+        let mut victor = fake_character(5);
+        let mut defeated = fake_character(5);
+        defeated.species = Arc::new(Species {
+            stats: Stats::from_values(0.1, 0.1, 0.1, 0.1, 0.1, 0.5),
+            ..(*defeated.species).clone()
+        });
+        assert_eq!(train(&mut victor, &defeated), vec!["fake trained its speed.".to_string()]);
+        assert_eq!(victor.attributes.effort.speed, EFFORT_PER_WIN);
+        for _ in 0..MAX_EFFORT {
+            train(&mut victor, &defeated);
+        }
+        assert_eq!(victor.attributes.effort.speed, MAX_EFFORT);
+        assert!(train(&mut victor, &defeated).is_empty());
+    }
+
+    #[test]
+    fn bonus_test() {
+        let mut character = fake_character(5);
+        let untrained = character.scaled_stats(5 * SCALING_FACTOR).speed;
+        character.attributes.effort.speed = MAX_EFFORT;
+        // it counts for every level, including the ones from before it was trained
+        character.recalculate_stats();
+        assert_eq!(character.attributes.stats.speed, untrained + 5 * MAX_EFFORT / EFFORT_PER_POINT);
+        character.gain_experience(character.experience_to_next_level());
+        assert_eq!(character.attributes.stats.speed, character.scaled_stats(6 * SCALING_FACTOR).speed);
+    }
+
+    #[test]
+    fn battle_test() {
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.enemy.state.health = 0;
+        let (_, logs) = battle.end_turn();
+        assert!(logs.contains(&"fake trained its health.".to_string()));
+        assert_eq!(battle.player.attributes.effort.health, EFFORT_PER_WIN);
+    }
+}
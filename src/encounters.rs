@@ -0,0 +1,143 @@
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::bosses::Phases;
+use crate::core::{Stat, States};
+use crate::onion::{OnionBattle, OnionBattleState, stage_log};
+use crate::rules::BattleHook;
+
+// wild kaizo try to get away once they're down to 1/FLEE_FRACTION of their health
+pub static FLEE_FRACTION: i32 = 4;
+pub static FLEE_CHANCE: f64 = 0.25;
+// bosses give the player this many turns before they start ramping up
+pub static ENRAGE_TURNS: u32 = 8;
+
+// who the battle is against, which decides the hooks it's played with
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum EncounterKind {
+    // a mirror or one of the gauntlet's masters
+    #[default]
+    Trainer,
+    Wild,
+    // the last master of the gauntlet, or a boss blocking the way
+    Boss,
+}
+
+impl EncounterKind {
+    pub fn hooks(&self) -> Vec<Box<dyn BattleHook>> {
+        match self {
+            EncounterKind::Trainer => vec![],
+            EncounterKind::Wild => vec![Box::new(Flee { fraction: FLEE_FRACTION, chance: FLEE_CHANCE })],
+            EncounterKind::Boss => vec![Box::new(Enrage { turns: ENRAGE_TURNS }), Box::new(Phases)],
+        }
+    }
+}
+
+// a hurt enemy might run off, taking its experience and the chance to recruit it with it
+pub struct Flee {
+    pub fraction: i32,
+    pub chance: f64,
+}
+
+impl BattleHook for Flee {
+    fn name(&self) -> String { "Flee".to_string() }
+
+    fn on_turn_end(&self, battle: &mut OnionBattle, rng: &mut dyn RngCore) -> States {
+        let enemy = &battle.enemy;
+        if enemy.state.health * self.fraction > enemy.attributes.stats.health as i32 || !rng.gen_bool(self.chance) {
+            return vec![];
+        }
+        battle.escaped = true;
+        vec![format!("The wild {} fled!", enemy.name)]
+    }
+}
+
+// once the battle has gone on `turns` turns, the enemy's attack and speed go up every turn
+pub struct Enrage {
+    pub turns: u32,
+}
+
+impl BattleHook for Enrage {
+    fn name(&self) -> String { "Enrage".to_string() }
+
+    fn on_turn_end(&self, battle: &mut OnionBattle, _: &mut dyn RngCore) -> States {
+        if battle.turn < self.turns {
+            return vec![];
+        }
+        let mut logs = Vec::new();
+        if battle.turn == self.turns {
+            logs.push(format!("{} is enraged!", battle.enemy.name));
+        }
+        for stat in [Stat::Attack, Stat::Speed] {
+            let moved = battle.enemy.change_stage(stat, 1);
+            if moved != 0 {
+                logs.push(stage_log(&battle.enemy, stat, 1, moved));
+            }
+        }
+        logs
+    }
+}
+
+// runs the encounter's hooks once a turn has ended with the battle still going, which can end it
+pub fn end_turn(battle: &mut OnionBattle, rng: &mut dyn RngCore) -> (OnionBattleState, States) {
+    let logs = battle.kind.hooks().iter().flat_map(|hook| hook.on_turn_end(battle, rng)).collect();
+    (battle.battle_state(), logs)
+}
+
+#[cfg(test)]
+mod encounters_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::onion::{ActionPool, testing};
+
+    fn fake_battle(kind: EncounterKind) -> OnionBattle {
+        let mut battle = OnionBattle::new(testing::fake_character(), testing::fake_character());
+        for character in [&mut battle.player, &mut battle.enemy] {
+            character.attributes.stats.health = 40;
+            character.refresh();
+        }
+        battle.kind = kind;
+        battle
+    }
+
+    #[test]
+    fn flee_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut battle = fake_battle(EncounterKind::Wild);
+        // healthy kaizo never run
+        for _ in 0..100 {
+            assert_eq!(end_turn(&mut battle, &mut rng), (OnionBattleState::InProcess, vec![]));
+        }
+
+        battle.enemy.state.health = 10;
+        let (state, logs) = (0..100).map(|_| end_turn(&mut battle, &mut rng)).find(|(_, logs)| !logs.is_empty()).unwrap();
+        assert_eq!(state, OnionBattleState::Escaped);
+        assert_eq!(logs, vec!["The wild fake fled!"]);
+        // and once it's gone nobody gets to act
+        assert!(battle.player_turn(&ActionPool::with_attacks(vec![])[0], &mut rng).is_empty());
+
+        // trainers stay to the end
+        let mut battle = fake_battle(EncounterKind::Trainer);
+        battle.enemy.state.health = 1;
+        for _ in 0..100 {
+            assert_eq!(end_turn(&mut battle, &mut rng), (OnionBattleState::InProcess, vec![]));
+        }
+    }
+
+    #[test]
+    fn enrage_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut battle = fake_battle(EncounterKind::Boss);
+        battle.turn = ENRAGE_TURNS - 1;
+        assert!(end_turn(&mut battle, &mut rng).1.is_empty());
+
+        battle.turn += 1;
+        assert_eq!(end_turn(&mut battle, &mut rng).1, vec!["fake is enraged!", "fake's attack rose.", "fake's speed rose."]);
+        battle.turn += 1;
+        assert_eq!(end_turn(&mut battle, &mut rng).1.len(), 2);
+        assert_eq!((battle.enemy.state.stages.attack, battle.enemy.state.stages.speed), (2, 2));
+    }
+}
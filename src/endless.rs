@@ -0,0 +1,142 @@
+use std::fmt;
+
+use rand::{Rng, RngCore};
+use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Stat, States};
+use crate::gauntlet::Run;
+use crate::onion::{OnionBattle, OnionWorld, Status};
+use crate::rules::{BattleHook, Ruleset};
+
+static STATS: [Stat; 5] = [Stat::Attack, Stat::Defense, Stat::SpecialAttack, Stat::SpecialDefense, Stat::Speed];
+// defend is only ever put on yourself, so it's never taken away
+static IMMUNITIES: [Status; 6] = [Status::Bleed, Status::Stun, Status::Burn, Status::Freeze, Status::Confuse, Status::Poison];
+
+// one more thing stacked against the player each time an endless gauntlet goes around; the same
+// curse can come up more than once, and stacks
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Curse {
+    // enemies start every battle a stage up
+    EnemyBoost(Stat),
+    // the player starts every battle a stage down
+    PlayerDebuff(Stat),
+    // enemies can't be given the status
+    Immunity(Status),
+}
+
+impl fmt::Display for Curse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Curse::EnemyBoost(stat) => write!(f, "Enemy {} up", stat),
+            Curse::PlayerDebuff(stat) => write!(f, "Your {} down", stat),
+            Curse::Immunity(status) => write!(f, "Enemy {} immunity", format!("{:?}", status).to_lowercase()),
+        }
+    }
+}
+
+impl Distribution<Curse> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Curse {
+        match rng.gen_range(0..STATS.len() * 2 + IMMUNITIES.len()) {
+            roll if roll < STATS.len() => Curse::EnemyBoost(STATS[roll]),
+            roll if roll < STATS.len() * 2 => Curse::PlayerDebuff(STATS[roll - STATS.len()]),
+            roll => Curse::Immunity(IMMUNITIES[roll - STATS.len() * 2]),
+        }
+    }
+}
+
+impl BattleHook for Curse {
+    fn name(&self) -> String { self.to_string() }
+
+    fn on_battle_start(&self, battle: &mut OnionBattle, _: &OnionWorld, _: &mut dyn RngCore) -> States {
+        match *self {
+            Curse::EnemyBoost(stat) => {
+                battle.enemy.change_stage(stat, 1);
+                vec![format!("The curse raised {}'s {}.", battle.enemy.name, stat)]
+            },
+            Curse::PlayerDebuff(stat) => {
+                battle.player.change_stage(stat, -1);
+                vec![format!("The curse lowered {}'s {}.", battle.player.name, stat)]
+            },
+            // stacking the same immunity twice does nothing more, so it isn't logged again
+            Curse::Immunity(status) => match battle.enemy.state.immune.insert(status, 0) {
+                None => vec![format!("The curse shields {} from {}.", battle.enemy.name, format!("{:?}", status).to_lowercase())],
+                Some(_) => vec![],
+            },
+        }
+    }
+}
+
+// starts the next time around once an endless gauntlet is won: a new curse is stacked onto the
+// rules and a new map is rolled from `level`
+pub fn next_cycle<R: Rng + ?Sized>(rules: &mut Ruleset, world: &OnionWorld, level: u32, rng: &mut R) -> (Run, States) {
+    let curse: Curse = rng.gen();
+    rules.curses.push(curse);
+    let logs = vec![
+        format!("The gauntlet begins again, cycle {}.", rules.curses.len() + 1),
+        format!("A new curse: {}.", curse),
+    ];
+    (Run::generate(world, level, rng), logs)
+}
+
+#[cfg(test)]
+mod endless_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::gauntlet::Outcome;
+
+    fn fake_battle(world: &OnionWorld, rng: &mut StdRng) -> OnionBattle {
+        OnionBattle::new(world.sample_at_level(5, rng), world.sample_at_level(5, rng))
+    }
+
+    #[test]
+    fn curse_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+
+        let rules = Ruleset {
+            curses: vec![
+                Curse::EnemyBoost(Stat::Attack),
+                Curse::EnemyBoost(Stat::Attack),
+                Curse::PlayerDebuff(Stat::Speed),
+                Curse::Immunity(Status::Stun),
+                Curse::Immunity(Status::Stun),
+            ],
+            ..Ruleset::default()
+        };
+        let logs = rules.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(logs.len(), 4);
+        // the same curse twice stacks
+        assert_eq!(*battle.enemy.state.stages.get(Stat::Attack), 2);
+        assert_eq!(*battle.player.state.stages.get(Stat::Speed), -1);
+        assert!(battle.enemy.state.immune.contains_key(&Status::Stun));
+        assert!(!battle.player.state.immune.contains_key(&Status::Stun));
+
+        // immunities wear off with the battle
+        battle.enemy.refresh();
+        assert!(!battle.enemy.state.immune.contains_key(&Status::Stun));
+    }
+
+    #[test]
+    fn next_cycle_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut rules = Ruleset { endless: true, ..Ruleset::default() };
+
+        let (run, logs) = next_cycle(&mut rules, &world, 30, &mut rng);
+        assert_eq!(rules.curses.len(), 1);
+        assert_eq!(logs.len(), 2);
+        assert_eq!(run.outcome, Outcome::Fighting(0));
+        // the new map is rolled from the level the last one was won at
+        for enemy in run.floors[0].iter().filter_map(|node| node.enemy.as_ref()) {
+            assert!(enemy.attributes.level >= 30);
+        }
+
+        next_cycle(&mut rules, &world, 30, &mut rng);
+        assert_eq!(rules.curses.len(), 2);
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+// the app's scenes, without what's on them
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SceneKind {
+    Title,
+    Menu,
+    Map,
+    Shop,
+    Battle,
+    Learn,
+    Draft,
+    TeamBuilder,
+    War,
+    Mods,
+    Replay,
+    Stats,
+}
+
+// the app's messages, named after the scene whose buttons send them
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageKind {
+    Title,
+    Menu,
+    Map,
+    Shop,
+    Battle,
+    Learn,
+    Draft,
+    Team,
+    War,
+    Mods,
+    Replay,
+    Stats,
+}
+
+// a message that's valid where it arrived, and the scenes handling it can leave the app in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    pub from: SceneKind,
+    pub message: MessageKind,
+    pub to: &'static [SceneKind],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvalidTransition {
+    // e.g. a download that finished after the player left the menu
+    WrongScene { scene: SceneKind, message: MessageKind },
+    // handling the message left the app in a scene it shouldn't be able to get to from there
+    Unreachable { from: SceneKind, message: MessageKind, to: SceneKind },
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidTransition::WrongScene { scene, message } => write!(f, "A {:?} message arrived in the {:?} scene and was dropped.", message, scene),
+            InvalidTransition::Unreachable { from, message, to } => write!(f, "A {:?} message took the app from the {:?} scene to the {:?} scene, which it shouldn't.", message, from, to),
+        }
+    }
+}
+
+impl Transition {
+    pub fn check(&self, to: SceneKind) -> Result<(), InvalidTransition> {
+        if self.to.contains(&to) {
+            Ok(())
+        } else {
+            Err(InvalidTransition::Unreachable { from: self.from, message: self.message, to })
+        }
+    }
+}
+
+// every message belongs to one scene. starting a new run goes back to the title, and continuing an
+// old one can land in the middle of a battle
+pub fn transition(from: SceneKind, message: MessageKind) -> Result<Transition, InvalidTransition> {
+    let to: &'static [SceneKind] = match (from, message) {
+        (SceneKind::Title, MessageKind::Title) => &[SceneKind::Title, SceneKind::Menu],
+        (SceneKind::Menu, MessageKind::Menu) => &[
+            SceneKind::Title, SceneKind::Menu, SceneKind::Map, SceneKind::Shop, SceneKind::Battle,
+            SceneKind::TeamBuilder, SceneKind::War, SceneKind::Mods, SceneKind::Replay, SceneKind::Stats,
+        ],
+        (SceneKind::Map, MessageKind::Map) => &[SceneKind::Menu, SceneKind::Map, SceneKind::Battle, SceneKind::Learn],
+        (SceneKind::Shop, MessageKind::Shop) => &[SceneKind::Menu, SceneKind::Shop, SceneKind::Learn],
+        (SceneKind::Battle, MessageKind::Battle) => &[SceneKind::Menu, SceneKind::Battle, SceneKind::Learn, SceneKind::Draft],
+        (SceneKind::Learn, MessageKind::Learn) => &[SceneKind::Menu, SceneKind::Learn],
+        (SceneKind::Draft, MessageKind::Draft) => &[SceneKind::Menu, SceneKind::Learn, SceneKind::Draft],
+        (SceneKind::TeamBuilder, MessageKind::Team) => &[SceneKind::Menu, SceneKind::TeamBuilder],
+        (SceneKind::War, MessageKind::War) => &[SceneKind::Menu, SceneKind::War],
+        (SceneKind::Mods, MessageKind::Mods) => &[SceneKind::Menu, SceneKind::Mods],
+        (SceneKind::Replay, MessageKind::Replay) => &[SceneKind::Menu, SceneKind::Replay],
+        (SceneKind::Stats, MessageKind::Stats) => &[SceneKind::Menu],
+        (scene, message) => return Err(InvalidTransition::WrongScene { scene, message }),
+    };
+    Ok(Transition { from, message, to })
+}
+
+#[cfg(test)]
+mod flow_tests {
+    use super::*;
+
+    #[test]
+    fn transition_test() {
+        let battle = transition(SceneKind::Battle, MessageKind::Battle).unwrap();
+        assert_eq!(battle.check(SceneKind::Draft), Ok(()));
+        assert_eq!(
+            battle.check(SceneKind::Mods),
+            Err(InvalidTransition::Unreachable { from: SceneKind::Battle, message: MessageKind::Battle, to: SceneKind::Mods })
+        );
+
+        // tutors bought in the shop can have a move waiting to be learned
+        assert_eq!(transition(SceneKind::Shop, MessageKind::Shop).unwrap().check(SceneKind::Learn), Ok(()));
+
+        // every scene can stay where it is
+        for (scene, message) in [(SceneKind::Title, MessageKind::Title), (SceneKind::TeamBuilder, MessageKind::Team), (SceneKind::Map, MessageKind::Map)] {
+            assert_eq!(transition(scene, message).unwrap().check(scene), Ok(()));
+        }
+    }
+
+    #[test]
+    fn wrong_scene_test() {
+        let invalid = transition(SceneKind::Battle, MessageKind::Menu).unwrap_err();
+        assert_eq!(invalid, InvalidTransition::WrongScene { scene: SceneKind::Battle, message: MessageKind::Menu });
+        assert_eq!(invalid.to_string(), "A Menu message arrived in the Battle scene and was dropped.");
+        assert!(transition(SceneKind::Learn, MessageKind::Draft).is_err());
+    }
+}
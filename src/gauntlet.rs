@@ -0,0 +1,329 @@
+use rand::Rng;
+use rand::distributions::Distribution;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::Difficulty;
+use crate::bosses::{self, Phase};
+use crate::core::ActionId;
+use crate::onion::{OnionCharacter, OnionWorld, set_level};
+
+// floors in a gauntlet, ending in the final boss
+pub static GAUNTLET_LENGTH: usize = 10;
+// every this many floors the last one is a boss
+pub static BOSS_EVERY: usize = 5;
+// how much higher each floor's enemies are than the one before's, and how much higher again elites
+// and bosses are
+pub static LEVELS_PER_BATTLE: u32 = 2;
+pub static ELITE_LEVELS: u32 = 2;
+pub static BOSS_LEVELS: u32 = 3;
+// the most nodes on a floor that isn't a boss
+pub static MAX_WIDTH: usize = 3;
+// the experience every party member gets from resting, on top of being healed
+pub static REST_EXPERIENCE: u32 = 50;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NodeKind {
+    Battle,
+    // a tougher master that leaves a move behind
+    Elite,
+    // the party heals and trains instead of battling
+    Rest,
+    // a wild kaizo that can be recruited
+    Scout,
+    // a move to learn, for free
+    Treasure,
+    Boss,
+}
+
+// how often each kind comes up on a floor that isn't a boss, out of 20
+static KIND_WEIGHTS: [(NodeKind, u32); 5] = [
+    (NodeKind::Battle, 9),
+    (NodeKind::Elite, 3),
+    (NodeKind::Rest, 2),
+    (NodeKind::Scout, 3),
+    (NodeKind::Treasure, 3),
+];
+
+impl NodeKind {
+    pub fn description(&self) -> String {
+        match self {
+            NodeKind::Battle => "Battle a kaizo master.",
+            NodeKind::Elite => "Battle a tougher master, and find a move to learn if you win.",
+            NodeKind::Rest => "Heal the whole party and train it a little.",
+            NodeKind::Scout => "Find a wild kaizo to recruit.",
+            NodeKind::Treasure => "Find a move for your lead to learn, and an item.",
+            NodeKind::Boss => "Battle a boss.",
+        }.to_string()
+    }
+
+    // whether clearing it means winning a battle
+    pub fn is_battle(&self) -> bool {
+        matches!(self, NodeKind::Battle | NodeKind::Elite | NodeKind::Boss)
+    }
+}
+
+// one stop on the map, rolled when the map is so every run on a seed finds the same ones
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub kind: NodeKind,
+    // who's fought here, for the kinds with a battle
+    pub enemy: Option<OnionCharacter>,
+    pub ai: Difficulty,
+    // the nodes on the next floor this one leads to
+    pub next: Vec<usize>,
+    // how a boss's moves change as it's worn down; older runs' bosses don't have any
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    // the floor that's up next
+    Fighting(usize),
+    Won,
+    // the floor that was lost on
+    Lost(usize),
+}
+
+// floors of nodes that get harder as they go, joined into paths with bosses along the way; the
+// player picks one node a floor and losing a battle ends the run
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub floors: Vec<Vec<Node>>,
+    // the node picked on each floor so far, including the one being played
+    pub path: Vec<usize>,
+    pub outcome: Outcome,
+}
+
+fn roll_kind<R: Rng + ?Sized>(rng: &mut R) -> NodeKind {
+    let mut roll = rng.gen_range(0..KIND_WEIGHTS.iter().map(|(_, weight)| weight).sum::<u32>());
+    for (kind, weight) in KIND_WEIGHTS {
+        if roll < weight {
+            return kind;
+        }
+        roll -= weight;
+    }
+    NodeKind::Battle
+}
+
+// the node on a floor `to` wide that's across from `index` on a floor `from` wide
+fn across(index: usize, from: usize, to: usize) -> usize {
+    if from <= 1 { 0 } else { index * (to - 1) / (from - 1) }
+}
+
+impl Run {
+    // `level` is the level the player's kaizo starts the run at
+    pub fn generate<R: Rng + ?Sized>(world: &OnionWorld, level: u32, rng: &mut R) -> Run {
+        let mut floors: Vec<Vec<Node>> = Vec::new();
+        for floor in 0..GAUNTLET_LENGTH {
+            let boss = (floor + 1) % BOSS_EVERY == 0;
+            let width = if boss { 1 } else { rng.gen_range(2..=MAX_WIDTH) };
+            let mut nodes = Vec::new();
+            for _ in 0..width {
+                let kind = if boss { NodeKind::Boss } else { roll_kind(rng) };
+                let ai = match kind {
+                    NodeKind::Boss if floor + 1 == GAUNTLET_LENGTH => Difficulty::Kaizo,
+                    NodeKind::Boss | NodeKind::Elite => Difficulty::Hard,
+                    NodeKind::Scout => Difficulty::Easy,
+                    _ => Difficulty::for_wins(floor as u32),
+                };
+                let mut phases = Vec::new();
+                let enemy = match kind {
+                    NodeKind::Rest | NodeKind::Treasure => None,
+                    NodeKind::Boss => {
+                        let (enemy, boss_phases) = bosses::generate(world, level + LEVELS_PER_BATTLE * floor as u32 + BOSS_LEVELS, rng);
+                        phases = boss_phases;
+                        Some(enemy)
+                    },
+                    _ => {
+                        let mut enemy: OnionCharacter = world.sample(&mut *rng);
+                        let mut level = level + LEVELS_PER_BATTLE * floor as u32;
+                        if kind == NodeKind::Elite {
+                            level += ELITE_LEVELS;
+                            enemy.name = format!("Elite {}", enemy.name).into();
+                        }
+                        set_level(&mut enemy, level);
+                        Some(enemy)
+                    },
+                };
+                nodes.push(Node { kind, enemy, ai, next: Vec::new(), phases });
+            }
+            floors.push(nodes);
+        }
+
+        // each node leads to the one across from it on the next floor and maybe its neighbour,
+        // then any node nothing leads to is joined to the one across from it on the floor before
+        for floor in 0..floors.len() - 1 {
+            let (width, next_width) = (floors[floor].len(), floors[floor + 1].len());
+            for (index, node) in floors[floor].iter_mut().enumerate() {
+                let target = across(index, width, next_width);
+                node.next = vec![target];
+                if target + 1 < next_width && rng.gen_bool(0.5) {
+                    node.next.push(target + 1);
+                }
+            }
+            for target in 0..next_width {
+                if !floors[floor].iter().any(|node| node.next.contains(&target)) {
+                    let from = across(target, next_width, width);
+                    floors[floor][from].next.push(target);
+                    floors[floor][from].next.sort_unstable();
+                }
+            }
+        }
+        Run { floors, path: Vec::new(), outcome: Outcome::Fighting(0) }
+    }
+
+    pub fn floor(&self) -> Option<usize> {
+        match self.outcome {
+            Outcome::Fighting(floor) => Some(floor),
+            _ => None,
+        }
+    }
+
+    // the nodes that can be picked on the floor that's up next, none once one has been
+    pub fn choices(&self) -> Vec<usize> {
+        match self.floor() {
+            Some(floor) if self.path.len() == floor => match floor.checked_sub(1) {
+                Some(previous) => self.floors[previous][self.path[previous]].next.clone(),
+                None => (0..self.floors[0].len()).collect(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    // false if the node can't be reached from the one before it
+    pub fn pick(&mut self, index: usize) -> bool {
+        if !self.choices().contains(&index) {
+            return false;
+        }
+        self.path.push(index);
+        true
+    }
+
+    // the node that's been picked and not cleared yet
+    pub fn current(&self) -> Option<&Node> {
+        match self.floor() {
+            Some(floor) if self.path.len() > floor => self.floors[floor].get(self.path[floor]),
+            _ => None,
+        }
+    }
+
+    // the floors cleared so far
+    pub fn cleared(&self) -> usize {
+        match self.outcome {
+            Outcome::Fighting(floor) | Outcome::Lost(floor) => floor,
+            Outcome::Won => self.floors.len(),
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.floor().is_none()
+    }
+
+    // clears the picked node
+    pub fn win(&mut self) {
+        if let (Some(floor), Some(_)) = (self.floor(), self.current()) {
+            self.outcome = if floor + 1 >= self.floors.len() { Outcome::Won } else { Outcome::Fighting(floor + 1) };
+        }
+    }
+
+    pub fn lose(&mut self) {
+        if let Some(floor) = self.floor() {
+            self.outcome = Outcome::Lost(floor);
+        }
+    }
+}
+
+// a move the character doesn't know yet, from the world's pool, for treasure and elites
+pub fn treasure<R: Rng + ?Sized>(world: &OnionWorld, character: &OnionCharacter, rng: &mut R) -> Option<ActionId> {
+    let unknown: Vec<ActionId> = world.actions.ids().filter(|action| !character.attributes.actions.contains(action)).collect();
+    (!unknown.is_empty()).then(|| unknown[rng.gen_range(0..unknown.len())])
+}
+
+#[cfg(test)]
+mod gauntlet_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::Standard;
+    use rand::rngs::StdRng;
+
+    fn fake_run() -> Run {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        Run::generate(&world, 5, &mut rng)
+    }
+
+    #[test]
+    fn generate_test() {
+        let run = fake_run();
+        assert_eq!(run.floors.len(), GAUNTLET_LENGTH);
+        for (floor, nodes) in run.floors.iter().enumerate() {
+            let boss = (floor + 1) % BOSS_EVERY == 0;
+            assert_eq!(nodes.iter().any(|node| node.kind == NodeKind::Boss), boss);
+            for node in nodes {
+                assert_eq!(node.enemy.is_some(), !matches!(node.kind, NodeKind::Rest | NodeKind::Treasure));
+            }
+            // every node on the next floor can be reached from this one
+            if let Some(next) = run.floors.get(floor + 1) {
+                for target in 0..next.len() {
+                    assert!(nodes.iter().any(|node| node.next.contains(&target)));
+                }
+            }
+        }
+        let boss = run.floors[4][0].enemy.as_ref().unwrap();
+        assert_eq!(boss.attributes.level, 5 + 4 * LEVELS_PER_BATTLE + BOSS_LEVELS);
+        assert!(boss.name.starts_with("Boss "));
+        assert_eq!(run.floors.last().unwrap()[0].ai, Difficulty::Kaizo);
+        // the same seed rolls the same map
+        assert_eq!(run, fake_run());
+    }
+
+    #[test]
+    fn progress_test() {
+        let mut run = fake_run();
+        assert_eq!(run.current(), None);
+        assert_eq!(run.choices(), (0..run.floors[0].len()).collect::<Vec<_>>());
+        assert!(!run.pick(MAX_WIDTH));
+        assert!(run.pick(0));
+        assert_eq!(run.current(), Some(&run.floors[0][0]));
+        // only one pick a floor
+        assert!(run.choices().is_empty());
+
+        run.win();
+        assert_eq!(run.outcome, Outcome::Fighting(1));
+        assert_eq!(run.choices(), run.floors[0][0].next);
+        // nothing to clear until a node is picked
+        run.win();
+        assert_eq!(run.cleared(), 1);
+
+        let pick = run.choices()[0];
+        run.pick(pick);
+        run.lose();
+        assert_eq!(run.outcome, Outcome::Lost(1));
+        assert!(run.is_over());
+        assert_eq!(run.current(), None);
+        assert!(run.choices().is_empty());
+
+        let mut run = fake_run();
+        for _ in 0..GAUNTLET_LENGTH {
+            let pick = run.choices()[0];
+            run.pick(pick);
+            run.win();
+        }
+        assert_eq!(run.outcome, Outcome::Won);
+        assert_eq!(run.cleared(), GAUNTLET_LENGTH);
+    }
+
+    #[test]
+    fn treasure_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut character = world.sample_at_level(5, &mut rng);
+        let action = treasure(&world, &character, &mut rng).unwrap();
+        assert!(!character.attributes.actions.contains(&action));
+
+        character.attributes.actions = world.actions.ids().collect();
+        assert_eq!(treasure(&world, &character, &mut rng), None);
+    }
+}
@@ -0,0 +1,138 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+#[cfg(feature = "web")]
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::content::id::WorldId;
+use crate::seed::Seed;
+
+#[cfg(feature = "web")]
+static GHOSTS_KEY: &str = "kaizo-quest.ghosts";
+
+// the battles of a run, so a later run on the same seed in the same world can race it. battles
+// are measured in turns rather than time, so the race is over how well they're played and not how
+// fast the buttons are pressed
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ghost {
+    pub world: WorldId,
+    pub seed: Seed,
+    // the turns each win took, in order
+    pub turns: Vec<u32>,
+}
+
+// how a run is doing against a ghost after the same number of wins
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pace {
+    // by this many turns
+    Ahead(u32),
+    Behind(u32),
+    Even,
+    // further than the ghost ever got
+    Past,
+}
+
+impl fmt::Display for Pace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pace::Ahead(turns) => write!(f, "{} turns ahead", turns),
+            Pace::Behind(turns) => write!(f, "{} turns behind", turns),
+            Pace::Even => write!(f, "even"),
+            Pace::Past => write!(f, "past the ghost"),
+        }
+    }
+}
+
+impl Ghost {
+    pub fn new(world: WorldId, seed: Seed) -> Ghost {
+        Ghost { world, seed, turns: Vec::new() }
+    }
+
+    pub fn record(&mut self, turns: u32) {
+        self.turns.push(turns);
+    }
+
+    pub fn wins(&self) -> usize { self.turns.len() }
+
+    // only runs on the same seed in the same world are raced
+    pub fn races(&self, other: &Ghost) -> bool {
+        self.world == other.world && self.seed == other.seed
+    }
+
+    // how `run` is doing against this ghost after the wins it has so far
+    pub fn pace(&self, run: &Ghost) -> Pace {
+        if run.wins() > self.wins() {
+            return Pace::Past;
+        }
+        let ghost: u32 = self.turns.iter().take(run.wins()).sum();
+        let run: u32 = run.turns.iter().sum();
+        match run.cmp(&ghost) {
+            Ordering::Less => Pace::Ahead(ghost - run),
+            Ordering::Greater => Pace::Behind(run - ghost),
+            Ordering::Equal => Pace::Even,
+        }
+    }
+
+    // more wins, or as many in fewer turns
+    pub fn beats(&self, other: &Ghost) -> bool {
+        let turns = |ghost: &Ghost| ghost.turns.iter().sum::<u32>();
+        self.wins() > other.wins() || (self.wins() == other.wins() && turns(self) < turns(other))
+    }
+}
+
+#[cfg(feature = "web")]
+fn load_ghosts() -> Vec<Ghost> {
+    LocalStorage::get(GHOSTS_KEY).unwrap_or_default()
+}
+
+// the best run stored for the seed and world
+#[cfg(feature = "web")]
+pub fn load_ghost(world: WorldId, seed: Seed) -> Option<Ghost> {
+    let run = Ghost::new(world, seed);
+    load_ghosts().into_iter().find(|ghost| ghost.races(&run))
+}
+
+// only kept if it beats the stored run on its seed
+// TODO: there's no limit to how many ghosts are kept
+#[cfg(feature = "web")]
+pub fn store_ghost(run: &Ghost) {
+    let mut ghosts = load_ghosts();
+    match ghosts.iter_mut().find(|ghost| ghost.races(run)) {
+        Some(ghost) if run.beats(ghost) => *ghost = run.clone(),
+        Some(_) => return,
+        None => ghosts.push(run.clone()),
+    }
+    let _ = LocalStorage::set(GHOSTS_KEY, ghosts);
+}
+
+#[cfg(test)]
+mod ghost_tests {
+    use super::*;
+
+    fn run(turns: &[u32]) -> Ghost {
+        Ghost { world: WorldId(1), seed: Seed(2), turns: turns.to_vec() }
+    }
+
+    #[test]
+    fn pace_test() {
+        let ghost = run(&[3, 5, 4]);
+        assert_eq!(ghost.pace(&run(&[])), Pace::Even);
+        assert_eq!(ghost.pace(&run(&[2])), Pace::Ahead(1));
+        assert_eq!(ghost.pace(&run(&[2, 9])), Pace::Behind(3));
+        assert_eq!(ghost.pace(&run(&[3, 5, 4])), Pace::Even);
+        assert_eq!(ghost.pace(&run(&[3, 5, 4, 1])), Pace::Past);
+        assert_eq!(Pace::Behind(3).to_string(), "3 turns behind");
+    }
+
+    #[test]
+    fn beats_test() {
+        assert!(run(&[9, 9]).beats(&run(&[1])));
+        assert!(run(&[1, 2]).beats(&run(&[2, 2])));
+        assert!(!run(&[2, 2]).beats(&run(&[2, 2])));
+        assert!(!run(&[1]).beats(&run(&[9, 9])));
+
+        assert!(run(&[]).races(&run(&[1])));
+        assert!(!run(&[]).races(&Ghost::new(WorldId(1), Seed(3))));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::core::States;
+use crate::onion::{Field, OnionCharacter};
+
+pub static MAX_SPIKES: u32 = 3;
+
+// the spikes on each side of the battle, from the point of view of whoever is acting. the battle
+// keeps them per side and fills this in around each action, like the rolls
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Spikes {
+    pub user: u32,
+    pub target: u32,
+}
+
+// another layer on the target's side, hurting whatever is sent out there next
+pub fn lay(field: &mut Field, target: &OnionCharacter) -> States {
+    if field.spikes.target >= MAX_SPIKES {
+        return vec!["But there's no room for more spikes.".to_string()];
+    }
+    field.spikes.target += 1;
+    vec![format!("Spikes were scattered around {}.", target.name)]
+}
+
+// clears the user's own side
+pub fn clear(field: &mut Field, user: &OnionCharacter) -> States {
+    if field.spikes.user == 0 {
+        return vec!["But there was nothing to clear.".to_string()];
+    }
+    field.spikes.user = 0;
+    vec![format!("{} swept away the spikes.", user.name)]
+}
+
+// the share of its health a kaizo sent out onto `layers` of spikes loses, as a fraction
+fn share(layers: u32) -> (i32, i32) {
+    match layers {
+        0 => (0, 1),
+        1 => (1, 8),
+        2 => (1, 6),
+        _ => (1, 4),
+    }
+}
+
+pub fn switch_in(layers: u32, character: &mut OnionCharacter) -> States {
+    if layers == 0 {
+        return vec![];
+    }
+    let (numerator, denominator) = share(layers);
+    let damage = std::cmp::max(1, character.attributes.stats.health as i32 * numerator / denominator);
+    character.state.health = std::cmp::max(0, character.state.health - damage);
+    vec![format!("{} was hurt by the spikes.", character.name)]
+}
+
+#[cfg(test)]
+mod hazards_tests {
+    use super::*;
+
+    use crate::onion::{ActionPool, OnionBattle, testing};
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn lay_test() {
+        let mut field = Field::default();
+        let target = testing::fake_character();
+        for _ in 0..MAX_SPIKES {
+            assert_eq!(lay(&mut field, &target), vec!["Spikes were scattered around fake."]);
+        }
+        assert_eq!(lay(&mut field, &target), vec!["But there's no room for more spikes."]);
+        assert_eq!(field.spikes.target, MAX_SPIKES);
+
+        // the user can only clear its own side
+        assert_eq!(clear(&mut field, &target), vec!["But there was nothing to clear."]);
+        field.spikes.user = 2;
+        assert_eq!(clear(&mut field, &target), vec!["fake swept away the spikes."]);
+        assert_eq!(field.spikes, Spikes { user: 0, target: MAX_SPIKES });
+    }
+
+    #[test]
+    fn switch_in_test() {
+        let mut character = testing::fake_character();
+        character.attributes.stats.health = 48;
+        character.refresh();
+        assert!(switch_in(0, &mut character).is_empty());
+        assert_eq!(switch_in(1, &mut character), vec!["fake was hurt by the spikes."]);
+        assert_eq!(character.state.health, 42);
+        switch_in(3, &mut character);
+        assert_eq!(character.state.health, 30);
+    }
+
+    #[test]
+    fn battle_test() {
+        let pool = ActionPool::with_attacks(vec![]);
+        let (spikes, sweep) = (&pool[24], &pool[25]);
+        assert_eq!((spikes.name(), sweep.name()), ("Spikes", "Sweep"));
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut battle = OnionBattle::new(testing::fake_character(), testing::fake_character());
+        for character in [&mut battle.player, &mut battle.enemy] {
+            character.attributes.stats.health = 48;
+            character.refresh();
+        }
+        // each side keeps its own spikes
+        battle.player_turn(spikes, &mut rng);
+        battle.player_turn(spikes, &mut rng);
+        assert_eq!((battle.player_spikes, battle.enemy_spikes), (0, 2));
+        battle.enemy_turn(spikes, &mut rng);
+        assert_eq!((battle.player_spikes, battle.enemy_spikes), (1, 2));
+        // and sweeping only clears the sweeper's
+        battle.enemy_turn(sweep, &mut rng);
+        assert_eq!((battle.player_spikes, battle.enemy_spikes), (1, 0));
+        assert_eq!(battle.field.spikes, Spikes::default());
+
+        let mut next = testing::fake_character();
+        next.attributes.stats.health = 48;
+        next.refresh();
+        assert_eq!(battle.switch_in(next), vec!["fake was hurt by the spikes."]);
+        assert_eq!(battle.player.state.health, 42);
+    }
+}
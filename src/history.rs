@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "web")]
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::onion::OnionCharacter;
+
+#[cfg(feature = "web")]
+static HISTORY_KEY: &str = "kaizo-quest.history";
+
+// how one species has done in your battles
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeciesRecord {
+    // battles you fought with it, and won
+    pub used: u32,
+    pub won_with: u32,
+    // battles you fought against it, and won
+    pub faced: u32,
+    pub won_against: u32,
+}
+
+impl SpeciesRecord {
+    pub fn win_rate_with(&self) -> Option<f64> {
+        (self.used > 0).then(|| self.won_with as f64 / self.used as f64)
+    }
+
+    pub fn win_rate_against(&self) -> Option<f64> {
+        (self.faced > 0).then(|| self.won_against as f64 / self.faced as f64)
+    }
+}
+
+// every battle you've played, kept per species on this device only. species are found by name,
+// loosely like everywhere else, so the history carries over between worlds that share species
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BattleHistory {
+    species: BTreeMap<String, SpeciesRecord>,
+    // whether each battle was won, oldest first; histories from before this only have the totals
+    #[serde(default)]
+    outcomes: Vec<bool>,
+    // how many of your kaizo each species has knocked out
+    #[serde(default)]
+    faints: BTreeMap<String, u32>,
+}
+
+impl BattleHistory {
+    // fleeing is recorded as not winning
+    pub fn record(&mut self, player: &OnionCharacter, enemy: &OnionCharacter, won: bool) {
+        let used = self.species.entry(player.species.name.to_lowercase()).or_default();
+        used.used += 1;
+        used.won_with += won as u32;
+        let faced = self.species.entry(enemy.species.name.to_lowercase()).or_default();
+        faced.faced += 1;
+        faced.won_against += won as u32;
+        self.outcomes.push(won);
+        if player.state.health <= 0 {
+            *self.faints.entry(enemy.species.name.to_lowercase()).or_insert(0) += 1;
+        }
+    }
+
+    pub fn species(&self, name: &str) -> SpeciesRecord {
+        self.species.get(&name.to_lowercase()).copied().unwrap_or_default()
+    }
+
+    // the most any one species has been used, to scale usage against
+    pub fn most_used(&self) -> u32 {
+        self.species.values().map(|record| record.used).max().unwrap_or(0)
+    }
+
+    // every battle used one species, so these count the ones from before outcomes were kept too
+    pub fn battles(&self) -> u32 {
+        self.species.values().map(|record| record.used).sum()
+    }
+
+    pub fn wins(&self) -> u32 {
+        self.species.values().map(|record| record.won_with).sum()
+    }
+
+    // the species you've battled with the most, earliest by name on a tie
+    pub fn favorite(&self) -> Option<&str> {
+        self.species.iter()
+            .filter(|(_, record)| record.used > 0)
+            .max_by_key(|(name, record)| (record.used, std::cmp::Reverse(*name)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn outcomes(&self) -> &[bool] { &self.outcomes }
+
+    // the species that have knocked out your kaizo, the most first
+    pub fn faints(&self) -> Vec<(&str, u32)> {
+        let mut faints: Vec<(&str, u32)> = self.faints.iter().map(|(name, count)| (name.as_str(), *count)).collect();
+        faints.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        faints
+    }
+}
+
+#[cfg(feature = "web")]
+pub fn load_history() -> BattleHistory {
+    LocalStorage::get(HISTORY_KEY).unwrap_or_default()
+}
+
+#[cfg(feature = "web")]
+pub fn record_battle(player: &OnionCharacter, enemy: &OnionCharacter, won: bool) {
+    let mut history = load_history();
+    history.record(player, enemy, won);
+    let _ = LocalStorage::set(HISTORY_KEY, history);
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::OnionWorld;
+
+    #[test]
+    fn record_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut player = world.sample_at_level(5, &mut rng);
+        // of another species, so the two records don't mix
+        let enemy = std::iter::repeat_with(|| world.sample_at_level(5, &mut rng)).find(|enemy| enemy.species.name != player.species.name).unwrap();
+        let mut history = BattleHistory::default();
+        assert_eq!(history.species(&player.species.name).win_rate_with(), None);
+
+        history.record(&player, &enemy, true);
+        history.record(&player, &enemy, false);
+        player.name = "Rocky".into();
+        history.record(&player, &enemy, true);
+
+        let used = history.species(&player.species.name.to_uppercase());
+        assert_eq!(used, SpeciesRecord { used: 3, won_with: 2, faced: 0, won_against: 0 });
+        assert_eq!(used.win_rate_with(), Some(2.0 / 3.0));
+        assert_eq!(used.win_rate_against(), None);
+        assert_eq!(history.species(&enemy.species.name).win_rate_against(), Some(2.0 / 3.0));
+        assert_eq!(history.most_used(), 3);
+        assert_eq!((history.battles(), history.wins()), (3, 2));
+        assert_eq!(history.favorite(), Some(player.species.name.to_lowercase().as_str()));
+        assert_eq!(history.outcomes(), &[true, false, true]);
+        assert!(history.faints().is_empty());
+
+        player.state.health = 0;
+        history.record(&player, &enemy, false);
+        assert_eq!(history.faints(), vec![(enemy.species.name.to_lowercase().as_str(), 1)]);
+    }
+
+    #[test]
+    fn old_history_test() {
+        // from before outcomes and faints were kept
+        let history: BattleHistory = serde_json::from_str(r#"{"species":{"pebble":{"used":4,"won_with":1,"faced":0,"won_against":0}}}"#).unwrap();
+        assert_eq!(history.battles(), 4);
+        assert!(history.outcomes().is_empty());
+        assert_eq!(history.favorite(), Some("pebble"));
+    }
+}
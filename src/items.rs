@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Item, ItemId, Name, States};
+use crate::onion::{Alignment, OnionCharacter, Status};
+
+// how many of each of the world's items a new run starts with
+pub static STARTING_ITEMS: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    // restores this much health
+    Heal(u32),
+    // stops bleeding
+    Cure,
+    // brings a fainted kaizo back with this percent of its health
+    Revive(u32),
+}
+
+// items are plain data, so the world's definition lists them as they are
+//
+//     "items": [{ "name": "Potion", "effect": { "Heal": 20 } }, { "name": "Antidote", "effect": "Cure" }]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OnionItem {
+    pub name: Name,
+    pub effect: Effect,
+}
+
+impl Item<Alignment, Status> for OnionItem {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        match self.effect {
+            Effect::Heal(amount) => format!("Restores {} health.", amount),
+            Effect::Cure => "Stops bleeding.".to_string(),
+            Effect::Revive(percent) => format!("Revives a fainted kaizo with {}% of its health.", percent),
+        }
+    }
+
+    fn usable_on(&self, target: &OnionCharacter) -> bool {
+        let fainted = target.state.health <= 0;
+        match self.effect {
+            Effect::Heal(_) => !fainted && target.state.health < target.attributes.stats.health as i32,
+            Effect::Cure => !fainted && target.state.status.contains_key(&Status::Bleed),
+            Effect::Revive(_) => fainted,
+        }
+    }
+
+    fn apply(&self, target: &mut OnionCharacter) -> States {
+        let max = target.attributes.stats.health as i32;
+        match self.effect {
+            Effect::Heal(amount) => {
+                let health = target.state.health;
+                target.state.health = std::cmp::min(max, health + amount as i32);
+                vec![format!("{} regained {} health.", target.name, target.state.health - health)]
+            },
+            Effect::Cure => {
+                target.state.status.remove(&Status::Bleed);
+                vec![format!("{} stopped bleeding.", target.name)]
+            },
+            Effect::Revive(percent) => {
+                target.state.health = std::cmp::max(1, max * percent as i32 / 100);
+                vec![format!("{} was revived!", target.name)]
+            },
+        }
+    }
+}
+
+// the items worlds have when their definition doesn't list any
+pub fn default_items() -> Vec<OnionItem> {
+    vec![
+        OnionItem { name: "Potion".into(), effect: Effect::Heal(20) },
+        OnionItem { name: "Antidote".into(), effect: Effect::Cure },
+        OnionItem { name: "Revive".into(), effect: Effect::Revive(50) },
+    ]
+}
+
+// the player's bag, as how many of each of the world's items they have
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    items: BTreeMap<ItemId, u32>,
+}
+
+impl Inventory {
+    // a few of every item in the world
+    pub fn starting(items: &[OnionItem]) -> Inventory {
+        Inventory { items: (0..items.len()).map(|item| (item, STARTING_ITEMS)).collect() }
+    }
+
+    pub fn count(&self, item: ItemId) -> u32 {
+        self.items.get(&item).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, item: ItemId, count: u32) {
+        *self.items.entry(item).or_default() += count;
+    }
+
+    // the items there's at least one of, in id order
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, u32)> + '_ {
+        self.items.iter().filter(|(_, count)| **count > 0).map(|(item, count)| (*item, *count))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    // uses up one of the item on the target; nothing is used if there are none left or it wouldn't
+    // do anything
+    pub fn use_on(&mut self, items: &[OnionItem], item: ItemId, target: &mut OnionCharacter) -> Option<States> {
+        let used = items.get(item).filter(|used| self.count(item) > 0 && used.usable_on(target))?;
+        *self.items.get_mut(&item)? -= 1;
+        let mut logs = vec![format!("Used a {} on {}.", used.name, target.name)];
+        logs.extend(used.apply(target));
+        Some(logs)
+    }
+}
+
+#[cfg(test)]
+mod items_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::OnionWorld;
+
+    fn fake_character() -> OnionCharacter {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut character = world.sample_at_level(50, &mut rng);
+        character.name = "fake".into();
+        character
+    }
+
+    fn item(effect: Effect) -> OnionItem {
+        OnionItem { name: "fake".into(), effect }
+    }
+
+    #[test]
+    fn heal_test() {
+        let mut target = fake_character();
+        let potion = item(Effect::Heal(20));
+        assert!(!potion.usable_on(&target));
+
+        let max = target.state.health;
+        target.state.health -= 10;
+        assert!(potion.usable_on(&target));
+        assert_eq!(potion.apply(&mut target), vec!["fake regained 10 health."]);
+        assert_eq!(target.state.health, max);
+
+        target.state.health = 0;
+        assert!(!potion.usable_on(&target));
+    }
+
+    #[test]
+    fn cure_test() {
+        let mut target = fake_character();
+        let antidote = item(Effect::Cure);
+        assert!(!antidote.usable_on(&target));
+
+        target.state.status.insert(Status::Bleed, 3);
+        assert!(antidote.usable_on(&target));
+        antidote.apply(&mut target);
+        assert!(!target.state.status.contains_key(&Status::Bleed));
+    }
+
+    #[test]
+    fn revive_test() {
+        let mut target = fake_character();
+        let revive = item(Effect::Revive(50));
+        assert!(!revive.usable_on(&target));
+
+        target.state.health = 0;
+        assert!(revive.usable_on(&target));
+        revive.apply(&mut target);
+        assert_eq!(target.state.health, std::cmp::max(1, target.attributes.stats.health as i32 / 2));
+    }
+
+    #[test]
+    fn inventory_test() {
+        let items = default_items();
+        let mut inventory = Inventory::starting(&items);
+        assert_eq!(inventory.iter().count(), items.len());
+        assert_eq!(inventory.count(0), STARTING_ITEMS);
+
+        let mut target = fake_character();
+        // a potion on a healthy kaizo isn't used up
+        assert_eq!(inventory.use_on(&items, 0, &mut target), None);
+        assert_eq!(inventory.count(0), STARTING_ITEMS);
+
+        target.state.health = 1;
+        for _ in 0..STARTING_ITEMS {
+            let logs = inventory.use_on(&items, 0, &mut target).unwrap();
+            assert_eq!(logs[0], "Used a Potion on fake.");
+            target.state.health = 1;
+        }
+        assert_eq!(inventory.use_on(&items, 0, &mut target), None);
+        assert_eq!(inventory.iter().count(), items.len() - 1);
+
+        inventory.add(0, 1);
+        assert_eq!(inventory.count(0), 1);
+        // items the world doesn't have can't be used
+        inventory.add(items.len(), 1);
+        assert_eq!(inventory.use_on(&items, items.len(), &mut target), None);
+    }
+}
@@ -0,0 +1,120 @@
+use rand::Rng;
+
+use crate::core::{Stat, Stats};
+use crate::onion::OnionCharacter;
+
+// the most an individual value can raise a stat's share of the species' stats, in percent; see
+// `Character::scaled_stats`
+pub static MAX_IV: u32 = 15;
+// battles a kaizo has to fight to the end before it can be judged
+pub static REVEAL_BATTLES: u32 = 5;
+
+const STATS: [Stat; 5] = [Stat::Attack, Stat::Defense, Stat::SpecialAttack, Stat::SpecialDefense, Stat::Speed];
+
+pub fn roll<R: Rng + ?Sized>(rng: &mut R) -> Stats<u32> {
+    (0..6).map(|_| rng.gen_range(0..=MAX_IV)).collect::<Vec<u32>>().into()
+}
+
+// gives a kaizo that's just been met its values. runs roll them from a stream of their own, so
+// they don't shift what's met or how battles go
+pub fn assign<R: Rng + ?Sized>(character: &mut OnionCharacter, rng: &mut R) {
+    character.attributes.ivs = roll(rng);
+    character.recalculate_stats();
+    character.refresh();
+}
+
+// how far the values are toward perfect, in percent
+pub fn quality(ivs: &Stats<u32>) -> u32 {
+    Vec::from(ivs).iter().sum::<u32>() * 100 / (6 * MAX_IV)
+}
+
+fn grade(quality: u32) -> &'static str {
+    match quality {
+        90.. => "Outstanding",
+        65..=89 => "Relatively superior",
+        35..=64 => "Above average",
+        _ => "Decent",
+    }
+}
+
+// what a kaizo's values come to, once it's fought enough for them to show
+pub fn judge(character: &OnionCharacter) -> Option<String> {
+    if character.attributes.battles < REVEAL_BATTLES {
+        return None;
+    }
+    let ivs = &character.attributes.ivs;
+    // ties go to whichever stat is listed first, health before all of them. max_by_key keeps the
+    // last of the best, so the stats are gone through backwards
+    let best = STATS.iter().copied()
+        .filter(|stat| ivs.get(*stat) > &ivs.health)
+        .rev()
+        .max_by_key(|stat| ivs.get(*stat))
+        .map_or("health".to_string(), |stat| stat.to_string());
+    Some(format!("{} potential overall. Its best stat is {}.", grade(quality(ivs)), best))
+}
+
+#[cfg(test)]
+mod ivs_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::core::SCALING_FACTOR;
+    use crate::onion::OnionWorld;
+
+    #[test]
+    fn roll_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            assert!(Vec::from(roll(&mut rng)).iter().all(|iv| *iv <= MAX_IV));
+        }
+        assert_eq!(quality(&Stats::default()), 0);
+        assert_eq!(quality(&Stats::from_values(MAX_IV, MAX_IV, MAX_IV, MAX_IV, MAX_IV, MAX_IV)), 100);
+    }
+
+    #[test]
+    fn scale_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut character = world.sample_at_level(10, &mut rng);
+        character.attributes.ivs = Stats::default();
+        let plain = character.scaled_stats(SCALING_FACTOR);
+        character.attributes.ivs = Stats::from_values(0, MAX_IV, 0, 0, 0, 0);
+        let better = character.scaled_stats(SCALING_FACTOR);
+        assert!(better.attack > plain.attack);
+        let total = |stats: Stats<u32>| Vec::from(stats).iter().sum::<u32>();
+        assert!(total(better) > total(plain));
+    }
+
+    #[test]
+    fn assign_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        // meeting a kaizo doesn't roll its values any more, so they're left to the run's own stream
+        let mut character = world.sample_at_level(10, &mut rng);
+        assert_eq!(character.attributes.ivs, Stats::default());
+        assign(&mut character, &mut rng);
+        assert_eq!(character.attributes.stats, character.scaled_stats(10 * SCALING_FACTOR));
+        assert_eq!(character.state.health, character.attributes.stats.health as i32);
+    }
+
+    #[test]
+    fn judge_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut character = world.sample_at_level(10, &mut rng);
+        character.attributes.ivs = Stats::from_values(MAX_IV, MAX_IV, MAX_IV, MAX_IV, MAX_IV, MAX_IV - 1);
+        assert_eq!(judge(&character), None);
+        character.attributes.battles = REVEAL_BATTLES;
+        assert_eq!(judge(&character), Some("Outstanding potential overall. Its best stat is health.".to_string()));
+        character.attributes.ivs = Stats::from_values(0, 0, 0, 0, 0, 3);
+        assert_eq!(judge(&character), Some("Decent potential overall. Its best stat is speed.".to_string()));
+        // a tie goes to the stat listed first
+        character.attributes.ivs = Stats::from_values(0, 4, 4, 0, 0, 0);
+        assert!(judge(&character).unwrap().ends_with("Its best stat is attack."));
+        character.attributes.ivs = Stats::from_values(4, 4, 0, 0, 0, 4);
+        assert!(judge(&character).unwrap().ends_with("Its best stat is health."));
+    }
+}
@@ -1,3 +1,107 @@
+// `core` has no required dependencies; the rest of the game is behind the `world` and `web` features.
+// `prelude` is what other frontends should import
+extern crate alloc;
+
 pub mod core;
+
+#[cfg(feature = "world")]
+pub mod abilities;
+#[cfg(feature = "world")]
+pub mod adapter;
+#[cfg(feature = "world")]
+pub mod ai;
+#[cfg(feature = "world")]
+pub mod areas;
+#[cfg(feature = "world")]
+pub mod bosses;
+#[cfg(feature = "world")]
+pub mod breeding;
+#[cfg(feature = "world")]
+pub mod clauses;
+#[cfg(feature = "world")]
+pub mod clock;
+#[cfg(feature = "world")]
+pub mod config;
+#[cfg(feature = "world")]
+pub mod content;
+#[cfg(feature = "world")]
+pub mod correspondence;
+#[cfg(feature = "world")]
+pub mod daily;
+#[cfg(feature = "dev")]
+pub mod dev;
+#[cfg(feature = "world")]
+pub mod draft;
+#[cfg(feature = "world")]
+pub mod effort;
+#[cfg(feature = "world")]
+pub mod encounters;
+#[cfg(feature = "world")]
+pub mod endless;
+#[cfg(feature = "world")]
+pub mod flow;
+#[cfg(feature = "world")]
+pub mod gauntlet;
+#[cfg(feature = "world")]
+pub mod ghost;
+#[cfg(feature = "world")]
+pub mod hazards;
+#[cfg(feature = "world")]
+pub mod history;
+#[cfg(feature = "world")]
+pub mod items;
+#[cfg(feature = "world")]
+pub mod ivs;
+#[cfg(feature = "world")]
+pub mod migrate;
+#[cfg(feature = "world")]
+pub mod modifiers;
+#[cfg(feature = "web")]
+pub mod net;
+#[cfg(feature = "world")]
 pub mod onion;
-// pub mod ui;
+#[cfg(feature = "world")]
+pub mod practice;
+pub mod prelude;
+#[cfg(feature = "world")]
+pub mod render;
+#[cfg(feature = "world")]
+pub mod replay;
+#[cfg(feature = "world")]
+pub mod risks;
+#[cfg(feature = "world")]
+pub mod roster;
+#[cfg(feature = "world")]
+pub mod rules;
+#[cfg(feature = "web")]
+pub mod save;
+#[cfg(feature = "world")]
+pub mod scout;
+#[cfg(feature = "world")]
+pub mod seed;
+#[cfg(feature = "world")]
+pub mod session;
+#[cfg(feature = "world")]
+pub mod share;
+#[cfg(feature = "world")]
+pub mod shop;
+#[cfg(feature = "world")]
+pub mod sim;
+#[cfg(feature = "world")]
+pub mod snapshot;
+#[cfg(feature = "world")]
+pub mod statistics;
+#[cfg(feature = "world")]
+pub mod statuses;
+#[cfg(feature = "world")]
+pub mod team;
+#[cfg(feature = "world")]
+pub mod trainers;
+#[cfg(feature = "transfer")]
+pub mod transfer;
+#[cfg(feature = "web")]
+pub mod ui;
+#[cfg(feature = "world")]
+pub mod war;
+#[cfg(feature = "world")]
+pub mod weather;
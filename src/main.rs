@@ -1,13 +1,65 @@
+// yew 0.19's html! macro trips this lint on every component with more than one prop
+#![allow(clippy::unnecessary_operation)]
+
 use yew::prelude::*;
 
 use yew::html;
 use yew::html::Properties;
 
-use rand::{random, thread_rng};
+#[cfg(feature = "dev")]
+use gloo_timers::future::TimeoutFuture;
+use rand::Rng;
 use rand::distributions::{Distribution, Standard};
+use rand::seq::SliceRandom;
 
-use kaizo_quest::core::ActionId;
-use kaizo_quest::onion::{EXPERIENCE_TO_LEVEL, Experience, OnionBattle, OnionBattleState, OnionCharacter, OnionWorld, Scale};
+use kaizo_quest::adapter::InputLock;
+use kaizo_quest::ai::{Difficulty, Habits};
+use kaizo_quest::bosses::{self, Phase, boss_due};
+use kaizo_quest::breeding::{MAX_EGGS, compatible};
+use kaizo_quest::clauses::{CLAUSES, Clause};
+use kaizo_quest::clock::{Clock, TimeOfDay};
+use kaizo_quest::config::{GameConfig, PRESETS, Preset};
+use kaizo_quest::daily::Daily;
+#[cfg(feature = "dev")]
+use kaizo_quest::content::load_world;
+use kaizo_quest::content::id::WorldId;
+use kaizo_quest::content::pack::{ContentPack, IdMap, compose_world, load_packs, store_packs};
+use kaizo_quest::content::remote::{RemoteWorld, clear_remote_world, fetch_world, load_remote_world, store_remote_world};
+use kaizo_quest::core::{Ability, ActionId, Actions, Item, ItemId, Rarity, Stat, States};
+use kaizo_quest::encounters::{self, EncounterKind};
+use kaizo_quest::endless::next_cycle;
+use kaizo_quest::flow::{MessageKind, SceneKind, transition};
+#[cfg(feature = "dev")]
+use kaizo_quest::dev::{DEV_POLL_MILLIS, DEV_WORLD_URL, WorldWatcher};
+#[cfg(feature = "dev")]
+use kaizo_quest::draft::Upgrade;
+use kaizo_quest::draft::{Offer, draft};
+use kaizo_quest::gauntlet::{NodeKind, Outcome, REST_EXPERIENCE, Run, treasure};
+use kaizo_quest::ghost::{Ghost, load_ghost, store_ghost};
+use kaizo_quest::history::{BattleHistory, load_history, record_battle};
+use kaizo_quest::items::Inventory;
+use kaizo_quest::ivs::{self, REVEAL_BATTLES};
+#[cfg(feature = "dev")]
+use kaizo_quest::net::fetch_text;
+use kaizo_quest::onion::{ActionPool, Experience, OnionBattle, OnionBattleState, OnionCharacter, OnionWorld, TypeChart, alignment_names, learn_moves, replace_move};
+use kaizo_quest::practice::{Checkpoint, Profile, load_profile, standard_party, store_profile};
+use kaizo_quest::render::summary::{BattleSummary, battle_summary};
+use kaizo_quest::replay::{Playback, Replay, ReplayStep};
+use kaizo_quest::roster::{Member, PARTY_SIZE, Roster, RosterOrder};
+use kaizo_quest::risks::{RISKS, Risk, draft_picks};
+use kaizo_quest::rules::Ruleset;
+use kaizo_quest::save::{Encounter, SaveGame, export, has_save, import, load, save};
+use kaizo_quest::scout::{capture_chance, try_capture};
+use kaizo_quest::seed::{Seed, Streams};
+use kaizo_quest::share::{gain_capped, money_for, share};
+use kaizo_quest::shop::{REST_PRICE, Shop, reward, scale_price};
+use kaizo_quest::statistics::{BattlePace, battles_to_level, win_rates};
+use kaizo_quest::team::{MAX_ACTIONS, TEAM_LEVEL_CAP, Team, TeamMember, delete_team, learnpool, load_teams, store_team};
+use kaizo_quest::trainers::Trainer;
+use kaizo_quest::transfer::{Chunk, assemble, chunks, is_transfer};
+use kaizo_quest::ui::{QrCanvas, VirtualList};
+use kaizo_quest::war::{Soldier, muster, war};
+use kaizo_quest::weather::Weather;
 
 static RESOURCES: &str = "resources";
 
@@ -15,19 +67,38 @@ fn get_resource(resource: &str) -> String {
     format!("{}/{}.png", RESOURCES, resource)
 }
 
+// the browser's local hour, for a clock that follows real time
+fn current_hour() -> Option<u32> {
+    Some(js_sys::Date::new_0().get_hours())
+}
+
+fn today() -> Daily {
+    Daily::from_millis(js_sys::Date::now())
+}
+
 #[derive(Properties, PartialEq)]
-pub struct CharacterProps { pub character: OnionCharacter }
+pub struct CharacterProps {
+    pub character: OnionCharacter,
+    // the character's ability and what it does, for the tooltip next to its nature; components
+    // can't see the world
+    #[prop_or_default]
+    pub ability: Option<String>,
+}
 
 #[function_component(CharacterOverview)]
-pub fn character_overview(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn character_overview(CharacterProps { character, ability } : &CharacterProps) -> Html {
     html! {
         <div>
             <p style="text-align:left;">
-                <img title={
-                    format!("{:?}", character.species.alignment)
-                } style="alignment:left;" src={ get_resource(&format!("{:?}", character.species.alignment)).to_lowercase() }
-                width={"5%"} height={"5%"}/>
-                { format!(" {} (BST: {}) Lv{} ", character.name.clone(), character.species.bst, character.attributes.level) }
+                { for character.species.alignments().iter().map(|alignment| html! {
+                    <img title={
+                        format!("{:?}", alignment)
+                    } style="alignment:left;" src={ get_resource(&format!("{:?}", alignment)).to_lowercase() }
+                    width={"5%"} height={"5%"}/>
+                }) }
+                <span title={ format!("{}\n{}", character.attributes.nature.description(), ability.clone().unwrap_or_else(|| "No ability.".to_string())) }>{
+                    format!(" {} (BST: {}) Lv{} ", character.name.clone(), character.species.bst, character.attributes.level)
+                }</span>
                 // { format!(" {} ", character.name.clone()) }
                 { for character.state.status.keys().map(|status|
                     html! {
@@ -42,30 +113,88 @@ pub fn character_overview(CharacterProps { character } : &CharacterProps) -> Htm
     }
 }
 
+#[derive(Properties, PartialEq)]
+pub struct WeatherProps {
+    pub weather: Option<Weather>,
+    pub turns: u32,
+}
+
+// what the sky is doing over the battle, and for how much longer
+#[function_component(WeatherIndicator)]
+pub fn weather_indicator(WeatherProps { weather, turns } : &WeatherProps) -> Html {
+    match weather {
+        Some(weather) => html! {
+            <p class="weather" title={ weather.description() }>
+                <img alt={ format!("{:?}", weather) } src={ get_resource(weather.icon()) } width={"24"} height={"24"}/>
+                { format!(" {:?} ({} turns left)", weather, turns) }
+            </p>
+        },
+        None => html! { },
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct HudProps {
+    pub summary: BattleSummary,
+}
+
+// what's lasting on each side of the battle, as chips that stay put between turns
+#[function_component(BattleHud)]
+pub fn battle_hud(HudProps { summary } : &HudProps) -> Html {
+    html! {
+        <div class="hud">
+            { for [&summary.enemy, &summary.player].iter().filter(|side| !side.chips.is_empty()).map(|side| html! {
+                <p>
+                    { format!("{}: ", side.name) }
+                    { for side.chips.iter().map(|chip| html! {
+                        <span class={ if chip.good { "chip good" } else { "chip bad" } } title={ chip.description.clone() }>{ chip.label.clone() }</span>
+                    }) }
+                </p>
+            }) }
+        </div>
+    }
+}
+
 #[function_component(CharacterStats)]
-pub fn character_stats(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn character_stats(CharacterProps { character, .. } : &CharacterProps) -> Html {
+    // staged stats show how far they've moved in the battle
+    let stat = |stat: Stat| match *character.state.stages.get(stat) {
+        0 => character.stat(stat).to_string(),
+        stage => format!("{} ({:+})", character.stat(stat), stage),
+    };
     html! {
         <div>
             <img title={
-                format!("Attack determines damage dealt.")
-            } src={ get_resource("attack") } width={"15%"} height={"15%"}/>
-            { format!("{}", character.attributes.stats.attack) }
+                "Attack determines physical damage dealt.".to_string()
+            } src={ get_resource("attack") } width={"12%"} height={"12%"}/>
+            { stat(Stat::Attack) }
             { " " }
             <img title={
-                format!("Defense determines damage taken.")
-            } src={ get_resource("defense") } width={"15%"} height={"15%"}/>
-            { format!("{}", character.attributes.stats.defense) }
+                "Defense determines physical damage taken.".to_string()
+            } src={ get_resource("defense") } width={"12%"} height={"12%"}/>
+            { stat(Stat::Defense) }
+            { " " }
+            // the special stats share the physical icons, tinted
+            <img class="special-stat" title={
+                "Special attack determines special damage dealt.".to_string()
+            } src={ get_resource("attack") } width={"12%"} height={"12%"}/>
+            { stat(Stat::SpecialAttack) }
+            { " " }
+            <img class="special-stat" title={
+                "Special defense determines special damage taken.".to_string()
+            } src={ get_resource("defense") } width={"12%"} height={"12%"}/>
+            { stat(Stat::SpecialDefense) }
             { " " }
             <img title={
-                format!("Speed determines turn order.")
-            } src={ get_resource("speed") } width={"15%"} height={"15%"}/>
-            { format!("{}", character.attributes.stats.speed) }
+                "Speed determines turn order.".to_string()
+            } src={ get_resource("speed") } width={"12%"} height={"12%"}/>
+            { stat(Stat::Speed) }
         </div>
     }
 }
 
 #[function_component(HealthBar)]
-pub fn health_bar(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn health_bar(CharacterProps { character, .. } : &CharacterProps) -> Html {
     html! {
         <div>
             <div><CharacterOverview character={character.clone()}/></div>
@@ -79,11 +208,11 @@ pub fn health_bar(CharacterProps { character } : &CharacterProps) -> Html {
 }
 
 #[function_component(HealthBarWithValue)]
-pub fn health_bar_with_value(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn health_bar_with_value(CharacterProps { character, ability } : &CharacterProps) -> Html {
     let n = character.attributes.stats.health.to_string().len();
     html! {
         <div>
-            <div><CharacterOverview character={character.clone()}/></div>
+            <div><CharacterOverview character={character.clone()} ability={ability.clone()}/></div>
             <progress id="health" value={
                 format!("{}", character.state.health)
             } max={
@@ -96,33 +225,107 @@ pub fn health_bar_with_value(CharacterProps { character } : &CharacterProps) ->
 }
 
 #[function_component(ExperienceBar)]
-pub fn experience_bar(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn experience_bar(CharacterProps { character, .. } : &CharacterProps) -> Html {
     html! {
         <div>
             <progress id="experience" value={
                 format!("{}", character.attributes.experience)
-            } max={"100"}
-            data-label={ format!("EXP:{: >3}/{}", character.attributes.experience, EXPERIENCE_TO_LEVEL) }
+            } max={ character.experience_to_next_level().to_string() }
+            data-label={ format!("EXP:{: >3}/{}", character.attributes.experience, character.experience_to_next_level()) }
             title={ format!(
-                "{} will gain a level after gaining {} experience.",
+                "{} will gain a level after gaining {} experience. Its species grows at a {:?} rate.",
                 character.name,
-                EXPERIENCE_TO_LEVEL - character.attributes.experience
+                character.experience_to_next_level().saturating_sub(character.attributes.experience),
+                character.species.growth
             )} ></progress>
         </div>
     }
 }
 
+// a small bar for one of a species' numbers in the dex, `value` out of `max`
+// how many stretches of battles the win rate chart is split into
+static CHART_POINTS: usize = 20;
+static CHART_WIDTH: usize = 200;
+static CHART_HEIGHT: usize = 60;
+
+#[derive(Properties, PartialEq)]
+pub struct ChartProps {
+    // from 0 to 1, oldest first
+    pub rates: Vec<f64>,
+}
+
+// a line of win rates, with the even line drawn across for reference
+#[function_component(WinRateChart)]
+pub fn win_rate_chart(ChartProps { rates } : &ChartProps) -> Html {
+    if rates.is_empty() {
+        return html! { <div>{ "No battles yet." }</div> };
+    }
+    let step = CHART_WIDTH as f64 / rates.len().saturating_sub(1).max(1) as f64;
+    let points = rates.iter()
+        .enumerate()
+        .map(|(i, rate)| format!("{:.1},{:.1}", i as f64 * step, (1.0 - rate) * CHART_HEIGHT as f64))
+        .collect::<Vec<_>>()
+        .join(" ");
+    html! {
+        <svg class="chart" width={ CHART_WIDTH.to_string() } height={ CHART_HEIGHT.to_string() }>
+            <line x1="0" y1={ (CHART_HEIGHT / 2).to_string() } x2={ CHART_WIDTH.to_string() } y2={ (CHART_HEIGHT / 2).to_string() } stroke="gray" stroke-dasharray="4"/>
+            <polyline points={ points } fill="none" stroke="currentColor" stroke-width="2"/>
+        </svg>
+    }
+}
+
+fn dex_bar(value: u32, max: u32, label: String, title: &str) -> Html {
+    html! {
+        <div>
+            <progress value={ value.to_string() } max={ max.max(1).to_string() } data-label={ label } title={ title.to_string() }></progress>
+        </div>
+    }
+}
+
+// how often each of the character's moves gets picked, shaded by its share of the picks so
+// moves that never get clicked stand out
+fn usage_heatmap(character: &OnionCharacter, actions: &ActionPool) -> Html {
+    let total: u32 = character.attributes.actions.iter()
+        .filter_map(|action| character.attributes.usage.get(action))
+        .map(|usage| usage.used)
+        .sum();
+    html! {
+        <div class="columns" title="How often you've picked each move, and how often it landed.">
+            { for character.attributes.actions.iter().map(|action| {
+                let usage = character.attributes.usage.get(action).copied().unwrap_or_default();
+                let share = usage.used as f64 / total.max(1) as f64;
+                html! {
+                    <div class="usage-cell" style={ format!("background-color: rgba(200, 60, 40, {:.2});", share) }>
+                        <div>{ actions[*action].name() }</div>
+                        <div>{
+                            match usage.landed_rate() {
+                                Some(rate) => format!("{} uses, {:.0}% landed", usage.used, rate * 100.0),
+                                None => "Never used".to_string(),
+                            }
+                        }</div>
+                    </div>
+                }
+            }) }
+        </div>
+    }
+}
+
+// legendaries get a border of their own, so meeting one doesn't go unnoticed
+fn display_class(character: &OnionCharacter) -> Classes {
+    classes!("character-display", (character.species.rarity == Rarity::Legendary).then_some("legendary"))
+}
+
 #[function_component(PlayerDisplay)]
-pub fn player_display(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn player_display(CharacterProps { character, ability } : &CharacterProps) -> Html {
     html! {
         <div>
             <div class="columns">
-                <div class="character-display">
+                <div class={ display_class(character) }>
                     <div><img src={ get_resource("player") } style="position: relative;"/></div>
                     <div><CharacterStats character={character.clone()} /></div>
                 </div>
                 <div class="character-info">
-                    <div><HealthBarWithValue character={character.clone()} /></div>
+                    <div><HealthBarWithValue character={character.clone()} ability={ability.clone()} /></div>
                     <div><ExperienceBar character={character.clone()} /></div>
                 </div>
             </div>
@@ -131,14 +334,14 @@ pub fn player_display(CharacterProps { character } : &CharacterProps) -> Html {
 }
 
 #[function_component(EnemyDisplay)]
-pub fn enemy_display(CharacterProps { character } : &CharacterProps) -> Html {
+pub fn enemy_display(CharacterProps { character, ability } : &CharacterProps) -> Html {
     html! {
         <div>
             <div class="columns">
                 <div class="character-info">
-                    <div><HealthBarWithValue character={character.clone()} /></div>
+                    <div><HealthBarWithValue character={character.clone()} ability={ability.clone()} /></div>
                 </div>
-                <div class="character-display">
+                <div class={ display_class(character) }>
                     <div><img src={ get_resource("enemy") } style="position: relative;"/></div>
                 </div>
             </div>
@@ -146,200 +349,2563 @@ pub fn enemy_display(CharacterProps { character } : &CharacterProps) -> Html {
     }
 }
 
+fn member_summary(member: &Member) -> String {
+    let character = &member.character;
+    format!(
+        "{} Lv{} ({}, BST: {}) HP:{}/{}",
+        character.name,
+        character.attributes.level,
+        alignment_names(&character.species.alignments()),
+        character.species.bst,
+        character.state.health,
+        character.attributes.stats.health,
+    )
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RosterProps {
+    pub roster: Roster,
+    pub on_sort: Callback<RosterOrder>,
+    pub on_move: Callback<(usize, usize)>,
+    pub on_lead: Callback<usize>,
+    pub on_lead_from_reserve: Callback<usize>,
+    pub on_release: Callback<usize>,
+    pub on_retire: Callback<usize>,
+    pub on_breed: Callback<usize>,
+    pub limit: Option<usize>,
+}
+
+#[function_component(RosterView)]
+pub fn roster_view(RosterProps { roster, on_sort, on_move, on_lead, on_lead_from_reserve, on_release, on_retire, on_breed, limit } : &RosterProps) -> Html {
+    let party = roster.party().len();
+    let nesting = roster.eggs().len() < MAX_EGGS && !roster.is_full(*limit);
+    // the reserve kaizo waiting on a yes or no before it's let go
+    let releasing = use_state(|| None::<usize>);
+    html! {
+        <div class="roster">
+            <div>{ format!("Lead: {}", roster.lead().name) }</div>
+            <div>{ "Party" }</div>
+            { for roster.party().iter().enumerate().map(|(slot, member)| {
+                let up = { let on_move = on_move.clone(); move |_| on_move.emit((slot, slot - 1)) };
+                let down = { let on_move = on_move.clone(); move |_| on_move.emit((slot, slot + 1)) };
+                let lead = { let on_lead = on_lead.clone(); move |_| on_lead.emit(slot) };
+                html! {
+                    <div>
+                        { member_summary(member) }
+                        <button class="roster-button" disabled={ slot == 0 } onclick={ lead }
+                            title="Send this kaizo out first.">{ "Lead" }</button>
+                        <button class="roster-button" disabled={ slot == 0 } onclick={ up }
+                            title="Move this kaizo up in the party.">{ "^" }</button>
+                        <button class="roster-button" disabled={ slot + 1 == party } onclick={ down }
+                            title="Move this kaizo down in the party.">{ "v" }</button>
+                    </div>
+                }
+            }) }
+            <div>
+                { match limit {
+                    Some(limit) => format!("Reserve ({}/{}) ", roster.count(), limit),
+                    None => "Reserve ".to_string(),
+                } }
+                { for [RosterOrder::Level, RosterOrder::Bst, RosterOrder::Alignment, RosterOrder::Recency].iter().map(|order| {
+                    let order = *order;
+                    let onclick = { let on_sort = on_sort.clone(); move |_| on_sort.emit(order) };
+                    html! {
+                        <button class="roster-button" disabled={ roster.order() == order } { onclick }
+                            title={ format!("Sort the reserve by {:?}.", order).to_lowercase() }>{ format!("{:?}", order) }</button>
+                    }
+                }) }
+            </div>
+            <VirtualList row_height={ 20.0 } height={ 100.0 }>
+                { for roster.reserve().iter().enumerate().map(|(index, member)| {
+                    let lead = { let on_lead = on_lead_from_reserve.clone(); move |_| on_lead.emit(index) };
+                    let retire = { let on_retire = on_retire.clone(); move |_| on_retire.emit(index) };
+                    let breed = { let on_breed = on_breed.clone(); move |_| on_breed.emit(index) };
+                    let ask = { let releasing = releasing.clone(); move |_| releasing.set(Some(index)) };
+                    if *releasing == Some(index) {
+                        let release = { let (on_release, releasing) = (on_release.clone(), releasing.clone()); move |_| {
+                            releasing.set(None);
+                            on_release.emit(index);
+                        } };
+                        let cancel = { let releasing = releasing.clone(); move |_| releasing.set(None) };
+                        return html! {
+                            <div>
+                                { format!("Release {} for good? ", member.character.name) }
+                                <button class="roster-button" onclick={ release }>{ "Yes" }</button>
+                                <button class="roster-button" onclick={ cancel }>{ "No" }</button>
+                            </div>
+                        };
+                    }
+                    html! {
+                        <div>
+                            { member_summary(member) }
+                            <button class="roster-button" onclick={ lead }
+                                title="Bring this kaizo into the party and send it out first.">{ "Lead" }</button>
+                            <button class="roster-button" onclick={ ask }
+                                title="Let this kaizo go, for some experience for your lead.">{ "Release" }</button>
+                            <button class="roster-button" onclick={ retire }
+                                title="Keep this kaizo in the archive, where it won't fight or take up room.">{ "Retire" }</button>
+                            <button class="roster-button" disabled={ !nesting || !compatible(roster.lead(), &member.character) } onclick={ breed }
+                                title="Have an egg with your lead. Both have to be grown and share an alignment.">{ "Breed" }</button>
+                        </div>
+                    }
+                }) }
+            </VirtualList>
+            { if roster.eggs().is_empty() { html! { } } else { html! {
+                <div>
+                    { format!("Eggs ({}/{})", roster.eggs().len(), MAX_EGGS) }
+                    { for roster.eggs().iter().map(|egg| html! { <div>{ format!("Hatches in {} wins.", egg.battles) }</div> }) }
+                </div>
+            } } }
+            { if roster.retired().is_empty() { html! { } } else { html! {
+                <div>
+                    { "Retired" }
+                    { for roster.retired().iter().map(|member| html! { <div>{ member_summary(member) }</div> }) }
+                </div>
+            } } }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GlossaryProps {
+    pub chart: TypeChart,
+    pub inverse: bool,
+}
+
+#[function_component(Glossary)]
+pub fn glossary(GlossaryProps { chart, inverse } : &GlossaryProps) -> Html {
+    let icon = |alignment| html! {
+        <img title={ format!("{:?}", alignment) } alt={ format!("{:?}", alignment) }
+            src={ get_resource(&format!("{:?}", alignment)).to_lowercase() } width={"16"} height={"16"}/>
+    };
+    html! {
+        <div class="glossary">
+            <div title="How much damage an attack (rows) deals to each alignment (columns).">{
+                if *inverse { "Type chart (inverse)" } else { "Type chart" }
+            }</div>
+            <table>
+                <tr>
+                    <td/>
+                    { for chart.alignments().iter().map(|defense| html! { <td>{ icon(*defense) }</td> }) }
+                </tr>
+                { for chart.alignments().iter().map(|attack| html! {
+                    <tr>
+                        <td>{ icon(*attack) }</td>
+                        { for chart.alignments().iter().map(|defense| html! {
+                            <td>{ format!("{}x", chart.effectiveness(*attack, *defense) as f64 / 10.0) }</td>
+                        }) }
+                    </tr>
+                }) }
+            </table>
+        </div>
+    }
+}
+
+struct TeamBuilder {
+    team: Team,
+    // the member being edited
+    selected: usize,
+    saved: Vec<Team>,
+    // the import/export box and why its contents couldn't be imported
+    text: String,
+    text_error: Option<String>,
+    // the dex entry being looked at, and how every species has done in your battles
+    inspected: Option<usize>,
+    history: BattleHistory,
+}
+
+// the party set up to fight a war, which plays out without anyone at the controls
+struct WarPlan {
+    // party slots, in the order they fight
+    order: Vec<usize>,
+    // the ai each party slot fights with
+    strategies: Vec<Difficulty>,
+    // the last war's match report
+    report: States,
+}
+
+struct ModsScreen {
+    // edited here and only stored once they compose
+    packs: Vec<ContentPack>,
+    // the pack being pasted in
+    name: String,
+    text: String,
+    errors: Vec<String>,
+}
+
+// a kaizo that grew into more moves than it can know, picking which ones to forget
+struct LearnPrompt {
+    // the party slot of the kaizo that is learning
+    slot: usize,
+    // first one first
+    waiting: Actions,
+}
+
+// a kaizo that just won, picking upgrades to take from a draft
+struct DraftPrompt {
+    slot: usize,
+    offers: Vec<Offer>,
+    // how many more of the offers can be taken
+    picks: usize,
+    // moves the win taught it without room for them, to pick between once the draft is done
+    waiting: Actions,
+}
+
+// a recorded battle being watched, or one pasted in from someone else
+struct ReplayViewer {
+    playback: Option<Playback>,
+    // what the steps played so far logged
+    logs: States,
+    // the share box and why its contents couldn't be loaded
+    text: String,
+    text_error: Option<String>,
+}
+
 // TODO: all these helper enums need to be broken up
 enum Scene {
-    Battle(OnionBattle),
-    Menu(OnionCharacter),
+    Battle(Box<Encounter>),
+    Menu,
+    TeamBuilder(Box<TeamBuilder>),
+    Mods(Box<ModsScreen>),
+    Learn(Box<LearnPrompt>),
+    Draft(Box<DraftPrompt>),
+    War(Box<WarPlan>),
+    Replay(Box<ReplayViewer>),
+    // your lifetime play, from the battle history on this device
+    Stats(Box<BattleHistory>),
+    Map,
+    // where the run's money is spent
+    Shop,
+    // where every new run starts, to pick how it's played
+    Title,
+}
+
+impl Scene {
+    fn kind(&self) -> SceneKind {
+        match self {
+            Scene::Battle(_) => SceneKind::Battle,
+            Scene::Menu => SceneKind::Menu,
+            Scene::TeamBuilder(_) => SceneKind::TeamBuilder,
+            Scene::Mods(_) => SceneKind::Mods,
+            Scene::Learn(_) => SceneKind::Learn,
+            Scene::Draft(_) => SceneKind::Draft,
+            Scene::War(_) => SceneKind::War,
+            Scene::Replay(_) => SceneKind::Replay,
+            Scene::Stats(_) => SceneKind::Stats,
+            Scene::Map => SceneKind::Map,
+            Scene::Shop => SceneKind::Shop,
+            Scene::Title => SceneKind::Title,
+        }
+    }
 }
 
 enum BattleAction {
     ActionChosen(ActionId),
+    Recruit,
+    // an item and the party slot to use it on
+    UseItem(ItemId, usize),
     Flee,
 }
 
 enum MenuAction {
     Log(String),
-    Battle,
+    Map,
+    Shop,
+    // heals the party, for a price
+    Rest,
     Scout,
+    Sort(RosterOrder),
+    Move(usize, usize),
+    Lead(usize),
+    LeadFromReserve(usize),
+    Release(usize),
+    Retire(usize),
+    // with the lead
+    Breed(usize),
+    CycleRosterLimit,
+    ToggleChaos,
+    ToggleInverse,
+    ToggleEndless,
+    ToggleRealTime,
+    // risks can't be given back once taken
+    TakeRisk(Risk),
+    Mirror,
+    CycleMirrorAi,
+    // a trainer with a team of their own
+    Trainer,
+    TeamBuilder,
+    Mods,
+    War,
+    // watches the last battle again
+    Replay,
+    Stats,
+    Continue,
+    // fills the save box with the run, to download or copy
+    ExportSave,
+    EditSaveText(String),
+    // picks up the run pasted into the save box instead, whether it's a save or a transfer
+    ImportSave,
+    // shows the run as QR codes, to move it to another device
+    Transfer,
+    EditSeed(String),
+    // starts over from the typed in seed, or a random one if there isn't one
+    NewRun,
+    EditWorldUrl(String),
+    // downloads a world and starts a new run in it
+    LoadWorld,
+    WorldLoaded(Result<Box<RemoteWorld>, Vec<String>>),
+    // forgets the downloaded world, so runs are generated again
+    RandomWorld,
+    // an unlocked checkpoint, by its index in the profile
+    Practice(usize),
 }
 
+enum MapAction {
+    // picks a node on the floor that's up next and goes to it
+    Pick(usize),
+    // goes back to the picked node, e.g. after fleeing its battle
+    Enter,
+    Back,
+}
+
+enum ShopAction {
+    // buys what's on the shelf at that index
+    Buy(usize),
+    Back,
+}
+
+enum TitleAction {
+    Preset(Preset),
+    TogglePermadeath,
+    ToggleHealing,
+    CycleLevelCap,
+    Start,
+    // today's seed, with today's modifier on top of the picked preset
+    Daily,
+}
+
+enum LearnAction {
+    // forgets the move in a slot for the waiting one
+    Forget(usize),
+    Skip,
+}
+
+enum DraftAction {
+    Pick(usize),
+    Skip,
+}
+
+enum TeamAction {
+    Rename(String),
+    Add(usize),
+    Remove(usize),
+    Select(usize),
+    // shows a species' dex entry
+    Inspect(usize),
+    Level(u32),
+    ToggleAction(ActionId),
+    ToggleClause(Clause),
+    Save,
+    Load(usize),
+    // fights the party against a saved team
+    Battle(usize),
+    Delete(usize),
+    EditText(String),
+    Import,
+    Export,
+    Close,
+}
+
+enum WarAction {
+    // moves a kaizo one place earlier in the order
+    Raise(usize),
+    CycleStrategy(usize),
+    Fight,
+    Close,
+}
+
+enum StatsAction {
+    Close,
+}
+
+enum ReplayAction {
+    Step,
+    Restart,
+    EditText(String),
+    // watches the pasted replay instead
+    Load,
+    Close,
+}
+
+enum ModsAction {
+    EditName(String),
+    EditText(String),
+    Add,
+    Toggle(usize),
+    Remove(usize),
+    // moves a pack one place earlier, so the ones after it win
+    Raise(usize),
+    Apply,
+    Close,
+}
+
+// checking for a new world definition every so often
+#[cfg(feature = "dev")]
+enum DevAction {
+    Poll,
+    Fetched(Result<String, String>),
+}
+
+// the variants are named after the action enums they carry
+#[allow(clippy::enum_variant_names)]
 enum Msg {
     BattleAction(BattleAction),
     MenuAction(MenuAction),
+    LearnAction(LearnAction),
+    DraftAction(DraftAction),
+    MapAction(MapAction),
+    ShopAction(ShopAction),
+    TitleAction(TitleAction),
+    TeamAction(TeamAction),
+    ModsAction(ModsAction),
+    WarAction(WarAction),
+    ReplayAction(ReplayAction),
+    StatsAction(StatsAction),
+    #[cfg(feature = "dev")]
+    DevAction(DevAction),
+}
+
+impl Msg {
+    // none for messages that aren't for any one scene
+    fn kind(&self) -> Option<MessageKind> {
+        match self {
+            Msg::BattleAction(_) => Some(MessageKind::Battle),
+            Msg::MenuAction(_) => Some(MessageKind::Menu),
+            Msg::LearnAction(_) => Some(MessageKind::Learn),
+            Msg::DraftAction(_) => Some(MessageKind::Draft),
+            Msg::MapAction(_) => Some(MessageKind::Map),
+            Msg::ShopAction(_) => Some(MessageKind::Shop),
+            Msg::TitleAction(_) => Some(MessageKind::Title),
+            Msg::TeamAction(_) => Some(MessageKind::Team),
+            Msg::ModsAction(_) => Some(MessageKind::Mods),
+            Msg::WarAction(_) => Some(MessageKind::War),
+            Msg::ReplayAction(_) => Some(MessageKind::Replay),
+            Msg::StatsAction(_) => Some(MessageKind::Stats),
+            #[cfg(feature = "dev")]
+            Msg::DevAction(_) => None,
+        }
+    }
+
+    // messages that answer what's on screen, which a double click would otherwise send twice
+    fn locks(&self) -> bool {
+        matches!(self, Msg::BattleAction(_) | Msg::LearnAction(_) | Msg::DraftAction(_))
+    }
+}
+
+// trades experience past the level cap in for money. it takes the purse rather than the app, so it
+// can be paid out while a battle in the scene is still borrowed
+fn earn(purse: &mut u32, overflow: u32) -> Option<String> {
+    let money = money_for(overflow);
+    if money == 0 {
+        return None;
+    }
+    *purse += money;
+    Some(format!("Traded {} experience past the level cap for {} money.", overflow, money))
+}
+
+// a different world is a different race, so a run in it starts over against that world's ghost
+fn race(world: &OnionWorld, seed: Seed) -> (Ghost, Option<Ghost>) {
+    let run = Ghost::new(WorldId::of(world), seed);
+    let ghost = load_ghost(run.world, seed);
+    (run, ghost)
 }
 
 struct App {
     world: OnionWorld,
+    // `world` without the content packs
+    base_world: OnionWorld,
+    roster: Roster,
+    rules: Ruleset,
+    mirror_ai: Difficulty,
     scene: Scene,
     logs: Vec<String>,
+    wins: u32,
+    // from wins, and experience the level cap turned away
+    money: u32,
+    // rolled from the seed, so it's the same every time the run is played
+    shop: Shop,
+    // the bosses reached on this device, to practice against
+    profile: Profile,
+    // where the run is practicing, if it is. practice runs aren't rated or saved, and they're over
+    // after the one battle
+    practice: Option<Checkpoint>,
+    // off while an older run could still be continued, so a fresh page doesn't overwrite it
+    saving: bool,
+    // every roll in the run comes from one of `rng`'s streams, so sharing the seed replays the same run
+    seed: Seed,
+    rng: Streams,
+    seed_input: String,
+    // where the world was downloaded from, if it wasn't generated
+    world_url: Option<String>,
+    world_url_input: String,
+    // the save being exported or imported
+    save_text: String,
+    // the run cut up for a transfer, once one's been asked for, with each part's code drawn ahead
+    // of time since it's slow to work out
+    transfer: Vec<(Chunk, (usize, Vec<bool>))>,
+    // this run's wins, and the best earlier run on the same seed and world to race against
+    run: Ghost,
+    ghost: Option<Ghost>,
+    // how many times the run has been played, counting this time
+    sessions: u32,
+    // the moves picked this run, for the adaptive ai to learn from
+    habits: Habits,
+    // the masters this run has to get through
+    gauntlet: Run,
+    // picked on the title screen
+    config: GameConfig,
+    inventory: Inventory,
+    // held from a battle, learn or draft click until its result is drawn
+    input_lock: InputLock,
+    // what time of day it is in the world, which changes what can be met while scouting
+    clock: Clock,
+    // the last battle that ended, to watch again or share
+    last_replay: Option<Replay>,
+    #[cfg(feature = "dev")]
+    watcher: WorldWatcher,
 }
 
-impl Component for App {
-    type Message = Msg;
-    type Properties = ();
-
-    fn create(_: &Context<Self>) -> Self {
-        let world: OnionWorld = Standard.sample(&mut thread_rng());
-        let mut character: OnionCharacter = world.sample(&mut thread_rng());
-        character.gain_experience(EXPERIENCE_TO_LEVEL);
-        character.attributes.stats = character.species.stats.scale(EXPERIENCE_TO_LEVEL);
+impl App {
+    fn new_run(seed: Seed) -> App {
+        let mut rng = Streams::new(seed);
+        // new runs start in the last downloaded world until it's forgotten
+        let remote = load_remote_world().and_then(|remote| Some((remote.build().ok()?, remote.url)));
+        let (base_world, world_url) = match remote {
+            Some((world, url)) => (world, Some(url)),
+            None => (Standard.sample(&mut rng.worldgen), None),
+        };
+        // packs that no longer compose are left off until they're fixed in the mods screen
+        let world = compose_world(&base_world, &load_packs()).unwrap_or_else(|_| base_world.clone());
+        let mut character: OnionCharacter = world.sample(&mut rng.worldgen);
+        ivs::assign(&mut character, &mut rng.ivs);
+        character.gain_experience(character.experience_to_next_level());
         character.refresh();
-        Self {
-            scene: Scene::Menu(character),
+        let gauntlet = Run::generate(&world, character.attributes.level, &mut rng.worldgen);
+        let (run, ghost) = race(&world, seed);
+        let shop = Shop::generate(&world, &mut rng.shop);
+        App {
+            gauntlet,
+            shop,
+            config: GameConfig::default(),
+            inventory: Inventory::starting(&world.items),
+            run,
+            ghost,
+            sessions: 1,
+            habits: Habits::default(),
+            roster: Roster::with_lead(character),
+            rules: Ruleset::default(),
+            mirror_ai: Difficulty::Normal,
+            scene: Scene::Title,
             world,
+            base_world,
             logs: Vec::new(),
+            wins: 0,
+            money: 0,
+            profile: load_profile(),
+            practice: None,
+            saving: true,
+            seed,
+            rng,
+            seed_input: String::new(),
+            world_url,
+            world_url_input: String::new(),
+            save_text: String::new(),
+            transfer: Vec::new(),
+            input_lock: InputLock::default(),
+            clock: Clock::default(),
+            last_replay: None,
+            #[cfg(feature = "dev")]
+            watcher: WorldWatcher::default(),
         }
     }
 
-    fn update(&mut self, _: &Context<Self>, msg: Self::Message) -> bool {
-        self.logs.clear();
+    // an unrated run that starts right at a boss reached before, with the standard party. the
+    // world is put back together from the checkpoint's seed and whatever's loaded, so it has to
+    // come out the same as the one the boss was reached in
+    fn practice_run(&mut self, checkpoint: Checkpoint) {
+        let config = self.config;
+        let app = App::new_run(checkpoint.seed);
+        if WorldId::of(&app.world) != checkpoint.world {
+            self.logs.push(format!("{} is in a world that isn't loaded any more.", checkpoint.name()));
+            return;
+        }
+        *self = app;
+        self.config = config;
+        self.roster = standard_party(&self.world, checkpoint.level());
+        self.gauntlet = checkpoint.run();
+        self.ghost = None;
+        self.logs.push(format!("Practicing against {}. Practice runs aren't rated.", checkpoint.name()));
+        self.practice = Some(checkpoint);
+        self.enter_node();
+    }
+
+    // the real run was never overwritten, so it's there to continue like on a fresh page
+    fn end_practice(&mut self) {
+        let logs = std::mem::take(&mut self.logs);
+        *self = App { saving: !has_save(), ..App::new_run(Seed::random()) };
+        self.logs = logs;
+        self.logs.push("The practice run is over.".to_string());
+    }
+
+    fn ability_title(&self, character: &OnionCharacter) -> Option<String> {
+        let ability = self.world.abilities.get(character.species.ability?)?;
+        Some(format!("{}: {}", ability.name, ability.description()))
+    }
+
+    fn time(&self) -> TimeOfDay {
+        self.clock.time(&self.world.cycle, current_hour())
+    }
+
+    // nobody in the party is left standing. losing on the map ends the run, and the party is kept
+    // after losing on the map or on casual, unless permadeath took all of it
+    fn wipe_out(&mut self, gauntlet: bool) {
+        if gauntlet {
+            self.gauntlet.lose();
+            if self.practice.is_none() {
+                self.logs.push(format!("The run is over after {} masters.", self.gauntlet.cleared()));
+            }
+        }
+        self.logs.extend(self.config.bury(&mut self.roster));
+        if self.roster.party().is_empty() || !(gauntlet || self.config.heal_after_defeat) {
+            // re-roll player kaizo
+            let mut character = self.world.sample(&mut self.rng.encounters);
+            ivs::assign(&mut character, &mut self.rng.ivs);
+            character.gain_experience(character.experience_to_next_level());
+            character.refresh();
+            self.roster = Roster::with_lead(character);
+        } else {
+            self.roster.refresh();
+        }
+    }
+
+    // `phases` makes it a boss battle
+    fn start_battle(&mut self, mut enemy: OnionCharacter, ai: Difficulty, wild: bool, gauntlet: bool, mut trainer: Option<Trainer>, phases: Option<Vec<Phase>>) {
+        self.config.boost(&mut enemy);
+        if let Some(trainer) = &mut trainer {
+            trainer.team.iter_mut().for_each(|member| self.config.boost(member));
+        }
+        self.open_battle(enemy, ai, wild, gauntlet, trainer, phases);
+    }
+
+    // a trainer battle that doesn't count: nothing is boosted, and whatever happens to the party
+    // is undone once it's over
+    fn start_sandbox(&mut self, mut trainer: Trainer) {
+        if self.roster.next_conscious().is_none() {
+            self.logs.push("Nobody in your party can battle. Rest first.".to_string());
+            return;
+        }
+        let party = self.roster.clone();
+        let enemy = match trainer.lead() {
+            Some(enemy) => enemy,
+            None => return,
+        };
+        self.open_battle(enemy, trainer.ai, false, false, Some(trainer), None);
+        if let Scene::Battle(encounter) = &mut self.scene {
+            encounter.sandbox = Some(party);
+        }
+    }
+
+    fn end_sandbox(&mut self, party: Roster) {
+        self.roster = party;
+        self.logs.push("Your party is back the way it was before the battle.".to_string());
+        self.scene = Scene::Menu;
+    }
+
+    fn open_battle(&mut self, enemy: OnionCharacter, ai: Difficulty, wild: bool, gauntlet: bool, trainer: Option<Trainer>, phases: Option<Vec<Phase>>) {
+        match &trainer {
+            Some(trainer) => {
+                self.logs.push(format!("{} wants to battle!", trainer.name));
+                self.logs.push(format!("{} sent out {}!", trainer.name, enemy.name));
+            },
+            None if wild => self.logs.push(format!("A wild {} appeared!", enemy.name)),
+            None if phases.is_some() => self.logs.push(format!("{} blocks the way!", enemy.name)),
+            None => self.logs.push(format!("{} appeared!", enemy.name)),
+        }
+        // kaizo that fainted stay down until the party rests, so whoever's first still standing goes
+        // out. a party with nobody standing is dealt with like one that lost, which leaves a lead
+        if self.roster.next_conscious().is_none() {
+            self.wipe_out(false);
+        }
+        let slot = self.roster.next_conscious().unwrap();
+        let mut battle = OnionBattle::new(self.roster.get(slot).unwrap().clone(), enemy);
+        battle.level_cap = self.config.level_cap;
+        if let Some(trainer) = &trainer {
+            trainer.start(&mut battle);
+        }
+        match phases {
+            Some(phases) => bosses::start(&mut battle, phases),
+            None if wild => battle.kind = EncounterKind::Wild,
+            None => battle.kind = EncounterKind::Trainer,
+        }
+        self.logs.extend(self.rules.start_battle(&mut battle, &self.world, &mut self.rng.battle));
+        self.logs.extend(battle.start());
+        let replay = Replay::new(self.run.world, battle.clone(), Seed(self.rng.battle.gen()));
+        // every battle moves the day along, whoever it's against
+        self.logs.extend(self.clock.tick(&self.world.cycle));
+        self.scene = Scene::Battle(Box::new(Encounter { battle, slot, ai, wild, turns: 0, gauntlet, replay: Some(replay), trainer, sandbox: None }));
+    }
+
+    // plays out the node picked on the map
+    fn enter_node(&mut self) {
+        let node = match self.gauntlet.current() {
+            Some(node) => node.clone(),
+            None => return,
+        };
+        match (node.kind, node.enemy) {
+            (NodeKind::Rest, _) => {
+                self.logs.push("The party rested and trained.".to_string());
+                self.roster.refresh();
+                let cap = self.config.level_cap;
+                let overflow: u32 = self.roster.characters_mut().map(|character| gain_capped(character, REST_EXPERIENCE, cap).1).sum();
+                self.logs.extend(earn(&mut self.money, overflow));
+                self.gauntlet.win();
+                self.scene = Scene::Menu;
+            },
+            (NodeKind::Treasure, _) => {
+                let mut lead = self.roster.lead().clone();
+                let mut waiting = Vec::new();
+                match treasure(&self.world, &lead, &mut self.rng.drops) {
+                    Some(action) => {
+                        let (logs, left) = learn_moves(&mut lead, &[action], &self.world.actions);
+                        self.logs.extend(logs);
+                        self.roster.set(0, lead);
+                        waiting = left;
+                    },
+                    None => self.logs.push("There was nothing new to learn.".to_string()),
+                }
+                if !self.world.items.is_empty() {
+                    let item = self.rng.drops.gen_range(0..self.world.items.len());
+                    self.inventory.add(item, 1);
+                    self.logs.push(format!("Found a {}!", self.world.items[item].name));
+                }
+                self.gauntlet.win();
+                self.scene = if waiting.is_empty() {
+                    Scene::Menu
+                } else {
+                    Scene::Learn(Box::new(LearnPrompt { slot: 0, waiting }))
+                };
+            },
+            (kind, Some(mut enemy)) => {
+                // the checkpoint keeps the boss as it was met, so practice doesn't roll it again
+                if self.practice.is_none() {
+                    ivs::assign(&mut enemy, &mut self.rng.ivs);
+                    if let Some(mut checkpoint) = Checkpoint::reached(WorldId::of(&self.world), self.seed, &self.gauntlet) {
+                        checkpoint.boss.enemy = Some(enemy.clone());
+                        if self.profile.unlock(checkpoint) {
+                            store_profile(&self.profile);
+                            self.logs.push(format!("Unlocked a practice checkpoint at {}.", enemy.name));
+                        }
+                    }
+                }
+                self.start_battle(enemy, node.ai, kind == NodeKind::Scout, true, None, (kind == NodeKind::Boss).then_some(node.phases));
+            },
+            // only rests and treasure come without an enemy
+            (_, None) => self.scene = Scene::Menu,
+        }
+    }
+
+    fn save_game(&self) -> SaveGame {
+        SaveGame {
+            world: self.world.clone(),
+            roster: self.roster.clone(),
+            rules: self.rules.clone(),
+            mirror_ai: self.mirror_ai,
+            encounter: match &self.scene {
+                Scene::Battle(encounter) => Some(encounter.as_ref().clone()),
+                _ => None,
+            },
+            wins: self.wins,
+            money: self.money,
+            seed: self.seed,
+            base_world: Some(self.base_world.clone()),
+            world_url: self.world_url.clone(),
+            run: self.run.clone(),
+            sessions: self.sessions,
+            habits: self.habits.clone(),
+            gauntlet: Some(self.gauntlet.clone()),
+            config: self.config,
+            inventory: Some(self.inventory.clone()),
+            clock: self.clock,
+        }
+    }
+
+    // the save box's contents, put back together first if they're a transfer
+    fn import_text(&self) -> Result<SaveGame, String> {
+        let text = if is_transfer(&self.save_text) {
+            assemble(&self.save_text).map_err(|error| error.to_string())?
+        } else {
+            self.save_text.clone()
+        };
+        import(&text).map_err(|error| error.to_string())
+    }
+
+    // practice runs are never saved, so the real run stays in the slot
+    fn autosave(&mut self) {
+        if self.saving && self.practice.is_none() {
+            if let Err(error) = save(&self.save_game()) {
+                self.logs.push(format!("Couldn't save: {}", error));
+            }
+        }
+    }
+
+    fn load_game(&mut self, game: SaveGame) {
+        self.base_world = match game.base_world {
+            Some(world) => world,
+            None => game.world.clone(),
+        };
+        self.world = game.world;
+        self.roster = game.roster;
+        let world = &self.world;
+        self.roster.characters_mut().for_each(|character| world.intern(character));
+        self.rules = game.rules;
+        self.mirror_ai = game.mirror_ai;
+        self.scene = match game.encounter {
+            Some(mut encounter) => {
+                self.world.intern(&mut encounter.battle.player);
+                self.world.intern(&mut encounter.battle.enemy);
+                Scene::Battle(Box::new(encounter))
+            },
+            None => Scene::Menu,
+        };
+        self.wins = game.wins;
+        self.money = game.money;
+        // TODO: the rng's position isn't saved, so a continued run won't roll the same as one
+        //       played straight through
+        self.seed = game.seed;
+        self.world_url = game.world_url;
+        self.rng = Streams::new(game.seed);
+        self.shop = Shop::generate(&self.world, &mut self.rng.shop);
+        // older saves didn't keep their run, so it starts over from here
+        (self.run, self.ghost) = race(&self.world, self.seed);
+        if game.run.races(&self.run) {
+            self.run = game.run;
+        }
+        self.sessions = game.sessions.max(1) + 1;
+        self.habits = game.habits;
+        self.config = game.config;
+        // older saves didn't have a bag, so they get a new one
+        self.inventory = game.inventory.unwrap_or_else(|| Inventory::starting(&self.world.items));
+        self.clock = game.clock;
+        // older saves didn't have a gauntlet, so one is rolled for them
+        self.gauntlet = match game.gauntlet {
+            Some(mut gauntlet) => {
+                gauntlet.floors.iter_mut()
+                    .flatten()
+                    .filter_map(|node| node.enemy.as_mut())
+                    .for_each(|enemy| self.world.intern(enemy));
+                gauntlet
+            },
+            None => Run::generate(&self.world, self.roster.lead().attributes.level, &mut self.rng.worldgen),
+        };
+        // the definition being worked on wins over the one that was saved
+        #[cfg(feature = "dev")]
+        self.watcher.reset();
+    }
+
+    fn handle(&mut self, ctx: &Context<Self>, msg: Msg) {
         match (msg, &mut self.scene) {
-            (Msg::BattleAction(action), Scene::Battle(battle)) => {
-                // get player action
+            (Msg::BattleAction(action), Scene::Battle(encounter)) => {
+                let Encounter { battle, slot, ai, wild, turns, gauntlet, replay, trainer, sandbox } = encounter.as_mut();
+                // practice battles aren't rated, so they don't race the ghost or count toward the history
+                let rated = self.practice.is_none() && sandbox.is_none();
+                let mut player_move = None;
+                let mut chosen = None;
+                // get player action; a failed recruit uses up the player's turn
                 let player_action = match action {
-                    BattleAction::ActionChosen(action) => &self.world.actions[action],
+                    BattleAction::ActionChosen(action) => {
+                        // struggles and skips aren't counted as uses of the move
+                        player_move = self.world.actions.usable(&battle.player).find(|usable| *usable == action);
+                        chosen = Some(action);
+                        Some(self.world.actions.spend(&mut battle.player, action))
+                    },
+                    BattleAction::Recruit if *wild && self.roster.is_full(self.rules.roster_limit) => {
+                        self.logs.push("Your roster is full. Release or retire a kaizo to make room.".to_string());
+                        return;
+                    },
+                    BattleAction::Recruit if *wild => {
+                        if try_capture(&battle.enemy, &mut self.rng.battle) {
+                            if rated {
+                                record_battle(&battle.player, &battle.enemy, true);
+                            }
+                            let mut recruit = battle.enemy.clone();
+                            recruit.refresh();
+                            if self.roster.party().len() < PARTY_SIZE {
+                                self.logs.push(format!("{} joined your party!", recruit.name));
+                            } else {
+                                self.logs.push(format!("{} was sent to the reserve.", recruit.name));
+                            }
+                            self.roster.set(*slot, battle.player.clone());
+                            self.logs.extend(self.config.bury(&mut self.roster));
+                            self.config.after_battle(&mut self.roster);
+                            self.roster.recruit(recruit);
+                            if *gauntlet {
+                                self.gauntlet.win();
+                            }
+                            self.last_replay = replay.take();
+                            self.scene = Scene::Menu;
+                            return;
+                        }
+                        self.logs.push(format!("{} broke free!", battle.enemy.name));
+                        None
+                    },
+                    // only wild kaizo can be recruited
+                    BattleAction::Recruit => return,
+                    // using an item takes the turn, unless it wouldn't have done anything
+                    BattleAction::UseItem(item, target) => {
+                        let logs = if target == *slot {
+                            self.inventory.use_on(&self.world.items, item, &mut battle.player)
+                        } else {
+                            let mut character = match self.roster.get(target) {
+                                Some(character) => character.clone(),
+                                None => return,
+                            };
+                            let logs = self.inventory.use_on(&self.world.items, item, &mut character);
+                            self.roster.set(target, character);
+                            logs
+                        };
+                        match logs {
+                            Some(logs) => self.logs.extend(logs),
+                            None => {
+                                self.logs.push("It won't have any effect.".to_string());
+                                return;
+                            },
+                        }
+                        if let Some(replay) = replay {
+                            replay.record(ReplayStep::Player(Box::new(battle.player.clone())));
+                        }
+                        None
+                    },
+                    BattleAction::Flee if !self.config.flee => {
+                        self.logs.push("There's no fleeing on kaizo.".to_string());
+                        return;
+                    },
+                    // the battle is over as soon as the player runs, so the enemy doesn't get to act
                     BattleAction::Flee => {
-                        battle.player.refresh();
-                        self.scene = Scene::Menu(battle.player.clone());
-                        return true;
-                    }
+                        self.logs.extend(battle.flee());
+                        if let Some(replay) = replay {
+                            replay.record(ReplayStep::Flee);
+                        }
+                        None
+                    },
                 };
-                // get enemy action
-                let enemy_action = battle.enemy.attributes.actions.get(random::<usize>() % battle.enemy.attributes.actions.len()).copied().unwrap();
-                let enemy_action = &self.world.actions[enemy_action];
-
-                // determine action order:
-                //  - highest priority wins
-                //  - if a priority tie, highest speed wins
-                //  - if a speed tie, flip a coin
-                let player_first = if player_action.priority() > enemy_action.priority() {
-                    true
-                } else if player_action.priority() == enemy_action.priority() &&
-                    (battle.player.priority() > battle.enemy.priority() ||
-                        (battle.player.priority() == battle.enemy.priority() && random::<bool>())) {
-                    true
-                } else {
-                    false
+                // the turn rolls from the replay's seed, so it plays back the same; battles from
+                // before replays draw one from the run's rng
+                let mut rng = match replay {
+                    Some(replay) => replay.turn_rng(),
+                    None => Seed(self.rng.battle.gen()).rng(),
                 };
+                if battle.battle_state() == OnionBattleState::InProcess {
+                    // get enemy action
+                    let opening = *turns == 0;
+                    let enemy_choice = ai.policy(&self.habits, opening).choose(&battle.enemy, &battle.player, &battle.field, &self.world.actions, &mut self.rng.battle);
+                    // only once the enemy has picked, so it can't see this turn's move coming
+                    if let Some(action) = player_move {
+                        self.habits.record(action, opening);
+                    }
+                    let enemy_action = self.world.actions.spend(&mut battle.enemy, enemy_choice);
 
-                if player_first {
-                    self.logs.extend(battle.player_turn(player_action));
-                    self.logs.extend(battle.enemy_turn(enemy_action));
-                } else {
-                    self.logs.extend(battle.enemy_turn(enemy_action));
-                    self.logs.extend(battle.player_turn(player_action));
-                }
+                    self.logs.extend(battle.resolve_turn(player_action, enemy_action, &mut rng));
+                    if let Some(action) = player_move {
+                        let landed = battle.player.state.landed;
+                        battle.player.record_use(action, landed);
+                    }
+                    if let Some(replay) = replay {
+                        replay.record(ReplayStep::Turn { player: chosen, enemy: enemy_choice });
+                    }
 
-                match battle.end_turn() {
+                    *turns += 1;
+                }
+                let (state, logs) = battle.end_turn();
+                let (state, logs) = match state {
+                    OnionBattleState::InProcess => {
+                        self.logs.extend(logs);
+                        encounters::end_turn(battle, &mut rng)
+                    },
+                    state => (state, logs),
+                };
+                // both leads going down at once only ends a trainer's battle once either side is out
+                let (state, logs) = match (state, trainer.as_mut()) {
+                    (OnionBattleState::Draw, Some(trainer)) if !trainer.team.is_empty() => match trainer.after_draw(battle, &mut self.roster, slot) {
+                        Some(more) => {
+                            if let Some(replay) = replay {
+                                replay.record(ReplayStep::Player(Box::new(battle.player.clone())));
+                                replay.record(ReplayStep::Enemy(Box::new(battle.enemy.clone())));
+                            }
+                            (OnionBattleState::InProcess, logs.into_iter().chain(more).collect())
+                        },
+                        None => (OnionBattleState::Draw, logs),
+                    },
+                    (state, _) => (state, logs),
+                };
+                match (state, logs) {
+                    // a trainer's battle goes on until their whole team is down
+                    (OnionBattleState::Victory, logs) if trainer.as_ref().is_some_and(|trainer| !trainer.team.is_empty()) => {
+                        self.logs.extend(logs);
+                        if let Some(logs) = trainer.as_mut().and_then(|trainer| trainer.send_out(battle)) {
+                            self.logs.extend(logs);
+                            if let Some(replay) = replay {
+                                replay.record(ReplayStep::Enemy(Box::new(battle.enemy.clone())));
+                            }
+                        }
+                    },
                     (OnionBattleState::Victory, logs) => {
+                        if rated {
+                            record_battle(&battle.player, &battle.enemy, true);
+                        }
+                        self.last_replay = replay.take();
                         // award xp
                         self.logs.extend(logs);
+                        if let Some(party) = sandbox.take() {
+                            self.end_sandbox(party);
+                            return;
+                        }
                         // TODO: have to chose if the battle is over or if we are still going
-                        // TODO: if we learned moves, it needs to happen here
-                        battle.player.refresh();
+                        let mut learnable = battle.learnable.clone();
+                        if *gauntlet {
+                            // elites leave a move behind
+                            if self.gauntlet.current().map(|node| node.kind) == Some(NodeKind::Elite) {
+                                learnable.extend(treasure(&self.world, &battle.player, &mut self.rng.drops));
+                            }
+                            self.gauntlet.win();
+                            if self.gauntlet.outcome == Outcome::Won && rated {
+                                self.logs.push("You beat every master! The run is won.".to_string());
+                                if self.rules.endless {
+                                    let (gauntlet, logs) = next_cycle(&mut self.rules, &self.world, battle.player.attributes.level, &mut self.rng.worldgen);
+                                    self.gauntlet = gauntlet;
+                                    self.logs.extend(logs);
+                                }
+                            }
+                        }
+                        let (logs, waiting) = learn_moves(&mut battle.player, &learnable, &self.world.actions);
+                        self.logs.extend(logs);
+                        self.roster.set(*slot, battle.player.clone());
+                        let (logs, overflow) = share(&mut self.roster, *slot, battle.earned(), self.config.level_cap);
+                        self.logs.extend(logs);
+                        self.logs.extend(earn(&mut self.money, battle.overflow() + overflow));
+                        // permadeath takes whoever fainted, which moves the winner up past them
+                        let slot = if self.config.permadeath {
+                            *slot - self.roster.party()[..*slot].iter().filter(|member| member.character.state.health <= 0).count()
+                        } else {
+                            *slot
+                        };
+                        self.logs.extend(self.config.bury(&mut self.roster));
+                        self.config.after_battle(&mut self.roster);
+                        self.logs.extend(self.roster.incubate());
                         // TODO: if we add evos, it should happen before this
-                        self.scene = Scene::Menu(battle.player.clone());
+                        if !*wild && rated {
+                            let money = reward(battle.enemy.attributes.level);
+                            self.money += money;
+                            self.logs.push(format!("Got {} money!", money));
+                            self.wins += 1;
+                            self.run.record(*turns);
+                            store_ghost(&self.run);
+                        }
+                        // beating a master earns a draft, which any moves waiting to be learned wait for
+                        self.scene = if !*wild && rated {
+                            let offers = draft(&self.world, &battle.player, &mut self.rng.drops);
+                            Scene::Draft(Box::new(DraftPrompt { slot, offers, picks: draft_picks(&self.rules.risks), waiting }))
+                        } else if waiting.is_empty() {
+                            Scene::Menu
+                        } else {
+                            Scene::Learn(Box::new(LearnPrompt { slot, waiting }))
+                        };
                     },
                     (OnionBattleState::Defeat, logs) => {
+                        if rated {
+                            record_battle(&battle.player, &battle.enemy, false);
+                        }
                         self.logs.extend(logs);
-                        // re-roll player kaizo
-                        let mut character = self.world.sample(&mut thread_rng());
-                        character.gain_experience(EXPERIENCE_TO_LEVEL);
-                        character.attributes.stats = character.species.stats.scale(EXPERIENCE_TO_LEVEL);
-                        character.refresh();
-                        self.scene = Scene::Menu(character);
+                        self.roster.set(*slot, battle.player.clone());
+                        // send out the next kaizo in party order
+                        // spikes can knock out whoever is sent out, so keep going until someone is standing
+                        while let Some(next) = self.roster.next_conscious() {
+                            *slot = next;
+                            // risks last the whole run, so they hold for whoever is sent out next
+                            let mut character = self.roster.get(next).unwrap().clone();
+                            character.state.vulnerable = battle.player.state.vulnerable;
+                            self.logs.push(format!("Go, {}!", character.name));
+                            self.logs.extend(battle.switch_in(character));
+                            if let Some(replay) = replay {
+                                replay.record(ReplayStep::Player(Box::new(battle.player.clone())));
+                            }
+                            self.roster.set(next, battle.player.clone());
+                            if battle.player.state.health > 0 {
+                                return;
+                            }
+                            self.logs.push(format!("{} fainted!", battle.player.name));
+                        }
+                        self.last_replay = replay.take();
+                        if let Some(party) = sandbox.take() {
+                            self.end_sandbox(party);
+                            return;
+                        }
+                        let gauntlet = *gauntlet;
+                        self.wipe_out(gauntlet);
+                        self.scene = Scene::Menu;
+                    },
+                    // nobody won: either side got away, or both went down at once
+                    (OnionBattleState::Escaped | OnionBattleState::Fled | OnionBattleState::Draw, logs) => {
+                        if rated {
+                            record_battle(&battle.player, &battle.enemy, false);
+                        }
+                        self.last_replay = replay.take();
+                        self.logs.extend(logs);
+                        if let Some(party) = sandbox.take() {
+                            self.end_sandbox(party);
+                            return;
+                        }
+                        self.roster.set(*slot, battle.player.clone());
+                        self.logs.extend(self.config.bury(&mut self.roster));
+                        self.config.after_battle(&mut self.roster);
+                        // both going down at once with nobody left to send out is as good as losing
+                        let wiped_out = self.roster.next_conscious().is_none();
+                        // a wild kaizo can be left behind, but a master has to be beaten to move on
+                        if *gauntlet && *wild && !wiped_out {
+                            self.gauntlet.win();
+                        }
+                        if wiped_out {
+                            let gauntlet = *gauntlet;
+                            self.wipe_out(gauntlet);
+                        }
+                        self.scene = Scene::Menu;
                     },
-                    _ => ()
+                    (OnionBattleState::InProcess, logs) => self.logs.extend(logs),
                 }
             }
-            (Msg::MenuAction(action), Scene::Menu(player)) => match action {
-                MenuAction::Battle => {
-                    // TODO: we need to think in terms of generating a whole sequence of battles
-                    let player = player.clone();
-                    let enemy = self.world.sample_at_level(player.attributes.level, &mut thread_rng());
-                    self.logs.push(format!("{} appeared!", enemy.name));
-                    self.scene = Scene::Battle(OnionBattle { player, enemy });
+            (Msg::MenuAction(action), Scene::Menu) => match action {
+                MenuAction::Map => self.scene = Scene::Map,
+                MenuAction::Shop => self.scene = Scene::Shop,
+                MenuAction::Rest => {
+                    let price = scale_price(REST_PRICE, self.wins);
+                    if self.money < price {
+                        self.logs.push(format!("Resting costs {} money.", price));
+                        return;
+                    }
+                    self.money -= price;
+                    self.roster.refresh();
+                    self.logs.push(format!("The party rested for {} money.", price));
+                },
+                // the whole party is copied, and sent out in party order like a trainer's team
+                MenuAction::Mirror => {
+                    let team = self.roster.party().iter().map(|member| {
+                        let mut character = member.character.clone();
+                        // the mirror isn't worn down like the party might be
+                        character.refresh();
+                        character.name = format!("Mirror {}", character.name).into();
+                        character
+                    }).collect();
+                    self.start_sandbox(Trainer { name: "Your mirror".to_string(), team, ai: self.mirror_ai });
+                },
+                MenuAction::CycleMirrorAi => self.mirror_ai = self.mirror_ai.next(),
+                // every so often a boss is waiting instead
+                MenuAction::Trainer if boss_due(self.wins + 1) => {
+                    let level = self.roster.lead().attributes.level;
+                    let (mut enemy, phases) = bosses::generate(&self.world, level, &mut self.rng.encounters);
+                    ivs::assign(&mut enemy, &mut self.rng.ivs);
+                    self.start_battle(enemy, Difficulty::Hard, false, false, None, Some(phases));
+                },
+                MenuAction::Trainer => {
+                    let level = self.roster.lead().attributes.level;
+                    let mut trainer = Trainer::generate(&self.world, level, Difficulty::for_wins(self.wins), &mut self.rng.encounters);
+                    for member in trainer.team.iter_mut() {
+                        ivs::assign(member, &mut self.rng.ivs);
+                    }
+                    if let Some(enemy) = trainer.lead() {
+                        self.start_battle(enemy, trainer.ai, false, false, Some(trainer), None);
+                    }
+                },
+                MenuAction::TeamBuilder => {
+                    self.scene = Scene::TeamBuilder(Box::new(TeamBuilder {
+                        team: Team::default(),
+                        selected: 0,
+                        saved: load_teams(),
+                        text: String::new(),
+                        text_error: None,
+                        inspected: None,
+                        history: load_history(),
+                    }));
+                },
+                MenuAction::War => {
+                    let party = self.roster.party().len();
+                    self.scene = Scene::War(Box::new(WarPlan {
+                        order: (0..party).collect(),
+                        strategies: vec![Difficulty::Normal; party],
+                        report: Vec::new(),
+                    }));
+                },
+                MenuAction::Replay => {
+                    let replay = self.last_replay.clone().filter(|replay| replay.world == self.run.world);
+                    self.scene = Scene::Replay(Box::new(ReplayViewer {
+                        text: replay.as_ref().map(Replay::share).unwrap_or_default(),
+                        playback: replay.map(Playback::new),
+                        logs: Vec::new(),
+                        text_error: None,
+                    }));
+                },
+                MenuAction::Stats => self.scene = Scene::Stats(Box::new(load_history())),
+                MenuAction::Mods => {
+                    self.scene = Scene::Mods(Box::new(ModsScreen {
+                        packs: load_packs(),
+                        name: String::new(),
+                        text: String::new(),
+                        errors: Vec::new(),
+                    }));
+                },
+                MenuAction::Continue => match load() {
+                    Some(game) => {
+                        self.load_game(game);
+                        self.saving = true;
+                        self.logs.push("Welcome back!".to_string());
+                    },
+                    None => self.logs.push("The saved run couldn't be read.".to_string()),
+                },
+                MenuAction::ExportSave => self.save_text = export(&self.save_game()),
+                MenuAction::EditSaveText(text) => self.save_text = text,
+                MenuAction::ImportSave => match self.import_text() {
+                    Ok(game) => {
+                        self.load_game(game);
+                        self.save_text.clear();
+                        self.transfer.clear();
+                        self.logs.push("Imported the save. Welcome back!".to_string());
+                    },
+                    Err(error) => self.logs.push(error),
+                },
+                MenuAction::Transfer => {
+                    self.transfer = chunks(&export(&self.save_game())).into_iter().map(|chunk| {
+                        let qr = chunk.qr();
+                        (chunk, qr)
+                    }).collect();
+                },
+                MenuAction::EditSeed(text) => self.seed_input = text,
+                MenuAction::NewRun => {
+                    let seed = if self.seed_input.trim().is_empty() {
+                        Ok(Seed::random())
+                    } else {
+                        self.seed_input.parse()
+                    };
+                    match seed {
+                        Ok(seed) => {
+                            *self = App::new_run(seed);
+                            self.logs.push(format!("Started a new run with seed {}.", seed));
+                        },
+                        Err(error) => self.logs.push(error.to_string()),
+                    }
+                },
+                MenuAction::EditWorldUrl(text) => self.world_url_input = text,
+                MenuAction::LoadWorld => {
+                    let url = self.world_url_input.trim().to_string();
+                    if !url.is_empty() {
+                        self.logs.push(format!("Downloading {}...", url));
+                        ctx.link().send_future(async move { Msg::MenuAction(MenuAction::WorldLoaded(fetch_world(&url).await.map(Box::new))) });
+                    }
+                },
+                MenuAction::WorldLoaded(Ok(remote)) => {
+                    store_remote_world(&remote);
+                    *self = App::new_run(Seed::random());
+                    self.logs.push(format!("Started a new run in the world from {}.", remote.url));
+                },
+                MenuAction::WorldLoaded(Err(errors)) => {
+                    self.logs.push("The world couldn't be loaded:".to_string());
+                    self.logs.extend(errors);
+                },
+                MenuAction::RandomWorld => {
+                    clear_remote_world();
+                    *self = App::new_run(Seed::random());
+                    self.logs.push("Started a new run in a random world.".to_string());
+                },
+                MenuAction::Practice(index) => {
+                    if let Some(checkpoint) = self.profile.checkpoints.get(index).cloned() {
+                        self.practice_run(checkpoint);
+                    }
                 },
                 MenuAction::Log(log) => self.logs.push(log),
-                MenuAction::Scout => (),
+                MenuAction::Scout => {
+                    let level = self.roster.lead().attributes.level;
+                    if let Some(area) = self.world.area(level) {
+                        self.logs.push(format!("You search the {}.", area.name));
+                    }
+                    let mut enemy = self.world.sample_encounter(level, self.time(), &mut self.rng.encounters);
+                    ivs::assign(&mut enemy, &mut self.rng.ivs);
+                    self.start_battle(enemy, Difficulty::Easy, true, false, None, None);
+                },
+                MenuAction::Sort(order) => self.roster.sort(order),
+                MenuAction::Move(from, to) => self.roster.move_member(from, to),
+                MenuAction::Lead(slot) => {
+                    self.roster.set_lead(slot);
+                    self.logs.push(format!("{} is now leading the party.", self.roster.lead().name));
+                },
+                MenuAction::LeadFromReserve(index) => {
+                    self.roster.set_lead_from_reserve(index);
+                    self.logs.push(format!("{} is now leading the party.", self.roster.lead().name));
+                },
+                MenuAction::Release(index) => {
+                    if let Some(logs) = self.roster.release(index) {
+                        self.logs.extend(logs);
+                    }
+                },
+                MenuAction::Retire(index) => {
+                    if let Some(member) = self.roster.reserve().get(index) {
+                        self.logs.push(format!("{} retired.", member.character.name));
+                        self.roster.retire(index);
+                    }
+                },
+                MenuAction::Breed(index) => {
+                    let index = self.roster.party().len() + index;
+                    match self.roster.breed(0, index, &self.world, &mut self.rng.drops) {
+                        Some(logs) => self.logs.extend(logs),
+                        None => self.logs.push("They can't have an egg right now.".to_string()),
+                    }
+                },
+                MenuAction::CycleRosterLimit => self.rules.cycle_roster_limit(),
+                MenuAction::ToggleChaos => self.rules.chaos = !self.rules.chaos,
+                MenuAction::ToggleInverse => self.rules.inverse = !self.rules.inverse,
+                MenuAction::ToggleRealTime => self.clock.real_time = !self.clock.real_time,
+                MenuAction::ToggleEndless => {
+                    self.rules.endless = !self.rules.endless;
+                    // curses only last as long as endless does
+                    if !self.rules.endless {
+                        self.rules.curses.clear();
+                    } else if self.gauntlet.outcome == Outcome::Won {
+                        let (gauntlet, logs) = next_cycle(&mut self.rules, &self.world, self.roster.lead().attributes.level, &mut self.rng.worldgen);
+                        self.gauntlet = gauntlet;
+                        self.logs.extend(logs);
+                    }
+                },
+                MenuAction::TakeRisk(risk) => {
+                    if !self.rules.risks.contains(&risk) {
+                        self.rules.risks.push(risk);
+                        self.logs.push(format!("You took a risk: {}", risk.description()));
+                    }
+                },
+            },
+            (Msg::ShopAction(action), Scene::Shop) => match action {
+                ShopAction::Buy(index) => {
+                    let ware = match self.shop.buy(index, self.wins, &mut self.money) {
+                        Some(ware) => ware,
+                        None => return,
+                    };
+                    let mut lead = self.roster.lead().clone();
+                    let (logs, waiting) = ware.apply(&mut lead, &mut self.inventory, &self.world);
+                    self.logs.extend(logs);
+                    self.roster.set(0, lead);
+                    if !waiting.is_empty() {
+                        self.scene = Scene::Learn(Box::new(LearnPrompt { slot: 0, waiting }));
+                    }
+                },
+                ShopAction::Back => self.scene = Scene::Menu,
+            },
+            (Msg::MapAction(action), Scene::Map) => match action {
+                MapAction::Pick(index) => {
+                    if self.gauntlet.pick(index) {
+                        self.enter_node();
+                    }
+                },
+                MapAction::Enter => self.enter_node(),
+                MapAction::Back => self.scene = Scene::Menu,
+            },
+            (Msg::TitleAction(action), Scene::Title) => match action {
+                TitleAction::Preset(preset) => self.config = GameConfig::preset(preset),
+                TitleAction::TogglePermadeath => self.config.permadeath = !self.config.permadeath,
+                TitleAction::ToggleHealing => self.config.heal_between_battles = !self.config.heal_between_battles,
+                TitleAction::CycleLevelCap => self.config.level_cap = self.config.next_level_cap(),
+                TitleAction::Start => self.scene = Scene::Menu,
+                TitleAction::Daily => {
+                    let daily = today();
+                    let config = self.config;
+                    *self = App::new_run(daily.seed());
+                    self.config = config;
+                    daily.apply(&mut self.rules);
+                    self.logs.push(format!("Started the daily run for {}, with today's modifier: {}.", daily, daily.modifier()));
+                    self.scene = Scene::Menu;
+                },
+            },
+            (Msg::DraftAction(action), Scene::Draft(prompt)) => {
+                if let DraftAction::Pick(index) = action {
+                    let (offer, mut character) = match (prompt.offers.get(index), self.roster.get(prompt.slot)) {
+                        (Some(offer), Some(character)) => (*offer, character.clone()),
+                        _ => return,
+                    };
+                    let (logs, waiting) = offer.upgrade.apply(&mut character, &mut self.inventory, &self.world);
+                    self.logs.extend(logs);
+                    self.roster.set(prompt.slot, character);
+                    prompt.waiting.extend(waiting);
+                    prompt.offers.remove(index);
+                    prompt.picks -= 1;
+                    if prompt.picks > 0 && !prompt.offers.is_empty() {
+                        return;
+                    }
+                }
+                let (slot, waiting) = (prompt.slot, std::mem::take(&mut prompt.waiting));
+                self.scene = if waiting.is_empty() {
+                    Scene::Menu
+                } else {
+                    Scene::Learn(Box::new(LearnPrompt { slot, waiting }))
+                };
+            },
+            (Msg::LearnAction(action), Scene::Learn(prompt)) => {
+                if let (Some(learning), Some(character)) = (prompt.waiting.first().copied(), self.roster.get(prompt.slot)) {
+                    let mut character = character.clone();
+                    let slot = match action {
+                        LearnAction::Forget(slot) => Some(slot),
+                        LearnAction::Skip => None,
+                    };
+                    self.logs.extend(replace_move(&mut character, slot, learning, &self.world.actions));
+                    self.roster.set(prompt.slot, character);
+                    prompt.waiting.remove(0);
+                }
+                if prompt.waiting.is_empty() || self.roster.get(prompt.slot).is_none() {
+                    self.scene = Scene::Menu;
+                }
+            },
+            (Msg::TeamAction(action), Scene::TeamBuilder(builder)) => match action {
+                TeamAction::Rename(name) => builder.team.name = name,
+                TeamAction::Add(species) => {
+                    if builder.team.members.len() < PARTY_SIZE {
+                        let pool = learnpool(&self.world.species()[species], TEAM_LEVEL_CAP, &self.world.actions);
+                        let actions = pool.choose_multiple(&mut self.rng.encounters, MAX_ACTIONS).copied().collect();
+                        builder.team.members.push(TeamMember { species, level: TEAM_LEVEL_CAP, actions });
+                        builder.selected = builder.team.members.len() - 1;
+                    }
+                },
+                TeamAction::Remove(index) => {
+                    if index < builder.team.members.len() {
+                        builder.team.members.remove(index);
+                        builder.selected = builder.selected.min(builder.team.members.len().saturating_sub(1));
+                    }
+                },
+                TeamAction::Select(index) => builder.selected = index,
+                TeamAction::Inspect(index) => builder.inspected = Some(index),
+                TeamAction::Level(level) => {
+                    if let Some(member) = builder.team.members.get_mut(builder.selected) {
+                        member.level = level.clamp(1, TEAM_LEVEL_CAP);
+                    }
+                },
+                TeamAction::ToggleAction(action) => {
+                    if let Some(member) = builder.team.members.get_mut(builder.selected) {
+                        if let Some(index) = member.actions.iter().position(|known| *known == action) {
+                            member.actions.remove(index);
+                        } else if member.actions.len() < MAX_ACTIONS {
+                            member.actions.push(action);
+                        }
+                    }
+                },
+                TeamAction::ToggleClause(clause) => {
+                    if let Some(index) = self.rules.clauses.iter().position(|active| *active == clause) {
+                        self.rules.clauses.remove(index);
+                    } else {
+                        self.rules.clauses.push(clause);
+                    }
+                },
+                TeamAction::Save => match builder.team.validate(&self.world, &self.rules.clauses) {
+                    Ok(()) => {
+                        store_team(&builder.team);
+                        builder.saved = load_teams();
+                        self.logs.push(format!("Saved {}.", builder.team.name));
+                    },
+                    Err(errors) => {
+                        self.logs.push(format!("{} can't be saved:", builder.team.name));
+                        self.logs.extend(errors);
+                    },
+                },
+                TeamAction::Load(index) => {
+                    if let Some(team) = builder.saved.get(index) {
+                        builder.team = team.clone();
+                        builder.selected = 0;
+                    }
+                },
+                // the world or the clauses may have changed since it was saved, so it's checked again
+                TeamAction::Battle(index) => {
+                    let team = match builder.saved.get(index) {
+                        Some(team) => team.clone(),
+                        None => return,
+                    };
+                    match team.validate(&self.world, &self.rules.clauses) {
+                        Ok(()) => {
+                            // a saved team is only a spar, like the mirror
+                            self.start_sandbox(Trainer { name: team.name.clone(), team: team.build(&self.world), ai: Difficulty::for_wins(self.wins) });
+                        },
+                        Err(errors) => {
+                            self.logs.push(format!("{} can't be battled:", team.name));
+                            self.logs.extend(errors);
+                        },
+                    }
+                },
+                TeamAction::Delete(index) => {
+                    if let Some(team) = builder.saved.get(index) {
+                        delete_team(&team.name);
+                        builder.saved = load_teams();
+                    }
+                },
+                TeamAction::EditText(text) => builder.text = text,
+                TeamAction::Import => match Team::from_text(&builder.text, &self.world) {
+                    Ok(team) => {
+                        builder.team = team;
+                        builder.selected = 0;
+                        builder.text_error = None;
+                    },
+                    Err(error) => builder.text_error = Some(error.to_string()),
+                },
+                TeamAction::Export => {
+                    builder.text = builder.team.to_text(&self.world);
+                    builder.text_error = None;
+                },
+                TeamAction::Close => self.scene = Scene::Menu,
+            },
+            (Msg::WarAction(action), Scene::War(plan)) => match action {
+                WarAction::Raise(index) => {
+                    if index > 0 && index < plan.order.len() {
+                        plan.order.swap(index - 1, index);
+                    }
+                },
+                WarAction::CycleStrategy(slot) => {
+                    if let Some(strategy) = plan.strategies.get_mut(slot) {
+                        *strategy = strategy.next();
+                    }
+                },
+                WarAction::Fight => {
+                    let roster = &self.roster;
+                    let squad: Vec<Soldier> = plan.order.iter()
+                        .filter_map(|slot| Some(Soldier { character: roster.get(*slot)?.clone(), strategy: plan.strategies[*slot] }))
+                        .collect();
+                    let levels: Vec<u32> = squad.iter().map(|soldier| soldier.character.attributes.level).collect();
+                    let enemy = muster(&self.world, &levels, Difficulty::for_wins(self.wins), &mut self.rng.encounters);
+                    plan.report = war(&squad, &enemy, &self.world, &mut self.rng.battle).lines();
+                },
+                WarAction::Close => self.scene = Scene::Menu,
+            },
+            (Msg::ReplayAction(action), Scene::Replay(viewer)) => match action {
+                ReplayAction::Step => {
+                    let actions = &self.world.actions;
+                    if let Some(logs) = viewer.playback.as_mut().and_then(|playback| playback.step(actions)) {
+                        viewer.logs.extend(logs);
+                    }
+                },
+                ReplayAction::Restart => {
+                    if let Some(playback) = &mut viewer.playback {
+                        playback.restart();
+                        viewer.logs.clear();
+                    }
+                },
+                ReplayAction::EditText(text) => viewer.text = text,
+                ReplayAction::Load => match Replay::parse(&viewer.text, self.run.world) {
+                    Ok(replay) => {
+                        viewer.playback = Some(Playback::new(replay));
+                        viewer.logs.clear();
+                        viewer.text_error = None;
+                    },
+                    Err(error) => viewer.text_error = Some(error.to_string()),
+                },
+                ReplayAction::Close => self.scene = Scene::Menu,
+            },
+            (Msg::StatsAction(StatsAction::Close), Scene::Stats(_)) => self.scene = Scene::Menu,
+            (Msg::ModsAction(action), Scene::Mods(mods)) => match action {
+                ModsAction::EditName(name) => mods.name = name,
+                ModsAction::EditText(text) => mods.text = text,
+                ModsAction::Add => {
+                    let name = if mods.name.trim().is_empty() {
+                        format!("Pack {}", mods.packs.len() + 1)
+                    } else {
+                        mods.name.trim().to_string()
+                    };
+                    match ContentPack::from_json(&name, &mods.text) {
+                        Ok(pack) => {
+                            mods.packs.push(pack);
+                            mods.name.clear();
+                            mods.text.clear();
+                            mods.errors.clear();
+                        },
+                        Err(error) => mods.errors = vec![error.to_string()],
+                    }
+                },
+                ModsAction::Toggle(index) => {
+                    if let Some(pack) = mods.packs.get_mut(index) {
+                        pack.enabled = !pack.enabled;
+                    }
+                },
+                ModsAction::Remove(index) => {
+                    if index < mods.packs.len() {
+                        mods.packs.remove(index);
+                    }
+                },
+                ModsAction::Raise(index) => {
+                    if index > 0 && index < mods.packs.len() {
+                        mods.packs.swap(index - 1, index);
+                    }
+                },
+                ModsAction::Apply => match compose_world(&self.base_world, &mods.packs) {
+                    Ok(world) => {
+                        let map = IdMap::between(&self.world, &world);
+                        self.roster.characters_mut().for_each(|character| map.remap_character(&world, character));
+                        self.world = world;
+                        (self.run, self.ghost) = race(&self.world, self.seed);
+                        store_packs(&mods.packs);
+                        mods.errors.clear();
+                        self.logs.push(format!("Applied {} content packs.", mods.packs.iter().filter(|pack| pack.enabled).count()));
+                    },
+                    Err(conflicts) => mods.errors = conflicts.iter()
+                        .map(|conflict| conflict.to_string())
+                        .collect(),
+                },
+                ModsAction::Close => self.scene = Scene::Menu,
             },
+            // `transition` already turned these away
             _ => (),
         };
+    }
+
+    // swaps in a new base world under the running game. the stored packs are laid back over it,
+    // and everyone alive in the run is moved over to the new world's species and moves
+    #[cfg(feature = "dev")]
+    fn reload_world(&mut self, text: &str) {
+        let base = match load_world(text) {
+            Ok(base) => base,
+            Err(errors) => {
+                self.logs.push(format!("{} couldn't be loaded:", DEV_WORLD_URL));
+                self.logs.extend(errors.iter().map(|error| error.to_string()));
+                return;
+            },
+        };
+        let world = match compose_world(&base, &load_packs()) {
+            Ok(world) => world,
+            Err(conflicts) => {
+                self.logs.push("The content packs were left off since they no longer fit:".to_string());
+                self.logs.extend(conflicts.iter().map(|conflict| conflict.to_string()));
+                base.clone()
+            },
+        };
+        let map = IdMap::between(&self.world, &world);
+        self.roster.characters_mut().for_each(|character| map.remap_character(&world, character));
+        match &mut self.scene {
+            Scene::Battle(encounter) => {
+                map.remap_character(&world, &mut encounter.battle.player);
+                map.remap_character(&world, &mut encounter.battle.enemy);
+                // what it recorded so far is ids into the old world
+                encounter.replay = None;
+            },
+            Scene::TeamBuilder(builder) => {
+                map.remap_team(&mut builder.team);
+                builder.selected = builder.selected.min(builder.team.members.len().saturating_sub(1));
+            },
+            Scene::Learn(prompt) => prompt.waiting = prompt.waiting.iter().filter_map(|action| map.action(*action)).collect(),
+            Scene::Draft(prompt) => {
+                prompt.waiting = prompt.waiting.iter().filter_map(|action| map.action(*action)).collect();
+                // offers of moves and items the new world doesn't have are dropped
+                prompt.offers = prompt.offers.iter()
+                    .filter_map(|offer| match offer.upgrade {
+                        Upgrade::Move(action) => Some(Offer { upgrade: Upgrade::Move(map.action(action)?), ..*offer }),
+                        Upgrade::Item(item, _) if item >= world.items.len() => None,
+                        _ => Some(*offer),
+                    })
+                    .collect();
+            },
+            Scene::Menu | Scene::Mods(_) | Scene::War(_) | Scene::Replay(_) | Scene::Stats(_) | Scene::Map | Scene::Shop | Scene::Title => (),
+        }
+        self.base_world = base;
+        self.world = world;
+        self.shop = Shop::generate(&self.world, &mut Streams::new(self.seed).shop);
+        (self.run, self.ghost) = race(&self.world, self.seed);
+        self.logs.push(format!("Reloaded {}.", DEV_WORLD_URL));
+    }
+
+    #[cfg(feature = "dev")]
+    fn handle_dev(&mut self, ctx: &Context<Self>, action: DevAction) -> bool {
+        match action {
+            DevAction::Poll => {
+                ctx.link().send_future(async { Msg::DevAction(DevAction::Fetched(fetch_text(DEV_WORLD_URL).await)) });
+                false
+            },
+            DevAction::Fetched(fetched) => {
+                ctx.link().send_future(async {
+                    TimeoutFuture::new(DEV_POLL_MILLIS).await;
+                    Msg::DevAction(DevAction::Poll)
+                });
+                let fetched = match self.watcher.changed(fetched) {
+                    Some(fetched) => fetched.clone(),
+                    None => return false,
+                };
+                self.logs.clear();
+                match fetched {
+                    Ok(text) => self.reload_world(&text),
+                    Err(error) => self.logs.push(error),
+                }
+                self.autosave();
+                true
+            },
+        }
+    }
+
+    // every floor of the run, with the nodes that can be picked next enabled
+    // who an item would be used on in the encounter: whoever is battling, or else the first of the
+    // party it would help, e.g. a fainted kaizo for a revive
+    fn item_target(&self, encounter: &Encounter, item: ItemId) -> Option<usize> {
+        let used = self.world.items.get(item)?;
+        if used.usable_on(&encounter.battle.player) {
+            return Some(encounter.slot);
+        }
+        self.roster.party().iter()
+            .position(|member| used.usable_on(&member.character))
+            .filter(|slot| *slot != encounter.slot)
+    }
+
+    fn view_map(&self, ctx: &Context<Self>) -> Html {
+        let choices = self.gauntlet.choices();
+        html! {
+            <div>
+                { for self.gauntlet.floors.iter().enumerate().map(|(floor, nodes)| html! {
+                    <div>
+                        { format!("{}. ", floor + 1) }
+                        { for nodes.iter().enumerate().map(|(index, node)| {
+                            let picked = self.gauntlet.path.get(floor) == Some(&index);
+                            let label = match &node.enemy {
+                                Some(enemy) => format!("{:?} Lv{}", node.kind, enemy.attributes.level),
+                                None => format!("{:?}", node.kind),
+                            };
+                            html! {
+                                <button class={ if picked { "roster-button selected" } else { "roster-button" } }
+                                    disabled={ !choices.contains(&index) || floor != self.gauntlet.path.len() }
+                                    title={ node.kind.description() }
+                                    onclick={ ctx.link().callback(move |_| Msg::MapAction(MapAction::Pick(index))) }>{
+                                    label
+                                }</button>
+                            }
+                        }) }
+                    </div>
+                }) }
+                { if self.gauntlet.current().is_some() { html! {
+                    <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MapAction(MapAction::Enter))} title="Go back to the node you picked.">{
+                        "Go"
+                    }</button>
+                } } else { html! { } } }
+                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MapAction(MapAction::Back))} title="Back to the menu.">{
+                    "Back"
+                }</button>
+            </div>
+        }
+    }
+
+    // everything for sale at today's prices, with the ones that can't be afforded disabled
+    fn view_shop(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div>
+                <div>{ format!("You have {} money.", self.money) }</div>
+                { for self.shop.wares.iter().enumerate().map(|(index, (ware, _))| {
+                    let price = self.shop.price(index, self.wins).unwrap_or_default();
+                    html! {
+                        <button class="control-button" disabled={ price > self.money }
+                            onclick={ ctx.link().callback(move |_| Msg::ShopAction(ShopAction::Buy(index))) }>{
+                            format!("{} ({} money)", ware.description(&self.world), price)
+                        }</button>
+                    }
+                }) }
+                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::ShopAction(ShopAction::Back))} title="Back to the menu.">{
+                    "Back"
+                }</button>
+            </div>
+        }
+    }
+
+    fn view_title(&self, ctx: &Context<Self>) -> Html {
+        let selected = self.config.matching();
+        html! {
+            <div class="team-builder">
+                <div>{ "Kaizo Quest" }</div>
+                <div>
+                    { for PRESETS.iter().map(|preset| {
+                        let preset = *preset;
+                        html! {
+                            <button class={ if selected == Some(preset) { "roster-button selected" } else { "roster-button" } }
+                                title={ preset.description() }
+                                onclick={ ctx.link().callback(move |_| Msg::TitleAction(TitleAction::Preset(preset))) }>{
+                                format!("{:?}", preset)
+                            }</button>
+                        }
+                    }) }
+                </div>
+                <div>
+                    <button class="control-button" onclick={ctx.link().callback(move |_| Msg::TitleAction(TitleAction::TogglePermadeath))} title="Kaizo that faint leave your roster for good.">{
+                        if self.config.permadeath { "Permadeath: On" } else { "Permadeath: Off" }
+                    }</button>
+                    <button class="control-button" onclick={ctx.link().callback(move |_| Msg::TitleAction(TitleAction::ToggleHealing))} title="Heal the party after every battle. Otherwise damage and statuses carry over until the party rests.">{
+                        if self.config.heal_between_battles { "Healing between battles: On" } else { "Healing between battles: Off" }
+                    }</button>
+                    <button class="control-button" onclick={ctx.link().callback(move |_| Msg::TitleAction(TitleAction::CycleLevelCap))} title="Kaizo can't level past the cap; experience past it turns into money instead.">{
+                        match self.config.level_cap {
+                            Some(cap) => format!("Level cap: {}", cap),
+                            None => "Level cap: Off".to_string(),
+                        }
+                    }</button>
+                    <button class="control-button" onclick={ctx.link().callback(move |_| Msg::TitleAction(TitleAction::Start))} title="Start the run.">{
+                        "Start"
+                    }</button>
+                    <button class="control-button" onclick={ctx.link().callback(move |_| Msg::TitleAction(TitleAction::Daily))} title="Play the same run as everyone else today, with a modifier that changes every day.">{
+                        format!("Daily ({}): {}", today(), today().modifier())
+                    }</button>
+                </div>
+                <div> { for self.logs.iter().map(|log| html! { <div>{ log }</div> }) } </div>
+            </div>
+        }
+    }
+
+    fn view_war(&self, ctx: &Context<Self>, plan: &WarPlan) -> Html {
+        let war_action = |action: WarAction| Msg::WarAction(action);
+        html! {
+            <div class="team-builder">
+                <div>{ "War" }</div>
+                <div>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(move |_| war_action(WarAction::Fight)) } title="Fight a squad of wild kaizo, one at a time and in this order, with the AI picking every move. Your party isn't hurt.">{
+                        "Fight"
+                    }</button>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(move |_| war_action(WarAction::Close)) } title="Return to the menu.">{
+                        "Close"
+                    }</button>
+                </div>
+                <div class="columns">
+                    // the squad, in the order it fights
+                    <div class="team-column">
+                        { for plan.order.iter().enumerate().filter_map(|(index, slot)| Some((index, *slot, self.roster.get(*slot)?))).map(|(index, slot, character)| html! {
+                            <div>
+                                { format!("#{} {} Lv{} ", index + 1, character.name, character.attributes.level) }
+                                <button class="roster-button" title="Change how this kaizo picks its moves."
+                                    onclick={ ctx.link().callback(move |_| war_action(WarAction::CycleStrategy(slot))) }>{
+                                    format!("AI: {:?}", plan.strategies[slot])
+                                }</button>
+                                <button class="roster-button" disabled={ index == 0 } title="Fight earlier."
+                                    onclick={ ctx.link().callback(move |_| war_action(WarAction::Raise(index))) }>{ "Up" }</button>
+                            </div>
+                        }) }
+                    </div>
+                    // the match report
+                    <div class="team-column">
+                        { for plan.report.iter().map(|line| html! { <div>{ line }</div> }) }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_replay(&self, ctx: &Context<Self>, viewer: &ReplayViewer) -> Html {
+        let replay_action = |action: ReplayAction| Msg::ReplayAction(action);
+        let done = viewer.playback.as_ref().is_none_or(Playback::done);
+        html! {
+            <div class="team-builder">
+                <div>{ "Replay" }</div>
+                <div>
+                    <button class="control-button" disabled={ done }
+                        onclick={ ctx.link().callback(move |_| replay_action(ReplayAction::Step)) } title="Play the next turn.">{
+                        "Step"
+                    }</button>
+                    <button class="control-button" disabled={ viewer.playback.is_none() }
+                        onclick={ ctx.link().callback(move |_| replay_action(ReplayAction::Restart)) } title="Watch it again from the start.">{
+                        "Restart"
+                    }</button>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(move |_| replay_action(ReplayAction::Close)) } title="Return to the menu.">{
+                        "Close"
+                    }</button>
+                </div>
+                <div class="columns">
+                    // the battle as it stands, and what happened to get there
+                    <div class="team-column">
+                        { match &viewer.playback {
+                            Some(playback) => {
+                                let battle = playback.battle();
+                                html! {
+                                    <div>
+                                        <WeatherIndicator weather={ battle.field.weather } turns={ battle.field.weather_turns }/>
+                                        <BattleHud summary={ battle_summary(battle) }/>
+                                        <EnemyDisplay character={ battle.enemy.clone() } ability={ self.ability_title(&battle.enemy) }/>
+                                        <PlayerDisplay character={ battle.player.clone() } ability={ self.ability_title(&battle.player) }/>
+                                        { for viewer.logs.iter().map(|line| html! { <div>{ line }</div> }) }
+                                        { if done { html! { <div>{ "The end." }</div> } } else { html! { } } }
+                                    </div>
+                                }
+                            },
+                            None => html! { <div>{ "There's no battle to watch yet. Finish one, or paste a replay." }</div> },
+                        } }
+                    </div>
+                    // sharing
+                    <div class="team-column">
+                        <textarea class="team-text" value={ viewer.text.clone() } placeholder="Paste a replay here."
+                            oninput={ ctx.link().callback(|e: InputEvent| {
+                                let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+                                Msg::ReplayAction(ReplayAction::EditText(input.value()))
+                            }) }/>
+                        <div>
+                            <button class="roster-button"
+                                onclick={ ctx.link().callback(move |_| replay_action(ReplayAction::Load)) } title="Watch the pasted replay. It has to be from this world.">{ "Load" }</button>
+                        </div>
+                        { for viewer.text_error.iter().map(|error| html! { <div class="error">{ error }</div> }) }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_stats(&self, ctx: &Context<Self>, history: &BattleHistory) -> Html {
+        let battles = history.battles();
+        let faints = history.faints();
+        let most = faints.first().map_or(0, |(_, count)| *count);
+        html! {
+            <div class="team-builder">
+                <div>{ "Stats" }</div>
+                <div>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(|_| Msg::StatsAction(StatsAction::Close)) } title="Return to the menu.">{
+                        "Close"
+                    }</button>
+                </div>
+                <div class="columns">
+                    <div class="team-column">
+                        <div>{ format!("Battles: {}", battles) }</div>
+                        <div>{ format!("Wins: {} ({:.0}%)", history.wins(), 100.0 * history.wins() as f64 / battles.max(1) as f64) }</div>
+                        <div>{ format!("Favorite: {}", history.favorite().unwrap_or("none yet")) }</div>
+                        <div title="Your win rate over stretches of battles, oldest on the left.">{ "Win rate over time" }</div>
+                        <WinRateChart rates={ win_rates(history.outcomes(), CHART_POINTS) }/>
+                    </div>
+                    // who has knocked out your kaizo the most
+                    <div class="team-column">
+                        <div>{ "Knocked out by" }</div>
+                        { if faints.is_empty() {
+                            html! { <div>{ "Nothing yet." }</div> }
+                        } else {
+                            html! { for faints.iter().map(|(species, count)| dex_bar(*count, most, format!("{}: {}", species, count), "How many of your kaizo it has knocked out.")) }
+                        } }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_mods(&self, ctx: &Context<Self>, mods: &ModsScreen) -> Html {
+        let mods_action = |action: ModsAction| Msg::ModsAction(action);
+        let last = mods.packs.len().saturating_sub(1);
+        html! {
+            <div class="team-builder">
+                <div>{ "Mods" }</div>
+                <div>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Apply)) } title="Rebuild the world from these packs, in order.">{
+                        "Apply"
+                    }</button>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Close)) } title="Return to the menu. Unapplied changes are lost.">{
+                        "Close"
+                    }</button>
+                </div>
+                <div class="columns">
+                    // the packs, in the order they're applied
+                    <div class="team-column">
+                        { for mods.packs.iter().enumerate().map(|(index, pack)| html! {
+                            <div>
+                                { format!("#{} {} ", index + 1, pack.name) }
+                                <button class="roster-button"
+                                    onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Toggle(index))) }>{
+                                    if pack.enabled { "On" } else { "Off" }
+                                }</button>
+                                <button class="roster-button" disabled={ index == 0 } title="Apply this pack earlier."
+                                    onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Raise(index))) }>{ "Up" }</button>
+                                <button class="roster-button" disabled={ index == last } title="Apply this pack later."
+                                    onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Raise(index + 1))) }>{ "Down" }</button>
+                                <button class="roster-button"
+                                    onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Remove(index))) }>{ "Remove" }</button>
+                            </div>
+                        }) }
+                        { for mods.errors.iter().map(|error| html! { <div class="error">{ error }</div> }) }
+                    </div>
+                    // adding a pack
+                    <div class="team-column">
+                        <input value={ mods.name.clone() } placeholder="Pack name"
+                            oninput={ ctx.link().callback(|e: InputEvent| {
+                                let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                Msg::ModsAction(ModsAction::EditName(input.value()))
+                            }) }/>
+                        <textarea class="team-text" value={ mods.text.clone() } placeholder="Paste a content pack here."
+                            oninput={ ctx.link().callback(|e: InputEvent| {
+                                let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+                                Msg::ModsAction(ModsAction::EditText(input.value()))
+                            }) }/>
+                        <div>
+                            <button class="roster-button"
+                                onclick={ ctx.link().callback(move |_| mods_action(ModsAction::Add)) } title="Add the pasted pack to the end of the list.">{ "Add" }</button>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_team_builder(&self, ctx: &Context<Self>, builder: &TeamBuilder) -> Html {
+        let team = &builder.team;
+        let errors = team.validate(&self.world, &self.rules.clauses).err().unwrap_or_default();
+        let species_name = |species: usize| self.world.species().get(species)
+            .map(|species| species.name.to_string())
+            .unwrap_or_else(|| "???".to_string());
+        let team_action = |action: TeamAction| Msg::TeamAction(action);
+        html! {
+            <div class="team-builder">
+                <div>{ "Team Builder" }</div>
+                <div>
+                    <input value={ team.name.clone() } placeholder="Team name"
+                        oninput={ ctx.link().callback(|e: InputEvent| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            Msg::TeamAction(TeamAction::Rename(input.value()))
+                        }) }/>
+                    <button class="control-button" disabled={ !errors.is_empty() }
+                        onclick={ ctx.link().callback(move |_| team_action(TeamAction::Save)) } title="Save this team for later.">{
+                        "Save"
+                    }</button>
+                    <button class="control-button"
+                        onclick={ ctx.link().callback(move |_| team_action(TeamAction::Close)) } title="Return to the menu.">{
+                        "Close"
+                    }</button>
+                </div>
+                // clauses
+                <div> {
+                    for CLAUSES.iter().map(|clause| {
+                        let clause = *clause;
+                        let active = self.rules.clauses.contains(&clause);
+                        html! {
+                            <button class="roster-button" title={ clause.description() }
+                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::ToggleClause(clause))) }>{
+                                format!("{:?} clause: {}", clause, if active { "On" } else { "Off" })
+                            }</button>
+                        }
+                    })
+                } </div>
+                <div class="columns">
+                    // team members
+                    <div class="team-column">
+                        { for team.members.iter().enumerate().map(|(index, member)| html! {
+                            <div class={ if index == builder.selected { "selected" } else { "" } }>
+                                { format!("{} Lv{} ", species_name(member.species), member.level) }
+                                <button class="roster-button"
+                                    onclick={ ctx.link().callback(move |_| team_action(TeamAction::Select(index))) }>{ "Edit" }</button>
+                                <button class="roster-button"
+                                    onclick={ ctx.link().callback(move |_| team_action(TeamAction::Remove(index))) }>{ "Remove" }</button>
+                            </div>
+                        }) }
+                        { for errors.iter().map(|error| html! { <div class="error">{ error }</div> }) }
+                        // sharing teams as text
+                        <textarea class="team-text" value={ builder.text.clone() } placeholder="Paste a team here."
+                            oninput={ ctx.link().callback(|e: InputEvent| {
+                                let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+                                Msg::TeamAction(TeamAction::EditText(input.value()))
+                            }) }/>
+                        <div>
+                            <button class="roster-button"
+                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::Import)) } title="Replace this team with the pasted one.">{ "Import" }</button>
+                            <button class="roster-button"
+                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::Export)) } title="Write this team out as text to share.">{ "Export" }</button>
+                        </div>
+                        { for builder.text_error.iter().map(|error| html! { <div class="error">{ error }</div> }) }
+                        <div>{ "Saved teams" }</div>
+                        { for builder.saved.iter().enumerate().map(|(index, saved)| html! {
+                            <div>
+                                { format!("{} ({} kaizo) ", saved.name, saved.members.len()) }
+                                <button class="roster-button"
+                                    onclick={ ctx.link().callback(move |_| team_action(TeamAction::Load(index))) }>{ "Load" }</button>
+                                <button class="roster-button" title="Battle this team with your party."
+                                    onclick={ ctx.link().callback(move |_| team_action(TeamAction::Battle(index))) }>{ "Battle" }</button>
+                                <button class="roster-button"
+                                    onclick={ ctx.link().callback(move |_| team_action(TeamAction::Delete(index))) }>{ "Delete" }</button>
+                            </div>
+                        }) }
+                    </div>
+                    // the selected member's level and moves
+                    <div class="team-column"> {
+                        match team.members.get(builder.selected) {
+                            Some(member) => {
+                                let level = member.level;
+                                html! {
+                                    <div>
+                                        <div>
+                                            <button class="roster-button" disabled={ level <= 1 }
+                                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::Level(level.saturating_sub(10)))) }>{ "-10" }</button>
+                                            <button class="roster-button" disabled={ level <= 1 }
+                                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::Level(level - 1))) }>{ "-" }</button>
+                                            { format!(" Lv{} ", level) }
+                                            <button class="roster-button" disabled={ level >= TEAM_LEVEL_CAP }
+                                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::Level(level + 1))) }>{ "+" }</button>
+                                            <button class="roster-button" disabled={ level >= TEAM_LEVEL_CAP }
+                                                onclick={ ctx.link().callback(move |_| team_action(TeamAction::Level(level + 10))) }>{ "+10" }</button>
+                                        </div>
+                                        <VirtualList row_height={ 20.0 } height={ 200.0 }>
+                                            { for self.world.species().get(member.species).into_iter().flat_map(|species| learnpool(species, level, &self.world.actions)).map(|action| {
+                                                let known = member.actions.contains(&action);
+                                                html! {
+                                                    <button class={ if known { "roster-button selected" } else { "roster-button" } }
+                                                        title={ self.world.actions[action].description() }
+                                                        onclick={ ctx.link().callback(move |_| team_action(TeamAction::ToggleAction(action))) }>{
+                                                        self.world.actions[action].name()
+                                                    }</button>
+                                                }
+                                            }) }
+                                        </VirtualList>
+                                    </div>
+                                }
+                            },
+                            None => html! { <div>{ "Add a kaizo from the dex." }</div> },
+                        }
+                    } </div>
+                    // the dex
+                    <div class="team-column">
+                        <div>{ "Dex" }</div>
+                        <VirtualList row_height={ 20.0 } height={ 300.0 }>
+                            { for self.world.species().iter().enumerate().map(|(index, species)| html! {
+                                <div>
+                                    { format!("{} ({}, BST: {}) ", species.name, alignment_names(&species.alignments()), species.bst) }
+                                    <button class="roster-button"
+                                        onclick={ ctx.link().callback(move |_| team_action(TeamAction::Inspect(index))) }>{ "Info" }</button>
+                                    <button class="roster-button" disabled={ team.members.len() >= PARTY_SIZE }
+                                        onclick={ ctx.link().callback(move |_| team_action(TeamAction::Add(index))) }>{ "Add" }</button>
+                                </div>
+                            }) }
+                        </VirtualList>
+                        // how the inspected species has done in your battles
+                        { for builder.inspected.and_then(|index| self.world.species().get(index)).map(|species| {
+                            let record = builder.history.species(&species.name);
+                            html! {
+                                <div>
+                                    <div>{ species.name.to_string() }</div>
+                                    { dex_bar(record.used, builder.history.most_used(), format!("Used: {}", record.used), "Battles you've fought with it, against your most used species.") }
+                                    { dex_bar(record.won_with, record.used, format!("Won with: {}/{}", record.won_with, record.used), "Battles you've won with it.") }
+                                    { dex_bar(record.won_against, record.faced, format!("Won against: {}/{}", record.won_against, record.faced), "Battles you've won against it.") }
+                                </div>
+                            }
+                        }) }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Component for App {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        #[cfg(feature = "dev")]
+        ctx.link().send_message(Msg::DevAction(DevAction::Poll));
+        #[cfg(not(feature = "dev"))]
+        let _ = ctx;
+        Self { saving: !has_save(), ..App::new_run(Seed::random()) }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        #[cfg(feature = "dev")]
+        if let Msg::DevAction(action) = msg {
+            return self.handle_dev(ctx, action);
+        }
+        // the screen the click was made on hasn't been redrawn yet, so it's out of date
+        if msg.locks() && !self.input_lock.acquire() {
+            return false;
+        }
+        self.logs.clear();
+        // anything but continuing means this is the run to keep; the title screen only sets up a
+        // run, so it doesn't count
+        if !matches!(msg, Msg::MenuAction(MenuAction::Continue) | Msg::TitleAction(_)) {
+            self.saving = true;
+        }
+        // messages for a scene the app has already left are reported instead of being acted on
+        let checked = msg.kind().map(|message| transition(self.scene.kind(), message));
+        if let Some(Err(invalid)) = checked {
+            self.logs.push(invalid.to_string());
+            return true;
+        }
+        self.handle(ctx, msg);
+        if let Some(Err(invalid)) = checked.and_then(Result::ok).map(|transition| transition.check(self.scene.kind())) {
+            self.logs.push(invalid.to_string());
+        }
+        // a practice run is only the one battle
+        if self.practice.is_some() && !matches!(self.scene, Scene::Battle(_)) {
+            self.end_practice();
+        }
+        self.autosave();
         true
     }
 
+    fn rendered(&mut self, _: &Context<Self>, _: bool) {
+        self.input_lock.release();
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
         let player = match &self.scene {
-            Scene::Battle(battle) => battle.player.clone(),
-            Scene::Menu(player) => player.clone(),
+            Scene::Battle(encounter) => encounter.battle.player.clone(),
+            Scene::Menu | Scene::Map | Scene::Shop => self.roster.lead().clone(),
+            Scene::Learn(prompt) => self.roster.get(prompt.slot).unwrap_or_else(|| self.roster.lead()).clone(),
+            Scene::Draft(prompt) => self.roster.get(prompt.slot).unwrap_or_else(|| self.roster.lead()).clone(),
+            Scene::TeamBuilder(builder) => return self.view_team_builder(ctx, builder),
+            Scene::Mods(mods) => return self.view_mods(ctx, mods),
+            Scene::War(plan) => return self.view_war(ctx, plan),
+            Scene::Replay(viewer) => return self.view_replay(ctx, viewer),
+            Scene::Stats(history) => return self.view_stats(ctx, history),
+            Scene::Title => return self.view_title(ctx),
         };
         // TODO: i don't know enough html/css/etc to know how to decouple this well; the ui
         //       probably will be redesigned eventually anyways...
         html! {
             <div>
-                <div>{ "Kaizo Quest" }</div>
+                <div>
+                    { "Kaizo Quest" }
+                    <span class="seed" title="Share this seed to let someone else play the same run.">{ format!(" [seed {}]", self.seed) }</span>
+                    <span class="seed" title="Scores are only comparable between runs in the same world.">{ format!(" [world {}]", self.run.world) }</span>
+                    { for self.world_url.iter().map(|url| html! {
+                        <span class="seed" title="The world this run is in.">{ format!(" [{}]", url) }</span>
+                    }) }
+                    { if self.wins > 0 { html! { <span>{ format!(" ({} wins)", self.wins) }</span> } } else { html! { } } }
+                    { if self.money > 0 { html! { <span>{ format!(" ({} money)", self.money) }</span> } } else { html! { } } }
+                    <img class="clock" title={ format!("{}: different kaizo come out at night.", self.time()) } alt={ self.time().to_string() }
+                        src={ get_resource(self.time().icon()) } width={"24"} height={"24"}/>
+                    { for self.ghost.iter().map(|ghost| html! {
+                        <span class="seed" title="How this run compares to your best run on this seed, in the turns taken to win the same battles.">{
+                            format!(" [ghost: {}]", ghost.pace(&self.run))
+                        }</span>
+                    }) }
+                    { if self.practice.is_some() { html! {
+                        <span class="ruleset-flag" title="Practice runs aren't rated or saved.">{ " [PRACTICE]" }</span>
+                    } } else { html! { } } }
+                    { if self.rules.chaos { html! {
+                        <span class="ruleset-flag" title="Your moves are re-rolled at the start of every battle.">{ " [CHAOS]" }</span>
+                    } } else { html! { } } }
+                    { if self.rules.inverse { html! {
+                        <span class="ruleset-flag" title="Weaknesses and resistances are swapped.">{ " [INVERSE]" }</span>
+                    } } else { html! { } } }
+                    { if self.config.permadeath { html! {
+                        <span class="ruleset-flag" title="Kaizo that faint are gone for good.">{ " [PERMADEATH]" }</span>
+                    } } else { html! { } } }
+                    { if self.rules.endless { html! {
+                        <span class="ruleset-flag" title={ self.rules.curses.iter().map(|curse| curse.to_string()).collect::<Vec<_>>().join(", ") }>{
+                            format!(" [ENDLESS x{}]", self.rules.curses.len())
+                        }</span>
+                    } } else { html! { } } }
+                    { for self.rules.risks.iter().map(|risk| html! {
+                        <span class="ruleset-flag" title={ risk.description() }>{ format!(" [{}]", risk.to_string().to_uppercase()) }</span>
+                    }) }
+                </div>
                 <div class="columns">
                     <div class="game-area">
                         <div> {
                             match &self.scene {
-                                Scene::Battle(battle) => html! {
+                                Scene::Battle(encounter) => html! {
                                     <div>
+                                        { for encounter.trainer.iter().map(|trainer| html! {
+                                            <div class="battle-header" title="Every one of their kaizo has to be beaten to win.">{
+                                                format!("{} ({} more kaizo)", trainer.name, trainer.team.len())
+                                            }</div>
+                                        }) }
+                                        <WeatherIndicator weather={ encounter.battle.field.weather } turns={ encounter.battle.field.weather_turns }/>
+                                        <BattleHud summary={ battle_summary(&encounter.battle) }/>
                                         // enemy details
-                                        <div><EnemyDisplay character={battle.enemy.clone()} /></div>
+                                        <div><EnemyDisplay character={encounter.battle.enemy.clone()} ability={self.ability_title(&encounter.battle.enemy)} /></div>
                                     </div>
                                 },
-                                Scene::Menu(_) => html! { },
+                                _ => html! { },
                             }
                         } </div>
                         // player details
-                        <div><PlayerDisplay character={ player.clone() } /></div>
+                        <div><PlayerDisplay character={ player.clone() } ability={self.ability_title(&player)} /></div>
                         // player controls
                         <div>
                             // action controls
                             <div> {
-                                for player.attributes.actions.iter().map(|action| {
-                                    let action_id = action.clone();
-                                    let action = self.world.actions[action_id].name();
+                                for player.attributes.actions.iter().enumerate().map(|(index, action)| {
+                                    let action_id = *action;
+                                    let action = self.world.actions[action_id].name().to_string();
                                     let callback = match self.scene {
-                                        Scene::Battle(_) => ctx.link().callback(move |_| Msg::BattleAction(BattleAction::ActionChosen(action_id))),
-                                        Scene::Menu(_) => ctx.link().callback(
-                                            move |_| Msg::MenuAction(MenuAction::Log(format!("{}", action)))),
+                                        Scene::Battle(..) => ctx.link().callback(move |_| Msg::BattleAction(BattleAction::ActionChosen(action_id))),
+                                        Scene::Learn(..) => ctx.link().callback(move |_| Msg::LearnAction(LearnAction::Forget(index))),
+                                        _ => ctx.link().callback(
+                                            move |_| Msg::MenuAction(MenuAction::Log(action.clone()))),
                                     };
+                                    let max = self.world.actions[action_id].pp();
+                                    let left = player.pp_left(action_id, max);
                                     html! {
                                         <button
                                             class="action-button"
                                             title={ self.world.actions[action_id].description() }
+                                            disabled={ left == 0 }
                                             onclick={ callback }
                                         > {
-                                            format!("{}", self.world.actions[action_id].name())
+                                            format!("{} {}/{}", self.world.actions[action_id].name(), left, max)
                                         } </button>
                                     }
                                 })
-                            } </div>
+                            }
+                            // with every move used up, any of them is a struggle
+                            { if matches!(self.scene, Scene::Battle(..)) && self.world.actions.usable(&player).next().is_none() {
+                                let action_id = player.attributes.actions.first().copied().unwrap_or_default();
+                                html! {
+                                    <button class="action-button" title="Attack for a little damage and take some back."
+                                        onclick={ ctx.link().callback(move |_| Msg::BattleAction(BattleAction::ActionChosen(action_id))) }>{
+                                        "Struggle"
+                                    }</button>
+                                }
+                            } else { html! { } } }
+                            </div>
                             // scene controls
                             <div> {
                                 match &self.scene {
-                                    Scene::Battle(_) => html! {
-                                        <button class="control-button" onclick={ctx.link().callback(move |_| Msg::BattleAction(BattleAction::Flee))} title="Escape from this battle and return to the menu">{
-                                            "Flee"
-                                        } </button>
+                                    Scene::Battle(encounter) => html! {
+                                        <div>
+                                            { if encounter.wild { html! {
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::BattleAction(BattleAction::Recruit))} title="Try to recruit this kaizo. Weakened and hindered kaizo are easier to recruit.">{
+                                                    format!("Recruit ({:.0}%)", capture_chance(&encounter.battle.enemy) * 100.0)
+                                                } </button>
+                                            } } else { html! { } } }
+                                            <button class="control-button" disabled={ !self.config.flee } onclick={ctx.link().callback(move |_| Msg::BattleAction(BattleAction::Flee))} title="Escape from this battle and return to the menu">{
+                                                "Flee"
+                                            } </button>
+                                            { for self.inventory.iter().map(|(item, count)| {
+                                                let target = self.item_target(encounter, item);
+                                                html! {
+                                                    <button class="control-button" disabled={ target.is_none() } title={ self.world.items[item].description() }
+                                                        onclick={ ctx.link().callback(move |_| Msg::BattleAction(BattleAction::UseItem(item, target.unwrap_or_default()))) }>{
+                                                        format!("{} x{}", self.world.items[item].name, count)
+                                                    }</button>
+                                                }
+                                            }) }
+                                        </div>
                                     },
-                                    Scene::Menu(_) => html! {
+                                    Scene::Menu => html! {
                                         <div>
-                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Battle))} title="Battle the next kaizo master.">{
-                                                "Battle"
+                                            { usage_heatmap(&player, &self.world.actions) }
+                                            <div title="How your lead measures up to others of its species.">{
+                                                match ivs::judge(&player) {
+                                                    Some(judgement) => judgement,
+                                                    None => format!("Fight {} more battles to size up its potential.", REVEAL_BATTLES - player.attributes.battles),
+                                                }
+                                            }</div>
+                                            { for BattlePace::of(&self.run, self.sessions).iter().map(|pace| html! {
+                                                <div title="Worked out from the battles this run has won.">{
+                                                    format!("{:.1} turns a battle, {:.1} battles a session.", pace.turns_per_battle, pace.battles_per_session)
+                                                }</div>
+                                            }) }
+                                            { for battles_to_level(&player).iter().map(|battles| html! {
+                                                <div title="A guess, since every enemy gives different experience.">{
+                                                    match BattlePace::of(&self.run, self.sessions) {
+                                                        Some(pace) => format!("About {} battles ({} turns) to level {}.", battles, pace.turns_for(*battles), player.attributes.level + 1),
+                                                        None => format!("About {} battles to level {}.", battles, player.attributes.level + 1),
+                                                    }
+                                                }</div>
+                                            }) }
+                                            { if self.saving { html! { } } else { html! {
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Continue))} title="Pick your last run back up. Doing anything else starts a new one.">{
+                                                    "Continue"
+                                                }</button>
+                                            } } }
+                                            <div title="Lose a battle on the map and the run is over.">{
+                                                match self.gauntlet.outcome {
+                                                    Outcome::Fighting(floor) => format!("Floor {}/{}.", floor + 1, self.gauntlet.floors.len()),
+                                                    Outcome::Won => "Every master is beaten.".to_string(),
+                                                    Outcome::Lost(_) => format!("Run over after {} floors.", self.gauntlet.cleared()),
+                                                }
+                                            }</div>
+                                            <button class="control-button" disabled={ self.gauntlet.is_over() } onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Map))} title="Pick where to go next.">{
+                                                "Map"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Shop))} title="Spend your money on items and moves. Prices go up the more you win.">{
+                                                "Shop"
                                             }</button>
-                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Scout))} title="Search for a new kaizo.">{
+                                            <button class="control-button" disabled={ self.money < scale_price(REST_PRICE, self.wins) } onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Rest))} title="Heal the whole party and clear their statuses. The party isn't healed between battles otherwise, except on casual.">{
+                                                format!("Rest ({} money)", scale_price(REST_PRICE, self.wins))
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Scout))} title="Search for a wild kaizo to recruit.">{
                                                 "Scout"
                                             }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::ToggleChaos))} title="Re-roll your moves at the start of every battle.">{
+                                                if self.rules.chaos { "Chaos: On" } else { "Chaos: Off" }
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::ToggleInverse))} title="Swap weaknesses and resistances.">{
+                                                if self.rules.inverse { "Inverse: On" } else { "Inverse: Off" }
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::ToggleEndless))} title="Keep going after the final boss, with a new curse stacked on every time around.">{
+                                                if self.rules.endless { "Endless: On" } else { "Endless: Off" }
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::CycleRosterLimit))} title="How many kaizo your party and reserve can hold.">{
+                                                match self.rules.roster_limit {
+                                                    Some(limit) => format!("Roster: {}", limit),
+                                                    None => "Roster: Unlimited".to_string(),
+                                                }
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::ToggleRealTime))} title="Follow your own clock instead of counting days in battles.">{
+                                                if self.clock.real_time { "Clock: Real time" } else { "Clock: Battles" }
+                                            }</button>
+                                            { for RISKS.iter().map(|risk| {
+                                                let risk = *risk;
+                                                html! {
+                                                    <button class="control-button" disabled={ self.rules.risks.contains(&risk) } onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::TakeRisk(risk)))} title={ risk.description() }>{
+                                                        format!("Risk: {}", risk)
+                                                    }</button>
+                                                }
+                                            }) }
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Mirror))} title="Battle an exact copy of your party.">{
+                                                "Mirror"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::CycleMirrorAi))} title="Change how the mirror kaizo picks its moves.">{
+                                                format!("Mirror AI: {:?}", self.mirror_ai)
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Trainer))} title="Battle a trainer and their whole team, for extra experience.">{
+                                                "Trainer"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::TeamBuilder))} title="Build and save teams of kaizo.">{
+                                                "Team Builder"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Mods))} title="Add content packs with new kaizo and moves.">{
+                                                "Mods"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::War))} title="Pit your party against a squad of wild kaizo, with the AI fighting both sides.">{
+                                                "War"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Replay))} title="Watch your last battle again, or share it.">{
+                                                "Replay"
+                                            }</button>
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Stats))} title="See how you've done over every run on this device.">{
+                                                "Stats"
+                                            }</button>
+                                            <input
+                                                class="seed-input"
+                                                placeholder="Seed"
+                                                value={ self.seed_input.clone() }
+                                                oninput={ ctx.link().callback(|e: InputEvent| {
+                                                    let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                                    Msg::MenuAction(MenuAction::EditSeed(input.value()))
+                                                }) }
+                                            />
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::NewRun))} title="Start over from the seed, or from a random one if it's empty. Your current run is lost.">{
+                                                "New Run"
+                                            }</button>
+                                            <input
+                                                class="seed-input"
+                                                placeholder="World URL"
+                                                value={ self.world_url_input.clone() }
+                                                oninput={ ctx.link().callback(|e: InputEvent| {
+                                                    let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                                    Msg::MenuAction(MenuAction::EditWorldUrl(input.value()))
+                                                }) }
+                                            />
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::LoadWorld))} title="Download a world as JSON, or RON if the URL ends in .ron, and start a new run in it. Your current run is lost.">{
+                                                "Load World"
+                                            }</button>
+                                            { if self.world_url.is_some() { html! {
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::RandomWorld))} title="Forget the downloaded world and start a new run in a random one. Your current run is lost.">{
+                                                    "Random World"
+                                                }</button>
+                                            } } else { html! { } } }
+                                            { for self.profile.checkpoints.iter().enumerate().map(|(index, checkpoint)| html! {
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Practice(index)))} title="Battle this boss again with a standard party. Practice runs aren't rated, and your run can be continued after.">{
+                                                    format!("Practice: {}", checkpoint.name())
+                                                }</button>
+                                            }) }
+                                            <div>
+                                                <textarea class="team-text" value={ self.save_text.clone() } placeholder="Export your run to move it to another browser, or paste one here."
+                                                    oninput={ ctx.link().callback(|e: InputEvent| {
+                                                        let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+                                                        Msg::MenuAction(MenuAction::EditSaveText(input.value()))
+                                                    }) }/>
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::ExportSave))} title="Write your run out as text, to copy or download.">{
+                                                    "Export Save"
+                                                }</button>
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::ImportSave))} title="Pick up the pasted run instead. Your current run is lost.">{
+                                                    "Import Save"
+                                                }</button>
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::MenuAction(MenuAction::Transfer))} title="Show your run as QR codes to move it to another device. Paste their text into the box there and import it.">{
+                                                    "Transfer"
+                                                }</button>
+                                                { if self.save_text.is_empty() { html! { } } else { html! {
+                                                    <a download="kaizo-quest-save.json" href={ format!("data:application/json,{}", String::from(js_sys::encode_uri_component(&self.save_text))) }>{
+                                                        "Download"
+                                                    }</a>
+                                                } } }
+                                                { for self.transfer.iter().map(|(chunk, (width, modules))| html! {
+                                                    <div>
+                                                        <div>{ format!("Part {} of {}", chunk.index + 1, chunk.total) }</div>
+                                                        <QrCanvas width={ *width } modules={ modules.clone() }/>
+                                                        <div class="transfer-text">{ chunk.to_string() }</div>
+                                                    </div>
+                                                }) }
+                                            </div>
                                         </div>
                                     },
+                                    Scene::Learn(prompt) => html! {
+                                        <div>
+                                            { for prompt.waiting.iter().take(1).map(|action| html! {
+                                                <span title={ self.world.actions[*action].description() }>{
+                                                    format!("{} wants to learn {}. Pick a move to forget for it. ", player.name, self.world.actions[*action].name())
+                                                }</span>
+                                            }) }
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::LearnAction(LearnAction::Skip))} title="Keep the moves you have.">{
+                                                "Don't Learn"
+                                            }</button>
+                                        </div>
+                                    },
+                                    Scene::Draft(prompt) => html! {
+                                        <div>
+                                            <span>{ match prompt.picks {
+                                                1 => format!("{} won! Pick an upgrade. ", player.name),
+                                                picks => format!("{} won! Pick {} upgrades. ", player.name, picks),
+                                            } }</span>
+                                            { for prompt.offers.iter().enumerate().map(|(index, offer)| html! {
+                                                <button class="control-button" onclick={ctx.link().callback(move |_| Msg::DraftAction(DraftAction::Pick(index)))}>{
+                                                    format!("[{:?}] {}", offer.rarity, offer.upgrade.description(&self.world))
+                                                }</button>
+                                            }) }
+                                            <button class="control-button" onclick={ctx.link().callback(move |_| Msg::DraftAction(DraftAction::Skip))} title="Take nothing.">{
+                                                "Skip"
+                                            }</button>
+                                        </div>
+                                    },
+                                    Scene::Map => self.view_map(ctx),
+                                    Scene::Shop => self.view_shop(ctx),
+                                    Scene::TeamBuilder(_) | Scene::Mods(_) | Scene::War(_) | Scene::Replay(_) | Scene::Stats(_) | Scene::Title => html! { },
                                 }
                             } </div>
                         </div>
+                        // roster and glossary
+                        <div> {
+                            match &self.scene {
+                                // the roster stays put while a kaizo is learning, so its slot doesn't move
+                                Scene::Battle(..) | Scene::TeamBuilder(_) | Scene::Mods(_) | Scene::War(_) | Scene::Replay(_) | Scene::Stats(_) | Scene::Learn(_) | Scene::Draft(_) | Scene::Map | Scene::Shop | Scene::Title => html! { },
+                                Scene::Menu => html! {
+                                    <div>
+                                        <RosterView
+                                            roster={ self.roster.clone() }
+                                            on_sort={ ctx.link().callback(|order| Msg::MenuAction(MenuAction::Sort(order))) }
+                                            on_move={ ctx.link().callback(|(from, to)| Msg::MenuAction(MenuAction::Move(from, to))) }
+                                            on_lead={ ctx.link().callback(|slot| Msg::MenuAction(MenuAction::Lead(slot))) }
+                                            on_lead_from_reserve={ ctx.link().callback(|index| Msg::MenuAction(MenuAction::LeadFromReserve(index))) }
+                                            on_release={ ctx.link().callback(|index| Msg::MenuAction(MenuAction::Release(index))) }
+                                            on_retire={ ctx.link().callback(|index| Msg::MenuAction(MenuAction::Retire(index))) }
+                                            on_breed={ ctx.link().callback(|index| Msg::MenuAction(MenuAction::Breed(index))) }
+                                            limit={ self.rules.roster_limit }
+                                        />
+                                        <div>
+                                            { "Bag" }
+                                            { if self.inventory.is_empty() { html! { <div>{ "Empty." }</div> } } else { html! { } } }
+                                            { for self.inventory.iter().map(|(item, count)| html! {
+                                                <div title={ self.world.items[item].description() }>{ format!("{} x{}", self.world.items[item].name, count) }</div>
+                                            }) }
+                                        </div>
+                                        <Glossary chart={ self.rules.chart(self.world.chart()) } inverse={ self.rules.inverse }/>
+                                    </div>
+                                },
+                            }
+                        } </div>
                     </div>
                     <div class="logs">
-                    { for self.logs.iter().map(move |log| { html! { <div>{ format!("{}", log) }</div> } }) }
+                    { for self.logs.iter().map(move |log| { html! { <div>{ log.to_string() }</div> } }) }
                     </div>
                 </div>
             </div>
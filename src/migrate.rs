@@ -0,0 +1,135 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+// a file tagged with the version of its schema that wrote it. files from before there were versions
+// aren't tagged, and are all version 1
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    // saves were briefly tagged with `game` instead
+    #[serde(alias = "game")]
+    pub payload: Value,
+}
+
+impl SaveFile {
+    // the file's tag and what it wraps, or the json as it was if it isn't tagged
+    pub fn read(json: Value) -> Result<SaveFile, Value> {
+        match json {
+            Value::Object(ref object) if object.contains_key("version") => serde_json::from_value(json.clone()).map_err(|_| json),
+            json => Err(json),
+        }
+    }
+}
+
+// rewrites a file from one version into the next, or says why it can't
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+// what one kind of file looks like now. the version is bumped whenever a change to its shape can't
+// be papered over by `#[serde(default)]`, e.g. `Stats`, `ActionPool` or `Species` being laid out
+// differently, and a migration is added for the files from before
+pub struct Schema {
+    pub version: u32,
+    // the first one takes version 1 files to version 2, and so on
+    pub migrations: &'static [Migration],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MigrationError {
+    Parse(String),
+    // written by a newer version of the game, which this one doesn't know how to read
+    Newer(u32),
+    // a migration couldn't make sense of the file it was given
+    Failed { from: u32, message: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationError::Parse(message) => write!(f, "That file couldn't be read: {}", message),
+            MigrationError::Newer(version) => write!(f, "That file is from a newer version of the game (version {}).", version),
+            MigrationError::Failed { from, message } => write!(f, "That file couldn't be brought up from version {}: {}", from, message),
+        }
+    }
+}
+
+impl Schema {
+    pub fn wrap<T: Serialize>(&self, payload: &T) -> SaveFile {
+        // everything the game writes out is plain data, so this can't fail
+        SaveFile { version: self.version, payload: serde_json::to_value(payload).unwrap() }
+    }
+
+    // brings `json` from `from_version` up to date and reads it
+    pub fn migrate<T: DeserializeOwned>(&self, from_version: u32, json: Value) -> Result<T, MigrationError> {
+        if from_version > self.version {
+            return Err(MigrationError::Newer(from_version));
+        }
+        let json = self.migrations.iter()
+            .zip(1..)
+            .skip(from_version.saturating_sub(1) as usize)
+            .try_fold(json, |json, (migrate, from)| migrate(json).map_err(|message| MigrationError::Failed { from, message }))?;
+        serde_json::from_value(json).map_err(|error| MigrationError::Parse(error.to_string()))
+    }
+
+    // a file tagged or not, from any version up to this one
+    pub fn load<T: DeserializeOwned>(&self, json: Value) -> Result<T, MigrationError> {
+        match SaveFile::read(json) {
+            Ok(file) => self.migrate(file.version, file.payload),
+            Err(json) => self.migrate(1, json),
+        }
+    }
+}
+
+#[cfg(test)]
+mod migrate_tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Fake {
+        health: u32,
+    }
+
+    // version 2 renamed `hp` to `health`, and version 3 stopped storing it in tenths
+    fn rename(mut json: Value) -> Result<Value, String> {
+        let hp = json.as_object_mut().and_then(|object| object.remove("hp")).ok_or("no hp")?;
+        json["health"] = hp;
+        Ok(json)
+    }
+
+    fn tenths(mut json: Value) -> Result<Value, String> {
+        let health = json["health"].as_u64().ok_or("no health")?;
+        json["health"] = (health / 10).into();
+        Ok(json)
+    }
+
+    static SCHEMA: Schema = Schema { version: 3, migrations: &[rename, tenths] };
+
+    #[test]
+    fn migrate_test() {
+        assert_eq!(SCHEMA.migrate(1, json!({ "hp": 500 })), Ok(Fake { health: 50 }));
+        assert_eq!(SCHEMA.migrate(2, json!({ "health": 500 })), Ok(Fake { health: 50 }));
+        assert_eq!(SCHEMA.migrate(3, json!({ "health": 50 })), Ok(Fake { health: 50 }));
+        assert_eq!(SCHEMA.migrate::<Fake>(4, json!({ "health": 50 })), Err(MigrationError::Newer(4)));
+        assert_eq!(
+            SCHEMA.migrate::<Fake>(1, json!({ "health": 50 })),
+            Err(MigrationError::Failed { from: 1, message: "no hp".to_string() })
+        );
+        assert!(matches!(SCHEMA.migrate::<Fake>(3, json!({ "hp": 50 })), Err(MigrationError::Parse(_))));
+    }
+
+    #[test]
+    fn load_test() {
+        // untagged files are version 1
+        assert_eq!(SCHEMA.load(json!({ "hp": 500 })), Ok(Fake { health: 50 }));
+        assert_eq!(SCHEMA.load(json!({ "version": 2, "payload": { "health": 500 } })), Ok(Fake { health: 50 }));
+        assert_eq!(SCHEMA.load(json!({ "version": 3, "game": { "health": 50 } })), Ok(Fake { health: 50 }));
+
+        let wrapped = SCHEMA.wrap(&json!({ "health": 50 }));
+        assert_eq!(wrapped.version, 3);
+        assert_eq!(SCHEMA.load(serde_json::to_value(wrapped).unwrap()), Ok(Fake { health: 50 }));
+    }
+}
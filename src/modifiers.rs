@@ -0,0 +1,86 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::Difficulty;
+use crate::clauses::Clause;
+use crate::endless::Curse;
+use crate::rules::Ruleset;
+
+// something that made a run harder, shown as a badge next to its result and scaling its score
+// TODO: a no-items modifier, once there are items
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Modifier {
+    Difficulty(Difficulty),
+    Clause(Clause),
+    Chaos,
+    Inverse,
+    Curse(Curse),
+}
+
+impl Modifier {
+    pub fn multiplier(&self) -> f64 {
+        match self {
+            Modifier::Difficulty(Difficulty::Easy) => 1.0,
+            Modifier::Difficulty(Difficulty::Normal) => 1.25,
+            Modifier::Difficulty(Difficulty::Hard | Difficulty::Adaptive) => 1.5,
+            Modifier::Difficulty(Difficulty::Kaizo) => 2.0,
+            Modifier::Clause(_) => 1.1,
+            Modifier::Chaos => 1.25,
+            Modifier::Inverse => 1.1,
+            Modifier::Curse(_) => 1.1,
+        }
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Modifier::Difficulty(difficulty) => write!(f, "{:?}", difficulty),
+            Modifier::Clause(clause) => write!(f, "{:?} Clause", clause),
+            Modifier::Chaos => write!(f, "Chaos"),
+            Modifier::Inverse => write!(f, "Inverse"),
+            Modifier::Curse(curse) => write!(f, "{}", curse),
+        }
+    }
+}
+
+// the modifiers battles against `ai` under `rules` are played with
+pub fn active(ai: Difficulty, rules: &Ruleset) -> Vec<Modifier> {
+    let mut modifiers = vec![Modifier::Difficulty(ai)];
+    modifiers.extend(rules.clauses.iter().map(|clause| Modifier::Clause(*clause)));
+    if rules.chaos {
+        modifiers.push(Modifier::Chaos);
+    }
+    if rules.inverse {
+        modifiers.push(Modifier::Inverse);
+    }
+    modifiers.extend(rules.curses.iter().map(|curse| Modifier::Curse(*curse)));
+    modifiers
+}
+
+// every modifier's multiplier together
+pub fn multiplier(modifiers: &[Modifier]) -> f64 {
+    modifiers.iter().map(Modifier::multiplier).product()
+}
+
+// the modifiers as badges, e.g. "[Kaizo] [Chaos]"
+pub fn badges(modifiers: &[Modifier]) -> String {
+    modifiers.iter().map(|modifier| format!("[{}]", modifier)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod modifiers_tests {
+    use super::*;
+
+    #[test]
+    fn active_test() {
+        assert_eq!(active(Difficulty::Easy, &Ruleset::default()), vec![Modifier::Difficulty(Difficulty::Easy)]);
+        assert_eq!(multiplier(&active(Difficulty::Easy, &Ruleset::default())), 1.0);
+
+        let rules = Ruleset { chaos: true, inverse: true, clauses: vec![Clause::Stun], ..Ruleset::default() };
+        let modifiers = active(Difficulty::Kaizo, &rules);
+        assert_eq!(badges(&modifiers), "[Kaizo] [Stun Clause] [Chaos] [Inverse]");
+        assert!((multiplier(&modifiers) - 2.0 * 1.1 * 1.25 * 1.1).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,22 @@
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{RequestCache, RequestInit, Response};
+
+fn describe(error: JsValue) -> String {
+    error.as_string().unwrap_or_else(|| format!("{:?}", error))
+}
+
+// the whole file, skipping the browser's cache so edits show up
+pub async fn fetch_text(url: &str) -> Result<String, String> {
+    let window = web_sys::window().ok_or_else(|| "There's no window to fetch from.".to_string())?;
+    let init = RequestInit::new();
+    init.set_cache(RequestCache::NoStore);
+    let response = JsFuture::from(window.fetch_with_str_and_init(url, &init)).await.map_err(describe)?;
+    let response: Response = response.dyn_into().map_err(describe)?;
+    if !response.ok() {
+        return Err(format!("Fetching {} failed with status {}.", url, response.status()));
+    }
+    let text = JsFuture::from(response.text().map_err(describe)?).await.map_err(describe)?;
+    text.as_string().ok_or_else(|| format!("{} isn't text.", url))
+}
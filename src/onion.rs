@@ -1,34 +1,104 @@
 use std::cmp::{Eq, PartialEq};
 
 use std::hash::Hash;
-use std::ops::Index;
+use std::ops::{Index, Range, RangeInclusive};
+use std::sync::Arc;
 use std::vec::Vec;
 
-use rand::{Rng, random, thread_rng};
+use rand::{Rng, RngCore};
 use rand::distributions::{Distribution, Standard};
 use rand::seq::SliceRandom;
 use serde::{Serialize, Deserialize};
 
-use crate::core::{Action, ActionId, Character, Species, States, Stats};
+use crate::abilities::{OnionAbility, default_abilities};
+use crate::areas::{Area, area_at, generate_areas};
+use crate::bosses::{Boss, Phase};
+use crate::clauses::Clause;
+use crate::clock::{DayCycle, TimeOfDay};
+use crate::effort;
+use crate::encounters::EncounterKind;
+use crate::hazards::{self, Spikes};
+use crate::items::{OnionItem, default_items};
+use crate::share::gain_capped;
+use crate::statuses;
+use crate::team::MAX_ACTIONS;
+use crate::weather::{self, WEATHER_TURNS, Weather};
+use crate::core::{Ability, Action, ActionId, Actions, BASE_ACCURACY, Category, Character, DEFAULT_PP, GrowthRate, NATURES, Pending, Rarity, Stat, Name, Species, State, States, Stats, StatusKey};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Status { Defend, Bleed, Stun, Burn, Freeze, Confuse, Poison }
+
+impl StatusKey for Status {
+    fn index(&self) -> usize { *self as usize }
+
+    fn from_index(index: usize) -> Option<Self> {
+        [Status::Defend, Status::Bleed, Status::Stun, Status::Burn, Status::Freeze, Status::Confuse, Status::Poison].get(index).copied()
+    }
+}
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
-pub enum Status { Defend, Bleed, Stun }
+// a world only has as many of these as its type chart has rows, in this order; generated worlds
+// and the default chart only have the first three
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Alignment {
+    Rock, Paper, Scissors, Lizard, Spock, Fire, Water, Grass, Ice,
+    Wind, Thunder, Metal, Poison, Ghost, Light, Dark, Dragon, Fairy,
+}
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
-pub enum Alignment { Rock, Paper, Scissors }
+pub static ALIGNMENTS: [Alignment; 18] = [
+    Alignment::Rock, Alignment::Paper, Alignment::Scissors, Alignment::Lizard, Alignment::Spock, Alignment::Fire,
+    Alignment::Water, Alignment::Grass, Alignment::Ice, Alignment::Wind, Alignment::Thunder, Alignment::Metal,
+    Alignment::Poison, Alignment::Ghost, Alignment::Light, Alignment::Dark, Alignment::Dragon, Alignment::Fairy,
+];
+
+// e.g. `Rock/Paper`, for a species or character with two alignments
+pub fn alignment_names(alignments: &[Alignment]) -> String {
+    alignments.iter().map(|alignment| format!("{:?}", alignment)).collect::<Vec<String>>().join("/")
+}
 
 pub type OnionCharacter = Character<Alignment, Status>;
+pub type OnionState = State<Alignment, Status>;
+
+// statuses that end with the battle; the rest are carried into the next one until the kaizo rests
+pub static VOLATILE_STATUSES: [Status; 1] = [Status::Defend];
+
+// clears what only lasts a battle, e.g. stat stages, but keeps the kaizo's health, pp and every
+// status that isn't volatile, so the next battle starts where this one left off
+pub fn settle(character: &mut OnionCharacter) {
+    let (health, pp) = (character.state.health, std::mem::take(&mut character.attributes.pp));
+    let lasting: Vec<(Status, i32)> = character.state.status.iter().filter(|(status, _)| !VOLATILE_STATUSES.contains(status)).collect();
+    character.refresh();
+    character.state.health = health;
+    character.attributes.pp = pp;
+    for (status, value) in lasting {
+        character.state.status.insert(status, value);
+    }
+}
 
 #[cfg(test)]
-mod testing {
+mod status_tests {
+    use super::*;
+
+    use crate::core::MAX_STATUSES;
+
+    #[test]
+    fn status_order_test() {
+        let statuses: Vec<Status> = (0..MAX_STATUSES).map_while(Status::from_index).collect();
+        assert_eq!(statuses.len(), 7);
+        assert!(statuses.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(statuses.iter().enumerate().all(|(index, status)| status.index() == index));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
     use super::*;
 
     pub fn fake_stats() -> Stats<f64> {
-        Stats::from_values(0.25, 0.25, 0.25, 0.25)
+        Stats::from_values(0.25, 0.25, 0.25, 0.25, 0.25, 0.25)
     }
 
     pub fn fake_stats_with_value<T: Copy>(value: T) -> Stats<T> {
-        Stats::from_values(value, value, value, value)
+        Stats::from_values(value, value, value, value, value, value)
     }
 
     pub fn fake_species() -> Species<Alignment> {
@@ -37,10 +107,15 @@ mod testing {
 
     pub fn fake_species_with_bst(bst: u32) -> Species<Alignment> {
         Species {
-            name: "fake".to_string(),
+            name: "fake".into(),
             bst,
             stats: fake_stats(),
             alignment: Alignment::Rock,
+            secondary: None,
+            learnset: Vec::new(),
+            ability: None,
+            rarity: Rarity::Common,
+            growth: GrowthRate::Medium,
         }
     }
 
@@ -54,169 +129,1036 @@ mod testing {
 }
 
 // action implementations
-trait Effectiveness {
-    fn effectiveness(self, other: Alignment) -> u32;
-}
-
-impl Effectiveness for Alignment {
-    fn effectiveness(self, other: Alignment) -> u32 {
-        // TODO: we did something stupid here, see the note in attack
-        match (self, other) {
-            (Alignment::Rock, Alignment::Paper) |
-            (Alignment::Paper, Alignment::Scissors) |
-            (Alignment::Scissors, Alignment::Rock) => 5,
-            (Alignment::Rock, Alignment::Scissors) |
-            (Alignment::Scissors, Alignment::Paper) |
-            (Alignment::Paper, Alignment::Rock) => 20,
-            _ => 10,
+// how effective each alignment is against the others, in tenths (10 is neutral)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TypeChart {
+    effectiveness: Vec<Vec<u32>>,
+}
+
+impl Default for TypeChart {
+    fn default() -> Self {
+        // rows attack, columns defend; in the order of `Alignment`
+        TypeChart {
+            effectiveness: vec![
+                vec![10, 5, 20],
+                vec![20, 10, 5],
+                vec![5, 20, 10],
+            ],
         }
     }
 }
 
+impl TypeChart {
+    // rows attack, columns defend; the chart is square, and its size is how many alignments the
+    // world has, up to every one there is
+    pub fn from_rows(effectiveness: Vec<Vec<u32>>) -> Option<TypeChart> {
+        let n = effectiveness.len();
+        if (1..=ALIGNMENTS.len()).contains(&n) && effectiveness.iter().all(|row| row.len() == n) {
+            Some(TypeChart { effectiveness })
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> &[Vec<u32>] { &self.effectiveness }
+
+    // the alignments this chart has a row and a column for
+    pub fn alignments(&self) -> &'static [Alignment] {
+        &ALIGNMENTS[..self.effectiveness.len().min(ALIGNMENTS.len())]
+    }
+
+    // TODO: we did something stupid here, see the note in attack
+    // alignments the chart doesn't have are neutral to everything
+    pub fn effectiveness(&self, attack: Alignment, defense: Alignment) -> u32 {
+        self.effectiveness.get(attack as usize).and_then(|row| row.get(defense as usize)).copied().unwrap_or(10)
+    }
+
+    // against something with more than one alignment, each one's effectiveness is multiplied in,
+    // e.g. two weaknesses make 4x and a weakness and an immunity make 0x
+    pub fn effectiveness_against(&self, attack: Alignment, defenses: &[Alignment]) -> u32 {
+        defenses.iter().fold(10, |total, defense| total * self.effectiveness(attack, *defense) / 10)
+    }
+
+    // swaps weaknesses and resistances; immunities become weaknesses
+    pub fn inverse(&self) -> TypeChart {
+        TypeChart {
+            effectiveness: self.effectiveness.iter()
+                .map(|row| row.iter().map(|e| if *e == 0 { 20 } else { 100 / e }).collect())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod type_chart_tests {
+    use super::*;
+
+    #[test]
+    fn type_chart_test() {
+        let chart = TypeChart::default();
+        assert_eq!(chart.effectiveness(Alignment::Rock, Alignment::Scissors), 20);
+        assert_eq!(chart.effectiveness(Alignment::Rock, Alignment::Paper), 5);
+        assert_eq!(chart.effectiveness(Alignment::Rock, Alignment::Rock), 10);
+    }
+
+    #[test]
+    fn inverse_test() {
+        let chart = TypeChart::default().inverse();
+        assert_eq!(chart.effectiveness(Alignment::Rock, Alignment::Scissors), 5);
+        assert_eq!(chart.effectiveness(Alignment::Rock, Alignment::Paper), 20);
+        assert_eq!(chart.effectiveness(Alignment::Rock, Alignment::Rock), 10);
+        assert_eq!(chart.inverse(), TypeChart::default());
+
+        let chart = TypeChart { effectiveness: vec![vec![0, 10], vec![20, 5]] }.inverse();
+        assert_eq!(chart, TypeChart { effectiveness: vec![vec![20, 10], vec![5, 20]] });
+    }
+
+    #[test]
+    fn from_rows_test() {
+        assert_eq!(TypeChart::from_rows(TypeChart::default().effectiveness), Some(TypeChart::default()));
+        assert_eq!(TypeChart::from_rows(vec![vec![10; 3]; 2]), None);
+        assert_eq!(TypeChart::from_rows(vec![vec![10; 3], vec![10; 3], vec![10; 2]]), None);
+        assert_eq!(TypeChart::from_rows(vec![]), None);
+        assert_eq!(TypeChart::from_rows(vec![vec![10; 19]; 19]), None);
+
+        let chart = TypeChart::from_rows(vec![vec![10; 6]; 6]).unwrap();
+        assert_eq!(chart.alignments(), &ALIGNMENTS[..6]);
+        assert_eq!(TypeChart::from_rows(vec![vec![10; 18]; 18]).unwrap().alignments(), &ALIGNMENTS[..]);
+    }
+
+    #[test]
+    fn effectiveness_against_test() {
+        let mut rows = vec![vec![10; 6]; 6];
+        rows[Alignment::Fire as usize][Alignment::Lizard as usize] = 20;
+        rows[Alignment::Fire as usize][Alignment::Rock as usize] = 5;
+        rows[Alignment::Fire as usize][Alignment::Spock as usize] = 0;
+        let chart = TypeChart::from_rows(rows).unwrap();
+        // grass isn't in a chart this small, so it's neutral
+        assert_eq!(chart.effectiveness(Alignment::Fire, Alignment::Grass), 10);
+        assert_eq!(chart.effectiveness_against(Alignment::Fire, &[Alignment::Lizard]), 20);
+        assert_eq!(chart.effectiveness_against(Alignment::Fire, &[Alignment::Lizard, Alignment::Lizard]), 40);
+        assert_eq!(chart.effectiveness_against(Alignment::Fire, &[Alignment::Lizard, Alignment::Rock]), 10);
+        assert_eq!(chart.effectiveness_against(Alignment::Fire, &[Alignment::Lizard, Alignment::Spock]), 0);
+    }
+}
+
+// in tenths, like type effectiveness
+pub static DEFAULT_CRIT_MULTIPLIER: u32 = 15;
+// the crit chance of a normal attack between two equally fast characters is half of this
+static SPEED_CRIT_CHANCE: f64 = 0.125;
+static HIGH_CRIT_FACTOR: f64 = 3.0;
+
+fn default_crit_multiplier() -> u32 { DEFAULT_CRIT_MULTIPLIER }
+
+// in percent; the most of a target's health one action can take at once
+pub static DEFAULT_FRACTION_CAP: u32 = 50;
+
+fn default_fraction_cap() -> u32 { DEFAULT_FRACTION_CAP }
+
+// in percent; how often a one-hit KO lands on a target at the user's level
+pub static OHKO_ACCURACY: u32 = 30;
+static OHKO_PP: u32 = 5;
+
+fn ohko_accuracy() -> u32 { OHKO_ACCURACY }
+
+fn ohko_pp() -> u32 { OHKO_PP }
+
+// the random numbers for the action being taken. `take_turn` draws them so that actions don't
+// need an rng; simulated turns leave them empty, which means nothing is left to luck
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Rolls {
+    // from 0 to 1
+    pub crit: Option<f64>,
+    // the crit rolls for every hit after the first, for actions that hit more than once
+    pub hits: Vec<f64>,
+}
+
+impl Rolls {
+    pub fn roll(rng: &mut dyn RngCore, hits: RangeInclusive<u32>) -> Rolls {
+        let crit = Some(rng.gen());
+        // actions that always hit the same number of times don't roll for it, so they draw the
+        // same numbers they did before anything could hit more than once
+        let count = if hits.start() < hits.end() { rng.gen_range(hits) } else { *hits.start() };
+        Rolls { crit, hits: (1..count).map(|_| rng.gen()).collect() }
+    }
+}
+
+// the battle-wide state that actions can see
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    // shared, since it's fixed for the whole battle and the field is cloned for every simulated turn
+    pub chart: Arc<TypeChart>,
+    pub clauses: Vec<Clause>,
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: u32,
+    #[serde(default = "default_fraction_cap")]
+    pub fraction_cap: u32,
+    // the world's abilities, which the species' ids point into; shared like the chart
+    #[serde(default = "default_shared_abilities")]
+    pub abilities: Arc<Vec<OnionAbility>>,
+    // set by actions, and counted down at the end of every turn
+    #[serde(default)]
+    pub weather: Option<Weather>,
+    #[serde(default)]
+    pub weather_turns: u32,
+    #[serde(skip)]
+    pub rolls: Rolls,
+    #[serde(skip)]
+    pub spikes: Spikes,
+}
+
+fn default_shared_abilities() -> Arc<Vec<OnionAbility>> { Arc::new(default_abilities()) }
+
+impl Field {
+    pub fn ability(&self, character: &OnionCharacter) -> Option<&OnionAbility> {
+        character.species.ability.and_then(|ability| self.abilities.get(ability))
+    }
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Field {
+            chart: Arc::default(),
+            clauses: Vec::new(),
+            crit_multiplier: DEFAULT_CRIT_MULTIPLIER,
+            fraction_cap: DEFAULT_FRACTION_CAP,
+            abilities: default_shared_abilities(),
+            weather: None,
+            weather_turns: 0,
+            rolls: Rolls::default(),
+            spikes: Spikes::default(),
+        }
+    }
+}
+
+// faster characters land more crits, and moves with a high crit ratio land them three times as often
+pub fn crit_chance(user: &OnionCharacter, target: &OnionCharacter, high_crit: bool) -> f64 {
+    let speed = user.stat(Stat::Speed) as f64;
+    let total = (user.stat(Stat::Speed) + target.stat(Stat::Speed)).max(1) as f64;
+    let chance = SPEED_CRIT_CHANCE * speed / total;
+    (if high_crit { chance * HIGH_CRIT_FACTOR } else { chance }).min(1.0)
+}
+
 trait Damage {
     fn deal_damage(&mut self, damage: u32);
+
+    // damage from an attack, which counters can pay back if it was physical
+    fn take_hit(&mut self, damage: u32, category: Category);
 }
 
 impl Damage for OnionCharacter {
     fn deal_damage(&mut self, damage: u32) {
         self.state.health = std::cmp::max(0, self.state.health - damage as i32);
     }
+
+    fn take_hit(&mut self, damage: u32, category: Category) {
+        let health = self.state.health;
+        self.deal_damage(damage);
+        if category == Category::Physical {
+            self.state.taken += (health - self.state.health) as u32;
+        }
+    }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Attack {
-    name: String,
+    name: Name,
     power: u32,
     alignment: Alignment,
+    #[serde(default)]
     priority: i32,
+    #[serde(default)]
+    high_crit: bool,
+    #[serde(default = "base_accuracy")]
+    accuracy: u32,
+    #[serde(default)]
+    category: Category,
+    #[serde(default = "default_pp")]
+    pp: u32,
+}
+
+fn base_accuracy() -> u32 { BASE_ACCURACY }
+
+fn default_pp() -> u32 { DEFAULT_PP }
+
+impl Attack {
+    pub fn new(name: &str, power: u32, alignment: Alignment, priority: i32) -> Attack {
+        Attack { name: name.into(), power, alignment, priority, high_crit: false, accuracy: BASE_ACCURACY, category: Category::Physical, pp: DEFAULT_PP }
+    }
+
+    pub fn with_category(self, category: Category) -> Attack {
+        Attack { category, ..self }
+    }
+
+    pub fn with_accuracy(self, accuracy: u32) -> Attack {
+        Attack { accuracy, ..self }
+    }
+
+    pub fn with_high_crit(self) -> Attack {
+        Attack { high_crit: true, ..self }
+    }
+
+    pub fn with_pp(self, pp: u32) -> Attack {
+        Attack { pp, ..self }
+    }
+
+    pub fn power(&self) -> u32 { self.power }
+
+    pub fn alignment(&self) -> Alignment { self.alignment }
+
+    pub fn high_crit(&self) -> bool { self.high_crit }
+
+    // a single hit, with its own crit roll. `announce` says how effective it was, which only needs
+    // saying once however many times it hits
+    fn strike(&self, user: &OnionCharacter, target: &mut OnionCharacter, field: &Field, crit: Option<f64>, announce: bool) -> States {
+        let mut logs = Vec::new();
+        let level = 2 * user.attributes.level / 5 + 2;
+        let stats = match self.category {
+            Category::Physical => user.stat(Stat::Attack) / target.stat(Stat::Defense).max(1),
+            Category::Special => user.stat(Stat::SpecialAttack) / target.stat(Stat::SpecialDefense).max(1),
+        };
+        // TODO: this is a little stupid. this should be 1.5/1.0 but then the compiler gets
+        //       mad because of u32 * float. so i offset it to the final computation
+        let stab = if user.state.alignments().contains(&self.alignment) { 15 } else { 10 };
+        let effectiveness = field.chart.effectiveness_against(self.alignment, &target.state.alignments());
+        match effectiveness {
+            0 if announce => logs.push("It has no effect.".to_string()),
+            e if e > 10 && announce => logs.push("It's very effective.".to_string()),
+            e if e < 10 && announce => logs.push("It's not very effective.".to_string()),
+            _ => (),
+        };
+        let crit = match crit {
+            Some(roll) if effectiveness > 0 && roll < crit_chance(user, target, self.high_crit) => {
+                logs.push("A critical hit!".to_string());
+                field.crit_multiplier
+            },
+            _ => 10,
+        };
+        // immunities take nothing, not even the flat 2. worked out wide, since a strong attack
+        // between far apart levels doesn't fit in a u32 before it's divided back down
+        let damage = match effectiveness {
+            0 => 0,
+            _ => {
+                let product = [level, self.power, stats, stab, effectiveness, crit].iter().fold(1u64, |product, factor| product * *factor as u64);
+                // with room left for the weather's boost
+                (product / 50 / 10 / 10 / 10 + 2).min(u32::MAX as u64 / 100) as u32
+            },
+        };
+        // after the rest, so it's only ever applied to damage that fits
+        let damage = match field.weather {
+            Some(weather) => damage * weather.boost(self.alignment) / 10,
+            None => damage,
+        };
+        let damage = match field.ability(target) {
+            Some(ability) => ability.on_hit(target, &self.alignment, damage),
+            None => damage,
+        };
+        target.take_hit(damage, self.category);
+        logs
+    }
 }
 
-impl Action<Alignment, Status> for Attack {
-    fn name(&self) -> String { format!("{}", self.name) }
+impl Action<Alignment, Status, Field> for Attack {
+    fn name(&self) -> &str { &self.name }
 
     fn description(&self) -> String {
         format!(
-            "{:?}-aligned Attack with {} power.{}",
+            "{:?}-aligned {:?} Attack with {} power.{}{}{}",
             self.alignment,
+            self.category,
             self.power,
-            if self.priority > 0 { "\nHas priority." } else { "" }
+            if self.priority > 0 { "\nHas priority." } else { "" },
+            if self.high_crit { "\nHigh critical hit ratio." } else { "" },
+            if self.accuracy < BASE_ACCURACY { format!("\n{}% accurate.", self.accuracy) } else { String::new() },
         )
     }
 
     fn priority(&self) -> i32 { self.priority }
 
-    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter) -> States {
-        // target: &mut Character<A, S>) where A: Alignment, S: Status -> States {
+    fn accuracy(&self) -> u32 { self.accuracy }
+
+    fn category(&self) -> Category { self.category }
+
+    fn pp(&self) -> u32 { self.pp }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
         let mut logs = Vec::new();
         logs.push(format!("{} used {}.", user.name, self.name));
         if target.state.status.contains_key(&Status::Defend) {
             logs.push(format!("{} blocked {}'s {}.", target.name, user.name, self.name))
         } else {
-            let level = 2 * user.attributes.level / 5 + 2;
-            // TODO: this only handles "physical" alignments
-            let stats = user.attributes.stats.attack / target.attributes.stats.defense;
-            // TODO: this is a little stupid. this should be 1.5/1.0 but then the compiler gets
-            //       mad because of u32 * float. so i offset it to the final computation
-            let stab = if user.state.alignment == self.alignment { 15 } else { 10 };
-            let effectiveness = self.alignment.effectiveness(target.state.alignment);
-            match effectiveness {
-                20 => logs.push("It's very effective.".to_string()),
-                5 => logs.push("It's not very effective.".to_string()),
-                0 => logs.push("It has no effect.".to_string()),
-                _ => (),
-            };
-            // TODO: add crits
-            let damage = level * self.power * stats * stab * effectiveness / 50 / 10 / 10 + 2;
-            target.deal_damage(damage);
+            logs.extend(self.strike(user, target, field, field.rolls.crit, true));
         }
         logs
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct PureAttack { name: String, power: u32 }
+// an attack that hits MIN_HITS to MAX_HITS times, each hit with its own crit roll
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiHitAttack {
+    #[serde(flatten)]
+    attack: Attack,
+}
+
+pub static MIN_HITS: u32 = 2;
+pub static MAX_HITS: u32 = 5;
+// what simulated turns, which don't roll, count on
+static AVERAGE_HITS: u32 = 3;
+
+impl MultiHitAttack {
+    pub fn new(attack: Attack) -> MultiHitAttack {
+        MultiHitAttack { attack }
+    }
+}
 
-impl Action<Alignment, Status> for PureAttack {
-    fn name(&self) -> String { format!("{}", self.name) }
+impl Action<Alignment, Status, Field> for MultiHitAttack {
+    fn name(&self) -> &str { &self.attack.name }
+
+    fn description(&self) -> String {
+        format!("{}\nHits {} to {} times.", self.attack.description(), MIN_HITS, MAX_HITS)
+    }
+
+    fn priority(&self) -> i32 { self.attack.priority }
+
+    fn accuracy(&self) -> u32 { self.attack.accuracy }
+
+    fn category(&self) -> Category { self.attack.category }
+
+    fn pp(&self) -> u32 { self.attack.pp }
+
+    fn hits(&self) -> RangeInclusive<u32> { MIN_HITS..=MAX_HITS }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.attack.name));
+        if target.state.status.contains_key(&Status::Defend) {
+            logs.push(format!("{} blocked {}'s {}.", target.name, user.name, self.attack.name));
+            return logs;
+        }
+        let crits: Vec<Option<f64>> = match field.rolls.crit {
+            Some(crit) => std::iter::once(crit).chain(field.rolls.hits.iter().copied()).map(Some).collect(),
+            None => vec![None; AVERAGE_HITS as usize],
+        };
+        let mut hits = 0;
+        for crit in crits {
+            logs.extend(self.attack.strike(user, target, field, crit, hits == 0));
+            hits += 1;
+            // the rest of the hits have nothing left to hit
+            if target.state.health == 0 {
+                break;
+            }
+        }
+        logs.push(if hits == 1 { "Hit 1 time!".to_string() } else { format!("Hit {} times!", hits) });
+        logs
+    }
+}
+
+// an attack that hurts the user for 1/`recoil` of the damage it deals
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecoilAttack {
+    #[serde(flatten)]
+    attack: Attack,
+    recoil: u32,
+}
+
+pub static DEFAULT_RECOIL: u32 = 3;
+
+impl RecoilAttack {
+    pub fn new(attack: Attack, recoil: u32) -> RecoilAttack {
+        RecoilAttack { attack, recoil: recoil.max(1) }
+    }
+}
+
+impl Action<Alignment, Status, Field> for RecoilAttack {
+    fn name(&self) -> &str { &self.attack.name }
+
+    fn description(&self) -> String {
+        format!("{}\nThe user takes 1/{} of the damage back.", self.attack.description(), self.recoil)
+    }
+
+    fn priority(&self) -> i32 { self.attack.priority }
+
+    fn accuracy(&self) -> u32 { self.attack.accuracy }
+
+    fn category(&self) -> Category { self.attack.category }
+
+    fn pp(&self) -> u32 { self.attack.pp }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let health = target.state.health;
+        let mut logs = self.attack.act(user, target, field);
+        // only what was actually taken off, so finishing a kaizo off doesn't hurt as much
+        let dealt = (health - target.state.health) as u32;
+        if dealt > 0 {
+            user.deal_damage((dealt / self.recoil.max(1)).max(1));
+            logs.push(format!("{} was hurt by the recoil.", user.name));
+        }
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PureAttack { name: Name, power: u32 }
+
+impl Action<Alignment, Status, Field> for PureAttack {
+    fn name(&self) -> &str { &self.name }
 
     fn description(&self) -> String {
         format!("Attack for exactly {} damage.", self.power)
     }
 
-    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter) -> States {
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, _: &mut Field) -> States {
         let mut logs = Vec::new();
         logs.push(format!("{} used {}.", user.name, self.name));
         if target.state.status.contains_key(&Status::Defend) {
             logs.push(format!("{} blocked {}'s attack", target.name, user.name))
         } else {
-            target.deal_damage(self.power);
+            target.take_hit(self.power, Category::Physical);
         }
         logs
     }
 }
 
 // TODO: i broke the status up into separate structs but it might be easier to manage as a match-like
-#[derive(Clone, Serialize, Deserialize)]
-struct Defend { name: String }
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Defend { name: Name }
 
-impl Action<Alignment, Status> for Defend {
-    fn name(&self) -> String { format!("{}", self.name) }
+impl Action<Alignment, Status, Field> for Defend {
+    fn name(&self) -> &str { &self.name }
 
-    fn description(&self) -> String { format!("Defend against attacks.") }
+    fn description(&self) -> String { "Defend against attacks.".to_string() }
 
     fn priority(&self) -> i32 { 2 }
 
-    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter) -> States {
+    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter, field: &mut Field) -> States {
         let mut logs = Vec::new();
         logs.push(format!("{} is defending.", user.name));
-        user.state.status.entry(Status::Defend).or_insert(0);
+        logs.extend(statuses::apply(Status::Defend, user, 1, field));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bleed { name: Name, power: u32 }
+
+impl Action<Alignment, Status, Field> for Bleed {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        format!("Applies {} bleeding to the enemy.", self.power)
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(statuses::apply(Status::Bleed, target, self.power as i32, field));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stun { name: Name }
+
+impl Action<Alignment, Status, Field> for Stun {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Stuns the enemy.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(statuses::apply(Status::Stun, target, 1, field));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Burn { name: Name }
+
+impl Action<Alignment, Status, Field> for Burn {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Burns the enemy, lowering its attack and hurting it every turn.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(statuses::apply(Status::Burn, target, 1, field));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Freeze { name: Name }
+
+impl Action<Alignment, Status, Field> for Freeze {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Freezes the enemy, which may lose its turn for a few turns.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(statuses::apply(Status::Freeze, target, 1, field));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Confuse { name: Name }
+
+impl Action<Alignment, Status, Field> for Confuse {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Confuses the enemy, which may hurt itself instead of acting for a few turns.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(statuses::apply(Status::Confuse, target, 1, field));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Poison { name: Name }
+
+impl Action<Alignment, Status, Field> for Poison {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Poisons the enemy, hurting it more every turn.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(statuses::apply(Status::Poison, target, 1, field));
+        logs
+    }
+}
+
+// changes the weather for both sides
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SetWeather { name: Name, weather: Weather }
+
+impl Action<Alignment, Status, Field> for SetWeather {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        format!("Brings {:?} for {} turns. {}", self.weather, WEATHER_TURNS, self.weather.description())
+    }
+
+    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(weather::set(field, self.weather, WEATHER_TURNS));
+        logs
+    }
+}
+
+// scatters spikes on the enemy's side, for whatever it sends out next
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LaySpikes { name: Name }
+
+impl Action<Alignment, Status, Field> for LaySpikes {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Hurts every kaizo the enemy sends out. Stacks up to 3 times.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(hazards::lay(field, target));
+        logs
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClearSpikes { name: Name }
+
+impl Action<Alignment, Status, Field> for ClearSpikes {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        "Clears the spikes from your side.".to_string()
+    }
+
+    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        logs.extend(hazards::clear(field, user));
+        logs
+    }
+}
+
+// hits back for double the physical damage the user took this turn, so it waits for everyone else
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Counter { name: Name }
+
+static COUNTER_PRIORITY: i32 = -5;
+static COUNTER_MULTIPLIER: u32 = 2;
+
+impl Action<Alignment, Status, Field> for Counter {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        format!("Hit back for {} times the physical damage taken this turn. Goes last.", COUNTER_MULTIPLIER)
+    }
+
+    fn priority(&self) -> i32 { COUNTER_PRIORITY }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, _: &mut Field) -> States {
+        let mut logs = vec![format!("{} used {}.", user.name, self.name)];
+        if user.state.taken == 0 {
+            logs.push("But it failed.".to_string());
+        } else if target.state.status.contains_key(&Status::Defend) {
+            logs.push(format!("{} blocked {}'s {}.", target.name, user.name, self.name));
+        } else {
+            target.take_hit(user.state.taken * COUNTER_MULTIPLIER, Category::Physical);
+        }
+        logs
+    }
+}
+
+// what happened to a stat after trying to move it `change` stages
+pub(crate) fn stage_log(character: &OnionCharacter, stat: Stat, change: i32, moved: i32) -> String {
+    match moved {
+        0 if change > 0 => format!("{}'s {} won't go any higher.", character.name, stat),
+        0 => format!("{}'s {} won't go any lower.", character.name, stat),
+        1 => format!("{}'s {} rose.", character.name, stat),
+        -1 => format!("{}'s {} fell.", character.name, stat),
+        moved if moved > 0 => format!("{}'s {} rose sharply.", character.name, stat),
+        _ => format!("{}'s {} harshly fell.", character.name, stat),
+    }
+}
+
+fn stages(stages: i32) -> String {
+    if stages == 1 { "1 stage".to_string() } else { format!("{} stages", stages) }
+}
+
+// raises one of the user's stats for the rest of the battle
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Raise { name: Name, stat: Stat, stages: i32 }
+
+impl Action<Alignment, Status, Field> for Raise {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        format!("Raises your {} by {}.", self.stat, stages(self.stages))
+    }
+
+    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter, _: &mut Field) -> States {
+        let moved = user.change_stage(self.stat, self.stages);
+        vec![format!("{} used {}.", user.name, self.name), stage_log(user, self.stat, self.stages, moved)]
+    }
+}
+
+// lowers one of the enemy's stats for the rest of the battle
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Lower { name: Name, stat: Stat, stages: i32 }
+
+impl Action<Alignment, Status, Field> for Lower {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        format!("Lowers the enemy's {} by {}.", self.stat, stages(self.stages))
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, _: &mut Field) -> States {
+        let mut logs = Vec::new();
+        logs.push(format!("{} used {}.", user.name, self.name));
+        if target.state.status.contains_key(&Status::Defend) {
+            logs.push(format!("{} blocked {}'s {}.", target.name, user.name, self.name));
+        } else {
+            let moved = target.change_stage(self.stat, -self.stages);
+            logs.push(stage_log(target, self.stat, -self.stages, moved));
+        }
+        logs
+    }
+}
+
+// an attack that heals the user for `percent` of the damage it deals
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DrainAttack {
+    #[serde(flatten)]
+    attack: Attack,
+    percent: u32,
+}
+
+pub static DEFAULT_DRAIN: u32 = 50;
+
+impl DrainAttack {
+    pub fn new(attack: Attack, percent: u32) -> DrainAttack {
+        DrainAttack { attack, percent }
+    }
+
+    pub fn attack(&self) -> &Attack { &self.attack }
+
+    pub fn percent(&self) -> u32 { self.percent }
+}
+
+impl Action<Alignment, Status, Field> for DrainAttack {
+    fn name(&self) -> &str { &self.attack.name }
+
+    fn description(&self) -> String {
+        format!("{}\nHeals the user for {}% of the damage.", self.attack.description(), self.percent)
+    }
+
+    fn priority(&self) -> i32 { self.attack.priority }
+
+    fn accuracy(&self) -> u32 { self.attack.accuracy }
+
+    fn category(&self) -> Category { self.attack.category }
+
+    fn pp(&self) -> u32 { self.attack.pp }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let health = target.state.health;
+        let mut logs = self.attack.act(user, target, field);
+        let dealt = health - target.state.health;
+        let healed = (dealt * self.percent as i32 / 100).min(user.attributes.stats.health as i32 - user.state.health);
+        if healed > 0 {
+            user.state.health += healed;
+            logs.push(format!("{} drained {}'s health.", user.name, target.name));
+        }
+        logs
+    }
+}
+
+// an attack strong enough to end most battles, that knocks the user out even if it was blocked
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sacrifice {
+    #[serde(flatten)]
+    attack: Attack,
+}
+
+pub static SACRIFICE_POWER: u32 = 250;
+
+impl Sacrifice {
+    pub fn new(attack: Attack) -> Sacrifice {
+        Sacrifice { attack }
+    }
+
+    pub fn attack(&self) -> &Attack { &self.attack }
+}
+
+impl Action<Alignment, Status, Field> for Sacrifice {
+    fn name(&self) -> &str { &self.attack.name }
+
+    fn description(&self) -> String {
+        format!("{}\nThe user faints.", self.attack.description())
+    }
+
+    fn priority(&self) -> i32 { self.attack.priority }
+
+    fn accuracy(&self) -> u32 { self.attack.accuracy }
+
+    fn category(&self) -> Category { self.attack.category }
+
+    fn pp(&self) -> u32 { self.attack.pp }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = self.attack.act(user, target, field);
+        user.state.health = 0;
+        logs.push(format!("{} gave everything it had.", user.name));
+        logs
+    }
+}
+
+// an attack that spends a turn charging up before it goes off; the battle keeps track of which turn
+// it's on, so this only strikes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChargeAttack {
+    #[serde(flatten)]
+    attack: Attack,
+}
+
+impl ChargeAttack {
+    pub fn new(attack: Attack) -> ChargeAttack {
+        ChargeAttack { attack }
+    }
+}
+
+impl Action<Alignment, Status, Field> for ChargeAttack {
+    fn name(&self) -> &str { &self.attack.name }
+
+    fn description(&self) -> String {
+        format!("{}\nTakes a turn to charge up.", self.attack.description())
+    }
+
+    fn priority(&self) -> i32 { self.attack.priority }
+
+    fn accuracy(&self) -> u32 { self.attack.accuracy }
+
+    fn category(&self) -> Category { self.attack.category }
+
+    fn pp(&self) -> u32 { self.attack.pp }
+
+    fn charges(&self) -> bool { true }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        self.attack.act(user, target, field)
+    }
+}
+
+// an attack that leaves the user to recharge for a turn once it lands
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RechargeAttack {
+    #[serde(flatten)]
+    attack: Attack,
+}
+
+impl RechargeAttack {
+    pub fn new(attack: Attack) -> RechargeAttack {
+        RechargeAttack { attack }
+    }
+}
+
+impl Action<Alignment, Status, Field> for RechargeAttack {
+    fn name(&self) -> &str { &self.attack.name }
+
+    fn description(&self) -> String {
+        format!("{}\nThe user has to recharge next turn if it lands.", self.attack.description())
+    }
+
+    fn priority(&self) -> i32 { self.attack.priority }
+
+    fn accuracy(&self) -> u32 { self.attack.accuracy }
+
+    fn category(&self) -> Category { self.attack.category }
+
+    fn pp(&self) -> u32 { self.attack.pp }
+
+    fn recharges(&self) -> bool { true }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        self.attack.act(user, target, field)
+    }
+}
+
+// takes a share of the target's health instead of a set amount, so it's as strong against anything.
+// the share is capped by the field, so one can't take a whole bar at once
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FractionAttack {
+    name: Name,
+    percent: u32,
+    // of the target's full health, rather than what it has left
+    #[serde(default)]
+    of_max: bool,
+}
+
+impl FractionAttack {
+    pub fn new(name: &str, percent: u32, of_max: bool) -> FractionAttack {
+        FractionAttack { name: name.into(), percent, of_max }
+    }
+}
+
+impl Action<Alignment, Status, Field> for FractionAttack {
+    fn name(&self) -> &str { &self.name }
+
+    fn description(&self) -> String {
+        format!("Takes {}% of the enemy's {} health.", self.percent, if self.of_max { "full" } else { "remaining" })
+    }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = vec![format!("{} used {}.", user.name, self.name)];
+        if target.state.status.contains_key(&Status::Defend) {
+            logs.push(format!("{} blocked {}'s {}.", target.name, user.name, self.name));
+        } else {
+            let health = if self.of_max { target.attributes.stats.health } else { target.state.health.max(0) as u32 };
+            target.take_hit(std::cmp::max(1, health * self.percent.min(field.fraction_cap) / 100), Category::Physical);
+        }
         logs
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct Bleed { name: String, power: u32 }
+// knocks the target out in one go when it lands, which is rarely: it hits more often the higher the
+// user's level is over the target's, and never hits a target of a higher level
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OhkoAttack {
+    name: Name,
+    alignment: Alignment,
+    #[serde(default = "ohko_accuracy")]
+    accuracy: u32,
+    #[serde(default = "ohko_pp")]
+    pp: u32,
+}
+
+impl OhkoAttack {
+    pub fn new(name: &str, alignment: Alignment) -> OhkoAttack {
+        OhkoAttack { name: name.into(), alignment, accuracy: OHKO_ACCURACY, pp: OHKO_PP }
+    }
+}
 
-impl Action<Alignment, Status> for Bleed {
-    fn name(&self) -> String { format!("{}", self.name) }
+impl Action<Alignment, Status, Field> for OhkoAttack {
+    fn name(&self) -> &str { &self.name }
 
     fn description(&self) -> String {
-        format!("Applies {} bleeding to the enemy.", self.power)
+        format!(
+            "{:?}-aligned one-hit KO.\n{}% accurate, and more against lower levels. Never hits a higher level.",
+            self.alignment,
+            self.accuracy,
+        )
     }
 
-    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter) -> States {
-        let mut logs = Vec::new();
-        logs.push(format!("{} used {}.", user.name, self.name));
-        if target.state.status.contains_key(&Status::Stun) {
-            logs.push(format!("But {} is stunned.", target.name));
-        } else {
-            target.state.status.entry(Status::Bleed).or_insert(0);
-            target.state.status.entry(Status::Bleed).and_modify(|s| { *s += self.power as i32; });
-            logs.push(format!("{} gained {} bleeding.", target.name, self.power));
+    fn accuracy(&self) -> u32 { self.accuracy }
+
+    fn pp(&self) -> u32 { self.pp }
+
+    fn one_hit_ko(&self) -> bool { true }
+
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field) -> States {
+        let mut logs = vec![format!("{} used {}.", user.name, self.name)];
+        if target.state.status.contains_key(&Status::Defend) {
+            logs.push(format!("{} blocked {}'s {}.", target.name, user.name, self.name));
+        } else if field.clauses.contains(&Clause::Ohko) {
+            logs.push("But it failed!".to_string());
+        } else if field.chart.effectiveness_against(self.alignment, &target.state.alignments()) == 0 {
+            logs.push("It has no effect.".to_string());
+        } else if field.rolls.crit.is_some() {
+            target.take_hit(target.state.health.max(0) as u32, Category::Physical);
+            logs.push("It's a one-hit KO!".to_string());
         }
+        // simulated turns don't roll, and count on it missing since it mostly does
         logs
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-struct Stun { name: String }
+// what a character does once every one of its moves is used up
+struct Struggle;
 
-impl Action<Alignment, Status> for Stun {
-    fn name(&self) -> String { format!("{}", self.name) }
+static STRUGGLE_POWER: u32 = 10;
+// the user takes back this fraction of the damage
+static STRUGGLE_RECOIL: u32 = 4;
+
+impl Action<Alignment, Status, Field> for Struggle {
+    fn name(&self) -> &str { "Struggle" }
 
     fn description(&self) -> String {
-        format!("Stuns the enemy.")
+        format!("Attack for exactly {} damage and take a quarter of it back. Used once there are no moves left.", STRUGGLE_POWER)
     }
 
-    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter) -> States {
+    fn act(&self, user: &mut OnionCharacter, target: &mut OnionCharacter, _: &mut Field) -> States {
         let mut logs = Vec::new();
-        logs.push(format!("{} used {}.", user.name, self.name));
-        if target.state.status.contains_key(&Status::Bleed) {
-            logs.push(format!("But {} is poisoned.", target.name));
+        logs.push(format!("{} has no moves left!", user.name));
+        logs.push(format!("{} used {}.", user.name, self.name()));
+        if target.state.status.contains_key(&Status::Defend) {
+            logs.push(format!("{} blocked {}'s attack", target.name, user.name))
         } else {
-            target.state.status.entry(Status::Stun).or_insert(0);
-            target.state.status.entry(Status::Stun).and_modify(|s| { *s += 1; });
-            logs.push(format!("{} is stunned.", target.name));
+            target.deal_damage(STRUGGLE_POWER);
+            user.deal_damage(STRUGGLE_POWER / STRUGGLE_RECOIL);
+            logs.push(format!("{} was hurt by the recoil.", user.name));
         }
         logs
     }
@@ -224,14 +1166,14 @@ impl Action<Alignment, Status> for Stun {
 
 struct Skip;
 
-impl Action<Alignment, Status> for Skip {
-    fn name(&self) -> String { "Skip".to_string() }
+impl Action<Alignment, Status, Field> for Skip {
+    fn name(&self) -> &str { "Skip" }
 
     fn description(&self) -> String {
         "User skips their next turn.".to_string()
     }
 
-    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter) -> States {
+    fn act(&self, user: &mut OnionCharacter, _: &mut OnionCharacter, _: &mut Field) -> States {
         vec![format!("{} used {}.", user.name, self.name())]
     }
 }
@@ -240,6 +1182,11 @@ impl Action<Alignment, Status> for Skip {
 mod action_tests {
     use super::*;
 
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::core::MAX_STAGE;
+
     fn fake_character_with_health(health: u32) -> OnionCharacter {
         let mut character = testing::fake_character();
         character.attributes.stats.health = health;
@@ -248,12 +1195,7 @@ mod action_tests {
     }
 
     pub fn fake_attack(power: u32) -> Attack {
-        Attack {
-            name: "fake".to_string(),
-            power,
-            alignment: Alignment::Scissors,
-            priority: 0
-        }
+        Attack::new("fake", power, Alignment::Scissors, 0)
     }
 
     // TODO: non-exhaustive cases
@@ -268,56 +1210,228 @@ mod action_tests {
 
         let action = fake_attack(11);
 
-        action.act(&mut user, &mut target);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 98);
+    }
+
+    #[test]
+    fn dual_alignment_test() {
+        let damage = |user: &mut OnionCharacter, target: &mut OnionCharacter| {
+            user.attributes.stats.attack = 50;
+            user.attributes.level = 50;
+            target.attributes.stats.defense = 50;
+            let logs = fake_attack(60).act(user, target, &mut Field::default());
+            (1000 - target.state.health, logs)
+        };
+        let (plain, logs) = damage(&mut testing::fake_character(), &mut fake_character_with_health(1000));
+        assert_eq!(logs[1], "It's not very effective.");
+
+        // scissors is weak against rock but strong against paper, which cancel out
+        let mut target = fake_character_with_health(1000);
+        target.state.secondary = Some(Alignment::Paper);
+        let (neutral, logs) = damage(&mut testing::fake_character(), &mut target);
+        assert_eq!(logs.len(), 1);
+        assert!(neutral > plain);
+
+        // and a secondary alignment gets stab too
+        let mut user = testing::fake_character();
+        user.state.secondary = Some(Alignment::Scissors);
+        let (stab, _) = damage(&mut user, &mut fake_character_with_health(1000));
+        assert!(stab > plain);
+    }
+
+    #[test]
+    fn immunity_test() {
+        let mut user = testing::fake_character();
+        user.attributes.stats.attack = 17;
+        user.attributes.level = 19;
+        let mut target = fake_character_with_health(100);
+        target.attributes.stats.defense = 13;
+
+        let mut rows = vec![vec![10; 3]; 3];
+        rows[Alignment::Scissors as usize][target.state.alignment as usize] = 0;
+        let mut field = Field { chart: Arc::new(TypeChart::from_rows(rows).unwrap()), ..Field::default() };
+        let logs = fake_attack(11).act(&mut user, &mut target, &mut field);
+        assert_eq!(logs[1], "It has no effect.");
+        assert_eq!(target.state.health, 100);
+    }
+
+    #[test]
+    fn special_test() {
+        let mut user = testing::fake_character();
+        user.attributes.stats.attack = 17;
+        user.attributes.stats.special_attack = 40;
+        user.attributes.level = 19;
+        let mut target = fake_character_with_health(100);
+        target.attributes.stats.defense = 13;
+        target.attributes.stats.special_defense = 13;
+
+        // special attacks go off the special stats
+        let action = fake_attack(11).with_category(Category::Special);
+        assert_eq!(action.category(), Category::Special);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 96);
+
+        target.refresh();
+        fake_attack(11).act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 98);
+    }
+
+    #[test]
+    fn crit_test() {
+        let mut user = testing::fake_character();
+        user.attributes.stats.attack = 17;
+        user.attributes.stats.speed = 10;
+        user.attributes.level = 19;
+        let mut target = fake_character_with_health(100);
+        target.attributes.stats.defense = 13;
+        target.attributes.stats.speed = 10;
+        assert_eq!(crit_chance(&user, &target, false), SPEED_CRIT_CHANCE / 2.0);
+        assert_eq!(crit_chance(&user, &target, true), SPEED_CRIT_CHANCE / 2.0 * HIGH_CRIT_FACTOR);
+        target.attributes.stats.speed = 0;
+        assert_eq!(crit_chance(&user, &target, false), SPEED_CRIT_CHANCE);
+
+        let action = fake_attack(11);
+        let mut field = Field { rolls: Rolls { crit: Some(0.0), hits: vec![] }, ..Field::default() };
+        let logs = action.act(&mut user, &mut target, &mut field);
+        assert_eq!(logs, vec!["fake used fake.", "It's not very effective.", "A critical hit!"]);
+        assert_eq!(target.state.health, 97);
+
+        // a bigger multiplier
+        target.refresh();
+        field.crit_multiplier = 30;
+        action.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 96);
+
+        // a miss on the roll, or no roll at all, is a normal hit
+        target.refresh();
+        field.rolls.crit = Some(0.99);
+        assert_eq!(action.act(&mut user, &mut target, &mut field).len(), 2);
+        assert_eq!(target.state.health, 98);
+        target.refresh();
+        field.rolls.crit = None;
+        action.act(&mut user, &mut target, &mut field);
         assert_eq!(target.state.health, 98);
     }
 
+    #[test]
+    fn seeded_crit_test() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let turns = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut user = testing::fake_character();
+            user.attributes.stats.speed = 10;
+            let mut target = fake_character_with_health(1000);
+            target.attributes.stats.defense = 1;
+            let action = fake_attack(11).with_high_crit();
+            (0..50)
+                .flat_map(|_| take_turn(&mut user, &mut target, &mut Field::default(), &action, &mut rng))
+                .filter(|log| log == "A critical hit!")
+                .count()
+        };
+        // the same seed lands the same crits, and a high crit move lands some in 50 turns
+        assert_eq!(turns(3), turns(3));
+        assert!(turns(3) > 0);
+    }
+
     #[test]
     fn pure_attack_test() {
         let mut user = testing::fake_character();
         let mut target = fake_character_with_health(10);
-        let action = PureAttack { name: "fake".to_string(), power: 5 };
+        let action = PureAttack { name: "fake".into(), power: 5 };
 
-        action.act(&mut user, &mut target);
+        action.act(&mut user, &mut target, &mut Field::default());
         assert_eq!(target.state.health, 5);
 
         let mut user = user.clone();
         let mut target = target.clone();
-        action.act(&mut user, &mut target);
+        action.act(&mut user, &mut target, &mut Field::default());
         assert_eq!(target.state.health, 0);
 
         let mut user = user.clone();
         let mut target = fake_character_with_health(4);
-        action.act(&mut user, &mut target);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 0);
+    }
+
+    #[test]
+    fn fraction_attack_test() {
+        let mut user = testing::fake_character();
+        let mut target = fake_character_with_health(100);
+        let mut field = Field::default();
+        let halve = FractionAttack::new("halve", 50, false);
+
+        halve.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 50);
+        halve.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 25);
+
+        // a share of the full bar is the same however much is left
+        let quarter = FractionAttack::new("quarter", 25, true);
+        quarter.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 0);
+
+        // always at least one, and never more than the cap
+        let mut target = fake_character_with_health(1);
+        halve.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 0);
+        let mut target = fake_character_with_health(100);
+        FractionAttack::new("all", 100, true).act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 100 - DEFAULT_FRACTION_CAP as i32);
+        field.fraction_cap = 10;
+        halve.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 45);
+    }
+
+    #[test]
+    fn ohko_test() {
+        let mut user = testing::fake_character();
+        let mut target = fake_character_with_health(100);
+        let mut field = Field::default();
+        let fissure = OhkoAttack::new("fissure", Alignment::Rock);
+
+        // simulated turns count on it missing
+        fissure.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.health, 100);
+        field.rolls.crit = Some(0.5);
+        fissure.act(&mut user, &mut target, &mut field);
         assert_eq!(target.state.health, 0);
+
+        // it fails under the clause
+        let mut target = fake_character_with_health(100);
+        field.clauses = vec![Clause::Ohko];
+        assert_eq!(fissure.act(&mut user, &mut target, &mut field).last().unwrap(), "But it failed!");
+        assert_eq!(target.state.health, 100);
     }
 
     #[test]
     fn defend_test() {
         let mut user = testing::fake_character();
         let mut target = fake_character_with_health(10);
-        let defend = Defend { name: "fake".to_string() };
+        let defend = Defend { name: "fake".into() };
 
-        let attack = PureAttack { name: "fake".to_string(), power: 5 };
+        let attack = PureAttack { name: "fake".into(), power: 5 };
 
-        defend.act(&mut target, &mut user);
-        assert_eq!(target.state.status.contains_key(&Status::Defend), true);
+        defend.act(&mut target, &mut user, &mut Field::default());
+        assert!(target.state.status.contains_key(&Status::Defend));
 
         let mut user = user.clone();
         let mut target = target.clone();
-        attack.act(&mut user, &mut target);
+        attack.act(&mut user, &mut target, &mut Field::default());
 
         assert_eq!(target.state.health, 10);
 
-        let attack = Attack { name: "fake".to_string(), power: 5, alignment: Alignment::Rock, priority: 0 };
+        let attack = Attack::new("fake", 5, Alignment::Rock, 0);
 
         let mut user = user.clone();
         let mut target = target.clone();
-        defend.act(&mut target, &mut user);
+        defend.act(&mut target, &mut user, &mut Field::default());
 
         let mut user = user.clone();
         let mut target = target.clone();
-        attack.act(&mut user, &mut target);
+        attack.act(&mut user, &mut target, &mut Field::default());
 
         assert_eq!(target.state.health, 10);
     }
@@ -326,35 +1440,255 @@ mod action_tests {
     fn stun_test() {
         let mut user = testing::fake_character();
         let mut target = testing::fake_character();
-        let action = Stun { name: "fake".to_string() };
+        let action = Stun { name: "fake".into() };
 
-        action.act(&mut user, &mut target);
-        assert_eq!(target.state.status.contains_key(&Status::Stun), true);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert!(target.state.status.contains_key(&Status::Stun));
         assert_eq!(target.state.status.get(&Status::Stun), Some(&1));
 
         let mut user = user.clone();
         let mut target = target.clone();
-        action.act(&mut user, &mut target);
-        assert_eq!(target.state.status.contains_key(&Status::Stun), true);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert!(target.state.status.contains_key(&Status::Stun));
         assert_eq!(target.state.status.get(&Status::Stun), Some(&2));
     }
 
+    #[test]
+    fn stun_clause_test() {
+        let mut user = testing::fake_character();
+        let mut target = testing::fake_character();
+        let mut field = Field { clauses: vec![Clause::Stun], ..Field::default() };
+        let action = Stun { name: "fake".into() };
+
+        action.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.status.get(&Status::Stun), Some(&1));
+
+        action.act(&mut user, &mut target, &mut field);
+        assert_eq!(target.state.status.get(&Status::Stun), Some(&1));
+    }
+
     #[test]
     fn bleed_test() {
         let mut user = testing::fake_character();
         let mut target = testing::fake_character();
-        let action = Bleed { name: "fake".to_string(), power: 1 };
+        let action = Bleed { name: "fake".into(), power: 1 };
 
-        action.act(&mut user, &mut target);
-        assert_eq!(target.state.status.contains_key(&Status::Bleed), true);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert!(target.state.status.contains_key(&Status::Bleed));
         assert_eq!(target.state.status.get(&Status::Bleed), Some(&1));
 
         let mut user = user.clone();
         let mut target = target.clone();
-        action.act(&mut user, &mut target);
-        assert_eq!(target.state.status.contains_key(&Status::Bleed), true);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert!(target.state.status.contains_key(&Status::Bleed));
+        assert_eq!(target.state.status.get(&Status::Bleed), Some(&2));
+    }
+
+    #[test]
+    fn immune_test() {
+        let mut user = testing::fake_character();
+        let mut target = testing::fake_character();
+        target.state.immune.insert(Status::Bleed, 0);
+        target.state.immune.insert(Status::Stun, 0);
+
+        let logs = Bleed { name: "fake".into(), power: 1 }.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(logs[1], "But fake can't bleed.");
+        let logs = Stun { name: "fake".into() }.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(logs[1], "But fake can't be stunned.");
+        assert!(target.state.status.is_empty());
+    }
+
+    #[test]
+    fn vulnerable_test() {
+        let mut user = testing::fake_character();
+        let mut target = testing::fake_character();
+        target.state.vulnerable.insert(Status::Bleed, 0);
+
+        let logs = Bleed { name: "fake".into(), power: 1 }.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(logs[1], "fake gained 2 bleeding.");
         assert_eq!(target.state.status.get(&Status::Bleed), Some(&2));
     }
+
+    #[test]
+    fn struggle_test() {
+        let mut user = fake_character_with_health(100);
+        let mut target = fake_character_with_health(100);
+        let logs = STRUGGLE.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(logs[0], "fake has no moves left!");
+        assert_eq!(target.state.health, 100 - STRUGGLE_POWER as i32);
+        assert_eq!(user.state.health, 100 - (STRUGGLE_POWER / STRUGGLE_RECOIL) as i32);
+    }
+
+    #[test]
+    fn raise_test() {
+        let mut user = testing::fake_character();
+        let mut target = testing::fake_character();
+        user.attributes.stats.speed = 10;
+        let action = Raise { name: "fake".into(), stat: Stat::Speed, stages: 2 };
+
+        assert_eq!(action.act(&mut user, &mut target, &mut Field::default())[1], "fake's speed rose sharply.");
+        assert_eq!(user.state.stages.speed, 2);
+        assert_eq!(user.stat(Stat::Speed), 20);
+        assert_eq!(user.priority(), 20);
+        // only one stage is left to go
+        user.state.stages.speed = MAX_STAGE - 1;
+        assert_eq!(action.act(&mut user, &mut target, &mut Field::default())[1], "fake's speed rose.");
+        assert_eq!(action.act(&mut user, &mut target, &mut Field::default())[1], "fake's speed won't go any higher.");
+        assert_eq!(user.state.stages.speed, MAX_STAGE);
+
+        user.refresh();
+        assert_eq!(user.state.stages.speed, 0);
+    }
+
+    #[test]
+    fn lower_test() {
+        let mut user = testing::fake_character();
+        user.attributes.stats.attack = 17;
+        user.attributes.level = 19;
+        let mut target = fake_character_with_health(100);
+        target.attributes.stats.defense = 13;
+        let action = Lower { name: "fake".into(), stat: Stat::Attack, stages: 1 };
+
+        assert_eq!(action.act(&mut target, &mut user, &mut Field::default())[1], "fake's attack fell.");
+        assert_eq!(user.state.stages.attack, -1);
+        // 17 attack at -1 is 11, which is no longer more than the target's defense
+        fake_attack(11).act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 100 - 2);
+
+        user.state.status.insert(Status::Defend, 0);
+        action.act(&mut target, &mut user, &mut Field::default());
+        assert_eq!(user.state.stages.attack, -1);
+    }
+
+    #[test]
+    fn rolls_test() {
+        // single hits draw just the crit, like they always have
+        let (mut rng, mut plain) = (StdRng::seed_from_u64(0), StdRng::seed_from_u64(0));
+        assert!(Rolls::roll(&mut rng, 1..=1).hits.is_empty());
+        plain.gen::<f64>();
+        assert_eq!(rng.gen::<u64>(), plain.gen::<u64>());
+
+        for _ in 0..100 {
+            let rolls = Rolls::roll(&mut rng, MIN_HITS..=MAX_HITS);
+            assert!((MIN_HITS as usize - 1..MAX_HITS as usize).contains(&rolls.hits.len()));
+        }
+    }
+
+    #[test]
+    fn multi_hit_test() {
+        let mut user = testing::fake_character();
+        // fast enough to crit at all
+        user.attributes.stats.speed = 10;
+        let action = MultiHitAttack::new(fake_attack(0));
+        // every hit does the flat 2, and only the second crits
+        let mut field = Field { rolls: Rolls { crit: Some(0.99), hits: vec![0.0, 0.99] }, ..Field::default() };
+        let mut target = fake_character_with_health(100);
+        assert_eq!(action.act(&mut user, &mut target, &mut field), vec![
+            "fake used fake.", "It's not very effective.", "A critical hit!", "Hit 3 times!",
+        ]);
+        assert_eq!(target.state.health, 94);
+
+        // hits after the target faints are lost
+        let mut target = fake_character_with_health(100);
+        target.state.health = 3;
+        assert_eq!(action.act(&mut user, &mut target, &mut field).last().unwrap(), "Hit 2 times!");
+        assert_eq!(target.state.health, 0);
+
+        // nothing's left to luck in simulated turns
+        let mut target = fake_character_with_health(100);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 100 - 2 * AVERAGE_HITS as i32);
+
+        // and a block stops every hit
+        target.state.status.insert(Status::Defend, 0);
+        assert_eq!(action.act(&mut user, &mut target, &mut field).len(), 2);
+        assert_eq!(target.state.health, 100 - 2 * AVERAGE_HITS as i32);
+    }
+
+    #[test]
+    fn recoil_test() {
+        let mut user = fake_character_with_health(100);
+        user.attributes.stats.attack = 50;
+        user.attributes.level = 50;
+        let action = RecoilAttack::new(fake_attack(60), DEFAULT_RECOIL);
+        let mut target = fake_character_with_health(1000);
+        target.attributes.stats.defense = 50;
+        let logs = action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(logs.last().unwrap(), "fake was hurt by the recoil.");
+        let dealt = 1000 - target.state.health;
+        assert_eq!(100 - user.state.health, dealt / DEFAULT_RECOIL as i32);
+
+        // only what the target had left counts, but it always costs something
+        let mut target = fake_character_with_health(1000);
+        target.state.health = 1;
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(100 - user.state.health, dealt / DEFAULT_RECOIL as i32 + 1);
+
+        // blocked attacks don't
+        let health = user.state.health;
+        let mut target = fake_character_with_health(1000);
+        target.state.status.insert(Status::Defend, 0);
+        assert_eq!(action.act(&mut user, &mut target, &mut Field::default()).len(), 2);
+        assert_eq!(user.state.health, health);
+    }
+
+    #[test]
+    fn drain_test() {
+        let mut user = fake_character_with_health(100);
+        user.attributes.stats.attack = 50;
+        user.attributes.level = 50;
+        user.state.health = 10;
+        let action = DrainAttack::new(fake_attack(60), DEFAULT_DRAIN);
+        let mut target = fake_character_with_health(1000);
+        target.attributes.stats.defense = 50;
+        let logs = action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(logs.last().unwrap(), "fake drained fake's health.");
+        let dealt = 1000 - target.state.health;
+        assert_eq!(user.state.health, 10 + dealt * DEFAULT_DRAIN as i32 / 100);
+
+        // it can't heal past full
+        user.state.health = 99;
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(user.state.health, 100);
+        assert_eq!(action.act(&mut user, &mut target, &mut Field::default()).len(), 2);
+    }
+
+    #[test]
+    fn counter_test() {
+        let counter = Counter { name: "Counter".into() };
+        let mut user = fake_character_with_health(100);
+        let mut target = fake_character_with_health(100);
+        assert_eq!(counter.act(&mut user, &mut target, &mut Field::default()), vec!["fake used Counter.", "But it failed."]);
+
+        // only physical damage counts
+        fake_attack(0).with_category(Category::Special).act(&mut target, &mut user, &mut Field::default());
+        assert_eq!(user.state.taken, 0);
+        fake_attack(0).act(&mut target, &mut user, &mut Field::default());
+        assert_eq!(user.state.taken, 2);
+        counter.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(target.state.health, 100 - 2 * COUNTER_MULTIPLIER as i32);
+
+        // and only this turn's
+        let mut battle = OnionBattle::new(user, target);
+        battle.end_turn();
+        assert_eq!(battle.player.state.taken, 0);
+    }
+
+    #[test]
+    fn sacrifice_test() {
+        let mut user = fake_character_with_health(100);
+        let action = Sacrifice::new(fake_attack(SACRIFICE_POWER));
+        let mut target = fake_character_with_health(1000);
+        assert_eq!(action.act(&mut user, &mut target, &mut Field::default()).last().unwrap(), "fake gave everything it had.");
+        assert!(target.state.health < 1000);
+        assert_eq!(user.state.health, 0);
+
+        // even when it's blocked
+        let mut user = fake_character_with_health(100);
+        target.state.status.insert(Status::Defend, 0);
+        action.act(&mut user, &mut target, &mut Field::default());
+        assert_eq!(user.state.health, 0);
+    }
 }
 
 // growth functions
@@ -366,12 +1700,8 @@ pub trait Experience<E> {
 
 // TODO: maybe these should be configurable? might have to be part of the species
 static BASE_EXPERIENCE: u32 = 31;
-static GROWTH_FACTOR: u32 = 47;
 
-pub static EXPERIENCE_TO_LEVEL: u32 = 100;
-pub static SCALING_FACTOR: u32 = 100;
-
-impl <A, S: Eq + Hash + PartialEq> Experience<u32> for Character<A, S> {
+impl <A, S: StatusKey> Experience<u32> for Character<A, S> {
     fn experience(&self) -> u32 {
         if self.attributes.level == 0 || self.species.bst == 0 { return 0; }
         let log2u32 = |x| if x > 0 { (x as f64).log(2.0) as u32 } else { 0 };
@@ -383,14 +1713,17 @@ impl <A, S: Eq + Hash + PartialEq> Experience<u32> for Character<A, S> {
     fn gain_experience(&mut self, experience: u32) -> States {
         let mut logs = vec![];
         logs.push(format!("Gained {} experience!", experience));
-        let experience = self.attributes.experience + experience;
-        self.attributes.experience = experience % EXPERIENCE_TO_LEVEL;
-        let levels = experience / EXPERIENCE_TO_LEVEL;
-        self.attributes.level += levels;
-        if levels > 0 {
-            let stats = self.species.stats.scale(SCALING_FACTOR);
-            logs.push(format!("Stats increased by {:?}", stats));
-            self.attributes.stats += stats;
+        let mut experience = self.attributes.experience + experience;
+        let level = self.attributes.level;
+        while experience >= self.experience_to_next_level() {
+            experience -= self.experience_to_next_level();
+            self.attributes.level += 1;
+        }
+        self.attributes.experience = experience;
+        if self.attributes.level > level {
+            let before = self.attributes.stats;
+            self.recalculate_stats();
+            logs.push(format!("Stats increased by {:?}", self.attributes.stats.saturating_sub(&before)));
         }
         logs
     }
@@ -400,6 +1733,8 @@ impl <A, S: Eq + Hash + PartialEq> Experience<u32> for Character<A, S> {
 mod experience_tests {
     use super::*;
 
+    use crate::core::{EXPERIENCE_TO_LEVEL, GROWTH_RATES, SCALING_FACTOR};
+
     #[test]
     fn experience_sanity_test() {
         let mut character = testing::fake_character();
@@ -413,10 +1748,61 @@ mod experience_tests {
 
         // no level
         character.attributes.level = 0;
-        character.species.bst = 1;
+        Arc::make_mut(&mut character.species).bst = 1;
         assert_eq!(character.experience(), 0);
     }
 
+    #[test]
+    fn growth_test() {
+        // 100 times the fourth roots of 3 and 4
+        assert_eq!(GrowthRate::Medium.experience_between(3, 5), 131 + 141);
+        assert!(GROWTH_RATES.iter().all(|growth| growth.experience_to_next_level(1) == EXPERIENCE_TO_LEVEL));
+        // every level takes at least as much as the one before, and more the slower the curve
+        for growth in [GrowthRate::Fast, GrowthRate::Medium, GrowthRate::Slow] {
+            assert!((1..50).all(|level| growth.experience_to_next_level(level) <= growth.experience_to_next_level(level + 1)));
+            assert!(growth.experience_to_next_level(1) < growth.experience_to_next_level(50));
+        }
+        assert!(GrowthRate::Fast.experience_to_next_level(10) < GrowthRate::Medium.experience_to_next_level(10));
+        assert!(GrowthRate::Medium.experience_to_next_level(10) < GrowthRate::Slow.experience_to_next_level(10));
+        assert_eq!(GrowthRate::Erratic.experience_to_next_level(10), GrowthRate::Fast.experience_to_next_level(10));
+        assert_eq!(GrowthRate::Erratic.experience_to_next_level(11), GrowthRate::Slow.experience_to_next_level(11));
+
+        let mut character = testing::fake_character_with_bst(400);
+        Arc::make_mut(&mut character.species).growth = GrowthRate::Slow;
+        set_level(&mut character, 5);
+        assert_eq!(character.attributes.level, 5);
+        let needed = character.experience_to_next_level();
+        assert_eq!(needed, GrowthRate::Slow.experience_to_next_level(5));
+        character.gain_experience(needed - 1);
+        assert_eq!(character.attributes.level, 5);
+        character.gain_experience(1 + GrowthRate::Slow.experience_to_next_level(6) + 50);
+        assert_eq!(character.attributes.level, 7);
+        assert_eq!(character.attributes.experience, 50);
+
+        // levelling it by hand keeps the progress it had toward the next one
+        set_level(&mut character, 9);
+        assert_eq!(character.attributes.level, 9);
+        assert_eq!(character.attributes.experience, 50);
+    }
+
+    #[test]
+    fn recalculate_test() {
+        let mut character = testing::fake_character_with_bst(400);
+        set_level(&mut character, 5);
+        assert_eq!(character.attributes.stats, character.scaled_stats(5 * SCALING_FACTOR));
+
+        // a bonus, like genes, is still there after levelling up
+        let plain = character.scaled_stats(6 * SCALING_FACTOR);
+        character.attributes.bonus.attack = 7;
+        character.gain_experience(character.experience_to_next_level());
+        assert_eq!(character.attributes.level, 6);
+        assert_eq!(character.attributes.stats, Stats { attack: plain.attack + 7, ..plain });
+        // but stats changed by hand aren't
+        character.attributes.stats.attack += 3;
+        character.recalculate_stats();
+        assert_eq!(character.attributes.stats, Stats { attack: plain.attack + 7, ..plain });
+    }
+
     // TODO: make parameterized tests
     // TODO: we should get this from ground truth values
     #[test]
@@ -425,22 +1811,22 @@ mod experience_tests {
 
         character.attributes.level = 1;
 
-        character.species.bst = 100;
+        Arc::make_mut(&mut character.species).bst = 100;
         assert_eq!(character.experience(), 19);
 
-        character.species.bst = 200;
+        Arc::make_mut(&mut character.species).bst = 200;
         assert_eq!(character.experience(), 45);
 
-        character.species.bst = 300;
+        Arc::make_mut(&mut character.species).bst = 300;
         assert_eq!(character.experience(), 77);
 
-        character.species.bst = 400;
+        Arc::make_mut(&mut character.species).bst = 400;
         assert_eq!(character.experience(), 103);
 
-        character.species.bst = 500;
+        Arc::make_mut(&mut character.species).bst = 500;
         assert_eq!(character.experience(), 129);
 
-        character.species.bst = 600;
+        Arc::make_mut(&mut character.species).bst = 600;
         assert_eq!(character.experience(), 174);
     }
 
@@ -448,7 +1834,7 @@ mod experience_tests {
     fn experience_table_test2() {
         let mut character = testing::fake_character();
 
-        character.species.bst = 450;
+        Arc::make_mut(&mut character.species).bst = 450;
 
         character.attributes.level = 1;
         assert_eq!(character.experience(), 116);
@@ -486,34 +1872,10 @@ mod experience_tests {
         assert_eq!(character.attributes.experience, 0);
         // assert_eq!(levels, 1);
 
+        // level 2 takes 118 on the medium curve, and level 3 takes more than what's left
         let _ = character.gain_experience(234);
-        assert_eq!(character.attributes.experience, 34);
-        // assert_eq!(levels, 2);
-    }
-}
-
-pub trait Scale {
-    fn scale(&self, a: u32) -> Stats<u32>;
-}
-
-impl Scale for Stats<f64> {
-    // linearly scales floats to have a total sum equal to some integer; there may be a rounding error
-    fn scale(&self, a: u32) -> Stats<u32> {
-        let x: Vec<f64> = self.into();
-        let z: f64 = x.to_vec().into_iter().sum();
-        x.iter().map(|x| a as f64 * *x / z).map(|x| x as u32).collect::<Vec<u32>>().into()
-    }
-}
-
-impl <A> Scale for Species<A> {
-    fn scale(&self, a: u32) -> Stats<u32> {
-        let growth_factor = a * self.bst / GROWTH_FACTOR;
-        let mut stats: Vec<u32> = self.stats.scale(growth_factor).into();
-        // TODO: randomly correct the stats if they don't add up to the growth factor
-        let growth_factor = (growth_factor - stats.clone().iter().sum::<u32>()) as usize;
-        let n = stats.len();
-        let _ = &thread_rng().sample_iter(Standard).take(growth_factor).for_each(|i: usize| stats[i % n] += 1);
-        return stats.into();
+        assert_eq!(character.attributes.experience, 116);
+        // assert_eq!(levels, 1);
     }
 }
 
@@ -521,148 +1883,367 @@ impl <A> Scale for Species<A> {
 mod scale_tests {
     use super::*;
 
+    use crate::core::Scale;
+
     #[test]
     fn scale_stats_test() {
         let base_stats = testing::fake_stats();
 
         let scaled_stats = testing::fake_stats_with_value(25);
 
-        assert_eq!(base_stats.scale(100), scaled_stats);
+        assert_eq!(base_stats.scale(150), scaled_stats);
 
         let scaled_stats = testing::fake_stats_with_value(560);
 
-        assert_eq!(base_stats.scale(2243), scaled_stats);
-    }
-
-    // TODO: this test doesn't do anything useful
-    #[test]
-    fn scale_species_test() {
-        let species = testing::fake_species_with_bst(400);
-
-        let scaled_stats = Stats {
-            health: 2,
-            attack: 2,
-            defense: 2,
-            speed: 2,
-        };
-
-        assert_eq!(species.scale(1), scaled_stats);
-
-        let species = testing::fake_species_with_bst(450);
-
-        let scaled_stats = Stats {
-            health: 2,
-            attack: 2,
-            defense: 2,
-            speed: 2,
-        };
-
-        assert_ne!(species.scale(1), scaled_stats);
-
-        let species = testing::fake_species_with_bst(550);
-
-        let scaled_stats = Stats {
-            health: 3,
-            attack: 3,
-            defense: 3,
-            speed: 3,
-        };
-
-        assert_ne!(species.scale(1), scaled_stats);
+        assert_eq!(base_stats.scale(3363), scaled_stats);
     }
 }
 
 // battle logic
-fn take_turn(user: &mut OnionCharacter, target: &mut OnionCharacter, action: &dyn Action<Alignment, Status>) -> States {
-    if user.state.status.contains_key(&Status::Stun) {
-        if random::<u32>() % (*user.state.status.get(&Status::Stun).unwrap() as u32 + 1) == 0 {
-            user.state.status.remove(&Status::Stun);
-            let mut logs = Vec::new();
-            logs.push(format!("{} is no longer stunned.", user.name));
-            logs.extend(action.act(user, target));
-            logs
-        } else {
-            vec![format!("{} is stunned.", user.name)]
+// from 0 to 1; the action's accuracy, scaled by the user's accuracy against the target's evasion
+pub fn hit_chance(action: &dyn Action<Alignment, Status, Field>, user: &OnionCharacter, target: &OnionCharacter) -> f64 {
+    // one-hit KOs go by the difference in levels instead, and can't be helped along
+    if action.one_hit_ko() {
+        let (user, target) = (user.attributes.level, target.attributes.level);
+        if target > user {
+            return 0.0;
         }
-    } else if user.state.status.contains_key(&Status::Bleed) {
-        let mut logs = Vec::new();
-        logs.extend(action.act(user, target));
-        user.state.health = std::cmp::max(0, user.state.health - *user.state.status.get(&Status::Bleed).unwrap());
-        logs.push(format!("{} was hurt by bleed.", user.name));
-        logs
-    } else {
-        action.act(user, target)
+        return ((action.accuracy() + user - target) as f64 / BASE_ACCURACY as f64).min(1.0);
     }
+    let chance = action.accuracy() as f64 / BASE_ACCURACY as f64 * user.state.accuracy as f64 / target.state.evasion.max(1) as f64;
+    chance.clamp(0.0, 1.0)
 }
 
-fn clean_up(character: &mut OnionCharacter) {
-    if character.state.status.contains_key(&Status::Defend) {
-        character.state.status.remove(&Status::Defend);
+fn act(user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field, action: &dyn Action<Alignment, Status, Field>, rng: &mut dyn RngCore) -> States {
+    if !rng.gen_bool(hit_chance(action, user, target)) {
+        return vec![format!("{} used {}.", user.name, action.name()), "It missed!".to_string()];
     }
+    user.state.landed = true;
+    field.rolls = Rolls::roll(rng, action.hits());
+    let logs = action.act(user, target, field);
+    field.rolls = Rolls::default();
+    logs
 }
 
-#[derive(Clone)]
+// statuses go off around the action, and can cost the user it. a move that takes two turns spends
+// one of them charging or recharging instead, and losing the turn to a status loses the charge
+fn take_turn(user: &mut OnionCharacter, target: &mut OnionCharacter, field: &mut Field, action: &dyn Action<Alignment, Status, Field>, rng: &mut dyn RngCore) -> States {
+    if user.state.pending == Some(Pending::Recharge) {
+        user.state.pending = None;
+        let mut logs = vec![format!("{} must recharge!", user.name)];
+        logs.extend(statuses::turn_end(user));
+        return logs;
+    }
+    let (acts, mut logs) = statuses::turn_start(user, rng);
+    match user.state.pending.take() {
+        Some(Pending::Charging(action)) if acts => {
+            logs.push(format!("{} is charging up!", user.name));
+            user.state.pending = Some(Pending::Charged(action));
+        },
+        _ if acts => {
+            logs.extend(act(user, target, field, action, rng));
+            if action.recharges() && user.state.landed {
+                user.state.pending = Some(Pending::Recharge);
+            }
+        },
+        _ => (),
+    }
+    logs.extend(statuses::turn_end(user));
+    logs
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OnionBattleState {
     Defeat,
     InProcess,
     Victory,
+    // the enemy got away, so nobody won
+    Escaped,
+    // the player ran from the battle
+    Fled,
+    // both sides went down on the same turn
+    Draw,
 }
 
+/// A one-on-one battle, driven a turn at a time by whoever owns it.
+///
+/// Each turn both sides act in priority order and then the turn is ended, which reports whether the
+/// battle is over:
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand::rngs::StdRng;
+///
+/// use kaizo_quest::core::{Character, GrowthRate, Rarity, Species, Stats};
+/// use kaizo_quest::onion::{ActionPool, Alignment, Attack, OnionBattle, OnionBattleState, set_level};
+///
+/// let actions = ActionPool::with_attacks(vec![Attack::new("Tackle", 40, Alignment::Rock, 0)]);
+/// let species = Species {
+///     name: "Pebble".into(),
+///     bst: 300,
+///     stats: Stats::from_values(0.25, 0.25, 0.25, 0.25, 0.25, 0.25),
+///     alignment: Alignment::Rock,
+///     secondary: None,
+///     learnset: Vec::new(),
+///     ability: None,
+///     rarity: Rarity::Common,
+///     growth: GrowthRate::Medium,
+/// };
+/// let mut player = Character::from_species_and_actions(species.clone(), vec![0]);
+/// let mut enemy = Character::from_species_and_actions(species, vec![0]);
+/// set_level(&mut player, 20);
+/// set_level(&mut enemy, 5);
+///
+/// let mut battle = OnionBattle::new(player, enemy);
+/// let mut rng = StdRng::seed_from_u64(0);
+/// let state = loop {
+///     battle.player_turn(&actions[0], &mut rng);
+///     battle.enemy_turn(&actions[0], &mut rng);
+///     match battle.end_turn() {
+///         (OnionBattleState::InProcess, _) => continue,
+///         (state, _) => break state,
+///     }
+/// };
+/// assert_eq!(state, OnionBattleState::Victory);
+/// ```
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OnionBattle {
     pub player: OnionCharacter,
     pub enemy: OnionCharacter,
+    pub field: Field,
+    // actions the player's kaizo reached the level for by winning, that it doesn't know yet
+    #[serde(default)]
+    pub learnable: Actions,
+    // how many times over the win's experience is given, e.g. for a risk taken
+    #[serde(default = "default_experience_multiplier")]
+    pub(crate) experience_multiplier: u32,
+    // how many turns have been played, counting from 0
+    #[serde(default)]
+    pub(crate) turn: u32,
+    // layers of spikes on each side, hurting whoever is sent out there
+    #[serde(default)]
+    pub(crate) player_spikes: u32,
+    #[serde(default)]
+    pub(crate) enemy_spikes: u32,
+    // who the enemy is, for the hooks the battle is played with
+    #[serde(default)]
+    pub kind: EncounterKind,
+    #[serde(default)]
+    pub(crate) escaped: bool,
+    #[serde(default)]
+    pub(crate) fled: bool,
+    // a boss's phases that haven't started yet, highest health first
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+    // the highest level the player's kaizo can reach by winning, if the run has one
+    #[serde(default)]
+    pub level_cap: Option<u32>,
+    // the experience the win was worth, and how much of it the cap kept the player's kaizo from
+    // gaining, over every kaizo beaten
+    #[serde(default)]
+    pub(crate) earned: u32,
+    #[serde(default)]
+    pub(crate) overflow: u32,
 }
 
+fn default_experience_multiplier() -> u32 { 1 }
+
 // TODO: this is better but is still messy
 impl OnionBattle {
-    fn battle_state(&self) -> OnionBattleState {
-        if self.player.state.health == 0 {
-            return OnionBattleState::Defeat
+    pub fn new(mut player: OnionCharacter, mut enemy: OnionCharacter) -> OnionBattle {
+        // nobody carries a half-finished move in from another battle
+        player.state.pending = None;
+        enemy.state.pending = None;
+        OnionBattle { player, enemy, field: Field::default(), learnable: Vec::new(), experience_multiplier: 1, turn: 0, player_spikes: 0, enemy_spikes: 0, kind: EncounterKind::default(), escaped: false, fled: false, phases: Vec::new(), level_cap: None, earned: 0, overflow: 0 }
+    }
+
+    pub fn turn(&self) -> u32 { self.turn }
+
+    pub fn earned(&self) -> u32 { self.earned }
+
+    pub fn overflow(&self) -> u32 { self.overflow }
+
+    // layers of spikes on the player's side, then the enemy's
+    pub fn spikes(&self) -> (u32, u32) { (self.player_spikes, self.enemy_spikes) }
+
+    pub fn battle_state(&self) -> OnionBattleState {
+        if self.player.state.health == 0 && self.enemy.state.health == 0 {
+            OnionBattleState::Draw
+        } else if self.player.state.health == 0 {
+            OnionBattleState::Defeat
         } else if self.enemy.state.health == 0 {
-            return OnionBattleState::Victory
+            OnionBattleState::Victory
+        } else if self.escaped {
+            OnionBattleState::Escaped
+        } else if self.fled {
+            OnionBattleState::Fled
         } else {
-            return OnionBattleState::InProcess
+            OnionBattleState::InProcess
         }
     }
 
     fn clean_up(&mut self) {
-        clean_up(&mut self.player);
-        clean_up(&mut self.enemy);
+        statuses::expire(&mut self.player);
+        statuses::expire(&mut self.enemy);
+    }
+
+    // runs both sides' abilities before the first turn
+    pub fn start(&mut self) -> States {
+        let mut logs = Vec::new();
+        if let Some(ability) = self.field.ability(&self.player).cloned() {
+            logs.extend(ability.on_battle_start(&mut self.player, &mut self.enemy, &mut self.field));
+        }
+        if let Some(ability) = self.field.ability(&self.enemy).cloned() {
+            logs.extend(ability.on_battle_start(&mut self.enemy, &mut self.player, &mut self.field));
+        }
+        logs
+    }
+
+    fn end_abilities(&mut self) -> States {
+        let mut logs = Vec::new();
+        if let Some(ability) = self.field.ability(&self.player).cloned() {
+            logs.extend(ability.on_end_turn(&mut self.player, &mut self.field));
+        }
+        if let Some(ability) = self.field.ability(&self.enemy).cloned() {
+            logs.extend(ability.on_end_turn(&mut self.enemy, &mut self.field));
+        }
+        logs
+    }
+
+    // what the character's ability adds to its actions' priority this turn
+    fn ability_priority(&self, character: &OnionCharacter) -> i32 {
+        self.field.ability(character).map_or(0, |ability| ability.priority(self.turn))
+    }
+
+    // determine action order:
+    //  - highest priority wins, counting what abilities add
+    //  - if a priority tie, highest speed wins
+    //  - if a speed tie, flip a coin
+    pub fn player_first(&self, player_action: &dyn Action<Alignment, Status, Field>, enemy_action: &dyn Action<Alignment, Status, Field>, rng: &mut dyn RngCore) -> bool {
+        let player_priority = player_action.priority() + self.ability_priority(&self.player);
+        let enemy_priority = enemy_action.priority() + self.ability_priority(&self.enemy);
+        player_priority > enemy_priority ||
+            (player_priority == enemy_priority &&
+                (self.player.priority() > self.enemy.priority() ||
+                    (self.player.priority() == self.enemy.priority() && rng.gen::<bool>())))
+    }
+
+    pub fn player_turn(&mut self, action: &dyn Action<Alignment, Status, Field>, rng: &mut dyn RngCore) -> States {
+        self.player.state.landed = false;
+        let state = self.battle_state();
+        if let OnionBattleState::InProcess = state {
+            self.field.spikes = Spikes { user: self.player_spikes, target: self.enemy_spikes };
+            let logs = take_turn(&mut self.player, &mut self.enemy, &mut self.field, action, rng);
+            (self.player_spikes, self.enemy_spikes) = (self.field.spikes.user, self.field.spikes.target);
+            self.field.spikes = Spikes::default();
+            logs
+        } else { vec![] }
+    }
+
+    pub fn enemy_turn(&mut self, action: &dyn Action<Alignment, Status, Field>, rng: &mut dyn RngCore) -> States {
+        self.enemy.state.landed = false;
+        let state = self.battle_state();
+        if let OnionBattleState::InProcess = state {
+            self.field.spikes = Spikes { user: self.enemy_spikes, target: self.player_spikes };
+            let logs = take_turn(&mut self.enemy, &mut self.player, &mut self.field, action, rng);
+            (self.enemy_spikes, self.player_spikes) = (self.field.spikes.user, self.field.spikes.target);
+            self.field.spikes = Spikes::default();
+            logs
+        } else { vec![] }
+    }
+
+    // gives up the battle in place of the player's move. nobody acts after, and the next `end_turn`
+    // reports it
+    pub fn flee(&mut self) -> States {
+        if self.battle_state() != OnionBattleState::InProcess {
+            return vec![];
+        }
+        self.fled = true;
+        vec![format!("{} fled from {}.", self.player.name, self.enemy.name)]
+    }
+
+    // plays out both sides' actions in order. a player that gave up its action this turn, e.g. to
+    // use an item, has none, and only the enemy acts
+    pub fn resolve_turn(&mut self, player_action: Option<&dyn Action<Alignment, Status, Field>>, enemy_action: &dyn Action<Alignment, Status, Field>, rng: &mut dyn RngCore) -> States {
+        let mut logs = Vec::new();
+        match player_action {
+            Some(player_action) if self.player_first(player_action, enemy_action, rng) => {
+                logs.extend(self.player_turn(player_action, rng));
+                logs.extend(self.enemy_turn(enemy_action, rng));
+            },
+            Some(player_action) => {
+                logs.extend(self.enemy_turn(enemy_action, rng));
+                logs.extend(self.player_turn(player_action, rng));
+            },
+            None => logs.extend(self.enemy_turn(enemy_action, rng)),
+        }
+        logs
     }
 
-    pub fn player_turn(&mut self, action: &dyn Action<Alignment, Status>) -> States {
-        let state = self.battle_state();
-        if let OnionBattleState::InProcess = state {
-            take_turn(&mut self.player, &mut self.enemy, action)
-        } else { vec![] }
+    // sends in the player's next kaizo, onto whatever spikes are on its side
+    pub fn switch_in(&mut self, mut character: OnionCharacter) -> States {
+        let logs = hazards::switch_in(self.player_spikes, &mut character);
+        self.player = character;
+        logs
     }
 
-    pub fn enemy_turn(&mut self, action: &dyn Action<Alignment, Status>) -> States {
-        let state = self.battle_state();
-        if let OnionBattleState::InProcess = state {
-            take_turn(&mut self.enemy, &mut self.player, action)
-        } else { vec![] }
+    // sends in the enemy's next kaizo, onto whatever spikes are on its side
+    pub fn switch_in_enemy(&mut self, mut character: OnionCharacter) -> States {
+        let logs = hazards::switch_in(self.enemy_spikes, &mut character);
+        self.enemy = character;
+        logs
     }
 
     pub fn end_turn(&mut self) -> (OnionBattleState, States) {
         let mut logs = Vec::new();
-        let state = match self.battle_state() {
+        // counters only pay back this turn's damage
+        self.player.state.taken = 0;
+        self.enemy.state.taken = 0;
+        // before the battle is checked, so weather can finish either side off
+        if self.battle_state() == OnionBattleState::InProcess {
+            logs.extend(weather::end_turn(&mut self.field, &mut self.player, &mut self.enemy));
+        }
+        let state = self.battle_state();
+        if matches!(state, OnionBattleState::Victory | OnionBattleState::Defeat | OnionBattleState::Draw) {
+            self.player.attributes.battles += 1;
+            self.enemy.attributes.battles += 1;
+        }
+        let state = match state {
             OnionBattleState::Victory => {
                 // award xp
                 logs.push(format!("Defeated {}!", self.enemy.name));
-                let experience: u32 = self.enemy.experience() / self.player.attributes.level;
-                logs.extend(self.player.gain_experience(experience));
+                logs.extend(effort::train(&mut self.player, &self.enemy));
+                let experience: u32 = self.enemy.experience() / self.player.attributes.level * self.experience_multiplier;
+                let level = self.player.attributes.level;
+                let (gained, overflow) = gain_capped(&mut self.player, experience, self.level_cap);
+                logs.extend(gained);
+                self.earned += experience;
+                self.overflow += overflow;
+                let known = &self.player.attributes.actions;
+                // a trainer's team can be beaten one kaizo at a time, each adding to what can be learned
+                let learnable: Actions = self.player.species.learned_between(level, self.player.attributes.level)
+                    .into_iter()
+                    .filter(|action| !known.contains(action))
+                    .collect();
+                self.learnable.extend(learnable);
                 OnionBattleState::Victory
             },
             OnionBattleState::Defeat => {
                 logs.push(format!("{} died!", self.player.name));
                 OnionBattleState::Defeat
             },
-            _ => {
+            OnionBattleState::Draw => {
+                logs.push(format!("{} and {} went down together!", self.player.name, self.enemy.name));
+                OnionBattleState::Draw
+            },
+            OnionBattleState::InProcess => {
                 self.clean_up();
+                logs.extend(self.end_abilities());
+                self.turn += 1;
                 OnionBattleState::InProcess
-            }
+            },
+            // whoever left already said so
+            state => state,
         };
         (state, logs)
     }
@@ -672,23 +2253,213 @@ impl OnionBattle {
 mod battle_tests {
     use super::*;
 
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
     fn fake_character(level: u32) -> OnionCharacter {
         let mut character = testing::fake_character_with_bst(400);
-        character.attributes.level = level;
-        character.attributes.stats = character.species.stats.scale(10 * level);
-        character.refresh();
+        set_level(&mut character, level);
         character
     }
 
     // TODO: this does nothing; exercise all cases
     #[test]
     fn battle_test() {
-        let mut battle = OnionBattle { player: fake_character(5), enemy: fake_character(5) };
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+
+        let action = action_tests::fake_attack(30);
+        let logs = battle.player_turn(&action, &mut StdRng::seed_from_u64(0));
+
+        assert!(!logs.contains(&"A critical hit!".to_string()));
+        assert_eq!(battle.enemy.state.health, 80);
+    }
+
+    #[test]
+    fn ability_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let action = action_tests::fake_attack(30);
+        // quick start, out of the default abilities
+        let mut player = fake_character(5);
+        Arc::make_mut(&mut player.species).ability = Some(1);
+        player.attributes.stats.speed = 0;
+        let mut battle = OnionBattle::new(player, fake_character(5));
+        assert!(battle.player_first(&action, &action, &mut rng));
+        battle.end_turn();
+        assert!(!battle.player_first(&action, &action, &mut rng));
+
+        // thick skin only resists rock attacks
+        let mut thick_skin = fake_character(5);
+        Arc::make_mut(&mut thick_skin.species).ability = Some(0);
+        let health = |enemy: &OnionCharacter, action: &Attack| {
+            let mut battle = OnionBattle::new(fake_character(5), enemy.clone());
+            battle.player_turn(action, &mut StdRng::seed_from_u64(0));
+            battle.enemy.state.health
+        };
+        assert_eq!(health(&thick_skin, &action), 80);
+        let rock = Attack::new("fake", 30, Alignment::Rock, 0);
+        assert!(health(&thick_skin, &rock) > health(&fake_character(5), &rock));
+    }
+
+    #[test]
+    fn resolve_turn_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let tackle = action_tests::fake_attack(30);
+        let quick = Attack::new("quick", 30, Alignment::Scissors, 1);
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.enemy.name = "enemy".into();
+
+        // priority goes first whatever the speeds
+        battle.player.attributes.stats.speed = 0;
+        let logs = battle.resolve_turn(Some(&quick), &tackle, &mut rng);
+        assert_eq!(logs[0], "fake used quick.");
+        // then speed
+        let logs = battle.resolve_turn(Some(&tackle), &tackle, &mut rng);
+        assert_eq!(logs[0], "enemy used fake.");
+        // a player that used its turn up doesn't act
+        let logs = battle.resolve_turn(None, &tackle, &mut rng);
+        assert!(logs.iter().all(|log| !log.starts_with("fake used")));
+        assert_eq!(logs[0], "enemy used fake.");
+
+        // nobody acts once it's over
+        battle.enemy.state.health = 0;
+        assert!(battle.resolve_turn(Some(&tackle), &tackle, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn two_turn_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut pool = ActionPool::with_attacks(vec![action_tests::fake_attack(30)]);
+        let beam = pool.actions.register(OnionAction::Charge(ChargeAttack::new(Attack::new("Beam", 30, Alignment::Scissors, 0))));
+        let blast = pool.actions.register(OnionAction::Recharge(RechargeAttack::new(Attack::new("Blast", 30, Alignment::Scissors, 0))));
+        let tackle = 0;
+        assert_eq!((pool.kind(beam), pool.kind(blast)), (ActionKind::Charge, ActionKind::Recharge));
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.player.attributes.actions = vec![tackle, beam, blast];
+
+        // the first turn goes on charging, and the second strikes whatever was picked for it
+        let action = pool.spend(&mut battle.player, beam);
+        assert_eq!(battle.player_turn(action, &mut rng), vec!["fake is charging up!"]);
+        assert_eq!(battle.enemy.state.health, battle.enemy.attributes.stats.health as i32);
+        let action = pool.spend(&mut battle.player, tackle);
+        assert_eq!(action.name(), "Beam");
+        battle.player_turn(action, &mut rng);
+        assert!(battle.enemy.state.health < battle.enemy.attributes.stats.health as i32);
+        assert_eq!(battle.player.state.pending, None);
+        // for one use
+        assert_eq!(battle.player.pp_left(beam, DEFAULT_PP), DEFAULT_PP - 1);
+        assert_eq!(battle.player.pp_left(tackle, DEFAULT_PP), DEFAULT_PP);
+
+        // recharging costs the turn after
+        let action = pool.spend(&mut battle.player, blast);
+        battle.player_turn(action, &mut rng);
+        assert_eq!(battle.player.state.pending, Some(Pending::Recharge));
+        let action = pool.spend(&mut battle.player, tackle);
+        assert_eq!(battle.player_turn(action, &mut rng), vec!["fake must recharge!"]);
+        assert_eq!(battle.player.state.pending, None);
+
+        // and a charge is lost with the turn
+        battle.player.state.status.insert(Status::Freeze, 0);
+        let logs = (0..100).map(|_| {
+            battle.player.state.pending = Some(Pending::Charging(beam));
+            battle.player_turn(&pool[beam], &mut rng)
+        }).find(|logs| logs.contains(&"fake is frozen solid.".to_string())).unwrap();
+        assert!(!logs.contains(&"fake is charging up!".to_string()));
+        assert_eq!(battle.player.state.pending, None);
+    }
+
+    #[test]
+    fn learnable_test() {
+        let mut player = fake_character(5);
+        Arc::make_mut(&mut player.species).learnset = vec![(6, 3), (6, 0), (50, 4)];
+        player.attributes.actions = vec![0];
+        let mut battle = OnionBattle::new(player, fake_character(50));
+        battle.enemy.state.health = 0;
+
+        assert_eq!(battle.end_turn().0, OnionBattleState::Victory);
+        assert_eq!(battle.player.attributes.level, 6);
+        // moves it already knows aren't learned again
+        assert_eq!(battle.learnable, vec![3]);
+    }
+
+    #[test]
+    fn draw_test() {
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.player.state.health = 0;
+        battle.enemy.state.health = 0;
+        let turn = battle.turn;
+        assert_eq!(battle.end_turn(), (OnionBattleState::Draw, vec!["fake and fake went down together!".to_string()]));
+        // nobody wins, so nobody learns anything
+        assert_eq!(battle.player.attributes.level, 5);
+        assert_eq!(battle.turn, turn);
+    }
 
+    #[test]
+    fn flee_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        assert_eq!(battle.flee(), vec!["fake fled from fake."]);
+        // once it's run nobody gets to act
+        assert!(battle.enemy_turn(&action_tests::fake_attack(30), &mut rng).is_empty());
+        assert_eq!(battle.end_turn(), (OnionBattleState::Fled, vec![]));
+        assert!(battle.flee().is_empty());
+
+        // a battle that's already been decided can't be run from
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.enemy.state.health = 0;
+        assert!(battle.flee().is_empty());
+        assert_eq!(battle.end_turn().0, OnionBattleState::Victory);
+    }
+
+    #[test]
+    fn hit_chance_test() {
+        let mut user = fake_character(5);
+        let mut target = fake_character(5);
         let action = action_tests::fake_attack(30);
-        battle.player_turn(&action);
+        assert_eq!(hit_chance(&action, &user, &target), 1.0);
+        let wild = action_tests::fake_attack(30).with_accuracy(50);
+        assert_eq!(hit_chance(&wild, &user, &target), 0.5);
+
+        target.state.evasion = 200;
+        assert_eq!(hit_chance(&action, &user, &target), 0.5);
+        user.state.accuracy = 150;
+        assert_eq!(hit_chance(&wild, &user, &target), 0.375);
+        // anything over 100% always hits
+        user.state.accuracy = 1000;
+        assert_eq!(hit_chance(&action, &user, &target), 1.0);
+
+        target.refresh();
+        assert_eq!(target.state.evasion, BASE_ACCURACY);
+
+        // one-hit KOs only go by the levels
+        let ohko = OhkoAttack::new("fissure", Alignment::Rock);
+        assert_eq!(hit_chance(&ohko, &user, &target), 0.3);
+        let target = fake_character(3);
+        assert_eq!(hit_chance(&ohko, &user, &target), 0.32);
+        assert_eq!(hit_chance(&ohko, &fake_character(200), &target), 1.0);
+        assert_eq!(hit_chance(&ohko, &target, &user), 0.0);
+    }
+
+    #[test]
+    fn miss_test() {
+        let action = action_tests::fake_attack(30).with_accuracy(50);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut misses = 0;
+        for _ in 0..100 {
+            let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+            let logs = battle.player_turn(&action, &mut rng);
+            if logs.contains(&"It missed!".to_string()) {
+                assert_eq!(logs[0], "fake used fake.");
+                assert_eq!(battle.enemy.state.health, battle.enemy.attributes.stats.health as i32);
+                misses += 1;
+            } else {
+                assert!(battle.enemy.state.health < battle.enemy.attributes.stats.health as i32);
+            }
+        }
+        assert!((30..70).contains(&misses));
 
-        assert_eq!(battle.enemy.state.health, 9);
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.player.state.accuracy = 0;
+        assert_eq!(battle.player_turn(&action_tests::fake_attack(30), &mut rng)[1], "It missed!");
     }
 }
 
@@ -696,7 +2467,7 @@ mod battle_tests {
 // TODO: figure out how to implement sample_iter?
 impl Distribution<Stats<f64>> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Stats<f64> {
-        let x = self.sample_iter(rng).take(4).collect::<Vec<f64>>();
+        let x = self.sample_iter(rng).take(6).collect::<Vec<f64>>();
         let z: f64 = x.iter().sum();
         x.iter().map(|x| x / z).collect::<Vec<f64>>().into()
     }
@@ -712,9 +2483,34 @@ impl Distribution<Alignment> for Standard {
     }
 }
 
-// TODO: this is only generatable through rust. we want to define this stuff externally
+// random worlds; hand-made ones are loaded through `content`
 static WORST_BST: u32 = 200u32;
 static BEST_BST: u32 = 700u32;
+// the lowest bst of each rarity above common, and what its species' names start with, so the
+// strongest few of a world stand out
+static RARITIES: [(Rarity, u32, &str); 3] = [
+    (Rarity::Uncommon, 450, ""),
+    (Rarity::Rare, 580, "Elder "),
+    (Rarity::Legendary, 660, "Mythic "),
+];
+
+pub fn rarity_of(bst: u32) -> Rarity {
+    RARITIES.iter().rev().find(|(_, lowest, _)| bst >= *lowest).map_or(Rarity::Common, |(rarity, _, _)| *rarity)
+}
+
+// the stronger the species, the longer it takes to grow; rare ones grow in fits and starts
+fn growth_of(rarity: Rarity) -> GrowthRate {
+    match rarity {
+        Rarity::Common => GrowthRate::Fast,
+        Rarity::Uncommon => GrowthRate::Medium,
+        Rarity::Rare => GrowthRate::Erratic,
+        Rarity::Legendary => GrowthRate::Slow,
+    }
+}
+
+fn rarity_prefix(rarity: Rarity) -> &'static str {
+    RARITIES.iter().find(|(other, _, _)| *other == rarity).map_or("", |(_, _, prefix)| prefix)
+}
 
 #[derive(Debug)]
 enum OnionName {
@@ -739,14 +2535,33 @@ impl Distribution<OnionName> for Standard {
     }
 }
 
+// one in this many species has a second alignment
+static DUAL_ALIGNMENT_CHANCE: u32 = 4;
+
+fn sample_secondary<R: Rng + ?Sized>(alignment: Alignment, rng: &mut R) -> Option<Alignment> {
+    if !rng.gen_ratio(1, DUAL_ALIGNMENT_CHANCE) {
+        return None;
+    }
+    // a species can't be the same alignment twice, and random worlds only have the first three
+    let others: Vec<Alignment> = ALIGNMENTS[..3].iter().copied().filter(|other| *other != alignment).collect();
+    Some(others[rng.gen_range(0..others.len())])
+}
+
 impl Distribution<Species<Alignment>> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Species<Alignment> {
         let alignment = self.sample(rng);
         let suffix: OnionName = self.sample(rng);
+        let bst = rng.gen_range(WORST_BST..BEST_BST);
+        let rarity = rarity_of(bst);
         Species {
-            name: format!("{:?} {:?}", alignment, suffix), // TODO: generate species name
-            bst: rng.gen_range(WORST_BST..BEST_BST),
+            name: format!("{}{:?} {:?}", rarity_prefix(rarity), alignment, suffix).into(), // TODO: generate species name
+            bst,
             stats: self.sample(rng),
+            learnset: Vec::new(),
+            secondary: sample_secondary(alignment, rng),
+            ability: None,
+            rarity,
+            growth: growth_of(rarity),
             alignment,
         }
     }
@@ -791,128 +2606,572 @@ impl Distribution<AttackName> for Standard {
     }
 }
 
+#[derive(Debug)]
+enum SpecialAttackName {
+    Beam,
+    Wave,
+    Pulse,
+    Ray,
+    Spark,
+    Storm,
+}
+
+impl Distribution<SpecialAttackName> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SpecialAttackName {
+        match rng.gen_range(0..6) {
+            0 => SpecialAttackName::Beam,
+            1 => SpecialAttackName::Wave,
+            2 => SpecialAttackName::Pulse,
+            3 => SpecialAttackName::Ray,
+            4 => SpecialAttackName::Spark,
+            _ => SpecialAttackName::Storm,
+        }
+    }
+}
+
+impl Distribution<Category> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Category {
+        if rng.gen() { Category::Physical } else { Category::Special }
+    }
+}
+
 static WORST_ATTACK: u32 = 10u32;
+// how likely each generated attack is to come again as each of the variants, by weight
+static VARIANT_WEIGHTS: [(Option<ActionKind>, u32); 7] = [
+    (None, 16),
+    (Some(ActionKind::MultiHit), 2),
+    (Some(ActionKind::Recoil), 2),
+    (Some(ActionKind::Drain), 2),
+    (Some(ActionKind::Sacrifice), 1),
+    (Some(ActionKind::Charge), 2),
+    (Some(ActionKind::Recharge), 1),
+];
+// the actions that take a share of the target's health, each only in some worlds
+static FRACTIONS: [(&str, u32, bool); 2] = [("Gnaw", 50, false), ("Wither", 25, true)];
+static FRACTION_CHANCE: u32 = 3;
+// the one-hit KOs, which are rarer still
+static OHKOS: [(&str, Alignment); 2] = [("Fissure", Alignment::Rock), ("Sheer Cold", Alignment::Ice)];
+static OHKO_CHANCE: u32 = 8;
 static BEST_ATTACK: u32 = 150u32;
 static PRIORITY_MOVE_CHANCE: i32 = 4i32;
+static HIGH_CRIT_MOVE_CHANCE: u32 = 8u32;
+static ACCURATE_ATTACK: u32 = 100u32;
+static WORST_ACCURACY: u32 = 70u32;
+// stronger attacks can be used fewer times
+static STRONG_ATTACK: u32 = 60u32;
+static STRONG_ATTACK_PP: u32 = 10u32;
+static STRONGEST_ATTACK_PP: u32 = 5u32;
 
 impl Distribution<Attack> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Attack {
         let alignment: Alignment = self.sample(rng);
-        let suffix: AttackName = self.sample(rng);
+        let category: Category = self.sample(rng);
+        let suffix = match category {
+            Category::Physical => format!("{:?}", rng.gen::<AttackName>()),
+            Category::Special => format!("{:?}", rng.gen::<SpecialAttackName>()),
+        };
+        let power = rng.gen_range(WORST_ATTACK..BEST_ATTACK);
         Attack {
-            name: format!("{:?} {:?}", alignment, suffix),
-            power: rng.gen_range(WORST_ATTACK..BEST_ATTACK),
+            name: format!("{:?} {}", alignment, suffix).into(),
+            power,
             alignment,
             priority: rng.gen::<i32>() % PRIORITY_MOVE_CHANCE / PRIORITY_MOVE_CHANCE,
+            high_crit: rng.gen_ratio(1, HIGH_CRIT_MOVE_CHANCE),
+            // strong attacks trade some accuracy for their power
+            accuracy: if power > ACCURATE_ATTACK { rng.gen_range(WORST_ACCURACY..=BASE_ACCURACY) } else { BASE_ACCURACY },
+            category,
+            pp: if power > ACCURATE_ATTACK {
+                STRONGEST_ATTACK_PP
+            } else if power > STRONG_ATTACK {
+                STRONG_ATTACK_PP
+            } else {
+                DEFAULT_PP
+            },
         }
     }
 }
 
 // TODO: do we need any tests?
 
-// TODO: this is a stupid hack since the actions for characters are usize
 static SKIP: Skip = Skip;
+static STRUGGLE: Struggle = Struggle;
+
+// the id that always means skipping the turn, e.g. for a side that has nothing to pick
+pub static SKIP_ID: ActionId = ActionId::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ActionKind { Attack, PureAttack, Defend, Bleed, Stun, Raise, Lower, Burn, Freeze, Confuse, Poison, Weather, Spikes, ClearSpikes, Counter, MultiHit, Recoil, Drain, Sacrifice, Charge, Recharge, Fraction, Ohko, Skip }
+
+// what the actions of a pool are taken as when they aren't being used
+type DynAction = dyn Action<Alignment, Status, Field>;
+
+// any action a pool can hold, described by its kind, e.g. `{ "kind": "Stun", "name": "Yawn" }`, the
+// same way in saves and in content files
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum OnionAction {
+    Attack(Attack),
+    PureAttack(PureAttack),
+    Defend(Defend),
+    Bleed(Bleed),
+    Stun(Stun),
+    Raise(Raise),
+    Lower(Lower),
+    Burn(Burn),
+    Freeze(Freeze),
+    Confuse(Confuse),
+    Poison(Poison),
+    Weather(SetWeather),
+    Spikes(LaySpikes),
+    ClearSpikes(ClearSpikes),
+    Counter(Counter),
+    MultiHit(MultiHitAttack),
+    Recoil(RecoilAttack),
+    Drain(DrainAttack),
+    Sacrifice(Sacrifice),
+    Charge(ChargeAttack),
+    Recharge(RechargeAttack),
+    Fraction(FractionAttack),
+    Ohko(OhkoAttack),
+}
+
+impl OnionAction {
+    pub fn kind(&self) -> ActionKind {
+        match self {
+            OnionAction::Attack(_) => ActionKind::Attack,
+            OnionAction::PureAttack(_) => ActionKind::PureAttack,
+            OnionAction::Defend(_) => ActionKind::Defend,
+            OnionAction::Bleed(_) => ActionKind::Bleed,
+            OnionAction::Stun(_) => ActionKind::Stun,
+            OnionAction::Raise(_) => ActionKind::Raise,
+            OnionAction::Lower(_) => ActionKind::Lower,
+            OnionAction::Burn(_) => ActionKind::Burn,
+            OnionAction::Freeze(_) => ActionKind::Freeze,
+            OnionAction::Confuse(_) => ActionKind::Confuse,
+            OnionAction::Poison(_) => ActionKind::Poison,
+            OnionAction::Weather(_) => ActionKind::Weather,
+            OnionAction::Spikes(_) => ActionKind::Spikes,
+            OnionAction::ClearSpikes(_) => ActionKind::ClearSpikes,
+            OnionAction::Counter(_) => ActionKind::Counter,
+            OnionAction::MultiHit(_) => ActionKind::MultiHit,
+            OnionAction::Recoil(_) => ActionKind::Recoil,
+            OnionAction::Drain(_) => ActionKind::Drain,
+            OnionAction::Sacrifice(_) => ActionKind::Sacrifice,
+            OnionAction::Charge(_) => ActionKind::Charge,
+            OnionAction::Recharge(_) => ActionKind::Recharge,
+            OnionAction::Fraction(_) => ActionKind::Fraction,
+            OnionAction::Ohko(_) => ActionKind::Ohko,
+        }
+    }
+
+    pub fn action(&self) -> &DynAction {
+        match self {
+            OnionAction::Attack(action) => action,
+            OnionAction::PureAttack(action) => action,
+            OnionAction::Defend(action) => action,
+            OnionAction::Bleed(action) => action,
+            OnionAction::Stun(action) => action,
+            OnionAction::Raise(action) => action,
+            OnionAction::Lower(action) => action,
+            OnionAction::Burn(action) => action,
+            OnionAction::Freeze(action) => action,
+            OnionAction::Confuse(action) => action,
+            OnionAction::Poison(action) => action,
+            OnionAction::Weather(action) => action,
+            OnionAction::Spikes(action) => action,
+            OnionAction::ClearSpikes(action) => action,
+            OnionAction::Counter(action) => action,
+            OnionAction::MultiHit(action) => action,
+            OnionAction::Recoil(action) => action,
+            OnionAction::Drain(action) => action,
+            OnionAction::Sacrifice(action) => action,
+            OnionAction::Charge(action) => action,
+            OnionAction::Recharge(action) => action,
+            OnionAction::Fraction(action) => action,
+            OnionAction::Ohko(action) => action,
+        }
+    }
+
+    // the alignment of whatever it attacks with; pure attacks and everything that isn't an attack
+    // don't have one
+    pub fn alignment(&self) -> Option<Alignment> {
+        match self {
+            OnionAction::Attack(attack) => Some(attack.alignment),
+            OnionAction::MultiHit(action) => Some(action.attack.alignment),
+            OnionAction::Recoil(action) => Some(action.attack.alignment),
+            OnionAction::Drain(action) => Some(action.attack.alignment),
+            OnionAction::Sacrifice(action) => Some(action.attack.alignment),
+            OnionAction::Charge(action) => Some(action.attack.alignment),
+            OnionAction::Recharge(action) => Some(action.attack.alignment),
+            OnionAction::Ohko(action) => Some(action.alignment),
+            _ => None,
+        }
+    }
+}
+
+// the actions of a world by id. ids are handed out in the order actions are registered and never
+// move, so whatever refers to one keeps referring to the same action
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct ActionRegistry {
+    actions: Vec<OnionAction>,
+}
+
+impl ActionRegistry {
+    fn register(&mut self, action: OnionAction) -> ActionId {
+        self.actions.push(action);
+        self.actions.len() - 1
+    }
+
+    fn register_all<A>(&mut self, actions: Vec<A>, pooled: fn(A) -> OnionAction) {
+        self.actions.extend(actions.into_iter().map(pooled));
+    }
+
+    pub fn get(&self, id: ActionId) -> Option<&DynAction> {
+        if id == SKIP_ID {
+            return Some(&SKIP);
+        }
+        self.actions.get(id).map(OnionAction::action)
+    }
+
+    pub fn kind(&self, id: ActionId) -> Option<ActionKind> {
+        if id == SKIP_ID {
+            return Some(ActionKind::Skip);
+        }
+        self.actions.get(id).map(OnionAction::kind)
+    }
+
+    pub fn alignment(&self, id: ActionId) -> Option<Alignment> {
+        self.actions.get(id).and_then(OnionAction::alignment)
+    }
+
+    pub fn len(&self) -> usize { self.actions.len() }
+
+    fn iter(&self) -> impl Iterator<Item = &OnionAction> {
+        self.actions.iter()
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "SavedPool")]
 pub struct ActionPool {
+    actions: ActionRegistry,
+    // generated worlds roll this many ids past the last action, which do nothing
+    padding: usize,
+}
+
+// pools used to be saved as a list for each kind of action, with ids counting through them in order
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedPool {
+    Registry { actions: ActionRegistry, padding: usize },
+    Legacy(Box<LegacyPool>),
+}
+
+#[derive(Deserialize)]
+struct LegacyPool {
     attack: Vec<Attack>,
     pure_attack: Vec<PureAttack>,
     defend: Vec<Defend>,
     bleed: Vec<Bleed>,
     stun: Vec<Stun>,
+    // older saved worlds don't have these
+    #[serde(default)]
+    raise: Vec<Raise>,
+    #[serde(default)]
+    lower: Vec<Lower>,
+    #[serde(default)]
+    burn: Vec<Burn>,
+    #[serde(default)]
+    freeze: Vec<Freeze>,
+    #[serde(default)]
+    confuse: Vec<Confuse>,
+    #[serde(default)]
+    poison: Vec<Poison>,
+    #[serde(default)]
+    weather: Vec<SetWeather>,
+    #[serde(default)]
+    spikes: Vec<LaySpikes>,
+    #[serde(default)]
+    clear_spikes: Vec<ClearSpikes>,
+    #[serde(default)]
+    counter: Vec<Counter>,
+    #[serde(default)]
+    multi_hit: Vec<MultiHitAttack>,
+    #[serde(default)]
+    recoil: Vec<RecoilAttack>,
+    #[serde(default)]
+    drain: Vec<DrainAttack>,
+    #[serde(default)]
+    sacrifice: Vec<Sacrifice>,
+    #[serde(default)]
+    charge: Vec<ChargeAttack>,
+    #[serde(default)]
+    recharge: Vec<RechargeAttack>,
     padding: usize,
 }
 
+impl From<SavedPool> for ActionPool {
+    fn from(saved: SavedPool) -> ActionPool {
+        let pool = match saved {
+            SavedPool::Registry { actions, padding } => return ActionPool { actions, padding },
+            SavedPool::Legacy(pool) => *pool,
+        };
+        let mut actions = ActionRegistry::default();
+        actions.register_all(pool.attack, OnionAction::Attack);
+        actions.register_all(pool.pure_attack, OnionAction::PureAttack);
+        actions.register_all(pool.defend, OnionAction::Defend);
+        actions.register_all(pool.bleed, OnionAction::Bleed);
+        actions.register_all(pool.stun, OnionAction::Stun);
+        actions.register_all(pool.raise, OnionAction::Raise);
+        actions.register_all(pool.lower, OnionAction::Lower);
+        actions.register_all(pool.burn, OnionAction::Burn);
+        actions.register_all(pool.freeze, OnionAction::Freeze);
+        actions.register_all(pool.confuse, OnionAction::Confuse);
+        actions.register_all(pool.poison, OnionAction::Poison);
+        actions.register_all(pool.weather, OnionAction::Weather);
+        actions.register_all(pool.spikes, OnionAction::Spikes);
+        actions.register_all(pool.clear_spikes, OnionAction::ClearSpikes);
+        actions.register_all(pool.counter, OnionAction::Counter);
+        actions.register_all(pool.multi_hit, OnionAction::MultiHit);
+        actions.register_all(pool.recoil, OnionAction::Recoil);
+        actions.register_all(pool.drain, OnionAction::Drain);
+        actions.register_all(pool.sacrifice, OnionAction::Sacrifice);
+        actions.register_all(pool.charge, OnionAction::Charge);
+        actions.register_all(pool.recharge, OnionAction::Recharge);
+        ActionPool { actions, padding: pool.padding }
+    }
+}
+
 impl ActionPool {
+    #[cfg(test)]
     fn empty_pool() -> ActionPool {
-        ActionPool {
-            attack: vec![],
-            pure_attack: vec![],
-            defend: vec![],
-            bleed: vec![],
-            stun: vec![],
-            padding: 0,
-        }
+        ActionPool { actions: ActionRegistry::default(), padding: 0 }
     }
 
     fn with_padding(attack: Vec<Attack>, padding: usize) -> ActionPool {
-        ActionPool {
-            attack,
-            pure_attack: vec![
-                PureAttack { name: "Burst".to_string(), power: 20 },
-                PureAttack { name: "Blast".to_string(), power: 40 },
-            ],
-            defend: vec![
-                Defend { name: "Block".to_string() },
-                Defend { name: "Dodge".to_string() },
-            ],
-            bleed: vec![
-                Bleed { name: "Cut".to_string(), power: 1 },
-                Bleed { name: "Slice".to_string(), power: 1 },
-            ],
-            stun: vec![
-                Stun { name: "Lullabye".to_string() },
-                Stun { name: "Paralyze".to_string() },
-                Stun { name: "Yawn".to_string() },
-            ],
-            padding
-        }
+        let mut actions = ActionRegistry::default();
+        actions.register_all(attack, OnionAction::Attack);
+        actions.register_all(vec![
+            PureAttack { name: "Burst".into(), power: 20 },
+            PureAttack { name: "Blast".into(), power: 40 },
+        ], OnionAction::PureAttack);
+        actions.register_all(vec![
+            Defend { name: "Block".into() },
+            Defend { name: "Dodge".into() },
+        ], OnionAction::Defend);
+        actions.register_all(vec![
+            Bleed { name: "Cut".into(), power: 1 },
+            Bleed { name: "Slice".into(), power: 1 },
+        ], OnionAction::Bleed);
+        actions.register_all(vec![
+            Stun { name: "Lullabye".into() },
+            Stun { name: "Paralyze".into() },
+            Stun { name: "Yawn".into() },
+        ], OnionAction::Stun);
+        actions.register_all(vec![
+            Raise { name: "Sharpen".into(), stat: Stat::Attack, stages: 1 },
+            Raise { name: "Harden".into(), stat: Stat::Defense, stages: 1 },
+            Raise { name: "Agility".into(), stat: Stat::Speed, stages: 2 },
+        ], OnionAction::Raise);
+        actions.register_all(vec![
+            Lower { name: "Growl".into(), stat: Stat::Attack, stages: 1 },
+            Lower { name: "Leer".into(), stat: Stat::Defense, stages: 1 },
+        ], OnionAction::Lower);
+        actions.register_all(vec![
+            Burn { name: "Singe".into() },
+            Burn { name: "Scald".into() },
+        ], OnionAction::Burn);
+        actions.register_all(vec![
+            Freeze { name: "Chill".into() },
+            Freeze { name: "Frostbite".into() },
+        ], OnionAction::Freeze);
+        actions.register_all(vec![
+            Confuse { name: "Swirl".into() },
+            Confuse { name: "Befuddle".into() },
+        ], OnionAction::Confuse);
+        actions.register_all(vec![
+            Poison { name: "Sting".into() },
+            Poison { name: "Toxin".into() },
+        ], OnionAction::Poison);
+        actions.register_all(vec![
+            SetWeather { name: "Rain Dance".into(), weather: Weather::Rain },
+            SetWeather { name: "Sandstorm".into(), weather: Weather::Sandstorm },
+        ], OnionAction::Weather);
+        actions.register(OnionAction::Spikes(LaySpikes { name: "Spikes".into() }));
+        actions.register(OnionAction::ClearSpikes(ClearSpikes { name: "Sweep".into() }));
+        actions.register(OnionAction::Counter(Counter { name: "Counter".into() }));
+        ActionPool { actions, padding }
+    }
+
+    pub fn with_attacks(attack: Vec<Attack>) -> ActionPool {
+        ActionPool::with_padding(attack, 0usize)
     }
 
-    fn with_attacks(attack: Vec<Attack>) -> ActionPool {
-        ActionPool::with_padding(attack, 0usize)
+    // the attacks are the only part of the pool that differs between worlds, besides the variants
+    pub fn attacks(&self) -> impl Iterator<Item = &Attack> {
+        self.actions.iter().filter_map(|action| match action {
+            OnionAction::Attack(attack) => Some(attack),
+            _ => None,
+        })
     }
 
-    fn len(&self) -> usize {
-        self.attack.len() +
-        self.pure_attack.len() +
-        self.defend.len() +
-        self.bleed.len() +
-        self.stun.len()
+    pub fn drains(&self) -> impl Iterator<Item = &DrainAttack> {
+        self.actions.iter().filter_map(|action| match action {
+            OnionAction::Drain(drain) => Some(drain),
+            _ => None,
+        })
     }
-}
 
-impl Index<ActionId> for ActionPool {
-    type Output = dyn Action<Alignment, Status>;
+    pub fn sacrifices(&self) -> impl Iterator<Item = &Sacrifice> {
+        self.actions.iter().filter_map(|action| match action {
+            OnionAction::Sacrifice(sacrifice) => Some(sacrifice),
+            _ => None,
+        })
+    }
 
-    fn index(&self, action: ActionId) -> &Self::Output {
-        let mut id = action.clone();
-        if id < self.attack.len() {
-            return &self.attack[id];
-        } else {
-            id -= self.attack.len();
+    // a world's own drains and sacrifices, after everything else in the pool
+    pub fn with_specials(mut self, drain: Vec<DrainAttack>, sacrifice: Vec<Sacrifice>) -> ActionPool {
+        self.actions.register_all(drain, OnionAction::Drain);
+        self.actions.register_all(sacrifice, OnionAction::Sacrifice);
+        self
+    }
+
+    // actions of any kind, after everything else in the pool
+    pub fn with_actions(mut self, actions: Vec<OnionAction>) -> ActionPool {
+        for action in actions {
+            self.actions.register(action);
         }
+        self
+    }
 
-        if id < self.pure_attack.len() {
-            return &self.pure_attack[id];
-        } else {
-            id -= self.pure_attack.len();
+    // the action an id refers to. ids rolled into the padding do nothing, like skipping
+    pub fn get(&self, action: ActionId) -> Option<&DynAction> {
+        match self.actions.get(action) {
+            None if self.padded(action) => Some(&SKIP),
+            found => found,
         }
+    }
 
-        if id < self.defend.len() {
-            return &self.defend[id];
-        } else {
-            id -= self.defend.len();
+    // which kind of action an id refers to; anything that isn't a real action skips
+    pub fn kind(&self, action: ActionId) -> ActionKind {
+        self.actions.kind(action).unwrap_or(ActionKind::Skip)
+    }
+
+    // the alignment an id attacks with, if it's an attack with one
+    pub fn alignment(&self, action: ActionId) -> Option<Alignment> {
+        self.actions.alignment(action)
+    }
+
+    fn padded(&self, action: ActionId) -> bool {
+        (self.actions.len()..self.actions.len() + self.padding).contains(&action)
+    }
+
+    // each of the attacks might come again hitting several times or with recoil, by weight; after
+    // everything else, so the ids before them stay where they were
+    fn roll_variants<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut variants = Vec::new();
+        for attack in self.attacks() {
+            let variant = |name: String, power: u32| Attack { name: name.into(), power, ..attack.clone() };
+            match VARIANT_WEIGHTS.choose_weighted(rng, |(_, weight)| *weight).unwrap().0 {
+                Some(ActionKind::MultiHit) => {
+                    let power = (attack.power / AVERAGE_HITS).max(WORST_ATTACK);
+                    variants.push(OnionAction::MultiHit(MultiHitAttack::new(variant(format!("{} Flurry", attack.name), power))));
+                },
+                Some(ActionKind::Recoil) => {
+                    let power = (attack.power * 3 / 2).min(BEST_ATTACK);
+                    variants.push(OnionAction::Recoil(RecoilAttack::new(variant(format!("Reckless {}", attack.name), power), DEFAULT_RECOIL)));
+                },
+                Some(ActionKind::Drain) => {
+                    let power = (attack.power * 2 / 3).max(WORST_ATTACK);
+                    variants.push(OnionAction::Drain(DrainAttack::new(variant(format!("Draining {}", attack.name), power), DEFAULT_DRAIN)));
+                },
+                Some(ActionKind::Sacrifice) => {
+                    variants.push(OnionAction::Sacrifice(Sacrifice::new(variant(format!("Final {}", attack.name), SACRIFICE_POWER))));
+                },
+                Some(ActionKind::Charge) => {
+                    let power = (attack.power * 2).min(BEST_ATTACK);
+                    variants.push(OnionAction::Charge(ChargeAttack::new(variant(format!("Charged {}", attack.name), power))));
+                },
+                Some(ActionKind::Recharge) => {
+                    let power = (attack.power * 2).min(BEST_ATTACK);
+                    variants.push(OnionAction::Recharge(RechargeAttack::new(variant(format!("Hyper {}", attack.name), power))));
+                },
+                _ => (),
+            }
         }
+        // grouped by kind, the way they were laid out before there was a registry
+        variants.sort_by_key(|action| action.kind() as u8);
+        for action in variants {
+            self.actions.register(action);
+        }
+    }
 
-        if id < self.bleed.len() {
-            return &self.bleed[id];
-        } else {
-            id -= self.bleed.len();
+    // now and then a world has actions that take a share of the target's health, after the variants
+    fn roll_fractions<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        for (name, percent, of_max) in FRACTIONS.iter() {
+            if rng.gen_ratio(1, FRACTION_CHANCE) {
+                self.actions.register(OnionAction::Fraction(FractionAttack::new(name, *percent, *of_max)));
+            }
+        }
+    }
+
+    // rarer still, a world has actions that knock the target out in one go
+    fn roll_ohkos<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        for (name, alignment) in OHKOS.iter() {
+            if rng.gen_ratio(1, OHKO_CHANCE) {
+                self.actions.register(OnionAction::Ohko(OhkoAttack::new(name, *alignment)));
+            }
         }
+    }
+
+    // every id that refers to a real action
+    pub fn ids(&self) -> Range<ActionId> { 0..self.actions.len() }
+
+    // the actions the character knows that it has uses of left this battle
+    pub fn usable<'a>(&'a self, character: &'a OnionCharacter) -> impl Iterator<Item = ActionId> + 'a {
+        character.attributes.actions.iter()
+            .copied()
+            .filter(move |action| character.pp_left(*action, self[*action].pp()) > 0)
+    }
 
-        if id < self.stun.len() {
-            return &self.stun[id];
+    // uses up one use of the action and returns what the character actually does: the action, a
+    // struggle once all of its moves are used up, or a skip if it picked one that has run out. a
+    // character in the middle of a two-turn move does that instead, without using it up again
+    pub fn spend(&self, character: &mut OnionCharacter, action: ActionId) -> &dyn Action<Alignment, Status, Field> {
+        match character.state.pending {
+            Some(Pending::Charged(charged)) => return &self[charged],
+            Some(Pending::Recharge) => return &SKIP,
+            _ => (),
         }
+        if self.usable(character).next().is_none() {
+            return &STRUGGLE;
+        }
+        if !character.spend_pp(action, self[action].pp()) {
+            return &SKIP;
+        }
+        if self[action].charges() {
+            character.state.pending = Some(Pending::Charging(action));
+        }
+        &self[action]
+    }
+}
+
+impl Index<ActionId> for ActionPool {
+    type Output = DynAction;
 
-        &SKIP
+    fn index(&self, action: ActionId) -> &Self::Output {
+        match self.get(action) {
+            Some(action) => action,
+            None => panic!("no action with id {}", action),
+        }
     }
 }
 
 // TODO: figure out how to implement sample_iter
 impl Distribution<ActionId> for ActionPool {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ActionId {
-        rng.gen_range(0..(self.len() + self.padding))
+        rng.gen_range(0..(self.actions.len() + self.padding))
     }
 }
 
@@ -924,40 +3183,133 @@ mod action_pool_tests {
     fn empty_action_pool_test() {
         let pool = ActionPool::empty_pool();
 
-        let name = SKIP.name();
-        assert_eq!(pool[0].name(), name);
-        assert_eq!(pool[1].name(), name);
-        assert_eq!(pool[std::usize::MAX].name(), name);
-        assert_eq!(pool[std::usize::MIN].name(), name);
+        assert!(pool.get(0).is_none());
+        assert!(pool.get(1).is_none());
+        assert_eq!(pool[SKIP_ID].name(), SKIP.name());
+        assert_eq!(pool.kind(0), ActionKind::Skip);
     }
 
     #[test]
     fn action_pool_test1() {
         let action = action_tests::fake_attack(0);
-        let action_name = action.name();
+        let action_name = action.name().to_string();
         let mut pool = ActionPool::empty_pool();
-        pool.attack.push(action);
+        assert_eq!(pool.actions.register(OnionAction::Attack(action)), 0);
 
-        let skip_name = SKIP.name();
         assert_eq!(pool[0].name(), action_name);
-        assert_eq!(pool[1].name(), skip_name);
-        assert_eq!(pool[std::usize::MIN].name(), action_name);
-        assert_eq!(pool[std::usize::MAX].name(), skip_name);
+        assert!(pool.get(1).is_none());
+        assert_eq!(pool[SKIP_ID].name(), SKIP.name());
+
+        // padded ids do nothing, but only up to the padding
+        pool.padding = 2;
+        assert_eq!(pool[2].name(), SKIP.name());
+        assert!(pool.get(3).is_none());
+        assert_eq!(pool.ids(), 0..1);
+    }
+
+    #[test]
+    #[should_panic(expected = "no action with id 1")]
+    fn missing_action_test() {
+        let _ = &ActionPool::empty_pool()[1];
+    }
+
+    #[test]
+    fn saved_pool_test() {
+        let pool = ActionPool::with_attacks(vec![action_tests::fake_attack(0)]);
+        let saved = serde_json::to_string(&pool).unwrap();
+        let loaded: ActionPool = serde_json::from_str(&saved).unwrap();
+        assert!(pool.ids().all(|id| pool.kind(id) == loaded.kind(id) && pool[id].name() == loaded[id].name()));
+
+        // pools saved as a list for each kind keep their ids
+        let legacy: ActionPool = serde_json::from_str(r#"{
+            "attack": [{ "name": "Tackle", "power": 40, "alignment": "Rock", "priority": 0 }],
+            "pure_attack": [],
+            "defend": [{ "name": "Block" }],
+            "bleed": [],
+            "stun": [{ "name": "Yawn" }],
+            "drain": [{ "name": "Leech", "power": 40, "alignment": "Paper", "priority": 0, "percent": 50 }],
+            "padding": 1
+        }"#).unwrap();
+        assert_eq!(legacy.ids(), 0..4);
+        assert_eq!(legacy.ids().map(|id| legacy.kind(id)).collect::<Vec<_>>(), vec![ActionKind::Attack, ActionKind::Defend, ActionKind::Stun, ActionKind::Drain]);
+        assert_eq!(legacy[3].name(), "Leech");
+        assert_eq!(legacy[4].name(), SKIP.name());
+    }
+
+    #[test]
+    fn action_kind_test() {
+        let pool = ActionPool::with_attacks(vec![action_tests::fake_attack(0)]);
+
+        assert_eq!(pool.kind(0), ActionKind::Attack);
+        assert_eq!(pool.kind(1), ActionKind::PureAttack);
+        assert_eq!(pool.kind(3), ActionKind::Defend);
+        assert_eq!(pool.kind(5), ActionKind::Bleed);
+        assert_eq!(pool.kind(7), ActionKind::Stun);
+        assert_eq!(pool.kind(9), ActionKind::Stun);
+        assert_eq!(pool.kind(10), ActionKind::Raise);
+        assert_eq!(pool.kind(13), ActionKind::Lower);
+        assert_eq!(pool[13].name(), "Growl");
+        assert_eq!(pool.kind(15), ActionKind::Burn);
+        assert_eq!(pool.kind(17), ActionKind::Freeze);
+        assert_eq!(pool.kind(19), ActionKind::Confuse);
+        assert_eq!(pool.kind(21), ActionKind::Poison);
+        assert_eq!(pool[22].name(), "Toxin");
+        assert_eq!(pool.kind(23), ActionKind::Weather);
+        assert_eq!(pool[24].name(), "Sandstorm");
+        assert_eq!(pool.kind(25), ActionKind::Spikes);
+        assert_eq!(pool.kind(26), ActionKind::ClearSpikes);
+        assert_eq!(pool.kind(27), ActionKind::Counter);
+        assert_eq!(pool.kind(28), ActionKind::Skip);
+    }
+
+    #[test]
+    fn spend_test() {
+        let pool = ActionPool::with_attacks(vec![action_tests::fake_attack(0).with_pp(2)]);
+        let mut character = testing::fake_character();
+        character.attributes.actions = vec![0, 1];
+
+        assert_eq!(pool.spend(&mut character, 0).name(), "fake");
+        assert_eq!(character.pp_left(0, 2), 1);
+        assert_eq!(pool.spend(&mut character, 0).name(), "fake");
+        assert_eq!(pool.usable(&character).collect::<Vec<_>>(), vec![1]);
+        // a move that's run out does nothing while there are others left
+        assert_eq!(pool.spend(&mut character, 0).name(), SKIP.name());
+
+        character.attributes.pp.insert(1, 0);
+        assert_eq!(pool.spend(&mut character, 1).name(), STRUGGLE.name());
+
+        // every battle starts with full pp
+        character.refresh();
+        assert_eq!(pool.usable(&character).count(), 2);
+        assert_eq!(pool.kind(usize::MAX), ActionKind::Skip);
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct OnionWorld {
-    species: Vec<Species<Alignment>>,
+    // shared with every character sampled from the world
+    species: Vec<Arc<Species<Alignment>>>,
     pub actions: ActionPool,
+    #[serde(default)]
+    chart: TypeChart,
+    // older saved worlds don't have these
+    #[serde(default = "default_items")]
+    pub items: Vec<OnionItem>,
+    #[serde(default = "default_abilities")]
+    pub abilities: Vec<OnionAbility>,
+    #[serde(default)]
+    pub cycle: DayCycle,
+    // where wild kaizo are met; without any, every species can be met anywhere at any level
+    #[serde(default)]
+    pub areas: Vec<Area>,
+    // the bosses a run meets; without any, bosses are rolled from the world's species
+    #[serde(default)]
+    pub bosses: Vec<Boss>,
 }
 
 impl Distribution<OnionCharacter> for OnionWorld {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> OnionCharacter {
-        Character::from_species_and_actions(
-            self.species.choose(rng).unwrap().clone(),
-            self.actions.clone().sample_iter(&mut thread_rng()).take(4).collect()
-        )
+        self.roll(self.species.choose(rng).unwrap(), rng)
     }
 }
 
@@ -970,24 +3322,280 @@ impl Distribution<ActionPool> for Standard {
 }
 
 static SPECIES_COUNT: usize = 351usize;
+// generated species learn a random action at each of these levels
+static LEARNSET_LEVELS: [u32; 4] = [8, 16, 24, 32];
 
 impl Distribution<OnionWorld> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> OnionWorld {
-        OnionWorld {
-            actions: rng.gen(),
-            species: self.sample_iter(rng).take(SPECIES_COUNT).collect(),
+        let mut actions: ActionPool = rng.gen();
+        let mut species: Vec<Species<Alignment>> = self.sample_iter(&mut *rng).take(SPECIES_COUNT).collect();
+        // rolled after everything else, so the species and actions a seed generates stay the same
+        for species in species.iter_mut() {
+            species.learnset = LEARNSET_LEVELS.iter().map(|level| (*level, rng.gen_range(actions.ids()))).collect();
+        }
+        for species in species.iter_mut() {
+            species.ability = Some(rng.gen_range(0..default_abilities().len()));
         }
+        actions.roll_variants(rng);
+        actions.roll_ohkos(rng);
+        actions.roll_fractions(rng);
+        let world = OnionWorld::new(species, actions, TypeChart::default());
+        let areas = generate_areas(world.species());
+        world.with_areas(areas)
     }
 }
 
 impl OnionWorld {
+    pub fn new(species: Vec<Species<Alignment>>, actions: ActionPool, chart: TypeChart) -> OnionWorld {
+        OnionWorld { species: species.into_iter().map(Arc::new).collect(), actions, chart, items: default_items(), abilities: default_abilities(), cycle: DayCycle::default(), areas: Vec::new(), bosses: Vec::new() }
+    }
+
+    pub fn with_items(mut self, items: Vec<OnionItem>) -> OnionWorld {
+        self.items = items;
+        self
+    }
+
+    pub fn with_cycle(mut self, cycle: DayCycle) -> OnionWorld {
+        self.cycle = cycle;
+        self
+    }
+
+    pub fn with_areas(mut self, areas: Vec<Area>) -> OnionWorld {
+        self.areas = areas;
+        self
+    }
+
+    pub fn with_bosses(mut self, bosses: Vec<Boss>) -> OnionWorld {
+        self.bosses = bosses;
+        self
+    }
+
+    // where a party led by a kaizo at that level searches for wild kaizo
+    pub fn area(&self, level: u32) -> Option<&Area> { area_at(&self.areas, level) }
+
+    pub fn chart(&self) -> &TypeChart { &self.chart }
+
+    pub fn sample_actions<R: Rng + ?Sized>(&self, rng: &mut R) -> Actions {
+        (&self.actions).sample_iter(rng).take(4).collect()
+    }
+
+    fn roll<R: Rng + ?Sized>(&self, species: &Arc<Species<Alignment>>, rng: &mut R) -> OnionCharacter {
+        let mut character = Character::from_shared_species(species.clone());
+        character.attributes.actions = self.sample_actions(rng);
+        character.attributes.nature = *NATURES.choose(rng).unwrap();
+        character
+    }
+
     pub fn sample_at_level<R: Rng + ?Sized>(&self, level: u32, rng: &mut R) -> OnionCharacter {
         let mut character = self.sample(rng);
-        character.gain_experience(level * EXPERIENCE_TO_LEVEL);
-        character.attributes.stats = character.species.stats.scale(level * SCALING_FACTOR);
-        character.refresh();
+        set_level(&mut character, level);
+        character
+    }
+
+    // a wild kaizo from the encounter table of the area a lead at `level` searches, at that time of
+    // day and at a level from the area's range. worlds without areas meet any species at `level`
+    pub fn sample_encounter<R: Rng + ?Sized>(&self, level: u32, time: TimeOfDay, rng: &mut R) -> OnionCharacter {
+        let (table, levels) = match self.area(level) {
+            Some(area) => (area.table(&self.species, &self.cycle, time), area.levels),
+            None => (self.cycle.table(&self.species, time), (level, level)),
+        };
+        let mut character = self.roll(table.choose(rng).unwrap(), rng);
+        let level = if levels.0 == levels.1 { levels.0 } else { rng.gen_range(levels.0..=levels.1) };
+        set_level(&mut character, level);
         character
     }
+
+    pub fn species(&self) -> &[Arc<Species<Alignment>>] { &self.species }
+
+    // characters read back from a save each come with their own copy of their species; this
+    // points them back at the world's, so they share it (and its name) again
+    pub fn intern(&self, character: &mut OnionCharacter) {
+        if let Some(species) = self.species.iter().find(|species| **species == character.species) {
+            if character.name == species.name {
+                character.name = species.name.clone();
+            }
+            character.species = species.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod world_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn shared_species_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let character = world.sample_at_level(5, &mut rng);
+        let species = world.species().iter().find(|species| **species == character.species).unwrap();
+        assert!(Arc::ptr_eq(species, &character.species));
+        assert!(Arc::ptr_eq(&species.name, &character.name));
+    }
+
+    #[test]
+    fn rarity_test() {
+        let world: OnionWorld = Standard.sample(&mut StdRng::seed_from_u64(0));
+        for species in world.species() {
+            assert_eq!(species.rarity, rarity_of(species.bst));
+            assert_eq!(species.name.starts_with("Mythic "), species.rarity == Rarity::Legendary);
+        }
+        let count = |rarity: Rarity| world.species().iter().filter(|species| species.rarity == rarity).count();
+        assert!(count(Rarity::Legendary) > 0);
+        assert!(count(Rarity::Legendary) < count(Rarity::Rare) && count(Rarity::Rare) < count(Rarity::Common));
+    }
+
+    #[test]
+    fn abilities_test() {
+        let world: OnionWorld = Standard.sample(&mut StdRng::seed_from_u64(0));
+        for species in world.species() {
+            assert!(species.ability.is_some_and(|ability| ability < world.abilities.len()));
+        }
+    }
+
+    #[test]
+    fn fractions_test() {
+        let worlds: Vec<OnionWorld> = (0..10).map(|seed| Standard.sample(&mut StdRng::seed_from_u64(seed))).collect();
+        let fractions = |world: &OnionWorld| world.actions.ids().filter(|id| world.actions.kind(*id) == ActionKind::Fraction).count();
+        // only some worlds have them, and never many
+        assert!(worlds.iter().any(|world| fractions(world) > 0));
+        assert!(worlds.iter().any(|world| fractions(world) == 0));
+        assert!(worlds.iter().all(|world| fractions(world) <= FRACTIONS.len()));
+        // after everything else
+        for world in worlds.iter().filter(|world| fractions(world) > 0) {
+            assert_eq!(world.actions.kind(world.actions.ids().len() - 1), ActionKind::Fraction);
+        }
+    }
+
+    #[test]
+    fn ohkos_test() {
+        let worlds: Vec<OnionWorld> = (0..20).map(|seed| Standard.sample(&mut StdRng::seed_from_u64(seed))).collect();
+        let ohkos = |world: &OnionWorld| world.actions.ids().filter(|id| world.actions.kind(*id) == ActionKind::Ohko).count();
+        // rare, but they do come up
+        assert!(worlds.iter().any(|world| ohkos(world) > 0));
+        assert!(worlds.iter().filter(|world| ohkos(world) > 0).count() < worlds.len() / 2);
+        for world in worlds.iter() {
+            assert!(world.actions.ids().filter(|id| world.actions.kind(*id) == ActionKind::Ohko).all(|id| world.actions[id].one_hit_ko()));
+        }
+    }
+
+    #[test]
+    fn variants_test() {
+        let world: OnionWorld = Standard.sample(&mut StdRng::seed_from_u64(0));
+        let ids: Vec<ActionId> = world.actions.ids().collect();
+        let multi_hit = ids.iter().filter(|id| world.actions.kind(**id) == ActionKind::MultiHit).count();
+        let recoil = ids.iter().filter(|id| world.actions.kind(**id) == ActionKind::Recoil).count();
+        assert!(multi_hit > 0 && recoil > 0);
+        assert!(world.actions.drains().next().is_some());
+        assert!(world.actions.drains().all(|drain| drain.name().starts_with("Draining ")));
+        assert!(world.actions.sacrifices().all(|sacrifice| sacrifice.name().starts_with("Final ")));
+        let named = |kind, prefix: &str| ids.iter().filter(|id| world.actions.kind(**id) == kind).all(|id| world.actions[*id].name().starts_with(prefix));
+        assert!(named(ActionKind::Charge, "Charged ") && named(ActionKind::Recharge, "Hyper "));
+        // roughly one in every ten attacks each
+        assert!(multi_hit + recoil < world.actions.attacks().count() / 2);
+        assert_eq!(world.actions[world.actions.ids().len() - 1].hits(), 1..=1);
+        let flurry = ids.iter().find(|id| world.actions.kind(**id) == ActionKind::MultiHit).unwrap();
+        assert!(world.actions[*flurry].name().ends_with(" Flurry"));
+        assert_eq!(world.actions[*flurry].hits(), MIN_HITS..=MAX_HITS);
+    }
+
+    #[test]
+    fn learnset_test() {
+        let world: OnionWorld = Standard.sample(&mut StdRng::seed_from_u64(0));
+        for species in world.species() {
+            assert_eq!(species.learnset.len(), LEARNSET_LEVELS.len());
+            assert!(species.learnset.iter().all(|(_, action)| world.actions.ids().contains(action)));
+        }
+    }
+
+    #[test]
+    fn intern_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let character = world.sample_at_level(5, &mut rng);
+        let mut loaded: OnionCharacter = serde_json::from_str(&serde_json::to_string(&character).unwrap()).unwrap();
+        assert!(!Arc::ptr_eq(&loaded.species, &character.species));
+
+        world.intern(&mut loaded);
+        assert_eq!(loaded, character);
+        assert!(Arc::ptr_eq(&loaded.species, &character.species));
+        assert!(Arc::ptr_eq(&loaded.name, &character.name));
+
+        // renamed characters keep their own name
+        let mut renamed = character.clone();
+        renamed.name = "Rocky".into();
+        world.intern(&mut renamed);
+        assert_eq!(&*renamed.name, "Rocky");
+    }
+
+    #[test]
+    fn encounter_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let areas = world.areas.clone();
+        // without a cycle or areas, encounters roll the same as any other kaizo
+        let world = world.with_areas(vec![]);
+        let (mut a, mut b) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(1));
+        assert_eq!(world.sample_encounter(5, TimeOfDay::Night, &mut a), world.sample_at_level(5, &mut b));
+
+        let owl = world.species()[0].name.clone();
+        let world = world.with_cycle(DayCycle { nocturnal: vec![owl.clone()], ..DayCycle::default() });
+        assert!((0..1000).all(|_| world.sample_encounter(5, TimeOfDay::Day, &mut rng).species.name != owl));
+
+        // with them, a weak lead only meets weak kaizo, at the first area's levels
+        let world = world.with_areas(areas);
+        let meadow = &world.areas[0];
+        for _ in 0..100 {
+            let character = world.sample_encounter(1, TimeOfDay::Night, &mut rng);
+            assert!(meadow.weight(&character.species) > 0);
+            assert!((meadow.levels.0..=meadow.levels.1).contains(&character.attributes.level));
+        }
+    }
+}
+
+// grows a character to a level, as if it had earned all of the experience. whatever it had toward
+// its next level carries over, as far as the new level's takes
+pub fn set_level(character: &mut OnionCharacter, level: u32) {
+    let progress = std::mem::take(&mut character.attributes.experience);
+    character.gain_experience(character.species.growth.experience_between(character.attributes.level, level));
+    character.attributes.experience = progress.min(character.experience_to_next_level() - 1);
+    character.recalculate_stats();
+    character.refresh();
+}
+
+// teaches the character the moves it has room for, and hands back the ones it would have to forget
+// a move to learn
+pub fn learn_moves(character: &mut OnionCharacter, moves: &[ActionId], actions: &ActionPool) -> (States, Actions) {
+    let mut logs = Vec::new();
+    let mut waiting = Vec::new();
+    for action in moves {
+        if character.attributes.actions.contains(action) {
+            continue;
+        }
+        if character.attributes.actions.len() < MAX_ACTIONS {
+            character.attributes.actions.push(*action);
+            logs.push(format!("{} learned {}!", character.name, actions[*action].name()));
+        } else {
+            logs.push(format!("{} wants to learn {}!", character.name, actions[*action].name()));
+            waiting.push(*action);
+        }
+    }
+    (logs, waiting)
+}
+
+// learns `action` in place of the move in `slot`, or gives up on it if there's no slot
+pub fn replace_move(character: &mut OnionCharacter, slot: Option<usize>, action: ActionId, actions: &ActionPool) -> States {
+    match slot.and_then(|slot| Some((slot, *character.attributes.actions.get(slot)?))) {
+        Some((slot, forgotten)) => {
+            character.attributes.actions[slot] = action;
+            character.attributes.pp.remove(&forgotten);
+            vec![format!("{} forgot {} and learned {}!", character.name, actions[forgotten].name(), actions[action].name())]
+        },
+        None => vec![format!("{} did not learn {}.", character.name, actions[action].name())],
+    }
 }
 
 // fn generate_world() {
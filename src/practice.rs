@@ -0,0 +1,158 @@
+#[cfg(feature = "web")]
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::content::id::WorldId;
+use crate::gauntlet::{BOSS_LEVELS, Node, NodeKind, Outcome, Run};
+use crate::onion::OnionWorld;
+use crate::roster::Roster;
+use crate::seed::Seed;
+
+#[cfg(feature = "web")]
+static PROFILE_KEY: &str = "kaizo-quest.profile";
+
+// how many kaizo the standard party brings
+pub static PRACTICE_PARTY: usize = 3;
+// the standard party is rolled from this seed, so every practice run in a world gets the same one
+static PRACTICE_SEED: Seed = Seed(0);
+
+// a boss a run has made it to. practice runs start right at it, against the same boss, with the
+// standard party instead of whatever the run had, and they aren't rated
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub world: WorldId,
+    pub seed: Seed,
+    // the boss's floor on the run's map
+    pub floor: usize,
+    pub boss: Node,
+}
+
+impl Checkpoint {
+    // the boss the run has just picked, if it's picked one
+    pub fn reached(world: WorldId, seed: Seed, run: &Run) -> Option<Checkpoint> {
+        let floor = run.floor()?;
+        let boss = run.current().filter(|node| node.kind == NodeKind::Boss)?;
+        Some(Checkpoint { world, seed, floor, boss: boss.clone() })
+    }
+
+    pub fn name(&self) -> String {
+        let boss = self.boss.enemy.as_ref().map_or("the boss".to_string(), |enemy| enemy.name.to_string());
+        format!("{} on floor {} of seed {}", boss, self.floor + 1, self.seed)
+    }
+
+    // the same checkpoint reached again, or on a different run of the seed
+    fn same(&self, other: &Checkpoint) -> bool {
+        self.world == other.world && self.seed == other.seed && self.floor == other.floor
+    }
+
+    // a map that's only the boss, already picked
+    pub fn run(&self) -> Run {
+        Run {
+            floors: vec![vec![Node { next: Vec::new(), ..self.boss.clone() }]],
+            path: vec![0],
+            outcome: Outcome::Fighting(0),
+        }
+    }
+
+    // the level a run usually has its party at by the boss
+    pub fn level(&self) -> u32 {
+        self.boss.enemy.as_ref().map_or(1, |enemy| enemy.attributes.level.saturating_sub(BOSS_LEVELS).max(1))
+    }
+}
+
+// the party every practice run at a level gets in the world
+pub fn standard_party(world: &OnionWorld, level: u32) -> Roster {
+    let mut rng = PRACTICE_SEED.rng();
+    let mut party = (0..PRACTICE_PARTY).map(|_| world.sample_at_level(level, &mut rng));
+    let mut roster = Roster::with_lead(party.next().unwrap());
+    party.for_each(|character| roster.recruit(character));
+    roster
+}
+
+// what's kept about the player between runs on this device
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl Profile {
+    // false if it was already unlocked
+    pub fn unlock(&mut self, checkpoint: Checkpoint) -> bool {
+        if self.checkpoints.iter().any(|other| other.same(&checkpoint)) {
+            return false;
+        }
+        self.checkpoints.push(checkpoint);
+        true
+    }
+}
+
+#[cfg(feature = "web")]
+pub fn load_profile() -> Profile {
+    LocalStorage::get(PROFILE_KEY).unwrap_or_default()
+}
+
+#[cfg(feature = "web")]
+pub fn store_profile(profile: &Profile) {
+    let _ = LocalStorage::set(PROFILE_KEY, profile);
+}
+
+#[cfg(test)]
+mod practice_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::gauntlet::BOSS_EVERY;
+
+    fn fake_world() -> OnionWorld {
+        Standard.sample(&mut StdRng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn checkpoint_test() {
+        let world = fake_world();
+        let mut run = Run::generate(&world, 5, &mut StdRng::seed_from_u64(0));
+        let id = WorldId::of(&world);
+        run.pick(0);
+        assert_eq!(Checkpoint::reached(id, Seed(1), &run), None);
+
+        // straight to the first boss
+        run.outcome = Outcome::Fighting(BOSS_EVERY - 1);
+        run.path = vec![0; BOSS_EVERY];
+        let checkpoint = Checkpoint::reached(id, Seed(1), &run).unwrap();
+        assert_eq!(checkpoint.floor, BOSS_EVERY - 1);
+        assert!(checkpoint.name().starts_with("Boss "));
+
+        let mut practice = checkpoint.run();
+        assert_eq!(practice.current(), Some(&Node { next: Vec::new(), ..checkpoint.boss.clone() }));
+        practice.win();
+        assert_eq!(practice.outcome, Outcome::Won);
+    }
+
+    #[test]
+    fn standard_party_test() {
+        let world = fake_world();
+        let party = standard_party(&world, 12);
+        assert_eq!(party.party().len(), PRACTICE_PARTY);
+        assert!(party.party().iter().all(|member| member.character.attributes.level == 12));
+        assert_eq!(party, standard_party(&world, 12));
+    }
+
+    #[test]
+    fn unlock_test() {
+        let world = fake_world();
+        let mut run = Run::generate(&world, 5, &mut StdRng::seed_from_u64(0));
+        run.outcome = Outcome::Fighting(BOSS_EVERY - 1);
+        run.path = vec![0; BOSS_EVERY];
+        let checkpoint = Checkpoint::reached(WorldId::of(&world), Seed(1), &run).unwrap();
+
+        let mut profile = Profile::default();
+        assert!(profile.unlock(checkpoint.clone()));
+        assert!(!profile.unlock(checkpoint.clone()));
+        assert!(profile.unlock(Checkpoint { seed: Seed(2), ..checkpoint }));
+        assert_eq!(profile.checkpoints.len(), 2);
+    }
+}
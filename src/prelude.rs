@@ -0,0 +1,38 @@
+// the stable way in for other frontends and modding tools: `use kaizo_quest::prelude::*;`. everything
+// else is still reachable through its own module, but can change without notice
+pub use crate::core::{Action, ActionId, Actions, Category, Character, Species, Stat, States, Stats};
+
+#[cfg(feature = "world")]
+pub use crate::adapter::{Event, GameLoop, Input};
+#[cfg(feature = "world")]
+pub use crate::ai::{AiPolicy, Difficulty};
+#[cfg(feature = "world")]
+pub use crate::content::{ContentError, WorldDefinition, load_world};
+#[cfg(feature = "world")]
+pub use crate::onion::{
+    ActionKind, ActionPool, Alignment, Attack, OnionAction, OnionBattle, OnionBattleState, OnionCharacter,
+    OnionWorld, SKIP_ID, Status, TypeChart, set_level,
+};
+#[cfg(feature = "world")]
+pub use crate::render::summary::{BattleSummary, Chip, SideSummary, battle_summary};
+#[cfg(feature = "world")]
+pub use crate::render::text::frame;
+#[cfg(feature = "world")]
+pub use crate::rules::Ruleset;
+#[cfg(feature = "world")]
+pub use crate::seed::{Seed, Stream, Streams};
+#[cfg(feature = "world")]
+pub use crate::session::{GameSession, SessionError};
+#[cfg(feature = "world")]
+pub use crate::sim::{BattleResult, simulate};
+#[cfg(feature = "world")]
+pub use crate::team::Team;
+
+// still finding their shape; these are here to be tried out, and can change between versions
+#[cfg(feature = "world")]
+pub mod experimental {
+    pub use crate::breeding::{Egg, breed};
+    pub use crate::correspondence::{Correspondence, TurnCode};
+    pub use crate::ghost::Ghost;
+    pub use crate::war::{Soldier, WarReport, war};
+}
@@ -0,0 +1,3 @@
+// ways to present a battle: a summary any frontend can draw, and plain text for ones without the yew app
+pub mod summary;
+pub mod text;
@@ -0,0 +1,123 @@
+use crate::core::{Pending, Stat};
+use crate::onion::{OnionBattle, OnionCharacter, Status};
+
+static STATS: [Stat; 5] = [Stat::Attack, Stat::Defense, Stat::SpecialAttack, Stat::SpecialDefense, Stat::Speed];
+
+// one thing that lasts between turns, short enough to sit in a strip: `+2 ATK`, `BLD 3`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chip {
+    pub label: String,
+    // the long form, for a tooltip
+    pub description: String,
+    // whether it helps whoever has it, for colouring
+    pub good: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SideSummary {
+    pub name: String,
+    pub chips: Vec<Chip>,
+}
+
+// what a frontend needs to show what's lasting on each side, without reading the battle's state
+#[derive(Clone, Debug, PartialEq)]
+pub struct BattleSummary {
+    pub player: SideSummary,
+    pub enemy: SideSummary,
+}
+
+pub fn battle_summary(battle: &OnionBattle) -> BattleSummary {
+    BattleSummary {
+        player: side(&battle.player, battle.player_spikes),
+        enemy: side(&battle.enemy, battle.enemy_spikes),
+    }
+}
+
+// stages first, then statuses, then whatever is waiting on the next turn
+fn side(character: &OnionCharacter, spikes: u32) -> SideSummary {
+    let mut chips: Vec<Chip> = STATS.iter()
+        .filter_map(|stat| match *character.state.stages.get(*stat) {
+            0 => None,
+            stage => Some(Chip {
+                label: format!("{:+} {}", stage, stat_label(*stat)),
+                description: format!("{} {} by {}", stat, if stage > 0 { "raised" } else { "lowered" }, stage.abs()),
+                good: stage > 0,
+            }),
+        })
+        .collect();
+    chips.extend(character.state.status.iter().map(|(status, amount)| Chip {
+        label: format!("{} {}", status_label(status), amount),
+        description: format!("{:?} ({})", status, amount),
+        good: status == Status::Defend,
+    }));
+    match character.state.pending {
+        Some(Pending::Charging(_)) | Some(Pending::Charged(_)) => chips.push(Chip {
+            label: "CHG".to_string(),
+            description: "Charging up a move".to_string(),
+            good: true,
+        }),
+        Some(Pending::Recharge) => chips.push(Chip {
+            label: "RCH".to_string(),
+            description: "Has to recharge".to_string(),
+            good: false,
+        }),
+        None => (),
+    }
+    if spikes > 0 {
+        chips.push(Chip {
+            label: format!("SPK {}", spikes),
+            description: format!("{} layers of spikes", spikes),
+            good: false,
+        });
+    }
+    SideSummary { name: character.name.to_string(), chips }
+}
+
+fn stat_label(stat: Stat) -> &'static str {
+    match stat {
+        Stat::Attack => "ATK",
+        Stat::Defense => "DEF",
+        Stat::SpecialAttack => "SPA",
+        Stat::SpecialDefense => "SPD",
+        Stat::Speed => "SPE",
+    }
+}
+
+fn status_label(status: Status) -> &'static str {
+    match status {
+        Status::Defend => "GRD",
+        Status::Bleed => "BLD",
+        Status::Stun => "STN",
+        Status::Burn => "BRN",
+        Status::Freeze => "FRZ",
+        Status::Confuse => "CNF",
+        Status::Poison => "PSN",
+    }
+}
+
+#[cfg(test)]
+mod summary_tests {
+    use super::*;
+
+    use crate::onion::testing;
+
+    #[test]
+    fn summary_test() {
+        let mut battle = OnionBattle::new(testing::fake_character(), testing::fake_character());
+        assert!(battle_summary(&battle).player.chips.is_empty());
+
+        battle.player.state.stages.attack = 2;
+        battle.player.state.stages.speed = -1;
+        battle.player.state.status.insert(Status::Bleed, 3);
+        battle.player.state.pending = Some(Pending::Recharge);
+        battle.player_spikes = 1;
+        let summary = battle_summary(&battle);
+        let labels: Vec<&str> = summary.player.chips.iter().map(|chip| chip.label.as_str()).collect();
+        assert_eq!(labels, vec!["+2 ATK", "-1 SPE", "BLD 3", "RCH", "SPK 1"]);
+        assert_eq!(summary.player.chips[0].description, "attack raised by 2");
+        assert_eq!(summary.player.chips.iter().filter(|chip| chip.good).count(), 1);
+        // each side only shows its own
+        assert!(summary.enemy.chips.is_empty());
+        assert_eq!(summary.enemy.name, "fake");
+    }
+}
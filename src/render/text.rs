@@ -0,0 +1,85 @@
+use crate::onion::{OnionBattle, OnionCharacter, alignment_names};
+
+// wide enough to read at a glance but narrow enough for a chat message on a phone
+pub static BAR_WIDTH: usize = 20;
+
+// `[#####-----]`, rounding partial health up so a living character never shows an empty bar
+pub fn health_bar(health: i32, max: u32, width: usize) -> String {
+    let health = health.max(0) as usize;
+    let filled = match max as usize {
+        0 => 0,
+        max => (health * width).div_ceil(max).min(width),
+    };
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+// `Pebble Lv20 Rock [########------------] 24/60 Bleed Stun`
+pub fn character_line(character: &OnionCharacter) -> String {
+    let max = character.attributes.stats.health;
+    let mut line = format!(
+        "{} Lv{} {} {} {}/{}",
+        character.name,
+        character.attributes.level,
+        alignment_names(&character.state.alignments()),
+        health_bar(character.state.health, max, BAR_WIDTH),
+        character.state.health,
+        max,
+    );
+    for status in character.state.status.keys() {
+        line.push_str(&format!(" {:?}", status));
+    }
+    line
+}
+
+// one screenful of battle: the enemy on top like the app, then the player, then what just happened
+pub fn frame(battle: &OnionBattle, logs: &[String]) -> String {
+    let mut lines = vec![character_line(&battle.enemy), character_line(&battle.player)];
+    lines.extend(logs.iter().map(|log| format!("> {}", log)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod text_tests {
+    use super::*;
+
+    use crate::core::{Character, Species, Stats};
+    use crate::onion::{Alignment, Status, testing};
+
+    fn fake_character(name: &str, health: i32) -> OnionCharacter {
+        let mut character = Character::from_species(Species {
+            name: name.into(),
+            alignment: Alignment::Paper,
+            ..testing::fake_species_with_bst(400)
+        });
+        character.attributes.level = 5;
+        character.attributes.stats = Stats::from_values(40, 10, 10, 10, 10, 10);
+        character.refresh();
+        character.state.health = health;
+        character
+    }
+
+    #[test]
+    fn health_bar_test() {
+        assert_eq!(health_bar(10, 10, 4), "[####]");
+        assert_eq!(health_bar(5, 10, 4), "[##--]");
+        assert_eq!(health_bar(1, 10, 4), "[#---]");
+        assert_eq!(health_bar(0, 10, 4), "[----]");
+        assert_eq!(health_bar(-3, 10, 4), "[----]");
+        assert_eq!(health_bar(3, 0, 4), "[----]");
+    }
+
+    #[test]
+    fn frame_test() {
+        let mut enemy = fake_character("Scrap", 10);
+        enemy.state.status.insert(Status::Stun, 1);
+        enemy.state.status.insert(Status::Bleed, 1);
+        enemy.state.secondary = Some(Alignment::Rock);
+        let battle = OnionBattle::new(fake_character("Pebble", 40), enemy);
+
+        assert_eq!(frame(&battle, &["Pebble used Cut.".to_string()]), [
+            "Scrap Lv5 Paper/Rock [#####---------------] 10/40 Bleed Stun",
+            "Pebble Lv5 Paper [####################] 40/40",
+            "> Pebble used Cut.",
+        ].join("\n"));
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::content::id::WorldId;
+use crate::core::{ActionId, States};
+use crate::encounters;
+use crate::onion::{ActionPool, OnionBattle, OnionBattleState, OnionCharacter};
+use crate::seed::Seed;
+
+// what happened between one look at the battle and the next
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayStep {
+    // both sides' picks; the player has none when its turn went on an item or a failed recruit
+    Turn { player: Option<ActionId>, enemy: ActionId },
+    // the player's kaizo changed between turns, from an item or from being sent out
+    Player(Box<OnionCharacter>),
+    // a trainer sent out their next kaizo
+    Enemy(Box<OnionCharacter>),
+    Flee,
+}
+
+// a battle as it started and every pick made in it, which is all it takes to play it out again:
+// like a correspondence battle, each turn rolls from the seed and the turn rather than the run's rng
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    // the pool the picks are ids into
+    pub world: WorldId,
+    pub battle: OnionBattle,
+    pub seed: Seed,
+    pub steps: Vec<ReplayStep>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayError {
+    Parse(String),
+    // the replay is of a battle in another world, so its picks would be other moves here
+    WrongWorld(WorldId),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Parse(message) => write!(f, "That isn't a replay: {}", message),
+            ReplayError::WrongWorld(world) => write!(f, "That replay is from world {}, not this one.", world),
+        }
+    }
+}
+
+impl Replay {
+    // from the battle once it has started, before the first turn
+    pub fn new(world: WorldId, battle: OnionBattle, seed: Seed) -> Replay {
+        Replay { world, battle, seed, steps: Vec::new() }
+    }
+
+    pub fn turns(&self) -> u32 {
+        self.steps.iter().filter(|step| matches!(step, ReplayStep::Turn { .. })).count() as u32
+    }
+
+    // what the next turn rolls with, both when it's played and when it's played back
+    pub fn turn_rng(&self) -> StdRng {
+        Seed(self.seed.0.wrapping_add(self.turns() as u64)).rng()
+    }
+
+    pub fn record(&mut self, step: ReplayStep) {
+        self.steps.push(step);
+    }
+
+    // compact json, to paste wherever
+    pub fn share(&self) -> String {
+        // the battle is all plain data, so this can't fail
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn parse(text: &str, world: WorldId) -> Result<Replay, ReplayError> {
+        let replay: Replay = serde_json::from_str(text.trim()).map_err(|error| ReplayError::Parse(error.to_string()))?;
+        if replay.world != world {
+            return Err(ReplayError::WrongWorld(replay.world));
+        }
+        Ok(replay)
+    }
+}
+
+// a replay being stepped through; the recording is left as it was, so it can be started over
+pub struct Playback {
+    replay: Replay,
+    battle: OnionBattle,
+    step: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        let battle = replay.battle.clone();
+        Playback { replay, battle, step: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay { &self.replay }
+
+    pub fn battle(&self) -> &OnionBattle { &self.battle }
+
+    pub fn done(&self) -> bool {
+        self.step >= self.replay.steps.len()
+    }
+
+    pub fn restart(&mut self) {
+        self.battle = self.replay.battle.clone();
+        self.step = 0;
+    }
+
+    // plays the next step, and what it logged; nothing once every step has been played
+    pub fn step(&mut self, actions: &ActionPool) -> Option<States> {
+        let step = self.replay.steps.get(self.step)?;
+        let turn = self.replay.steps[..self.step].iter().filter(|step| matches!(step, ReplayStep::Turn { .. })).count();
+        self.step += 1;
+        let mut logs = Vec::new();
+        match step {
+            ReplayStep::Turn { player, enemy } => {
+                let mut rng = Seed(self.replay.seed.0.wrapping_add(turn as u64)).rng();
+                let battle = &mut self.battle;
+                let player_action = player.map(|player| actions.spend(&mut battle.player, player));
+                let enemy_action = actions.spend(&mut battle.enemy, *enemy);
+                logs.extend(battle.resolve_turn(player_action, enemy_action, &mut rng));
+                let (state, end) = battle.end_turn();
+                logs.extend(end);
+                if state == OnionBattleState::InProcess {
+                    logs.extend(encounters::end_turn(battle, &mut rng).1);
+                }
+            },
+            ReplayStep::Player(character) => {
+                if character.name != self.battle.player.name {
+                    logs.push(format!("Go, {}!", character.name));
+                }
+                self.battle.player = character.as_ref().clone();
+            },
+            ReplayStep::Enemy(character) => {
+                logs.push(format!("{} was sent out!", character.name));
+                self.battle.enemy = character.as_ref().clone();
+            },
+            ReplayStep::Flee => {
+                logs.extend(self.battle.flee());
+                logs.extend(self.battle.end_turn().1);
+            },
+        }
+        Some(logs)
+    }
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::ai::{AiPolicy, Greedy, Random};
+    use crate::onion::OnionWorld;
+
+    // plays a battle out the way the app does, recording it as it goes
+    fn record(world: &OnionWorld, rng: &mut StdRng) -> (OnionBattle, Replay) {
+        let mut battle = OnionBattle::new(world.sample_at_level(8, rng), world.sample_at_level(8, rng));
+        battle.start();
+        let mut replay = Replay::new(WorldId(1), battle.clone(), Seed(rng.gen()));
+        while battle.battle_state() == OnionBattleState::InProcess {
+            let player = Greedy.choose(&battle.player, &battle.enemy, &battle.field, &world.actions, rng);
+            let enemy = Random.choose(&battle.enemy, &battle.player, &battle.field, &world.actions, rng);
+            let mut turn = replay.turn_rng();
+            let player_action = world.actions.spend(&mut battle.player, player);
+            let enemy_action = world.actions.spend(&mut battle.enemy, enemy);
+            battle.resolve_turn(Some(player_action), enemy_action, &mut turn);
+            replay.record(ReplayStep::Turn { player: Some(player), enemy });
+            if battle.end_turn().0 == OnionBattleState::InProcess {
+                encounters::end_turn(&mut battle, &mut turn);
+            }
+        }
+        (battle, replay)
+    }
+
+    #[test]
+    fn playback_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let (battle, replay) = record(&world, &mut rng);
+        assert!(replay.turns() > 0);
+
+        let mut playback = Playback::new(replay);
+        let mut logs = Vec::new();
+        while let Some(step) = playback.step(&world.actions) {
+            logs.extend(step);
+        }
+        assert!(playback.done());
+        assert!(!logs.is_empty());
+        // it ends the way the battle did
+        assert_eq!(playback.battle().player.state, battle.player.state);
+        assert_eq!(playback.battle().enemy.state, battle.enemy.state);
+
+        playback.restart();
+        assert_eq!(playback.battle().enemy.state, playback.replay().battle.enemy.state);
+        assert!(playback.step(&world.actions).is_some());
+    }
+
+    #[test]
+    fn share_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let (_, mut replay) = record(&world, &mut rng);
+        replay.record(ReplayStep::Flee);
+
+        let shared = Replay::parse(&replay.share(), WorldId(1)).unwrap();
+        assert_eq!(shared.steps, replay.steps);
+        assert_eq!(shared.seed, replay.seed);
+        assert_eq!(shared.battle.enemy, replay.battle.enemy);
+
+        assert_eq!(Replay::parse(&replay.share(), WorldId(2)).err(), Some(ReplayError::WrongWorld(WorldId(1))));
+        assert!(matches!(Replay::parse("not a replay", WorldId(1)), Err(ReplayError::Parse(_))));
+    }
+}
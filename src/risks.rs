@@ -0,0 +1,122 @@
+use std::fmt;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::config::boost_stats;
+use crate::core::States;
+use crate::onion::{OnionBattle, OnionWorld, Status};
+use crate::rules::BattleHook;
+
+// how much stronger enemies are once they've been empowered, in percent
+pub static EMPOWERED_BOOST: u32 = 10;
+
+// something the player can take on between battles in exchange for a reward; unlike a curse it's
+// never forced on them, but once taken it lasts the rest of the run
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Risk {
+    // enemies are tougher, for double the experience from every win
+    Empowered,
+    // the player's kaizo take twice the bleeding, for an extra pick from every draft
+    Frail,
+}
+
+pub static RISKS: [Risk; 2] = [Risk::Empowered, Risk::Frail];
+
+impl Risk {
+    pub fn description(&self) -> String {
+        match self {
+            Risk::Empowered => format!("Enemies have {}% more of every stat, but wins give double experience.", EMPOWERED_BOOST),
+            Risk::Frail => "Your kaizo take double bleeding for the rest of the run, but pick one more upgrade from every draft.".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Risk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl BattleHook for Risk {
+    fn name(&self) -> String { self.to_string() }
+
+    fn on_battle_start(&self, battle: &mut OnionBattle, _: &OnionWorld, _: &mut dyn RngCore) -> States {
+        match self {
+            Risk::Empowered => {
+                boost_stats(&mut battle.enemy, EMPOWERED_BOOST);
+                battle.experience_multiplier *= 2;
+                vec![format!("{} is empowered.", battle.enemy.name)]
+            },
+            Risk::Frail => {
+                battle.player.state.vulnerable.insert(Status::Bleed, 0);
+                vec![]
+            },
+        }
+    }
+}
+
+// how many upgrades each draft lets the player take
+pub fn draft_picks(risks: &[Risk]) -> usize {
+    1 + risks.iter().filter(|risk| **risk == Risk::Frail).count()
+}
+
+#[cfg(test)]
+mod risks_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::OnionBattleState;
+    use crate::rules::Ruleset;
+
+    fn fake_battle(world: &OnionWorld, rng: &mut StdRng) -> OnionBattle {
+        OnionBattle::new(world.sample_at_level(5, rng), world.sample_at_level(5, rng))
+    }
+
+    #[test]
+    fn empowered_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+        let enemy = battle.enemy.clone();
+
+        let rules = Ruleset { risks: vec![Risk::Empowered], ..Ruleset::default() };
+        let logs = rules.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(battle.enemy.attributes.stats.attack, enemy.attributes.stats.attack * 110 / 100);
+        assert_eq!(battle.experience_multiplier, 2);
+
+        // the win is worth twice as much
+        let mut plain = fake_battle(&world, &mut rng);
+        plain.enemy = battle.enemy.clone();
+        plain.player = battle.player.clone();
+        battle.enemy.state.health = 0;
+        plain.enemy.state.health = 0;
+        assert_eq!(battle.end_turn().0, OnionBattleState::Victory);
+        plain.end_turn();
+        let progress = |battle: &OnionBattle| (battle.player.attributes.level, battle.player.attributes.experience);
+        assert!(progress(&battle) > progress(&plain));
+    }
+
+    #[test]
+    fn frail_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+
+        let rules = Ruleset { risks: vec![Risk::Frail], ..Ruleset::default() };
+        rules.start_battle(&mut battle, &world, &mut rng);
+        assert!(battle.player.state.vulnerable.contains_key(&Status::Bleed));
+        assert!(!battle.enemy.state.vulnerable.contains_key(&Status::Bleed));
+    }
+
+    #[test]
+    fn draft_picks_test() {
+        assert_eq!(draft_picks(&[]), 1);
+        assert_eq!(draft_picks(&[Risk::Empowered]), 1);
+        assert_eq!(draft_picks(&RISKS), 2);
+    }
+}
@@ -0,0 +1,455 @@
+use std::cmp::Reverse;
+
+use num_traits::identities::Zero;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::breeding::{self, Egg, MAX_EGGS};
+use crate::core::{States, Stats};
+use crate::onion::{Experience, OnionCharacter, OnionWorld, settle};
+
+pub static PARTY_SIZE: usize = 6;
+// what the lead gets for each level of a kaizo it lets go
+pub static RELEASE_EXPERIENCE: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RosterOrder { Level, Bst, Alignment, Recency }
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub character: OnionCharacter,
+    // the order the character joined the roster in; used to sort by recency
+    pub recruited: u32,
+    // what it passes on to its eggs; only bred kaizo have any
+    #[serde(default = "Stats::zero")]
+    pub genes: Stats<u32>,
+}
+
+// every character the player owns. the party is ordered by hand and is fought with front to back;
+// everyone else sits in the reserve, which is kept sorted by `order`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Roster {
+    party: Vec<Member>,
+    reserve: Vec<Member>,
+    order: RosterOrder,
+    recruited: u32,
+    // kaizo that were kept rather than released, but don't fight or take up room anymore
+    #[serde(default)]
+    retired: Vec<Member>,
+    #[serde(default)]
+    eggs: Vec<Egg>,
+}
+
+impl Roster {
+    pub fn with_lead(character: OnionCharacter) -> Roster {
+        Roster {
+            party: vec![Member { character, recruited: 0, genes: Stats::zero() }],
+            reserve: vec![],
+            order: RosterOrder::Recency,
+            recruited: 1,
+            retired: vec![],
+            eggs: vec![],
+        }
+    }
+
+    pub fn party(&self) -> &[Member] { &self.party }
+
+    pub fn reserve(&self) -> &[Member] { &self.reserve }
+
+    pub fn order(&self) -> RosterOrder { self.order }
+
+    pub fn retired(&self) -> &[Member] { &self.retired }
+
+    pub fn eggs(&self) -> &[Egg] { &self.eggs }
+
+    // the party, the reserve and the eggs that will join them; retired kaizo don't count
+    pub fn count(&self) -> usize { self.party.len() + self.reserve.len() + self.eggs.len() }
+
+    // whether there's room for another recruit under a ruleset's `limit`
+    pub fn is_full(&self, limit: Option<usize>) -> bool {
+        limit.is_some_and(|limit| self.count() >= limit)
+    }
+
+    pub fn lead(&self) -> &OnionCharacter { &self.party[0].character }
+
+    pub fn get(&self, slot: usize) -> Option<&OnionCharacter> {
+        self.party.get(slot).map(|member| &member.character)
+    }
+
+    // writes a character that left the party (e.g. for a battle) back into its slot
+    pub fn set(&mut self, slot: usize, character: OnionCharacter) {
+        if let Some(member) = self.party.get_mut(slot) {
+            member.character = character;
+        }
+    }
+
+    // party members first, then the reserve, in the same order as `characters_mut`
+    pub fn member(&self, index: usize) -> Option<&Member> {
+        self.party.iter().chain(&self.reserve).nth(index)
+    }
+
+    // new characters join the party if there is room, otherwise they go to the reserve
+    pub fn recruit(&mut self, character: OnionCharacter) {
+        self.join(character, Stats::zero());
+    }
+
+    fn join(&mut self, character: OnionCharacter, genes: Stats<u32>) {
+        let member = Member { character, recruited: self.recruited, genes };
+        self.recruited += 1;
+        if self.party.len() < PARTY_SIZE {
+            self.party.push(member);
+        } else {
+            self.reserve.push(member);
+            self.sort(self.order);
+        }
+    }
+
+    pub fn sort(&mut self, order: RosterOrder) {
+        self.order = order;
+        // ties fall back to the newest character first
+        self.reserve.sort_by_key(|member| Reverse(member.recruited));
+        match order {
+            RosterOrder::Level => self.reserve.sort_by_key(|member| Reverse(member.character.attributes.level)),
+            RosterOrder::Bst => self.reserve.sort_by_key(|member| Reverse(member.character.species.bst)),
+            RosterOrder::Alignment => self.reserve.sort_by_key(|member| member.character.species.alignment),
+            RosterOrder::Recency => (),
+        }
+    }
+
+    // moves a party member to a new slot, shifting everyone in between
+    pub fn move_member(&mut self, from: usize, to: usize) {
+        if from < self.party.len() && to < self.party.len() {
+            let member = self.party.remove(from);
+            self.party.insert(to, member);
+        }
+    }
+
+    pub fn set_lead(&mut self, slot: usize) {
+        self.move_member(slot, 0);
+    }
+
+    // swaps a reserve character in as the lead; a full party sends its last member to the reserve
+    pub fn set_lead_from_reserve(&mut self, index: usize) {
+        if index < self.reserve.len() {
+            let member = self.reserve.remove(index);
+            self.party.insert(0, member);
+            if self.party.len() > PARTY_SIZE {
+                let member = self.party.pop().unwrap();
+                self.reserve.push(member);
+                self.sort(self.order);
+            }
+        }
+    }
+
+    // lets a reserve kaizo go for good, for some experience for the lead
+    pub fn release(&mut self, index: usize) -> Option<States> {
+        if index >= self.reserve.len() {
+            return None;
+        }
+        let released = self.reserve.remove(index).character;
+        let mut logs = vec![format!("Bye, {}!", released.name)];
+        logs.extend(self.party[0].character.gain_experience(released.attributes.level * RELEASE_EXPERIENCE));
+        Some(logs)
+    }
+
+    // moves a reserve kaizo to the archive, where it's kept but can't be brought back
+    pub fn retire(&mut self, index: usize) {
+        if index < self.reserve.len() {
+            let member = self.reserve.remove(index);
+            self.retired.push(member);
+        }
+    }
+
+    // lays an egg from two members, counted like `member`, if they're compatible and there's room
+    pub fn breed<R: Rng + ?Sized>(&mut self, first: usize, second: usize, world: &OnionWorld, rng: &mut R) -> Option<States> {
+        if first == second || self.eggs.len() >= MAX_EGGS {
+            return None;
+        }
+        let (parent, other) = (self.member(first)?, self.member(second)?);
+        let egg = breeding::breed(parent, other, world, rng)?;
+        let logs = vec![format!("{} and {} had an egg!", parent.character.name, other.character.name)];
+        self.eggs.push(egg);
+        Some(logs)
+    }
+
+    // every won battle brings the eggs closer to hatching; the ones that are ready join the roster
+    pub fn incubate(&mut self) -> States {
+        self.eggs.iter_mut().for_each(|egg| egg.battles = egg.battles.saturating_sub(1));
+        let (hatched, waiting): (Vec<Egg>, Vec<Egg>) = self.eggs.drain(..).partition(|egg| egg.battles == 0);
+        self.eggs = waiting;
+        hatched.into_iter().map(|egg| {
+            let log = format!("The egg hatched into {}!", egg.character.name);
+            self.join(egg.character, egg.genes);
+            log
+        }).collect()
+    }
+
+    // the first party member, in party order, that can still fight
+    pub fn next_conscious(&self) -> Option<usize> {
+        self.party.iter().position(|member| member.character.state.health > 0)
+    }
+
+    // removes the party members that fainted, for permadeath. if the whole party is gone the first
+    // of the reserve steps up, and if there's nobody left at all the party is left empty
+    pub fn bury(&mut self) -> Vec<OnionCharacter> {
+        let (fainted, conscious) = self.party.drain(..).partition(|member| member.character.state.health <= 0);
+        self.party = conscious;
+        if self.party.is_empty() && !self.reserve.is_empty() {
+            self.party.push(self.reserve.remove(0));
+        }
+        fainted.into_iter().map(|member: Member| member.character).collect()
+    }
+
+    // heals the party fully, e.g. from resting
+    pub fn refresh(&mut self) {
+        self.party.iter_mut().for_each(|member| member.character.refresh());
+    }
+
+    // tidies the party up after a battle without healing it
+    pub fn settle(&mut self) {
+        self.party.iter_mut().for_each(|member| settle(&mut member.character));
+    }
+
+    // the party and then the reserve
+    pub fn characters_mut(&mut self) -> impl Iterator<Item = &mut OnionCharacter> {
+        self.party.iter_mut().chain(self.reserve.iter_mut()).map(|member| &mut member.character)
+    }
+}
+
+#[cfg(test)]
+mod roster_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::breeding::{BREEDING_LEVEL, HATCH_BATTLES};
+    use crate::core::{Character, Species};
+    use crate::onion::{Alignment, Status, testing};
+
+    fn fake_character(name: &str, level: u32, bst: u32, alignment: Alignment) -> OnionCharacter {
+        let mut character = Character::from_species(Species { name: name.into(), alignment, ..testing::fake_species_with_bst(bst) });
+        character.attributes.level = level;
+        character.attributes.stats.health = 10;
+        character.refresh();
+        character
+    }
+
+    fn reserve_names(roster: &Roster) -> Vec<String> {
+        roster.reserve().iter().map(|member| member.character.name.to_string()).collect()
+    }
+
+    fn full_roster() -> Roster {
+        let mut roster = Roster::with_lead(fake_character("lead", 1, 1, Alignment::Rock));
+        (1..PARTY_SIZE).for_each(|_| roster.recruit(fake_character("party", 1, 1, Alignment::Rock)));
+        roster
+    }
+
+    #[test]
+    fn recruit_test() {
+        let mut roster = full_roster();
+        assert_eq!(roster.party().len(), PARTY_SIZE);
+        assert_eq!(roster.reserve().len(), 0);
+
+        roster.recruit(fake_character("reserve", 1, 1, Alignment::Rock));
+        assert_eq!(roster.party().len(), PARTY_SIZE);
+        assert_eq!(reserve_names(&roster), vec!["reserve"]);
+    }
+
+    #[test]
+    fn sort_test() {
+        let mut roster = full_roster();
+        roster.recruit(fake_character("a", 5, 300, Alignment::Scissors));
+        roster.recruit(fake_character("b", 10, 200, Alignment::Paper));
+        roster.recruit(fake_character("c", 1, 400, Alignment::Rock));
+
+        assert_eq!(reserve_names(&roster), vec!["c", "b", "a"]);
+
+        roster.sort(RosterOrder::Level);
+        assert_eq!(reserve_names(&roster), vec!["b", "a", "c"]);
+
+        roster.sort(RosterOrder::Bst);
+        assert_eq!(reserve_names(&roster), vec!["c", "a", "b"]);
+
+        roster.sort(RosterOrder::Alignment);
+        assert_eq!(reserve_names(&roster), vec!["c", "b", "a"]);
+
+        // new recruits respect the current order
+        roster.recruit(fake_character("d", 1, 350, Alignment::Rock));
+        roster.sort(RosterOrder::Bst);
+        assert_eq!(reserve_names(&roster), vec!["c", "d", "a", "b"]);
+    }
+
+    #[test]
+    fn move_member_test() {
+        let mut roster = Roster::with_lead(fake_character("a", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("b", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("c", 1, 1, Alignment::Rock));
+
+        roster.move_member(2, 0);
+        assert_eq!(&*roster.lead().name, "c");
+        assert_eq!(&*roster.get(1).unwrap().name, "a");
+        assert_eq!(&*roster.get(2).unwrap().name, "b");
+
+        // out of bounds moves are ignored
+        roster.move_member(0, 3);
+        assert_eq!(&*roster.lead().name, "c");
+    }
+
+    #[test]
+    fn set_lead_test() {
+        let mut roster = Roster::with_lead(fake_character("a", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("b", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("c", 1, 1, Alignment::Rock));
+
+        roster.set_lead(2);
+        assert_eq!(&*roster.lead().name, "c");
+        assert_eq!(&*roster.get(1).unwrap().name, "a");
+
+        roster.set_lead(0);
+        assert_eq!(&*roster.lead().name, "c");
+    }
+
+    #[test]
+    fn set_lead_from_reserve_test() {
+        let mut roster = full_roster();
+        roster.recruit(fake_character("a", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("b", 1, 1, Alignment::Rock));
+
+        // recency puts "b" first
+        roster.set_lead_from_reserve(0);
+        assert_eq!(&*roster.lead().name, "b");
+        assert_eq!(&*roster.get(1).unwrap().name, "lead");
+        assert_eq!(roster.party().len(), PARTY_SIZE);
+        assert_eq!(reserve_names(&roster), vec!["a", "party"]);
+
+        roster.set_lead_from_reserve(2);
+        assert_eq!(&*roster.lead().name, "b");
+    }
+
+    #[test]
+    fn next_conscious_test() {
+        let mut roster = Roster::with_lead(fake_character("a", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("b", 1, 1, Alignment::Rock));
+        roster.recruit(fake_character("c", 1, 1, Alignment::Rock));
+        roster.move_member(2, 1);
+        assert_eq!(roster.next_conscious(), Some(0));
+
+        let mut fainted = roster.lead().clone();
+        fainted.state.health = 0;
+        roster.set(0, fainted);
+        assert_eq!(roster.next_conscious(), Some(1));
+        assert_eq!(&*roster.get(1).unwrap().name, "c");
+
+        let mut fainted = roster.get(1).unwrap().clone();
+        fainted.state.health = 0;
+        roster.set(1, fainted.clone());
+        fainted.name = "b".into();
+        roster.set(2, fainted);
+        assert_eq!(roster.next_conscious(), None);
+
+        roster.refresh();
+        assert_eq!(roster.next_conscious(), Some(0));
+    }
+
+    #[test]
+    fn settle_test() {
+        let mut roster = Roster::with_lead(fake_character("a", 1, 1, Alignment::Rock));
+        let mut hurt = roster.lead().clone();
+        hurt.state.health = 3;
+        hurt.state.status.insert(Status::Bleed, 2);
+        hurt.state.status.insert(Status::Stun, 1);
+        hurt.state.status.insert(Status::Defend, 1);
+        hurt.state.stages.attack = 2;
+        hurt.attributes.pp.insert(0, 1);
+        roster.set(0, hurt);
+
+        roster.settle();
+        let lead = roster.lead();
+        assert_eq!(lead.state.health, 3);
+        assert_eq!(lead.state.status.keys().collect::<Vec<_>>(), vec![Status::Bleed, Status::Stun]);
+        assert_eq!(lead.state.stages.attack, 0);
+        assert_eq!(lead.attributes.pp.get(&0), Some(&1));
+
+        roster.refresh();
+        assert_eq!(roster.lead().state.health, 10);
+        assert!(roster.lead().state.status.is_empty());
+    }
+
+    #[test]
+    fn bury_test() {
+        let mut roster = full_roster();
+        roster.recruit(fake_character("reserve", 1, 1, Alignment::Rock));
+        let mut fainted = roster.get(1).unwrap().clone();
+        fainted.state.health = 0;
+        roster.set(1, fainted);
+        assert_eq!(roster.bury().len(), 1);
+        assert_eq!(roster.party().len(), PARTY_SIZE - 1);
+        assert_eq!(&*roster.lead().name, "lead");
+
+        // once the party is gone the reserve steps up
+        for slot in 0..roster.party().len() {
+            let mut fainted = roster.get(slot).unwrap().clone();
+            fainted.state.health = 0;
+            roster.set(slot, fainted);
+        }
+        assert_eq!(roster.bury().len(), PARTY_SIZE - 1);
+        assert_eq!(&*roster.lead().name, "reserve");
+        assert!(roster.reserve().is_empty());
+
+        let mut fainted = roster.lead().clone();
+        fainted.state.health = 0;
+        roster.set(0, fainted);
+        roster.bury();
+        assert!(roster.party().is_empty());
+    }
+
+    #[test]
+    fn release_test() {
+        let mut roster = full_roster();
+        roster.recruit(fake_character("a", 4, 1, Alignment::Rock));
+        roster.recruit(fake_character("b", 1, 1, Alignment::Rock));
+        assert_eq!(roster.count(), PARTY_SIZE + 2);
+        assert!(roster.is_full(Some(PARTY_SIZE + 2)));
+        assert!(!roster.is_full(None));
+
+        assert_eq!(roster.release(2), None);
+        assert_eq!(roster.release(1).unwrap(), vec!["Bye, a!", "Gained 20 experience!"]);
+        assert_eq!(roster.lead().attributes.experience, 20);
+        assert_eq!(reserve_names(&roster), vec!["b"]);
+
+        // retired kaizo are kept, but make room like released ones
+        roster.retire(0);
+        assert!(roster.reserve().is_empty());
+        assert_eq!(&*roster.retired()[0].character.name, "b");
+        assert!(!roster.is_full(Some(PARTY_SIZE + 1)));
+    }
+
+    #[test]
+    fn eggs_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let lead = world.sample_at_level(BREEDING_LEVEL, &mut rng);
+        let mut roster = Roster::with_lead(lead.clone());
+        roster.recruit(lead);
+        assert_eq!(roster.breed(0, 0, &world, &mut rng), None);
+        assert_eq!(roster.breed(0, 2, &world, &mut rng), None);
+        for _ in 0..MAX_EGGS {
+            assert!(roster.breed(0, 1, &world, &mut rng).is_some());
+        }
+        assert_eq!(roster.breed(0, 1, &world, &mut rng), None);
+        assert_eq!(roster.count(), 2 + MAX_EGGS);
+
+        for _ in 1..HATCH_BATTLES {
+            assert!(roster.incubate().is_empty());
+        }
+        let genes: Vec<Stats<u32>> = roster.eggs().iter().map(|egg| egg.genes).collect();
+        assert_eq!(roster.incubate().len(), MAX_EGGS);
+        assert!(roster.eggs().is_empty());
+        assert_eq!(roster.count(), 2 + MAX_EGGS);
+        // hatchlings keep their genes to pass on
+        assert_eq!(roster.party()[2..].iter().map(|member| member.genes).collect::<Vec<_>>(), genes);
+        assert_eq!(roster.member(2).unwrap().character.attributes.level, 1);
+    }
+}
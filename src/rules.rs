@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+
+use crate::clauses::Clause;
+use crate::core::States;
+use crate::endless::Curse;
+use crate::onion::{DEFAULT_CRIT_MULTIPLIER, DEFAULT_FRACTION_CAP, OnionBattle, OnionWorld, TypeChart};
+use crate::risks::Risk;
+
+// the roster limits that can be picked, in the order they're cycled through
+pub static ROSTER_LIMITS: [Option<usize>; 3] = [None, Some(30), Some(12)];
+
+// hooks let rulesets change a battle without the battle logic knowing about them
+pub trait BattleHook {
+    fn name(&self) -> String;
+
+    // called once both sides are known, before the first turn
+    fn on_battle_start(&self, _battle: &mut OnionBattle, _world: &OnionWorld, _rng: &mut dyn RngCore) -> States {
+        vec![]
+    }
+
+    // called after every turn that didn't end the battle
+    fn on_turn_end(&self, _battle: &mut OnionBattle, _rng: &mut dyn RngCore) -> States {
+        vec![]
+    }
+}
+
+// re-rolls the player's moveset from the world's pool at the start of every battle
+pub struct Chaos;
+
+impl BattleHook for Chaos {
+    fn name(&self) -> String { "Chaos".to_string() }
+
+    fn on_battle_start(&self, battle: &mut OnionBattle, world: &OnionWorld, rng: &mut dyn RngCore) -> States {
+        battle.player.attributes.actions = world.sample_actions(rng);
+        vec![format!("Chaos! {}'s moves were scrambled.", battle.player.name)]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ruleset {
+    pub chaos: bool,
+    pub inverse: bool,
+    pub clauses: Vec<Clause>,
+    // in tenths; the default multiplier when there isn't one
+    #[serde(default)]
+    pub crit_multiplier: Option<u32>,
+    // in percent; the most of a target's health an action that takes a share of it can take
+    #[serde(default)]
+    pub fraction_cap: Option<u32>,
+    // winning the gauntlet starts it again with another curse, instead of ending the run
+    #[serde(default)]
+    pub endless: bool,
+    // the curses stacked so far, every one applied each battle
+    #[serde(default)]
+    pub curses: Vec<Curse>,
+    // the risks taken on between battles, each kept for the rest of the run
+    #[serde(default)]
+    pub risks: Vec<Risk>,
+    // how many kaizo the party and reserve can hold between them; no limit when there isn't one
+    #[serde(default)]
+    pub roster_limit: Option<usize>,
+}
+
+impl Ruleset {
+    // the chart battles are fought with under these rules
+    pub fn chart(&self, chart: &TypeChart) -> TypeChart {
+        if self.inverse { chart.inverse() } else { chart.clone() }
+    }
+
+    pub fn cycle_roster_limit(&mut self) {
+        let index = ROSTER_LIMITS.iter().position(|limit| *limit == self.roster_limit).map_or(0, |index| index + 1);
+        self.roster_limit = ROSTER_LIMITS[index % ROSTER_LIMITS.len()];
+    }
+
+    pub fn hooks(&self) -> Vec<Box<dyn BattleHook>> {
+        let mut hooks: Vec<Box<dyn BattleHook>> = vec![];
+        if self.chaos {
+            hooks.push(Box::new(Chaos));
+        }
+        hooks.extend(self.curses.iter().map(|curse| Box::new(*curse) as Box<dyn BattleHook>));
+        hooks.extend(self.risks.iter().map(|risk| Box::new(*risk) as Box<dyn BattleHook>));
+        hooks
+    }
+
+    pub fn start_battle(&self, battle: &mut OnionBattle, world: &OnionWorld, rng: &mut dyn RngCore) -> States {
+        battle.field.chart = Arc::new(self.chart(world.chart()));
+        battle.field.clauses = self.clauses.clone();
+        battle.field.crit_multiplier = self.crit_multiplier.unwrap_or(DEFAULT_CRIT_MULTIPLIER);
+        battle.field.fraction_cap = self.fraction_cap.unwrap_or(DEFAULT_FRACTION_CAP);
+        battle.field.abilities = Arc::new(world.abilities.clone());
+        self.hooks().iter().flat_map(|hook| hook.on_battle_start(battle, world, rng)).collect()
+    }
+}
+
+#[cfg(test)]
+mod rules_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    fn fake_battle(world: &OnionWorld, rng: &mut StdRng) -> OnionBattle {
+        OnionBattle::new(world.sample_at_level(5, rng), world.sample_at_level(5, rng))
+    }
+
+    #[test]
+    fn default_ruleset_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+        let player = battle.player.clone();
+
+        let logs = Ruleset::default().start_battle(&mut battle, &world, &mut rng);
+        assert!(logs.is_empty());
+        assert_eq!(battle.player, player);
+    }
+
+    #[test]
+    fn chaos_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+        let player = battle.player.clone();
+
+        let logs = Ruleset { chaos: true, ..Ruleset::default() }.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(battle.player.attributes.actions.len(), 4);
+        assert_ne!(battle.player.attributes.actions, player.attributes.actions);
+        // only the moves change
+        assert_eq!(battle.player.attributes.stats, player.attributes.stats);
+        assert_eq!(battle.player.state, player.state);
+    }
+
+    #[test]
+    fn inverse_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+
+        let rules = Ruleset { inverse: true, ..Ruleset::default() };
+        rules.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(*battle.field.chart, world.chart().inverse());
+
+        Ruleset::default().start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(&*battle.field.chart, world.chart());
+    }
+
+    #[test]
+    fn clauses_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+
+        let rules = Ruleset { clauses: vec![Clause::Stun], ..Ruleset::default() };
+        rules.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(battle.field.clauses, vec![Clause::Stun]);
+    }
+
+    #[test]
+    fn crit_multiplier_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+
+        Ruleset { crit_multiplier: Some(20), ..Ruleset::default() }.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(battle.field.crit_multiplier, 20);
+        Ruleset::default().start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(battle.field.crit_multiplier, DEFAULT_CRIT_MULTIPLIER);
+    }
+
+    #[test]
+    fn fraction_cap_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut battle = fake_battle(&world, &mut rng);
+
+        Ruleset { fraction_cap: Some(25), ..Ruleset::default() }.start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(battle.field.fraction_cap, 25);
+        Ruleset::default().start_battle(&mut battle, &world, &mut rng);
+        assert_eq!(battle.field.fraction_cap, DEFAULT_FRACTION_CAP);
+    }
+}
@@ -0,0 +1,230 @@
+use gloo_storage::{LocalStorage, Storage};
+use gloo_storage::errors::StorageError;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::ai::{Difficulty, Habits};
+use crate::clock::Clock;
+use crate::config::GameConfig;
+use crate::gauntlet::Run;
+use crate::ghost::Ghost;
+use crate::items::Inventory;
+use crate::migrate::{MigrationError, Schema};
+use crate::onion::{OnionBattle, OnionWorld};
+use crate::replay::Replay;
+use crate::roster::Roster;
+use crate::rules::Ruleset;
+use crate::seed::Seed;
+use crate::trainers::Trainer;
+
+static SAVE_KEY: &str = "kaizo-quest.save";
+
+// saves without a version are all from version 1
+pub static SAVE_SCHEMA: Schema = Schema { version: 1, migrations: &[] };
+
+// a battle in progress
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Encounter {
+    pub battle: OnionBattle,
+    // the party slot that is fighting
+    pub slot: usize,
+    pub ai: Difficulty,
+    // wild kaizo can be recruited, but beating them doesn't count as a win
+    #[serde(default)]
+    pub wild: bool,
+    // turns played so far
+    #[serde(default)]
+    pub turns: u32,
+    // against the gauntlet's next master, rather than a mirror or a wild kaizo
+    #[serde(default)]
+    pub gauntlet: bool,
+    // the battle so far, to watch again once it's over; older saves don't have one
+    #[serde(default)]
+    pub replay: Option<Replay>,
+    // who the battle is against, when it's a trainer, with the kaizo they haven't sent out yet
+    #[serde(default)]
+    pub trainer: Option<Trainer>,
+    // a practice battle that can't cost or earn anything: the party as it was going in, to be put
+    // back once it's over
+    #[serde(default)]
+    pub sandbox: Option<Roster>,
+}
+
+// everything needed to pick a run back up; the team builder isn't saved since its teams are
+// stored separately
+// TODO: every character is saved with a full copy of its species; saving the species' index in
+//       the world instead would make saves a lot smaller
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub world: OnionWorld,
+    pub roster: Roster,
+    pub rules: Ruleset,
+    pub mirror_ai: Difficulty,
+    // the battle the run was saved in, if any
+    pub encounter: Option<Encounter>,
+    pub wins: u32,
+    // older saves didn't have any
+    #[serde(default)]
+    pub money: u32,
+    // older saves didn't have a seed
+    #[serde(default)]
+    pub seed: Seed,
+    // the world before any content packs were laid over it; missing when there weren't any
+    #[serde(default)]
+    pub base_world: Option<OnionWorld>,
+    // where the world was downloaded from, if it was
+    #[serde(default)]
+    pub world_url: Option<String>,
+    // the run's battles so far, for racing its ghost
+    #[serde(default)]
+    pub run: Ghost,
+    // how many times the run has been played, counting the first
+    #[serde(default)]
+    pub sessions: u32,
+    // the moves picked this run, for the adaptive ai
+    #[serde(default)]
+    pub habits: Habits,
+    // older saves didn't have one
+    #[serde(default)]
+    pub gauntlet: Option<Run>,
+    // older saves were played on classic
+    #[serde(default)]
+    pub config: GameConfig,
+    // older saves didn't have one
+    #[serde(default)]
+    pub inventory: Option<Inventory>,
+    // older saves start at the break of day
+    #[serde(default)]
+    pub clock: Clock,
+}
+
+// the save as text, to download or paste into another browser
+pub fn export(game: &SaveGame) -> String {
+    serde_json::to_string(&SAVE_SCHEMA.wrap(game)).unwrap()
+}
+
+// reads an exported save, or an untagged one from before saves had versions
+pub fn import(text: &str) -> Result<SaveGame, MigrationError> {
+    SAVE_SCHEMA.load(serde_json::from_str(text.trim()).map_err(|error| MigrationError::Parse(error.to_string()))?)
+}
+
+// brings a save's json from `from_version` up to date
+pub fn migrate(from_version: u32, json: Value) -> Result<SaveGame, MigrationError> {
+    SAVE_SCHEMA.migrate(from_version, json)
+}
+
+pub fn save(game: &SaveGame) -> Result<(), StorageError> {
+    LocalStorage::set(SAVE_KEY, SAVE_SCHEMA.wrap(game))
+}
+
+// saves from an older version that no longer parse are treated as missing
+pub fn load() -> Option<SaveGame> {
+    LocalStorage::get(SAVE_KEY).ok().and_then(|json| SAVE_SCHEMA.load(json).ok())
+}
+
+pub fn has_save() -> bool {
+    LocalStorage::raw().get_item(SAVE_KEY).ok().flatten().is_some()
+}
+
+pub fn delete_save() {
+    LocalStorage::delete(SAVE_KEY);
+}
+
+#[cfg(test)]
+mod save_tests {
+    use super::*;
+
+    use crate::content::id::WorldId;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    // local storage only exists in the browser, so this checks the part that can break natively
+    #[test]
+    fn round_trip_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let mut roster = Roster::with_lead(world.sample_at_level(5, &mut rng));
+        roster.recruit(world.sample_at_level(3, &mut rng));
+        let battle = OnionBattle::new(roster.lead().clone(), world.sample_at_level(5, &mut rng));
+        let mut habits = Habits::default();
+        habits.record(2, true);
+        let game = SaveGame {
+            world,
+            roster,
+            rules: Ruleset { chaos: true, ..Ruleset::default() },
+            mirror_ai: Difficulty::Easy,
+            encounter: Some(Encounter { battle, slot: 0, ai: Difficulty::Normal, wild: true, turns: 4, gauntlet: false, replay: None, trainer: None, sandbox: None }),
+            wins: 3,
+            money: 0,
+            seed: Seed(42),
+            base_world: None,
+            world_url: Some("https://example.com/world.json".to_string()),
+            run: Ghost { world: WorldId(7), seed: Seed(42), turns: vec![3, 4] },
+            sessions: 2,
+            habits,
+            gauntlet: None,
+            config: GameConfig::default(),
+            inventory: None,
+            clock: Clock::default(),
+        };
+
+        let loaded: SaveGame = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(loaded.world.species(), game.world.species());
+        assert_eq!(loaded.world.actions[5].name(), game.world.actions[5].name());
+        assert_eq!(loaded.roster, game.roster);
+        assert_eq!(loaded.rules, game.rules);
+        assert_eq!(loaded.mirror_ai, Difficulty::Easy);
+        let encounter = loaded.encounter.unwrap();
+        assert_eq!(encounter.battle.enemy, game.encounter.unwrap().battle.enemy);
+        assert!(encounter.wild);
+        assert_eq!(encounter.turns, 4);
+        assert_eq!(loaded.wins, 3);
+        assert_eq!(loaded.seed, Seed(42));
+        assert!(loaded.base_world.is_none());
+        assert_eq!(loaded.world_url, game.world_url);
+        assert_eq!(loaded.run, game.run);
+        assert_eq!(loaded.sessions, 2);
+        assert_eq!(loaded.habits, game.habits);
+    }
+
+    #[test]
+    fn import_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let game = SaveGame {
+            roster: Roster::with_lead(world.sample_at_level(5, &mut rng)),
+            world,
+            rules: Ruleset::default(),
+            mirror_ai: Difficulty::Easy,
+            encounter: None,
+            wins: 3,
+            money: 0,
+            seed: Seed(42),
+            base_world: None,
+            world_url: None,
+            run: Ghost::default(),
+            sessions: 1,
+            habits: Habits::default(),
+            gauntlet: None,
+            config: GameConfig::default(),
+            inventory: None,
+            clock: Clock::default(),
+        };
+
+        let exported = export(&game);
+        assert!(exported.starts_with(&format!("{{\"version\":{}", SAVE_SCHEMA.version)));
+        let imported = import(&exported).unwrap();
+        assert_eq!(imported.roster, game.roster);
+        assert_eq!(imported.wins, 3);
+        // saves from before they had versions
+        let untagged = import(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(untagged.seed, Seed(42));
+
+        let newer = exported.replacen(&SAVE_SCHEMA.version.to_string(), &(SAVE_SCHEMA.version + 1).to_string(), 1);
+        assert_eq!(import(&newer).err(), Some(MigrationError::Newer(SAVE_SCHEMA.version + 1)));
+        assert!(matches!(import("not a save"), Err(MigrationError::Parse(_))));
+        assert_eq!(migrate(1, serde_json::to_value(&game).unwrap()).unwrap().wins, 3);
+    }
+}
@@ -0,0 +1,93 @@
+use rand::Rng;
+
+use crate::onion::{OnionCharacter, Status};
+
+// the chance to recruit a wild kaizo at full health with no statuses
+static BASE_CAPTURE_CHANCE: f64 = 0.1;
+// how much of the rest of the chance is earned by wearing the kaizo down to 1 health
+static HEALTH_CAPTURE_WEIGHT: f64 = 0.7;
+static STUN_CAPTURE_BONUS: f64 = 0.2;
+static BLEED_CAPTURE_BONUS: f64 = 0.1;
+static FREEZE_CAPTURE_BONUS: f64 = 0.2;
+// burns, confusion and poison wear a kaizo down about as much as bleeding does
+static AILMENT_CAPTURE_BONUS: f64 = 0.1;
+// a kaizo that is defending is harder to catch
+static DEFEND_CAPTURE_PENALTY: f64 = 0.1;
+
+// how likely a wild kaizo is to join when recruited, from 0 to 1. the less health it has left and
+// the more it's hindered, the likelier it is
+pub fn capture_chance(target: &OnionCharacter) -> f64 {
+    if target.state.health <= 0 {
+        return 0.0;
+    }
+    let max = target.attributes.stats.health.max(1) as f64;
+    let missing = 1.0 - (target.state.health as f64 / max).min(1.0);
+    let mut chance = BASE_CAPTURE_CHANCE + (1.0 - BASE_CAPTURE_CHANCE) * HEALTH_CAPTURE_WEIGHT * missing;
+    for status in target.state.status.keys() {
+        chance += match status {
+            Status::Stun => STUN_CAPTURE_BONUS,
+            Status::Bleed => BLEED_CAPTURE_BONUS,
+            Status::Freeze => FREEZE_CAPTURE_BONUS,
+            Status::Burn | Status::Confuse | Status::Poison => AILMENT_CAPTURE_BONUS,
+            Status::Defend => -DEFEND_CAPTURE_PENALTY,
+        };
+    }
+    chance.clamp(0.0, 1.0)
+}
+
+pub fn try_capture<R: Rng + ?Sized>(target: &OnionCharacter, rng: &mut R) -> bool {
+    rng.gen_bool(capture_chance(target))
+}
+
+#[cfg(test)]
+mod scout_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::core::{Character, Species};
+    use crate::onion::testing;
+
+    fn fake_character(health: i32) -> OnionCharacter {
+        let mut character = Character::from_species(Species { name: "Pebble".into(), ..testing::fake_species_with_bst(400) });
+        character.attributes.stats.health = 40;
+        character.refresh();
+        character.state.health = health;
+        character
+    }
+
+    #[test]
+    fn capture_chance_test() {
+        let full = capture_chance(&fake_character(40));
+        let half = capture_chance(&fake_character(20));
+        let low = capture_chance(&fake_character(1));
+        assert_eq!(full, BASE_CAPTURE_CHANCE);
+        assert!(full < half && half < low && low < 1.0);
+        assert_eq!(capture_chance(&fake_character(0)), 0.0);
+
+        let mut stunned = fake_character(20);
+        stunned.state.status.insert(Status::Stun, 1);
+        assert!(capture_chance(&stunned) > half);
+        stunned.state.status.insert(Status::Bleed, 1);
+        let hindered = capture_chance(&stunned);
+        assert!(hindered > half + STUN_CAPTURE_BONUS);
+
+        let mut defending = fake_character(40);
+        defending.state.status.insert(Status::Defend, 0);
+        assert_eq!(capture_chance(&defending), 0.0);
+    }
+
+    #[test]
+    fn try_capture_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(!try_capture(&fake_character(0), &mut rng));
+
+        let mut weak = fake_character(1);
+        weak.state.status.insert(Status::Stun, 1);
+        weak.state.status.insert(Status::Bleed, 1);
+        assert!((0..10).all(|_| try_capture(&weak, &mut rng)));
+        let captured = (0..1000).filter(|_| try_capture(&fake_character(40), &mut rng)).count();
+        assert!((50..150).contains(&captured));
+    }
+}
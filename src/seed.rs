@@ -0,0 +1,179 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::{random, SeedableRng};
+use rand::rngs::StdRng;
+use serde_derive::{Deserialize, Serialize};
+
+// everything random in a run comes from one of these, so a run can be shared and replayed by
+// passing its seed around. shown in base 36 to keep it short enough to type
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    pub fn random() -> Seed {
+        Seed(random())
+    }
+
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.0)
+    }
+
+    // the rolls of one part of the game, apart from the others
+    pub fn stream(&self, stream: Stream) -> StdRng {
+        StdRng::seed_from_u64(self.0 ^ stream.salt())
+    }
+}
+
+// the parts of a run that roll. each draws from its own stream of the seed, so a new roll in one of
+// them doesn't shift what every other one rolls
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Stream {
+    // the world and the run's map
+    Worldgen,
+    // the kaizo met along the way, wild or not
+    Encounters,
+    // everything rolled once a battle has started, the ai's picks included
+    Battle,
+    // treasure, items, drafts and eggs
+    Drops,
+    // what the run's shop sells
+    Shop,
+    // the individual values of the kaizo met along the way
+    Ivs,
+}
+
+pub static STREAMS: [Stream; 6] = [Stream::Worldgen, Stream::Encounters, Stream::Battle, Stream::Drops, Stream::Shop, Stream::Ivs];
+
+impl Stream {
+    // worldgen draws from the seed as it is, so a seed still makes the world it always has
+    fn salt(&self) -> u64 {
+        match self {
+            Stream::Worldgen => 0,
+            Stream::Encounters => 0x9e37_79b9_7f4a_7c15,
+            Stream::Battle => 0xbf58_476d_1ce4_e5b9,
+            Stream::Drops => 0x94d0_49bb_1331_11eb,
+            Stream::Shop => 0xd6e8_feb8_6659_fd93,
+            Stream::Ivs => 0xa076_1d64_78bd_642f,
+        }
+    }
+}
+
+// a rng for each stream, for something that rolls in all of them like a run
+#[derive(Clone, Debug)]
+pub struct Streams {
+    pub worldgen: StdRng,
+    pub encounters: StdRng,
+    pub battle: StdRng,
+    pub drops: StdRng,
+    pub shop: StdRng,
+    pub ivs: StdRng,
+}
+
+impl Streams {
+    pub fn new(seed: Seed) -> Streams {
+        Streams {
+            worldgen: seed.stream(Stream::Worldgen),
+            encounters: seed.stream(Stream::Encounters),
+            battle: seed.stream(Stream::Battle),
+            drops: seed.stream(Stream::Drops),
+            shop: seed.stream(Stream::Shop),
+            ivs: seed.stream(Stream::Ivs),
+        }
+    }
+
+    pub fn get(&mut self, stream: Stream) -> &mut StdRng {
+        match stream {
+            Stream::Worldgen => &mut self.worldgen,
+            Stream::Encounters => &mut self.encounters,
+            Stream::Battle => &mut self.battle,
+            Stream::Drops => &mut self.drops,
+            Stream::Shop => &mut self.shop,
+            Stream::Ivs => &mut self.ivs,
+        }
+    }
+}
+
+// uppercase, so it reads the same as it's typed back in
+pub fn base36(mut n: u64) -> String {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(std::char::from_digit((n % 36) as u32, 36).unwrap().to_ascii_uppercase());
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", base36(self.0))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedParseError(pub String);
+
+impl fmt::Display for SeedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\" is not a seed; seeds are made of letters and digits.", self.0)
+    }
+}
+
+impl FromStr for Seed {
+    type Err = SeedParseError;
+
+    fn from_str(s: &str) -> Result<Seed, SeedParseError> {
+        u64::from_str_radix(s.trim(), 36).map(Seed).map_err(|_| SeedParseError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use super::*;
+
+    use rand::Rng;
+
+    #[test]
+    fn text_test() {
+        assert_eq!(Seed(0).to_string(), "0");
+        assert_eq!(Seed(35).to_string(), "Z");
+        assert_eq!(Seed(36).to_string(), "10");
+        for seed in [Seed(0), Seed(12345), Seed(u64::MAX)] {
+            assert_eq!(seed.to_string().parse(), Ok(seed));
+        }
+        assert_eq!(" 1z ".parse(), Ok(Seed(71)));
+        assert!("".parse::<Seed>().is_err());
+        assert!("no-dashes".parse::<Seed>().is_err());
+    }
+
+    #[test]
+    fn rng_test() {
+        let seed = Seed(7);
+        assert_eq!(seed.rng().gen::<u64>(), seed.rng().gen::<u64>());
+        assert_ne!(seed.rng().gen::<u64>(), Seed(8).rng().gen::<u64>());
+    }
+
+    #[test]
+    fn stream_test() {
+        let seed = Seed(7);
+        assert_eq!(seed.stream(Stream::Worldgen).gen::<u64>(), seed.rng().gen::<u64>());
+        let firsts: Vec<u64> = STREAMS.iter().map(|stream| seed.stream(*stream).gen()).collect();
+        for (index, first) in firsts.iter().enumerate() {
+            assert!(!firsts[..index].contains(first));
+        }
+
+        // rolling more in one stream leaves the others where they were
+        let mut quiet = Streams::new(seed);
+        let mut busy = Streams::new(seed);
+        for _ in 0..10 {
+            busy.get(Stream::Battle).gen::<u64>();
+        }
+        for stream in [Stream::Worldgen, Stream::Encounters, Stream::Drops] {
+            assert_eq!(quiet.get(stream).gen::<u64>(), busy.get(stream).gen::<u64>());
+        }
+        assert_ne!(quiet.battle.gen::<u64>(), busy.battle.gen::<u64>());
+    }
+}
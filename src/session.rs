@@ -0,0 +1,450 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{Difficulty, Habits};
+use crate::clock::{Clock, TimeOfDay};
+use crate::config::GameConfig;
+use crate::content::id::WorldId;
+use crate::core::{ActionId, Actions, States};
+use crate::encounters::EncounterKind;
+use crate::ghost::Ghost;
+use crate::ivs;
+use crate::modifiers::{Modifier, active, badges, multiplier};
+use crate::onion::{OnionBattle, OnionBattleState, OnionCharacter, OnionWorld, learn_moves, replace_move};
+use crate::rules::Ruleset;
+use crate::seed::{Seed, Streams};
+use crate::sim::play_turn;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SessionError {
+    NoBattle,
+    InBattle,
+    // the player's move slot that doesn't exist
+    NoSuchMove(usize),
+    NothingToLearn,
+    NoMulligans,
+    // there are no mulligans on kaizo
+    MulligansDisabled,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::NoBattle => write!(f, "There is no battle going on."),
+            SessionError::InBattle => write!(f, "A battle is already going on."),
+            SessionError::NoSuchMove(slot) => write!(f, "There is no move in slot {}.", slot.wrapping_add(1)),
+            SessionError::NothingToLearn => write!(f, "There is no move waiting to be learned."),
+            SessionError::NoMulligans => write!(f, "There are no mulligans left."),
+            SessionError::MulligansDisabled => write!(f, "There are no mulligans on kaizo."),
+        }
+    }
+}
+
+// how a run went, and what it was played with, so it's only ranked against runs in the same world
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunResult {
+    pub world: WorldId,
+    pub seed: Seed,
+    pub wins: u32,
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>,
+}
+
+impl RunResult {
+    // wins scaled by how much harder the modifiers made them
+    pub fn score(&self) -> u32 {
+        (self.wins as f64 * multiplier(&self.modifiers)).round() as u32
+    }
+}
+
+impl fmt::Display for RunResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} wins on seed {} in world {}", self.wins, self.seed, self.world)?;
+        if !self.modifiers.is_empty() {
+            write!(f, " {}", badges(&self.modifiers))?;
+        }
+        write!(f, ", scoring {}", self.score())
+    }
+}
+
+// how many times a run can re-roll the enemy it's about to battle
+pub static MULLIGANS: u32 = 3;
+
+// a single kaizo fighting an endless string of random enemies, for frontends that don't want to
+// know how a battle is put together
+// TODO: the yew app still drives its battles by hand
+pub struct GameSession {
+    world: OnionWorld,
+    world_id: WorldId,
+    player: OnionCharacter,
+    battle: Option<OnionBattle>,
+    pub ai: Difficulty,
+    pub rules: Ruleset,
+    // whether the kaizo is healed after the battles it wins
+    pub config: GameConfig,
+    seed: Seed,
+    rng: Streams,
+    wins: u32,
+    // moves the kaizo reached the level for without room to learn them, first one first
+    learning: Actions,
+    // the turns taken in the current battle and in every battle won, to race later runs against
+    turns: u32,
+    run: Ghost,
+    habits: Habits,
+    // the enemy the next battle is against, known ahead so it can be re-rolled with a mulligan
+    upcoming: OnionCharacter,
+    mulligans: u32,
+    // the modifiers every battle so far was played with; none until the first battle
+    modifiers: Option<Vec<Modifier>>,
+    // sessions can't tell the time, so their days only go by in battles
+    clock: Clock,
+}
+
+impl GameSession {
+    pub fn new(world: OnionWorld, player: OnionCharacter, seed: Seed) -> GameSession {
+        let world_id = WorldId::of(&world);
+        let mut rng = Streams::new(seed);
+        let clock = Clock::default();
+        let mut upcoming = world.sample_encounter(player.attributes.level, clock.time(&world.cycle, None), &mut rng.encounters);
+        ivs::assign(&mut upcoming, &mut rng.ivs);
+        GameSession {
+            world_id,
+            world,
+            player,
+            battle: None,
+            ai: Difficulty::Normal,
+            rules: Ruleset::default(),
+            config: GameConfig::default(),
+            seed,
+            rng,
+            wins: 0,
+            learning: Vec::new(),
+            turns: 0,
+            run: Ghost::new(world_id, seed),
+            habits: Habits::default(),
+            upcoming,
+            mulligans: MULLIGANS,
+            modifiers: None,
+            clock,
+        }
+    }
+
+    pub fn world(&self) -> &OnionWorld { &self.world }
+
+    // the player's kaizo as it is between battles
+    pub fn player(&self) -> &OnionCharacter { &self.player }
+
+    pub fn battle(&self) -> Option<&OnionBattle> { self.battle.as_ref() }
+
+    pub fn wins(&self) -> u32 { self.wins }
+
+    pub fn world_id(&self) -> WorldId { self.world_id }
+
+    pub fn run(&self) -> &Ghost { &self.run }
+
+    pub fn upcoming(&self) -> &OnionCharacter { &self.upcoming }
+
+    pub fn time(&self) -> TimeOfDay { self.clock.time(&self.world.cycle, None) }
+
+    fn sample_upcoming(&mut self) -> OnionCharacter {
+        let mut upcoming = self.world.sample_encounter(self.player.attributes.level, self.time(), &mut self.rng.encounters);
+        ivs::assign(&mut upcoming, &mut self.rng.ivs);
+        upcoming
+    }
+
+    // none on kaizo
+    pub fn mulligans(&self) -> u32 {
+        if self.ai == Difficulty::Kaizo { 0 } else { self.mulligans }
+    }
+
+    // re-rolls the enemy the next battle is against, using up a mulligan
+    pub fn mulligan(&mut self) -> Result<States, SessionError> {
+        if self.battle.is_some() {
+            return Err(SessionError::InBattle);
+        }
+        if self.ai == Difficulty::Kaizo {
+            return Err(SessionError::MulligansDisabled);
+        }
+        if self.mulligans == 0 {
+            return Err(SessionError::NoMulligans);
+        }
+        self.mulligans -= 1;
+        let upcoming = self.sample_upcoming();
+        let old = std::mem::replace(&mut self.upcoming, upcoming);
+        Ok(vec![format!("{} was swapped out for {}.", old.name, self.upcoming.name)])
+    }
+
+    // modifiers only count if they were on for the whole run
+    pub fn result(&self) -> RunResult {
+        let modifiers = self.modifiers.clone().unwrap_or_else(|| active(self.ai, &self.rules));
+        RunResult { world: self.world_id, seed: self.seed, wins: self.wins, modifiers }
+    }
+
+    // the move the kaizo wants to learn, if it has to forget one for it
+    pub fn learning(&self) -> Option<ActionId> { self.learning.first().copied() }
+
+    // learns the waiting move over the one in `slot`, or gives up on it without a slot
+    pub fn learn(&mut self, slot: Option<usize>) -> Result<States, SessionError> {
+        let action = self.learning().ok_or(SessionError::NothingToLearn)?;
+        if let Some(slot) = slot.filter(|slot| *slot >= self.player.attributes.actions.len()) {
+            return Err(SessionError::NoSuchMove(slot));
+        }
+        self.learning.remove(0);
+        Ok(replace_move(&mut self.player, slot, action, &self.world.actions))
+    }
+
+    // moves still waiting to be learned are given up on
+    pub fn start_battle(&mut self) -> Result<States, SessionError> {
+        if self.battle.is_some() {
+            return Err(SessionError::InBattle);
+        }
+        self.learning.clear();
+        self.turns = 0;
+        let active = active(self.ai, &self.rules);
+        self.modifiers = Some(match self.modifiers.take() {
+            Some(modifiers) => modifiers.into_iter().filter(|modifier| active.contains(modifier)).collect(),
+            None => active,
+        });
+        let mut battle = OnionBattle::new(self.player.clone(), self.upcoming.clone());
+        battle.kind = EncounterKind::Wild;
+        let mut logs = vec![format!("A wild {} appeared!", battle.enemy.name)];
+        logs.extend(self.rules.start_battle(&mut battle, &self.world, &mut self.rng.battle));
+        logs.extend(battle.start());
+        self.battle = Some(battle);
+        Ok(logs)
+    }
+
+    // plays out a turn with the move in the player's `slot`; once the battle is over the player's
+    // kaizo is kept, and healed if it lost since there's nobody else to send out
+    pub fn choose(&mut self, slot: usize) -> Result<(OnionBattleState, States), SessionError> {
+        let battle = self.battle.as_mut().ok_or(SessionError::NoBattle)?;
+        let player_action = *battle.player.attributes.actions.get(slot).ok_or(SessionError::NoSuchMove(slot))?;
+        let opening = self.turns == 0;
+        let enemy_action = self.ai.policy(&self.habits, opening).choose(&battle.enemy, &battle.player, &battle.field, &self.world.actions, &mut self.rng.battle);
+        self.habits.record(player_action, opening);
+        let (state, mut logs) = play_turn(battle, player_action, enemy_action, &self.world.actions, &mut self.rng.battle);
+        self.turns += 1;
+
+        if state != OnionBattleState::InProcess {
+            if state == OnionBattleState::Victory {
+                self.wins += 1;
+                self.run.record(self.turns);
+            }
+            logs.extend(self.end_battle());
+        }
+        Ok((state, logs))
+    }
+
+    pub fn flee(&mut self) -> Result<States, SessionError> {
+        let battle = self.battle.as_mut().ok_or(SessionError::NoBattle)?;
+        let mut logs = battle.flee();
+        logs.extend(battle.end_turn().1);
+        logs.extend(self.end_battle());
+        Ok(logs)
+    }
+
+    fn end_battle(&mut self) -> States {
+        match self.battle.take() {
+            Some(battle) => {
+                self.player = battle.player;
+                if self.player.state.health > 0 {
+                    self.config.recover(&mut self.player);
+                } else {
+                    self.player.refresh();
+                }
+                let (mut logs, waiting) = learn_moves(&mut self.player, &battle.learnable, &self.world.actions);
+                self.learning = waiting;
+                logs.extend(self.clock.tick(&self.world.cycle));
+                // after learning, so the enemy is at the level the kaizo just reached, and met at
+                // the time the battle left it
+                self.upcoming = self.sample_upcoming();
+                logs
+            },
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::config::Preset;
+
+    fn fake_session() -> GameSession {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let player = world.sample_at_level(5, &mut rng);
+        GameSession::new(world, player, Seed(0))
+    }
+
+    #[test]
+    fn battle_flow_test() {
+        let mut session = fake_session();
+        assert_eq!(session.choose(0), Err(SessionError::NoBattle));
+        assert_eq!(session.flee(), Err(SessionError::NoBattle));
+
+        let logs = session.start_battle().unwrap();
+        assert!(logs[0].starts_with("A wild "));
+        assert_eq!(session.start_battle(), Err(SessionError::InBattle));
+        assert_eq!(session.choose(4), Err(SessionError::NoSuchMove(4)));
+
+        let mut turns = 0;
+        let state = loop {
+            let (state, logs) = session.choose(turns % 4).unwrap();
+            assert!(!logs.is_empty());
+            turns += 1;
+            if state != OnionBattleState::InProcess || turns > 1000 {
+                break state;
+            }
+        };
+        assert_ne!(state, OnionBattleState::InProcess);
+        assert!(session.battle().is_none());
+        assert_eq!(session.wins(), if state == OnionBattleState::Victory { 1 } else { 0 });
+        let result = RunResult {
+            world: WorldId::of(session.world()),
+            seed: Seed(0),
+            wins: session.wins(),
+            modifiers: vec![Modifier::Difficulty(Difficulty::Normal)],
+        };
+        assert_eq!(session.result(), result);
+        // wins are kept to race later runs against
+        assert_eq!(session.run().wins(), session.wins() as usize);
+        assert_eq!(session.run().turns.iter().sum::<u32>(), if state == OnionBattleState::Victory { turns as u32 } else { 0 });
+        // the kaizo comes back healed from a loss
+        if state != OnionBattleState::Victory {
+            assert_eq!(session.player().state.health, session.player().attributes.stats.health as i32);
+        }
+    }
+
+    #[test]
+    fn flee_test() {
+        let mut session = fake_session();
+        session.start_battle().unwrap();
+        session.choose(0).unwrap();
+
+        assert_eq!(session.flee().unwrap().len(), 1);
+        assert!(session.battle().is_none());
+        assert_eq!(session.wins(), 0);
+        // it keeps its wounds, except on casual
+        assert!(session.player().state.health < session.player().attributes.stats.health as i32);
+
+        let mut session = fake_session();
+        session.config = GameConfig::preset(Preset::Casual);
+        session.start_battle().unwrap();
+        session.choose(0).unwrap();
+        session.flee().unwrap();
+        assert_eq!(session.player().state.health, session.player().attributes.stats.health as i32);
+    }
+
+    #[test]
+    fn clock_test() {
+        let mut session = fake_session();
+        let length = session.world().cycle.length;
+        for _ in 1..length {
+            session.start_battle().unwrap();
+            session.flee().unwrap();
+            assert_eq!(session.time(), TimeOfDay::Day);
+        }
+        // every battle counts, won or not
+        session.start_battle().unwrap();
+        assert!(session.flee().unwrap().contains(&"Night fell.".to_string()));
+        assert_eq!(session.time(), TimeOfDay::Night);
+    }
+
+    #[test]
+    fn learn_test() {
+        let mut session = fake_session();
+        assert_eq!(session.learn(None), Err(SessionError::NothingToLearn));
+        let known = session.player().attributes.actions.clone();
+        let new: Vec<ActionId> = session.world().actions.ids().filter(|action| !known.contains(action)).take(3).collect();
+
+        // with a free slot the first move is learned straight away
+        session.player.attributes.actions.pop();
+        session.start_battle().unwrap();
+        session.battle.as_mut().unwrap().learnable = new.clone();
+        let logs = session.flee().unwrap();
+        assert!(logs[1].contains(" learned "));
+        assert!(logs[2].contains(" wants to learn "));
+        assert_eq!(session.player().attributes.actions[3], new[0]);
+        assert_eq!(session.learning(), Some(new[1]));
+
+        assert_eq!(session.learn(Some(4)), Err(SessionError::NoSuchMove(4)));
+        assert!(session.learn(Some(0)).unwrap()[0].contains(" forgot "));
+        assert_eq!(session.player().attributes.actions[0], new[1]);
+        assert_eq!(session.learning(), Some(new[2]));
+        session.learn(None).unwrap();
+        assert!(!session.player().attributes.actions.contains(&new[2]));
+        assert_eq!(session.learning(), None);
+    }
+
+    #[test]
+    fn mulligan_test() {
+        let mut session = fake_session();
+        let upcoming = session.upcoming().clone();
+        for left in (0..MULLIGANS).rev() {
+            assert_eq!(session.mulligan().unwrap().len(), 1);
+            assert_eq!(session.mulligans(), left);
+        }
+        assert_ne!(session.upcoming(), &upcoming);
+        assert_eq!(session.mulligan(), Err(SessionError::NoMulligans));
+
+        // the battle is against the enemy that was shown
+        let upcoming = session.upcoming().clone();
+        session.start_battle().unwrap();
+        assert_eq!(session.battle().unwrap().enemy.species, upcoming.species);
+        assert_eq!(session.mulligan(), Err(SessionError::InBattle));
+        session.flee().unwrap();
+        // met at the levels of the area the player's kaizo searches
+        let levels = session.world().area(session.player().attributes.level).unwrap().levels;
+        assert!((levels.0..=levels.1).contains(&session.upcoming().attributes.level));
+
+        let mut session = fake_session();
+        session.ai = Difficulty::Kaizo;
+        assert_eq!(session.mulligans(), 0);
+        assert_eq!(session.mulligan(), Err(SessionError::MulligansDisabled));
+    }
+
+    #[test]
+    fn modifiers_test() {
+        let mut session = fake_session();
+        session.ai = Difficulty::Kaizo;
+        session.rules.chaos = true;
+        assert_eq!(session.result().modifiers, vec![Modifier::Difficulty(Difficulty::Kaizo), Modifier::Chaos]);
+
+        session.start_battle().unwrap();
+        session.flee().unwrap();
+        // turning chaos off for a battle loses it for the run, even once it's back on
+        session.rules.chaos = false;
+        session.start_battle().unwrap();
+        session.flee().unwrap();
+        session.rules.chaos = true;
+        assert_eq!(session.result().modifiers, vec![Modifier::Difficulty(Difficulty::Kaizo)]);
+
+        session.wins = 3;
+        assert_eq!(session.result().score(), 6);
+        assert_eq!(session.result().to_string(), format!("3 wins on seed 0 in world {} [Kaizo], scoring 6", session.world_id()));
+    }
+
+    #[test]
+    fn replay_test() {
+        let play = || {
+            let mut session = fake_session();
+            let mut logs = session.start_battle().unwrap();
+            for turn in 0..10 {
+                match session.choose(turn % 4) {
+                    Ok((_, turn_logs)) => logs.extend(turn_logs),
+                    Err(_) => break,
+                }
+            }
+            logs
+        };
+        // the same seed plays out the same way
+        assert_eq!(play(), play());
+    }
+}
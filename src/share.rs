@@ -0,0 +1,127 @@
+use crate::core::States;
+use crate::onion::{Experience, OnionCharacter};
+use crate::roster::Roster;
+
+// each kaizo sharing in a win gets this percent of what the one before it in the party got
+pub static SHARE_PERCENT: u32 = 50;
+// experience a kaizo at the level cap can't use is traded in at this many points for 1 money
+pub static EXPERIENCE_PER_MONEY: u32 = 10;
+// the caps the title screen cycles through, starting from none
+pub static LEVEL_CAPS: [Option<u32>; 4] = [None, Some(20), Some(35), Some(50)];
+
+// how much more experience a kaizo can take before it would go past the cap
+pub fn room(character: &OnionCharacter, cap: Option<u32>) -> u32 {
+    match cap {
+        Some(cap) => character.species.growth.experience_between(character.attributes.level, cap).saturating_sub(character.attributes.experience),
+        None => u32::MAX,
+    }
+}
+
+// gains as much of the experience as the cap allows, and hands back the rest
+pub fn gain_capped(character: &mut OnionCharacter, experience: u32, cap: Option<u32>) -> (States, u32) {
+    let gained = experience.min(room(character, cap));
+    let mut logs = character.gain_experience(gained);
+    if gained < experience {
+        logs.push(format!("{} is at the level cap.", character.name));
+    }
+    (logs, experience - gained)
+}
+
+// what the win's experience comes to for each kaizo after the winner, in party order
+pub fn shares(experience: u32, count: usize) -> Vec<u32> {
+    std::iter::successors(Some(experience * SHARE_PERCENT / 100), |share| Some(share * SHARE_PERCENT / 100))
+        .take(count)
+        .collect()
+}
+
+// the rest of the party gets a cut of the experience the kaizo in `slot` won with, smaller the
+// further down the party they are. kaizo that fainted don't get any. gives back the experience
+// the cap kept anyone from gaining
+pub fn share(roster: &mut Roster, slot: usize, experience: u32, cap: Option<u32>) -> (States, u32) {
+    let others: Vec<usize> = (0..roster.party().len())
+        .filter(|other| *other != slot && roster.party()[*other].character.state.health > 0)
+        .collect();
+    let mut logs = Vec::new();
+    let mut overflow = 0;
+    for (other, share) in others.into_iter().zip(shares(experience, roster.party().len())) {
+        let mut character = roster.party()[other].character.clone();
+        let (gained, left) = gain_capped(&mut character, share, cap);
+        logs.push(format!("{} shared in the win.", character.name));
+        logs.extend(gained);
+        overflow += left;
+        roster.set(other, character);
+    }
+    (logs, overflow)
+}
+
+pub fn money_for(overflow: u32) -> u32 {
+    overflow / EXPERIENCE_PER_MONEY
+}
+
+#[cfg(test)]
+mod share_tests {
+    use super::*;
+
+    use crate::onion::{OnionBattle, set_level, testing};
+
+    fn fake_character(level: u32) -> OnionCharacter {
+        let mut character = testing::fake_character_with_bst(400);
+        set_level(&mut character, level);
+        character.refresh();
+        character
+    }
+
+    #[test]
+    fn gain_capped_test() {
+        // level 9 takes 173 on the medium curve
+        let mut character = fake_character(9);
+        character.attributes.experience = 30;
+        assert_eq!(room(&character, Some(10)), 143);
+        assert_eq!(room(&character, None), u32::MAX);
+
+        let (_, overflow) = gain_capped(&mut character, 173, Some(10));
+        assert_eq!(overflow, 30);
+        assert_eq!(character.attributes.level, 10);
+        assert_eq!(character.attributes.experience, 0);
+        let (logs, overflow) = gain_capped(&mut character, 50, Some(10));
+        assert_eq!(overflow, 50);
+        assert_eq!(character.attributes.level, 10);
+        assert!(logs.contains(&"fake is at the level cap.".to_string()));
+        assert_eq!(gain_capped(&mut character, 50, None).1, 0);
+    }
+
+    #[test]
+    fn share_test() {
+        assert_eq!(shares(100, 3), vec![50, 25, 12]);
+
+        let mut roster = Roster::with_lead(fake_character(5));
+        roster.recruit(fake_character(5));
+        roster.recruit(fake_character(5));
+        roster.recruit(fake_character(5));
+        let mut fainted = roster.get(2).unwrap().clone();
+        fainted.state.health = 0;
+        roster.set(2, fainted);
+
+        let (_, overflow) = share(&mut roster, 1, 80, None);
+        assert_eq!(overflow, 0);
+        let experience: Vec<u32> = roster.party().iter().map(|member| member.character.attributes.experience).collect();
+        // the winner already has its own, and whoever fainted gets nothing
+        assert_eq!(experience, vec![40, 0, 0, 20]);
+
+        let (_, overflow) = share(&mut roster, 1, 80, Some(5));
+        assert_eq!(overflow, 60);
+        assert_eq!(money_for(overflow), 6);
+    }
+
+    #[test]
+    fn battle_test() {
+        let mut battle = OnionBattle::new(fake_character(5), fake_character(5));
+        battle.level_cap = Some(5);
+        battle.enemy.state.health = 0;
+        battle.end_turn();
+        assert_eq!(battle.player.attributes.level, 5);
+        assert_eq!(battle.player.attributes.experience, 0);
+        assert!(battle.earned() > 0);
+        assert_eq!(battle.overflow(), battle.earned());
+    }
+}
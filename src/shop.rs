@@ -0,0 +1,134 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{ActionId, Actions, Item, ItemId, States};
+use crate::items::{Effect, Inventory, OnionItem};
+use crate::onion::{OnionCharacter, OnionWorld, learn_moves};
+
+// money for every win, and more for every level the beaten kaizo was at
+pub static WIN_MONEY: u32 = 20;
+pub static MONEY_PER_LEVEL: u32 = 2;
+// prices go up by this percent for every win so far
+pub static PRICE_GROWTH: u32 = 10;
+// how many of the world's other items and how many tutors a shop stocks, at the most
+static SHOP_ITEMS: usize = 2;
+static TUTORS: usize = 3;
+static TUTOR_PRICE: u32 = 120;
+// what healing the whole party at the menu costs before the run's wins push it up
+pub static REST_PRICE: u32 = 40;
+
+// the money a win against a kaizo at that level is worth
+pub fn reward(level: u32) -> u32 {
+    WIN_MONEY + level * MONEY_PER_LEVEL
+}
+
+// a price after `wins` wins
+pub fn scale_price(price: u32, wins: u32) -> u32 {
+    price * (100 + wins * PRICE_GROWTH) / 100
+}
+
+// what an item costs before the run's wins push it up; the better it heals, the more
+pub fn item_price(item: &OnionItem) -> u32 {
+    match item.effect {
+        Effect::Heal(amount) => 10 + amount,
+        Effect::Cure => 25,
+        Effect::Revive(percent) => 50 + percent,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Ware {
+    // one of the world's items, for the bag
+    Item(ItemId),
+    // a move from the world's pool for the lead to learn
+    Tutor(ActionId),
+}
+
+impl Ware {
+    pub fn description(&self, world: &OnionWorld) -> String {
+        match self {
+            Ware::Item(item) => format!("A {}: {}", world.items[*item].name, world.items[*item].description()),
+            Ware::Tutor(action) => format!("Teach your lead {}.", world.actions[*action].name()),
+        }
+    }
+
+    // hands back any moves that are waiting for the kaizo to forget one, like a draft's upgrades
+    pub fn apply(&self, lead: &mut OnionCharacter, inventory: &mut Inventory, world: &OnionWorld) -> (States, Actions) {
+        match *self {
+            Ware::Item(item) => {
+                inventory.add(item, 1);
+                (vec![format!("Bought a {}.", world.items[item].name)], vec![])
+            },
+            Ware::Tutor(action) => learn_moves(lead, &[action], &world.actions),
+        }
+    }
+}
+
+// what's for sale, each at its price before the run's wins push it up. a run's shop is rolled once
+// from its seed, so everyone playing the seed finds the same one
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Shop {
+    pub wares: Vec<(Ware, u32)>,
+}
+
+impl Shop {
+    // every healing item the world has, and a few of its other items and moves
+    pub fn generate<R: Rng + ?Sized>(world: &OnionWorld, rng: &mut R) -> Shop {
+        let (heals, others): (Vec<ItemId>, Vec<ItemId>) = (0..world.items.len())
+            .partition(|item| matches!(world.items[*item].effect, Effect::Heal(_) | Effect::Revive(_)));
+        let items = heals.into_iter().chain(others.choose_multiple(rng, SHOP_ITEMS).copied())
+            .map(|item| (Ware::Item(item), item_price(&world.items[item])));
+        let actions: Vec<ActionId> = world.actions.ids().collect();
+        let tutors = actions.choose_multiple(rng, TUTORS).map(|action| (Ware::Tutor(*action), TUTOR_PRICE));
+        Shop { wares: items.chain(tutors).collect() }
+    }
+
+    pub fn price(&self, index: usize, wins: u32) -> Option<u32> {
+        self.wares.get(index).map(|(_, price)| scale_price(*price, wins))
+    }
+
+    // takes the price out of `money`; nothing is bought without enough of it
+    pub fn buy(&self, index: usize, wins: u32, money: &mut u32) -> Option<Ware> {
+        let price = self.price(index, wins).filter(|price| price <= money)?;
+        *money -= price;
+        Some(self.wares[index].0)
+    }
+}
+
+#[cfg(test)]
+mod shop_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::seed::{Seed, Stream};
+
+    #[test]
+    fn generate_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let shop = Shop::generate(&world, &mut Seed(3).stream(Stream::Shop));
+        // the potion and the revive, the antidote, and the tutors
+        assert_eq!(shop.wares.len(), 3 + TUTORS);
+        assert_eq!(shop.wares[0], (Ware::Item(0), 30));
+        assert_eq!(shop.wares.iter().filter(|(ware, _)| matches!(ware, Ware::Tutor(_))).count(), TUTORS);
+        assert_eq!(shop, Shop::generate(&world, &mut Seed(3).stream(Stream::Shop)));
+    }
+
+    #[test]
+    fn buy_test() {
+        let shop = Shop { wares: vec![(Ware::Item(0), 30), (Ware::Tutor(4), 100)] };
+        assert_eq!(shop.price(1, 5), Some(150));
+        let mut money = 100;
+        assert_eq!(shop.buy(1, 5, &mut money), None);
+        assert_eq!(money, 100);
+        assert_eq!(shop.buy(1, 0, &mut money), Some(Ware::Tutor(4)));
+        assert_eq!(money, 0);
+        assert_eq!(shop.buy(2, 0, &mut money), None);
+        assert_eq!(reward(10), WIN_MONEY + 10 * MONEY_PER_LEVEL);
+        assert_eq!(scale_price(REST_PRICE, 10), REST_PRICE * 2);
+    }
+}
@@ -0,0 +1,177 @@
+use rand::RngCore;
+
+use crate::ai::AiPolicy;
+use crate::core::{ActionId, States};
+use crate::encounters;
+use crate::onion::{ActionPool, OnionBattle, OnionBattleState};
+use crate::snapshot::Side;
+
+// battles that go on this long are called a draw, e.g. two kaizo that only ever defend
+pub static MAX_TURNS: u32 = 1000;
+
+// how a simulated battle went
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BattleResult {
+    // nobody wins a draw
+    pub winner: Option<Side>,
+    pub turns: u32,
+    // the health each side took off the other
+    pub player_damage: u32,
+    pub enemy_damage: u32,
+}
+
+// both sides spend the actions they picked and act in priority order, then the turn is ended
+pub fn play_turn(
+    battle: &mut OnionBattle,
+    player_action: ActionId,
+    enemy_action: ActionId,
+    actions: &ActionPool,
+    rng: &mut dyn RngCore,
+) -> (OnionBattleState, States) {
+    // struggles and skips aren't the character's own moves, so they aren't counted as uses
+    let player_move = actions.usable(&battle.player).find(|action| *action == player_action);
+    let enemy_move = actions.usable(&battle.enemy).find(|action| *action == enemy_action);
+    let player_action = actions.spend(&mut battle.player, player_action);
+    let enemy_action = actions.spend(&mut battle.enemy, enemy_action);
+
+    let mut logs = battle.resolve_turn(Some(player_action), enemy_action, rng);
+    if let Some(action) = player_move {
+        let landed = battle.player.state.landed;
+        battle.player.record_use(action, landed);
+    }
+    if let Some(action) = enemy_move {
+        let landed = battle.enemy.state.landed;
+        battle.enemy.record_use(action, landed);
+    }
+    let (mut state, end_logs) = battle.end_turn();
+    logs.extend(end_logs);
+    if state == OnionBattleState::InProcess {
+        let (after, hook_logs) = encounters::end_turn(battle, rng);
+        state = after;
+        logs.extend(hook_logs);
+    }
+    (state, logs)
+}
+
+// plays a battle out with both sides picked by ai and nothing watching, for balancing content by
+// running lots of matchups
+pub fn simulate(
+    mut battle: OnionBattle,
+    player_policy: &dyn AiPolicy,
+    enemy_policy: &dyn AiPolicy,
+    actions: &ActionPool,
+    rng: &mut dyn RngCore,
+) -> BattleResult {
+    play_out(&mut battle, player_policy, enemy_policy, actions, rng)
+}
+
+// the same, but the battle is left as it ended, e.g. to carry the winner's health on
+pub fn play_out(
+    battle: &mut OnionBattle,
+    player_policy: &dyn AiPolicy,
+    enemy_policy: &dyn AiPolicy,
+    actions: &ActionPool,
+    rng: &mut dyn RngCore,
+) -> BattleResult {
+    let mut result = BattleResult { winner: None, turns: 0, player_damage: 0, enemy_damage: 0 };
+    while result.turns < MAX_TURNS {
+        let player_action = player_policy.choose(&battle.player, &battle.enemy, &battle.field, actions, rng);
+        let enemy_action = enemy_policy.choose(&battle.enemy, &battle.player, &battle.field, actions, rng);
+        let (player_health, enemy_health) = (battle.player.state.health, battle.enemy.state.health);
+        let (state, _) = play_turn(battle, player_action, enemy_action, actions, rng);
+        result.turns += 1;
+        result.player_damage += (enemy_health - battle.enemy.state.health).max(0) as u32;
+        result.enemy_damage += (player_health - battle.player.state.health).max(0) as u32;
+        match state {
+            OnionBattleState::InProcess => (),
+            OnionBattleState::Victory => {
+                result.winner = Some(Side::Player);
+                break;
+            },
+            OnionBattleState::Defeat => {
+                result.winner = Some(Side::Enemy);
+                break;
+            },
+            OnionBattleState::Escaped | OnionBattleState::Fled | OnionBattleState::Draw => break,
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod sim_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::ai::{Greedy, Random};
+    use crate::onion::{ActionKind, Field, OnionCharacter, OnionWorld};
+
+    // always picks the same action
+    struct Always(ActionId);
+
+    impl AiPolicy for Always {
+        fn choose(&self, _: &OnionCharacter, _: &OnionCharacter, _: &Field, _: &ActionPool, _: &mut dyn RngCore) -> ActionId {
+            self.0
+        }
+    }
+
+    #[test]
+    fn simulate_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let battle = OnionBattle::new(world.sample_at_level(50, &mut rng), world.sample_at_level(5, &mut rng));
+        let health = battle.enemy.attributes.stats.health;
+
+        let result = simulate(battle.clone(), &Greedy, &Random, &world.actions, &mut rng);
+        assert_eq!(result.winner, Some(Side::Player));
+        assert!(result.turns > 0);
+        assert!(result.player_damage >= health);
+
+        // the same seed plays out the same way
+        let replay = |seed| simulate(battle.clone(), &Greedy, &Random, &world.actions, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(replay(1), replay(1));
+        // and keeps playing out that way, so a change to the battle rolls shows up here
+        assert_eq!(replay(0), BattleResult { winner: Some(Side::Player), turns: 6, player_damage: 158, enemy_damage: 4 });
+    }
+
+    #[test]
+    fn draw_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let defend = world.actions.ids().find(|action| world.actions.kind(*action) == ActionKind::Defend).unwrap();
+        let mut battle = OnionBattle::new(world.sample_at_level(5, &mut rng), world.sample_at_level(5, &mut rng));
+        battle.player.attributes.actions = vec![defend];
+        battle.enemy.attributes.actions = vec![defend];
+        // give both sides enough uses to defend for the whole battle
+        battle.player.attributes.pp.insert(defend, MAX_TURNS);
+        battle.enemy.attributes.pp.insert(defend, MAX_TURNS);
+
+        let result = simulate(battle, &Always(defend), &Always(defend), &world.actions, &mut rng);
+        assert_eq!(result, BattleResult { winner: None, turns: MAX_TURNS, player_damage: 0, enemy_damage: 0 });
+    }
+
+    #[test]
+    fn usage_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let defend = world.actions.ids().find(|action| world.actions.kind(*action) == ActionKind::Defend).unwrap();
+        let mut battle = OnionBattle::new(world.sample_at_level(5, &mut rng), world.sample_at_level(5, &mut rng));
+        battle.player.attributes.actions = vec![defend];
+        battle.enemy.attributes.actions = vec![defend];
+        battle.player.attributes.pp.insert(defend, 1);
+
+        play_turn(&mut battle, defend, defend, &world.actions, &mut rng);
+        let usage = battle.player.attributes.usage[&defend];
+        assert_eq!(usage.used, 1);
+        assert_eq!(usage.landed, battle.player.state.landed as u32);
+        assert_eq!(battle.enemy.attributes.usage[&defend].used, 1);
+
+        // struggling once the moves are used up isn't a use
+        play_turn(&mut battle, defend, defend, &world.actions, &mut rng);
+        assert_eq!(battle.player.attributes.usage[&defend].used, 1);
+        assert_eq!(battle.enemy.attributes.usage[&defend].used, 2);
+    }
+}
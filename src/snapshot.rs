@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use crate::core::Action;
+use crate::onion::{Alignment, Field, OnionBattle, OnionCharacter, OnionState, Status};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side { Player, Enemy }
+
+// a battle frozen for lookahead. the parts a turn can't change are shared, so branching a snapshot
+// only copies the two states
+#[derive(Clone, Debug, PartialEq)]
+pub struct BattleSnapshot {
+    player: Arc<OnionCharacter>,
+    enemy: Arc<OnionCharacter>,
+    field: Arc<Field>,
+    pub player_state: OnionState,
+    pub enemy_state: OnionState,
+}
+
+impl BattleSnapshot {
+    // `player` is just whichever side is searching; an ai playing the enemy passes itself first
+    pub fn new(player: &OnionCharacter, enemy: &OnionCharacter, field: &Field) -> BattleSnapshot {
+        BattleSnapshot {
+            player: Arc::new(player.clone()),
+            enemy: Arc::new(enemy.clone()),
+            field: Arc::new(field.clone()),
+            player_state: player.state,
+            enemy_state: enemy.state,
+        }
+    }
+
+    pub fn from_battle(battle: &OnionBattle) -> BattleSnapshot {
+        BattleSnapshot::new(&battle.player, &battle.enemy, &battle.field)
+    }
+
+    pub fn state(&self, side: Side) -> &OnionState {
+        match side {
+            Side::Player => &self.player_state,
+            Side::Enemy => &self.enemy_state,
+        }
+    }
+
+    pub fn field(&self) -> &Field { &self.field }
+}
+
+// the characters that snapshots get played out on; they're cloned once when the simulator is made
+// and after that only have their states overwritten
+pub struct Simulator {
+    player: OnionCharacter,
+    enemy: OnionCharacter,
+    field: Field,
+}
+
+impl Simulator {
+    pub fn new(snapshot: &BattleSnapshot) -> Simulator {
+        Simulator {
+            player: snapshot.player.as_ref().clone(),
+            enemy: snapshot.enemy.as_ref().clone(),
+            field: snapshot.field.as_ref().clone(),
+        }
+    }
+
+    // what the battle would look like after `side` used `action`; the snapshot itself is untouched
+    pub fn simulate_from_snapshot(
+        &mut self,
+        snapshot: &BattleSnapshot,
+        side: Side,
+        action: &dyn Action<Alignment, Status, Field>,
+    ) -> BattleSnapshot {
+        self.player.state = snapshot.player_state;
+        self.enemy.state = snapshot.enemy_state;
+        self.field.clone_from(&snapshot.field);
+        match side {
+            Side::Player => action.act(&mut self.player, &mut self.enemy, &mut self.field),
+            Side::Enemy => action.act(&mut self.enemy, &mut self.player, &mut self.field),
+        };
+        BattleSnapshot {
+            player: snapshot.player.clone(),
+            enemy: snapshot.enemy.clone(),
+            field: if self.field == *snapshot.field { snapshot.field.clone() } else { Arc::new(self.field.clone()) },
+            player_state: self.player.state,
+            enemy_state: self.enemy.state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::{ActionKind, OnionWorld};
+
+    fn fake_battle() -> (OnionWorld, OnionBattle) {
+        let mut rng = StdRng::seed_from_u64(2);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let battle = OnionBattle::new(world.sample_at_level(10, &mut rng), world.sample_at_level(10, &mut rng));
+        (world, battle)
+    }
+
+    #[test]
+    fn simulate_test() {
+        let (world, battle) = fake_battle();
+        let snapshot = BattleSnapshot::from_battle(&battle);
+        let mut simulator = Simulator::new(&snapshot);
+        let attack = &world.actions[0];
+
+        let next = simulator.simulate_from_snapshot(&snapshot, Side::Player, attack);
+        // the same as playing it out on the real battle
+        let mut played = battle.clone();
+        attack.act(&mut played.player, &mut played.enemy, &mut played.field);
+        assert_eq!(next.enemy_state, played.enemy.state);
+        assert_eq!(next.player_state, played.player.state);
+        assert!(next.enemy_state.health < snapshot.enemy_state.health);
+        // and the snapshot it came from is untouched
+        assert_eq!(snapshot, BattleSnapshot::from_battle(&battle));
+
+        // branches can keep going from each other
+        let again = simulator.simulate_from_snapshot(&next, Side::Player, attack);
+        assert!(again.enemy_state.health < next.enemy_state.health);
+        assert_eq!(again.state(Side::Player), &snapshot.player_state);
+    }
+
+    #[test]
+    fn sides_test() {
+        let (world, battle) = fake_battle();
+        let snapshot = BattleSnapshot::from_battle(&battle);
+        let mut simulator = Simulator::new(&snapshot);
+        let stun = world.actions.ids().find(|id| world.actions.kind(*id) == ActionKind::Stun).unwrap();
+
+        let next = simulator.simulate_from_snapshot(&snapshot, Side::Enemy, &world.actions[stun]);
+        assert!(next.player_state.status.contains_key(&Status::Stun));
+        assert!(next.enemy_state.status.is_empty());
+        assert!(Arc::ptr_eq(&next.field, &snapshot.field));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::ghost::Ghost;
+use crate::onion::{Experience, OnionCharacter};
+
+// how quickly a run is going, worked out from the battles it has won
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BattlePace {
+    pub turns_per_battle: f64,
+    pub battles_per_session: f64,
+}
+
+impl BattlePace {
+    // `sessions` is how many times the run has been played, counting the first; runs without a
+    // win yet have no pace
+    pub fn of(run: &Ghost, sessions: u32) -> Option<BattlePace> {
+        if run.turns.is_empty() {
+            return None;
+        }
+        let battles = run.turns.len() as f64;
+        Some(BattlePace {
+            turns_per_battle: run.turns.iter().sum::<u32>() as f64 / battles,
+            battles_per_session: battles / sessions.max(1) as f64,
+        })
+    }
+
+    // the turns it should take to win this many more battles
+    pub fn turns_for(&self, battles: u32) -> u32 {
+        (self.turns_per_battle * battles as f64).round() as u32
+    }
+}
+
+// the wins until the character's next level, guessing that every enemy gives as much experience as
+// one of the character's own species at its level would
+// TODO: enemies are picked at random, so this can be quite a way off for a single battle
+pub fn battles_to_level(character: &OnionCharacter) -> Option<u32> {
+    let per_battle = character.experience() / character.attributes.level.max(1);
+    if per_battle == 0 {
+        return None;
+    }
+    let needed = character.experience_to_next_level().saturating_sub(character.attributes.experience);
+    Some(needed.div_ceil(per_battle))
+}
+
+// the win rate over stretches of battles in a row, oldest first, so it can be charted; no more than
+// `points` of them, and none without any battles
+pub fn win_rates(outcomes: &[bool], points: usize) -> Vec<f64> {
+    if outcomes.is_empty() || points == 0 {
+        return Vec::new();
+    }
+    outcomes.chunks(outcomes.len().div_ceil(points))
+        .map(|chunk| chunk.iter().filter(|won| **won).count() as f64 / chunk.len() as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod statistics_tests {
+    use super::*;
+
+    use crate::content::id::WorldId;
+    use crate::core::{Character, Species};
+    use crate::onion::testing;
+    use crate::seed::Seed;
+
+    #[test]
+    fn pace_test() {
+        let mut run = Ghost::new(WorldId(0), Seed(0));
+        assert_eq!(BattlePace::of(&run, 1), None);
+
+        run.turns = vec![3, 5, 4, 6];
+        let pace = BattlePace::of(&run, 2).unwrap();
+        assert_eq!(pace.turns_per_battle, 4.5);
+        assert_eq!(pace.battles_per_session, 2.0);
+        assert_eq!(pace.turns_for(3), 14);
+        // a run that was never picked back up is still one session
+        assert_eq!(BattlePace::of(&run, 0).unwrap().battles_per_session, 4.0);
+    }
+
+    #[test]
+    fn battles_to_level_test() {
+        let mut character: OnionCharacter = Character::from_species(Species { name: "Pebble".into(), ..testing::fake_species_with_bst(450) });
+        assert_eq!(battles_to_level(&character), None);
+
+        // 232 experience at level 5 is 46 a win, and the level takes 149
+        character.attributes.level = 5;
+        assert_eq!(battles_to_level(&character), Some(4));
+        character.attributes.experience = 110;
+        assert_eq!(battles_to_level(&character), Some(1));
+    }
+
+    #[test]
+    fn win_rates_test() {
+        assert!(win_rates(&[], 5).is_empty());
+        assert_eq!(win_rates(&[true, false], 5), vec![1.0, 0.0]);
+        // stretches of two, with the last one short
+        let outcomes = [true, true, false, true, false, false, true];
+        assert_eq!(win_rates(&outcomes, 4), vec![1.0, 0.5, 0.0, 1.0]);
+        assert_eq!(win_rates(&outcomes, 1), vec![4.0 / 7.0]);
+    }
+}
@@ -0,0 +1,360 @@
+use rand::{Rng, RngCore};
+
+use crate::clauses::Clause;
+use crate::core::{Stat, States};
+use crate::onion::{Field, OnionCharacter, Status, stage_log};
+
+// what a status does to the kaizo that has it. each `Status` has one, and battles only go through
+// the functions at the bottom of this file, so a new status only needs a variant and an effect
+pub trait StatusEffect {
+    // puts `amount` of the status on the target, or says why it can't have it
+    fn on_apply(&self, target: &mut OnionCharacter, amount: i32, field: &Field) -> States;
+
+    // before the holder acts; false if it loses its action
+    fn on_turn_start(&self, _holder: &mut OnionCharacter, _rng: &mut dyn RngCore) -> (bool, States) { (true, vec![]) }
+
+    // after the holder acts
+    fn on_turn_end(&self, _holder: &mut OnionCharacter) -> States { vec![] }
+
+    // how many turns it lasts once applied, counted down at the end of each turn; none if it lasts
+    // until it wears off by itself or is cured, and its counter is free for something else
+    fn duration(&self) -> Option<u32> { None }
+}
+
+// defending only lasts the turn it was used
+static DEFEND_TURNS: u32 = 1;
+
+struct Defending;
+
+impl StatusEffect for Defending {
+    fn on_apply(&self, target: &mut OnionCharacter, _: i32, _: &Field) -> States {
+        target.state.status.insert(Status::Defend, DEFEND_TURNS as i32);
+        vec![]
+    }
+
+    fn duration(&self) -> Option<u32> { Some(DEFEND_TURNS) }
+}
+
+// the counter is how much health it takes every turn
+struct Bleeding;
+
+impl StatusEffect for Bleeding {
+    fn on_apply(&self, target: &mut OnionCharacter, amount: i32, _: &Field) -> States {
+        if target.state.immune.contains_key(&Status::Bleed) {
+            vec![format!("But {} can't bleed.", target.name)]
+        } else if target.state.status.contains_key(&Status::Stun) {
+            vec![format!("But {} is stunned.", target.name)]
+        } else {
+            let amount = if target.state.vulnerable.contains_key(&Status::Bleed) { amount * 2 } else { amount };
+            *target.state.status.get_or_insert(Status::Bleed, 0) += amount;
+            vec![format!("{} gained {} bleeding.", target.name, amount)]
+        }
+    }
+
+    fn on_turn_end(&self, holder: &mut OnionCharacter) -> States {
+        let bleed = holder.state.status.get(&Status::Bleed).copied().unwrap_or(0);
+        holder.state.health = std::cmp::max(0, holder.state.health - bleed);
+        vec![format!("{} was hurt by bleed.", holder.name)]
+    }
+}
+
+// the counter is how many times it was stunned; the more it was, the less likely it is to shake it off
+struct Stunned;
+
+impl StatusEffect for Stunned {
+    fn on_apply(&self, target: &mut OnionCharacter, amount: i32, field: &Field) -> States {
+        if target.state.immune.contains_key(&Status::Stun) {
+            vec![format!("But {} can't be stunned.", target.name)]
+        } else if target.state.status.contains_key(&Status::Bleed) {
+            vec![format!("But {} is poisoned.", target.name)]
+        } else if field.clauses.contains(&Clause::Stun) && target.state.status.contains_key(&Status::Stun) {
+            vec![format!("But {} is already stunned.", target.name)]
+        } else {
+            *target.state.status.get_or_insert(Status::Stun, 0) += if target.state.vulnerable.contains_key(&Status::Stun) { amount * 2 } else { amount };
+            vec![format!("{} is stunned.", target.name)]
+        }
+    }
+
+    fn on_turn_start(&self, holder: &mut OnionCharacter, rng: &mut dyn RngCore) -> (bool, States) {
+        let stacks = holder.state.status.get(&Status::Stun).copied().unwrap_or(0);
+        if rng.gen::<u32>().is_multiple_of(stacks as u32 + 1) {
+            holder.state.status.remove(&Status::Stun);
+            (true, vec![format!("{} is no longer stunned.", holder.name)])
+        } else {
+            (false, vec![format!("{} is stunned.", holder.name)])
+        }
+    }
+}
+
+// a fraction of the holder's max health, but always at least 1
+fn fraction(holder: &OnionCharacter, numerator: i32, denominator: i32) -> i32 {
+    std::cmp::max(1, holder.attributes.stats.health as i32 * numerator / denominator)
+}
+
+static BURN_FRACTION: i32 = 16;
+static BURN_ATTACK_STAGES: i32 = 2;
+
+// lowers the holder's attack when it's applied, and takes a little health every turn
+struct Burned;
+
+impl StatusEffect for Burned {
+    fn on_apply(&self, target: &mut OnionCharacter, _: i32, _: &Field) -> States {
+        if target.state.immune.contains_key(&Status::Burn) {
+            vec![format!("But {} can't be burned.", target.name)]
+        } else if target.state.status.contains_key(&Status::Burn) {
+            vec![format!("But {} is already burned.", target.name)]
+        } else {
+            target.state.status.insert(Status::Burn, 0);
+            let stages = if target.state.vulnerable.contains_key(&Status::Burn) { BURN_ATTACK_STAGES * 2 } else { BURN_ATTACK_STAGES };
+            let moved = target.change_stage(Stat::Attack, -stages);
+            vec![format!("{} was burned.", target.name), stage_log(target, Stat::Attack, -stages, moved)]
+        }
+    }
+
+    fn on_turn_end(&self, holder: &mut OnionCharacter) -> States {
+        holder.state.health = std::cmp::max(0, holder.state.health - fraction(holder, 1, BURN_FRACTION));
+        vec![format!("{} was hurt by its burn.", holder.name)]
+    }
+}
+
+static FREEZE_TURNS: u32 = 3;
+// the chance each turn that the holder can't move
+static FREEZE_CHANCE: f64 = 0.5;
+
+// the counter is how many turns it has left
+struct Frozen;
+
+impl StatusEffect for Frozen {
+    fn on_apply(&self, target: &mut OnionCharacter, _: i32, _: &Field) -> States {
+        if target.state.immune.contains_key(&Status::Freeze) {
+            vec![format!("But {} can't be frozen.", target.name)]
+        } else if target.state.status.contains_key(&Status::Freeze) {
+            vec![format!("But {} is already frozen.", target.name)]
+        } else {
+            let turns = if target.state.vulnerable.contains_key(&Status::Freeze) { FREEZE_TURNS * 2 } else { FREEZE_TURNS };
+            target.state.status.insert(Status::Freeze, turns as i32);
+            vec![format!("{} was frozen.", target.name)]
+        }
+    }
+
+    fn on_turn_start(&self, holder: &mut OnionCharacter, rng: &mut dyn RngCore) -> (bool, States) {
+        if rng.gen_bool(FREEZE_CHANCE) {
+            (false, vec![format!("{} is frozen solid.", holder.name)])
+        } else {
+            (true, vec![])
+        }
+    }
+
+    fn duration(&self) -> Option<u32> { Some(FREEZE_TURNS) }
+}
+
+static CONFUSE_TURNS: u32 = 3;
+// the chance each turn that the holder hits itself instead of acting
+static CONFUSE_CHANCE: f64 = 1.0 / 3.0;
+static CONFUSE_FRACTION: i32 = 10;
+
+// the counter is how many turns it has left
+struct Confused;
+
+impl StatusEffect for Confused {
+    fn on_apply(&self, target: &mut OnionCharacter, _: i32, _: &Field) -> States {
+        if target.state.immune.contains_key(&Status::Confuse) {
+            vec![format!("But {} can't be confused.", target.name)]
+        } else if target.state.status.contains_key(&Status::Confuse) {
+            vec![format!("But {} is already confused.", target.name)]
+        } else {
+            let turns = if target.state.vulnerable.contains_key(&Status::Confuse) { CONFUSE_TURNS * 2 } else { CONFUSE_TURNS };
+            target.state.status.insert(Status::Confuse, turns as i32);
+            vec![format!("{} became confused.", target.name)]
+        }
+    }
+
+    fn on_turn_start(&self, holder: &mut OnionCharacter, rng: &mut dyn RngCore) -> (bool, States) {
+        if rng.gen_bool(CONFUSE_CHANCE) {
+            holder.state.health = std::cmp::max(0, holder.state.health - fraction(holder, 1, CONFUSE_FRACTION));
+            (false, vec![format!("{} is confused.", holder.name), "It hurt itself in its confusion!".to_string()])
+        } else {
+            (true, vec![])
+        }
+    }
+
+    fn duration(&self) -> Option<u32> { Some(CONFUSE_TURNS) }
+}
+
+static POISON_FRACTION: i32 = 16;
+
+// the counter is how many turns it has been poisoned, and it takes that many sixteenths of its
+// health every turn
+struct Poisoned;
+
+impl StatusEffect for Poisoned {
+    fn on_apply(&self, target: &mut OnionCharacter, amount: i32, _: &Field) -> States {
+        if target.state.immune.contains_key(&Status::Poison) {
+            vec![format!("But {} can't be poisoned.", target.name)]
+        } else if target.state.status.contains_key(&Status::Poison) {
+            vec![format!("But {} is already poisoned.", target.name)]
+        } else {
+            let amount = if target.state.vulnerable.contains_key(&Status::Poison) { amount * 2 } else { amount };
+            target.state.status.insert(Status::Poison, amount);
+            vec![format!("{} was poisoned.", target.name)]
+        }
+    }
+
+    fn on_turn_end(&self, holder: &mut OnionCharacter) -> States {
+        let turns = holder.state.status.get(&Status::Poison).copied().unwrap_or(1);
+        holder.state.health = std::cmp::max(0, holder.state.health - fraction(holder, turns, POISON_FRACTION));
+        holder.state.status.insert(Status::Poison, turns + 1);
+        vec![format!("{} was hurt by poison.", holder.name)]
+    }
+}
+
+impl Status {
+    pub fn effect(&self) -> &'static dyn StatusEffect {
+        match self {
+            Status::Defend => &Defending,
+            Status::Bleed => &Bleeding,
+            Status::Stun => &Stunned,
+            Status::Burn => &Burned,
+            Status::Freeze => &Frozen,
+            Status::Confuse => &Confused,
+            Status::Poison => &Poisoned,
+        }
+    }
+}
+
+pub fn apply(status: Status, target: &mut OnionCharacter, amount: i32, field: &Field) -> States {
+    status.effect().on_apply(target, amount, field)
+}
+
+// the holder's statuses in order, stopping at the first that costs it its action
+pub fn turn_start(holder: &mut OnionCharacter, rng: &mut dyn RngCore) -> (bool, States) {
+    let mut logs = Vec::new();
+    let statuses: Vec<Status> = holder.state.status.keys().collect();
+    for status in statuses {
+        let (acts, status_logs) = status.effect().on_turn_start(holder, rng);
+        logs.extend(status_logs);
+        if !acts {
+            return (false, logs);
+        }
+    }
+    (true, logs)
+}
+
+pub fn turn_end(holder: &mut OnionCharacter) -> States {
+    let statuses: Vec<Status> = holder.state.status.keys().collect();
+    statuses.into_iter().flat_map(|status| status.effect().on_turn_end(holder)).collect()
+}
+
+// at the end of the battle's turn, after both sides have acted
+pub fn expire(holder: &mut OnionCharacter) {
+    let statuses: Vec<Status> = holder.state.status.keys().filter(|status| status.effect().duration().is_some()).collect();
+    for status in statuses {
+        if let Some(turns) = holder.state.status.get_mut(&status) {
+            *turns -= 1;
+            if *turns <= 0 {
+                holder.state.status.remove(&status);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod statuses_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::onion::testing;
+
+    #[test]
+    fn apply_test() {
+        let mut target = testing::fake_character();
+        let field = Field::default();
+        assert_eq!(apply(Status::Bleed, &mut target, 2, &field), vec!["fake gained 2 bleeding."]);
+        assert_eq!(apply(Status::Bleed, &mut target, 1, &field), vec!["fake gained 1 bleeding."]);
+        assert_eq!(target.state.status.get(&Status::Bleed), Some(&3));
+        // bleeding and stunned don't mix
+        assert_eq!(apply(Status::Stun, &mut target, 1, &field), vec!["But fake is poisoned."]);
+        assert!(!target.state.status.contains_key(&Status::Stun));
+    }
+
+    #[test]
+    fn turn_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut holder = testing::fake_character();
+        holder.state.health = 10;
+        holder.state.status.insert(Status::Bleed, 3);
+        assert_eq!(turn_start(&mut holder, &mut rng), (true, vec![]));
+        assert_eq!(turn_end(&mut holder), vec!["fake was hurt by bleed."]);
+        assert_eq!(holder.state.health, 7);
+
+        // stunned kaizo either shake it off and act, or lose the turn
+        holder.state.status.remove(&Status::Bleed);
+        holder.state.status.insert(Status::Stun, 1000);
+        assert_eq!(turn_start(&mut holder, &mut rng), (false, vec!["fake is stunned.".to_string()]));
+        holder.state.status.insert(Status::Stun, 0);
+        assert_eq!(turn_start(&mut holder, &mut rng), (true, vec!["fake is no longer stunned.".to_string()]));
+        assert!(holder.state.status.is_empty());
+    }
+
+    #[test]
+    fn expire_test() {
+        let mut holder = testing::fake_character();
+        apply(Status::Defend, &mut holder, 1, &Field::default());
+        holder.state.status.insert(Status::Bleed, 1);
+        assert!(holder.state.status.contains_key(&Status::Defend));
+        expire(&mut holder);
+        // defending lasts the turn, bleeding until it's cured
+        assert!(!holder.state.status.contains_key(&Status::Defend));
+        assert_eq!(holder.state.status.get(&Status::Bleed), Some(&1));
+    }
+
+    #[test]
+    fn damage_over_time_test() {
+        let mut holder = testing::fake_character();
+        holder.attributes.stats.health = 160;
+        holder.refresh();
+        let field = Field::default();
+
+        // burns lower attack and take a sixteenth every turn
+        assert_eq!(apply(Status::Burn, &mut holder, 1, &field), vec!["fake was burned.", "fake's attack harshly fell."]);
+        assert_eq!(apply(Status::Burn, &mut holder, 1, &field), vec!["But fake is already burned."]);
+        assert_eq!(turn_end(&mut holder), vec!["fake was hurt by its burn."]);
+        assert_eq!(holder.state.health, 150);
+
+        // poison takes more every turn it goes on
+        holder.state.status.remove(&Status::Burn);
+        apply(Status::Poison, &mut holder, 1, &field);
+        turn_end(&mut holder);
+        turn_end(&mut holder);
+        assert_eq!(holder.state.health, 120);
+        assert_eq!(holder.state.status.get(&Status::Poison), Some(&3));
+
+        holder.state.immune.insert(Status::Poison, 0);
+        holder.state.status.remove(&Status::Poison);
+        assert_eq!(apply(Status::Poison, &mut holder, 1, &field), vec!["But fake can't be poisoned."]);
+    }
+
+    #[test]
+    fn lose_turn_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut holder = testing::fake_character();
+        holder.attributes.stats.health = 100;
+        holder.refresh();
+        let field = Field::default();
+        apply(Status::Freeze, &mut holder, 1, &field);
+        apply(Status::Confuse, &mut holder, 1, &field);
+
+        // frozen or confused kaizo lose some turns but not all of them, and confused ones get hurt
+        let acted: Vec<bool> = (0..50).map(|_| turn_start(&mut holder, &mut rng).0).collect();
+        assert!(acted.contains(&true) && acted.contains(&false));
+        assert!(holder.state.health < 100);
+
+        // both wear off on their own
+        for _ in 0..3 {
+            expire(&mut holder);
+        }
+        assert!(holder.state.status.is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+
+#[cfg(feature = "web")]
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Serialize, Deserialize};
+
+use crate::clauses::{Clause, validate_team};
+use crate::core::{Actions, Character, Species};
+use crate::onion::{ActionPool, Alignment, OnionCharacter, OnionWorld, set_level};
+use crate::roster::PARTY_SIZE;
+
+pub static TEAM_LEVEL_CAP: u32 = 50;
+pub static MAX_ACTIONS: usize = 4;
+
+#[cfg(feature = "web")]
+static TEAMS_KEY: &str = "kaizo-quest.teams";
+
+// a hand-built character, described by the world's species index so it can be stored
+// TODO: species indices are only meaningful for the world the team was built in
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TeamMember {
+    pub species: usize,
+    pub level: u32,
+    pub actions: Actions,
+}
+
+// the moves a species can be built with at a level: whatever its learnset has taught it by then,
+// and every move of its own alignments or of none
+pub fn learnpool(species: &Species<Alignment>, level: u32, actions: &ActionPool) -> Actions {
+    let learned = species.learned_between(0, level);
+    let alignments = species.alignments();
+    actions.ids()
+        .filter(|action| learned.contains(action) || actions.alignment(*action).is_none_or(|alignment| alignments.contains(&alignment)))
+        .collect()
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+    pub name: String,
+    pub members: Vec<TeamMember>,
+}
+
+impl Team {
+    // builds the characters for a battle; members whose species isn't in the world are skipped
+    pub fn build(&self, world: &OnionWorld) -> Vec<OnionCharacter> {
+        self.members.iter().filter_map(|member| {
+            let species = world.species().get(member.species)?.clone();
+            let mut character = Character::from_shared_species(species);
+            character.attributes.actions = member.actions.clone();
+            set_level(&mut character, member.level);
+            Some(character)
+        }).collect()
+    }
+
+    // every reason this team can't be used, if any
+    pub fn validate(&self, world: &OnionWorld, clauses: &[Clause]) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("The team needs a name.".to_string());
+        }
+        if self.members.is_empty() || self.members.len() > PARTY_SIZE {
+            errors.push(format!("A team needs between 1 and {} kaizo.", PARTY_SIZE));
+        }
+        for member in self.members.iter() {
+            let species = match world.species().get(member.species) {
+                Some(species) => species,
+                None => {
+                    errors.push(format!("Species #{} doesn't exist in this world.", member.species));
+                    continue;
+                }
+            };
+            let name = &species.name;
+            if member.level == 0 || member.level > TEAM_LEVEL_CAP {
+                errors.push(format!("{} must be between Lv1 and Lv{}.", name, TEAM_LEVEL_CAP));
+            }
+            if member.actions.is_empty() || member.actions.len() > MAX_ACTIONS {
+                errors.push(format!("{} needs between 1 and {} moves.", name, MAX_ACTIONS));
+            }
+            if member.actions.iter().any(|action| !world.actions.ids().contains(action)) {
+                errors.push(format!("{} knows a move that doesn't exist in this world.", name));
+            }
+            let pool = learnpool(species, member.level, &world.actions);
+            for action in member.actions.iter().filter(|action| world.actions.ids().contains(action) && !pool.contains(action)) {
+                errors.push(format!("{} can't learn {} by Lv{}.", name, world.actions[*action].name(), member.level));
+            }
+        }
+        if let Err(violations) = validate_team(clauses, &self.build(world), &world.actions) {
+            errors.extend(violations.iter().map(|violation| violation.to_string()));
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+// where a pasted team stopped making sense; lines count from 1 like a text editor
+#[derive(Clone, Debug, PartialEq)]
+pub struct TeamParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TeamParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+// the text format is meant to be pasted in chat, so it mirrors showdown's exports:
+//
+//     === Team Name ===
+//
+//     Species
+//     Level: 50
+//     - Move
+//     - Move
+//
+// a member's level defaults to the level cap when it's left out
+impl Team {
+    pub fn to_text(&self, world: &OnionWorld) -> String {
+        let mut text = format!("=== {} ===\n", self.name);
+        for member in self.members.iter() {
+            let species = world.species().get(member.species)
+                .map(|species| species.name.as_ref())
+                .unwrap_or("???");
+            text.push_str(&format!("\n{}\nLevel: {}\n", species, member.level));
+            for action in member.actions.iter().filter(|action| world.actions.ids().contains(action)) {
+                text.push_str(&format!("- {}\n", world.actions[*action].name()));
+            }
+        }
+        text
+    }
+
+    // only checks that the text can be read; the team still needs to be validated
+    pub fn from_text(text: &str, world: &OnionWorld) -> Result<Team, TeamParseError> {
+        let mut team = Team::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: String| TeamParseError { line: index + 1, message };
+            if line.is_empty() {
+                continue;
+            } else if let Some(name) = line.strip_prefix("===").and_then(|name| name.strip_suffix("===")) {
+                team.name = name.trim().to_string();
+            } else if let Some(level) = line.strip_prefix("Level:") {
+                let member = team.members.last_mut()
+                    .ok_or_else(|| error("A level must come after a species.".to_string()))?;
+                member.level = level.trim().parse()
+                    .map_err(|_| error(format!("\"{}\" is not a level.", level.trim())))?;
+            } else if let Some(action) = line.strip_prefix('-') {
+                let action = action.trim();
+                let member = team.members.last_mut()
+                    .ok_or_else(|| error("A move must come after a species.".to_string()))?;
+                let id = world.actions.ids()
+                    .find(|id| world.actions[*id].name().eq_ignore_ascii_case(action))
+                    .ok_or_else(|| error(format!("There is no move called \"{}\".", action)))?;
+                member.actions.push(id);
+            } else {
+                let species = world.species().iter()
+                    .position(|species| species.name.eq_ignore_ascii_case(line))
+                    .ok_or_else(|| error(format!("There is no species called \"{}\".", line)))?;
+                team.members.push(TeamMember { species, level: TEAM_LEVEL_CAP, actions: vec![] });
+            }
+        }
+        Ok(team)
+    }
+}
+
+#[cfg(feature = "web")]
+pub fn load_teams() -> Vec<Team> {
+    LocalStorage::get(TEAMS_KEY).unwrap_or_default()
+}
+
+// saving a team with the same name as a stored one replaces it
+#[cfg(feature = "web")]
+pub fn store_team(team: &Team) {
+    let mut teams = load_teams();
+    teams.retain(|stored| stored.name != team.name);
+    teams.push(team.clone());
+    let _ = LocalStorage::set(TEAMS_KEY, teams);
+}
+
+#[cfg(feature = "web")]
+pub fn delete_team(name: &str) {
+    let mut teams = load_teams();
+    teams.retain(|stored| stored.name != name);
+    let _ = LocalStorage::set(TEAMS_KEY, teams);
+}
+
+#[cfg(test)]
+mod team_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    fn fake_world() -> OnionWorld {
+        Standard.sample(&mut StdRng::seed_from_u64(0))
+    }
+
+    // the first moves each member's learnpool has
+    fn fake_team(world: &OnionWorld) -> Team {
+        let actions = |species: usize, level: u32, count: usize| -> Actions {
+            learnpool(&world.species()[species], level, &world.actions).into_iter().take(count).collect()
+        };
+        Team {
+            name: "fake".to_string(),
+            members: vec![
+                TeamMember { species: 0, level: 10, actions: actions(0, 10, 2) },
+                TeamMember { species: 1, level: 20, actions: actions(1, 20, 1) },
+            ],
+        }
+    }
+
+    #[test]
+    fn build_test() {
+        let world = fake_world();
+        let team = fake_team(&world);
+        let characters = team.build(&world);
+
+        assert_eq!(characters.len(), 2);
+        assert_eq!(characters[0].species, world.species()[0]);
+        assert_eq!(characters[0].attributes.level, 10);
+        assert_eq!(characters[0].attributes.actions, team.members[0].actions);
+        assert_eq!(characters[1].attributes.level, 20);
+        assert_eq!(characters[1].state.health, characters[1].attributes.stats.health as i32);
+    }
+
+    #[test]
+    fn validate_test() {
+        let world = fake_world();
+        assert_eq!(fake_team(&world).validate(&world, &[]), Ok(()));
+
+        let mut team = fake_team(&world);
+        team.name = " ".to_string();
+        team.members[0].level = TEAM_LEVEL_CAP + 1;
+        team.members[1].actions = vec![];
+        assert_eq!(team.validate(&world, &[]).unwrap_err().len(), 3);
+
+        let mut team = fake_team(&world);
+        team.members[1].species = usize::MAX;
+        team.members[0].actions = vec![usize::MAX];
+        assert_eq!(team.validate(&world, &[]).unwrap_err().len(), 2);
+
+        let mut team = fake_team(&world);
+        team.members[1].species = 0;
+        assert_eq!(team.validate(&world, &[Clause::Species]).unwrap_err().len(), 1);
+
+        assert_eq!(Team::default().validate(&world, &[]).unwrap_err().len(), 2);
+
+        let mut team = fake_team(&world);
+        let unlearnable = world.actions.ids()
+            .find(|action| !learnpool(&world.species()[0], 10, &world.actions).contains(action))
+            .unwrap();
+        team.members[0].actions.push(unlearnable);
+        let errors = team.validate(&world, &[]).unwrap_err();
+        assert_eq!(errors, vec![format!("{} can't learn {} by Lv10.", world.species()[0].name, world.actions[unlearnable].name())]);
+    }
+
+    #[test]
+    fn learnpool_test() {
+        let world = fake_world();
+        let species = &world.species()[0];
+        let pool = learnpool(species, TEAM_LEVEL_CAP, &world.actions);
+        assert!(species.learnset.iter().all(|(_, action)| pool.contains(action)));
+        assert!(pool.iter().all(|action| species.learned_between(0, TEAM_LEVEL_CAP).contains(action) ||
+            world.actions.alignment(*action).is_none_or(|alignment| species.alignments().contains(&alignment))));
+
+        // a move of another alignment only joins once the learnset teaches it
+        let (species, level, action) = world.species().iter()
+            .flat_map(|species| species.learnset.iter().map(move |(level, action)| (species, *level, *action)))
+            .find(|(species, _, action)| world.actions.alignment(*action).is_some_and(|alignment| !species.alignments().contains(&alignment)))
+            .unwrap();
+        assert!(!learnpool(species, level - 1, &world.actions).contains(&action));
+        assert!(learnpool(species, level, &world.actions).contains(&action));
+    }
+
+    #[test]
+    fn text_round_trip_test() {
+        let world = fake_world();
+        let team = fake_team(&world);
+        let text = team.to_text(&world);
+
+        assert!(text.starts_with("=== fake ===\n"));
+        assert!(text.contains(&format!("\n{}\nLevel: 10\n- {}\n", world.species()[0].name, world.actions[team.members[0].actions[0]].name())));
+        assert_eq!(Team::from_text(&text, &world), Ok(team));
+    }
+
+    #[test]
+    fn text_parse_test() {
+        let world = fake_world();
+        let species = world.species()[1].name.to_uppercase();
+        let text = format!("{}\n  - {}  \n", species, world.actions[2].name());
+
+        let team = Team::from_text(&text, &world).unwrap();
+        assert_eq!(team.name, "");
+        assert_eq!(team.members, vec![TeamMember { species: 1, level: TEAM_LEVEL_CAP, actions: vec![2] }]);
+    }
+
+    #[test]
+    fn text_parse_error_test() {
+        let world = fake_world();
+        let species = &world.species()[0].name;
+        let error = |text: &str| Team::from_text(text, &world).unwrap_err();
+
+        assert_eq!(error("- Nothing").line, 1);
+        assert_eq!(error("=== a ===\n\nLevel: 5").line, 3);
+        assert_eq!(error("Missingno").message, "There is no species called \"Missingno\".");
+        assert_eq!(error(&format!("{}\nLevel: ten", species)).to_string(), "Line 2: \"ten\" is not a level.");
+        assert_eq!(error(&format!("{}\n- Nothing", species)).message, "There is no move called \"Nothing\".");
+    }
+}
@@ -0,0 +1,182 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::Difficulty;
+use crate::core::States;
+use crate::onion::{OnionBattle, OnionCharacter, OnionWorld};
+use crate::roster::Roster;
+
+// beating a trainer's kaizo is worth this many times the experience of beating it anywhere else
+pub static TRAINER_EXPERIENCE: u32 = 2;
+// how many kaizo a generated trainer brings, at the least and at the most
+static TEAM_SIZES: (usize, usize) = (2, 3);
+static TRAINER_NAMES: [&str; 8] = [
+    "Youngster Pip",
+    "Hiker Bram",
+    "Lass Wren",
+    "Ace Trainer Juno",
+    "Gardener Moss",
+    "Sailor Quill",
+    "Scholar Vesper",
+    "Veteran Odile",
+];
+
+// someone to battle with a whole team, sent out one at a time; the battle is only won once every
+// one of them is down
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trainer {
+    pub name: String,
+    // the kaizo still waiting to be sent out, in order
+    pub team: Vec<OnionCharacter>,
+    // how the trainer picks moves for every one of them
+    pub ai: Difficulty,
+}
+
+impl Trainer {
+    pub fn generate<R: Rng + ?Sized>(world: &OnionWorld, level: u32, ai: Difficulty, rng: &mut R) -> Trainer {
+        let name = TRAINER_NAMES[rng.gen_range(0..TRAINER_NAMES.len())].to_string();
+        let size = rng.gen_range(TEAM_SIZES.0..=TEAM_SIZES.1);
+        let team = (0..size).map(|_| world.sample_at_level(level, rng)).collect();
+        Trainer { name, team, ai }
+    }
+
+    // the kaizo the battle starts against, taken off the front of the team
+    pub fn lead(&mut self) -> Option<OnionCharacter> {
+        (!self.team.is_empty()).then(|| self.team.remove(0))
+    }
+
+    // sets the battle up as one against the trainer
+    pub fn start(&self, battle: &mut OnionBattle) {
+        battle.experience_multiplier *= TRAINER_EXPERIENCE;
+    }
+
+    // sends out the trainer's next kaizo in place of one that fainted. spikes can knock out whoever
+    // is sent out, so this keeps going until someone is standing; none when the team is all down
+    pub fn send_out(&mut self, battle: &mut OnionBattle) -> Option<States> {
+        let mut logs = Vec::new();
+        while let Some(next) = self.lead() {
+            logs.push(format!("{} sent out {}!", self.name, next.name));
+            logs.extend(battle.switch_in_enemy(next));
+            if battle.enemy.state.health > 0 {
+                return Some(logs);
+            }
+            logs.push(format!("{} fainted!", battle.enemy.name));
+        }
+        None
+    }
+
+    // both leads went down at once. the player's next kaizo standing goes out against the trainer's
+    // next one, and the battle goes on; none when the player has nobody left. `slot` follows whoever
+    // the player sends out
+    pub fn after_draw(&mut self, battle: &mut OnionBattle, roster: &mut Roster, slot: &mut usize) -> Option<States> {
+        let mut logs = Vec::new();
+        roster.set(*slot, battle.player.clone());
+        loop {
+            let next = roster.next_conscious()?;
+            *slot = next;
+            // risks last the whole run, so they hold for whoever is sent out next
+            let mut character = roster.get(next).unwrap().clone();
+            character.state.vulnerable = battle.player.state.vulnerable;
+            logs.push(format!("Go, {}!", character.name));
+            logs.extend(battle.switch_in(character));
+            roster.set(next, battle.player.clone());
+            if battle.player.state.health > 0 {
+                break;
+            }
+            logs.push(format!("{} fainted!", battle.player.name));
+        }
+        // if spikes take the trainer's whole team, the player is left standing and has won
+        logs.extend(self.send_out(battle).unwrap_or_default());
+        Some(logs)
+    }
+}
+
+#[cfg(test)]
+mod trainers_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    use crate::onion::{OnionBattleState, set_level, testing};
+
+    fn fake_trainer(team: Vec<OnionCharacter>) -> Trainer {
+        Trainer { name: "Lass Wren".to_string(), team, ai: Difficulty::Easy }
+    }
+
+    #[test]
+    fn generate_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let trainer = Trainer::generate(&world, 12, Difficulty::Hard, &mut rng);
+        assert!((TEAM_SIZES.0..=TEAM_SIZES.1).contains(&trainer.team.len()));
+        assert!(trainer.team.iter().all(|member| member.attributes.level == 12));
+        assert_eq!(trainer.ai, Difficulty::Hard);
+    }
+
+    fn fake_character(name: &str) -> OnionCharacter {
+        let mut character = testing::fake_character();
+        character.name = name.into();
+        character.attributes.stats.health = 40;
+        character.refresh();
+        character
+    }
+
+    #[test]
+    fn send_out_test() {
+        let mut trainer = fake_trainer(vec![fake_character("first"), fake_character("second")]);
+        let mut battle = OnionBattle::new(fake_character("player"), trainer.lead().unwrap());
+        battle.enemy.state.health = 0;
+
+        assert_eq!(trainer.send_out(&mut battle), Some(vec!["Lass Wren sent out second!".to_string()]));
+        assert_eq!(&*battle.enemy.name, "second");
+        assert!(trainer.team.is_empty());
+        battle.enemy.state.health = 0;
+        assert_eq!(trainer.send_out(&mut battle), None);
+        assert_eq!(battle.battle_state(), OnionBattleState::Victory);
+    }
+
+    #[test]
+    fn after_draw_test() {
+        let mut roster = Roster::with_lead(fake_character("player"));
+        roster.recruit(fake_character("backup"));
+        let mut trainer = fake_trainer(vec![fake_character("first"), fake_character("second")]);
+        let mut battle = OnionBattle::new(roster.lead().clone(), trainer.lead().unwrap());
+        battle.player.state.health = 0;
+        battle.enemy.state.health = 0;
+        assert_eq!(battle.battle_state(), OnionBattleState::Draw);
+
+        let mut slot = 0;
+        let logs = trainer.after_draw(&mut battle, &mut roster, &mut slot).unwrap();
+        assert_eq!(logs, vec!["Go, backup!".to_string(), "Lass Wren sent out second!".to_string()]);
+        assert_eq!(slot, 1);
+        assert_eq!(roster.lead().state.health, 0);
+        assert_eq!((&*battle.player.name, &*battle.enemy.name), ("backup", "second"));
+        assert_eq!(battle.battle_state(), OnionBattleState::InProcess);
+
+        // with nobody left to send out, it's over
+        battle.player.state.health = 0;
+        battle.enemy.state.health = 0;
+        trainer.team.push(fake_character("third"));
+        assert_eq!(trainer.after_draw(&mut battle, &mut roster, &mut slot), None);
+    }
+
+    #[test]
+    fn experience_test() {
+        let experience = |trainer: Option<&Trainer>| {
+            let (mut player, mut enemy) = (testing::fake_character_with_bst(400), testing::fake_character_with_bst(400));
+            set_level(&mut player, 5);
+            set_level(&mut enemy, 5);
+            let mut battle = OnionBattle::new(player, enemy);
+            if let Some(trainer) = trainer {
+                trainer.start(&mut battle);
+            }
+            battle.enemy.state.health = 0;
+            battle.end_turn();
+            battle.player.attributes.experience
+        };
+        assert!(experience(None) > 0);
+        assert_eq!(experience(Some(&fake_trainer(vec![]))), experience(None) * TRAINER_EXPERIENCE);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec;
+use qrcode::{Color, QrCode};
+
+use crate::content::id::fnv1a;
+use crate::seed::base36;
+
+// moving a save to another device without a server: the save is compressed, written out in base64
+// and cut into chunks small enough for one QR code each. the other device pastes the chunks back in,
+// in any order, since each one says where it goes
+
+// characters of base64 per chunk, which keeps each code small enough to scan off a phone screen
+pub static CHUNK_SIZE: usize = 800;
+static PREFIX: &str = "KQ";
+static COMPRESSION: u8 = 9;
+
+// one piece of a transfer, written as `KQ.<hash>.<index>.<total>.<data>` with the numbers in base 36
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chunk {
+    // of the whole compressed save, so chunks from two transfers aren't mixed up
+    pub hash: u64,
+    pub index: u32,
+    pub total: u32,
+    pub data: String,
+}
+
+impl fmt::Display for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}.{}.{}", PREFIX, base36(self.hash), base36(self.index as u64), base36(self.total as u64), self.data)
+    }
+}
+
+impl FromStr for Chunk {
+    type Err = TransferError;
+
+    fn from_str(s: &str) -> Result<Chunk, TransferError> {
+        let parse = |part: &str| u64::from_str_radix(part, 36).map_err(|_| TransferError::Parse(s.to_string()));
+        match s.trim().splitn(5, '.').collect::<Vec<_>>()[..] {
+            [prefix, hash, index, total, data] if prefix == PREFIX => Ok(Chunk {
+                hash: parse(hash)?,
+                index: parse(index)? as u32,
+                total: parse(total)? as u32,
+                data: data.to_string(),
+            }),
+            _ => Err(TransferError::Parse(s.to_string())),
+        }
+    }
+}
+
+impl Chunk {
+    // the code's modules row by row, dark ones true, and how many there are to a row
+    pub fn qr(&self) -> (usize, Vec<bool>) {
+        // chunks are cut well under what a code can hold, so this can't fail
+        let code = QrCode::new(self.to_string()).unwrap();
+        (code.width(), code.to_colors().into_iter().map(|color| color == Color::Dark).collect())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransferError {
+    Parse(String),
+    // chunks from more than one transfer were pasted in together
+    Mixed,
+    // by number, counting from 1
+    Missing(Vec<u32>),
+    // every chunk was there, but they don't make up the save they were cut from
+    Corrupt,
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransferError::Parse(chunk) => write!(f, "\"{}\" isn't part of a transfer.", chunk),
+            TransferError::Mixed => write!(f, "Those are parts of more than one transfer."),
+            TransferError::Missing(missing) => {
+                let missing: Vec<String> = missing.iter().map(u32::to_string).collect();
+                write!(f, "Parts {} of the transfer are missing.", missing.join(", "))
+            },
+            TransferError::Corrupt => write!(f, "The transfer got garbled along the way. Try copying it again."),
+        }
+    }
+}
+
+// whether pasted text is a transfer rather than a save as it is
+pub fn is_transfer(text: &str) -> bool {
+    text.trim_start().starts_with(PREFIX)
+}
+
+pub fn chunks(text: &str) -> Vec<Chunk> {
+    let compressed = compress_to_vec(text.as_bytes(), COMPRESSION);
+    let hash = fnv1a(&compressed);
+    let encoded = URL_SAFE_NO_PAD.encode(compressed);
+    // base64 is all ascii, so it can be cut anywhere
+    let pieces: Vec<&str> = encoded.as_bytes().chunks(CHUNK_SIZE).map(|piece| std::str::from_utf8(piece).unwrap()).collect();
+    let total = pieces.len() as u32;
+    pieces.into_iter()
+        .enumerate()
+        .map(|(index, data)| Chunk { hash, index: index as u32, total, data: data.to_string() })
+        .collect()
+}
+
+// the text the chunks were cut from; they're separated by any whitespace, in any order, and a
+// chunk pasted twice is fine
+pub fn assemble(pasted: &str) -> Result<String, TransferError> {
+    let chunks: Vec<Chunk> = pasted.split_whitespace().map(str::parse).collect::<Result<_, _>>()?;
+    let first = chunks.first().ok_or_else(|| TransferError::Parse(pasted.to_string()))?;
+    let (hash, total) = (first.hash, first.total);
+    if chunks.iter().any(|chunk| chunk.hash != hash || chunk.total != total) {
+        return Err(TransferError::Mixed);
+    }
+    let pieces: BTreeMap<u32, &str> = chunks.iter().map(|chunk| (chunk.index, chunk.data.as_str())).collect();
+    let missing: Vec<u32> = (0..total).filter(|index| !pieces.contains_key(index)).map(|index| index + 1).collect();
+    if !missing.is_empty() {
+        return Err(TransferError::Missing(missing));
+    }
+    let compressed = URL_SAFE_NO_PAD.decode(pieces.values().copied().collect::<String>()).map_err(|_| TransferError::Corrupt)?;
+    if fnv1a(&compressed) != hash {
+        return Err(TransferError::Corrupt);
+    }
+    let bytes = decompress_to_vec(&compressed).map_err(|_| TransferError::Corrupt)?;
+    String::from_utf8(bytes).map_err(|_| TransferError::Corrupt)
+}
+
+#[cfg(test)]
+mod transfer_tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use rand::distributions::Alphanumeric;
+    use rand::rngs::StdRng;
+
+    // text that doesn't compress much, so it takes a few chunks
+    fn fake_save(length: usize) -> String {
+        StdRng::seed_from_u64(0).sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let save = fake_save(3000);
+        let chunks = chunks(&save);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.data.len() <= CHUNK_SIZE));
+
+        // in any order, with a chunk pasted twice
+        let mut pasted: Vec<String> = chunks.iter().rev().map(Chunk::to_string).collect();
+        pasted.push(chunks[0].to_string());
+        assert!(is_transfer(&pasted.join("\n")));
+        assert_eq!(assemble(&pasted.join("\n")), Ok(save));
+        assert_eq!(chunks[1].to_string().parse(), Ok(chunks[1].clone()));
+    }
+
+    #[test]
+    fn errors_test() {
+        let chunks = chunks(&fake_save(3000));
+        let pasted = |chunks: &[Chunk]| chunks.iter().map(Chunk::to_string).collect::<Vec<_>>().join(" ");
+
+        assert_eq!(assemble(&pasted(&chunks[1..])), Err(TransferError::Missing(vec![1])));
+        let other = super::chunks("another save");
+        assert_eq!(assemble(&format!("{} {}", pasted(&chunks), other[0])), Err(TransferError::Mixed));
+        let mut garbled = chunks.clone();
+        garbled[0].data.replace_range(0..4, "AAAA");
+        assert_eq!(assemble(&pasted(&garbled)), Err(TransferError::Corrupt));
+        assert!(matches!(assemble("{\"version\":1}"), Err(TransferError::Parse(_))));
+        assert!(!is_transfer("{\"version\":1}"));
+    }
+
+    #[test]
+    fn qr_test() {
+        let chunk = &chunks(&fake_save(3000))[0];
+        let (width, modules) = chunk.qr();
+        assert_eq!(modules.len(), width * width);
+        // the finder pattern's corner is dark
+        assert!(modules[0]);
+    }
+}
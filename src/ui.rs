@@ -0,0 +1,125 @@
+use std::ops::Range;
+
+use yew::prelude::*;
+use yew::html;
+use yew::html::Properties;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, Element, HtmlCanvasElement};
+
+// windowing for long lists; only the rows inside the viewport (plus a little overscan) are turned
+// into dom nodes, which keeps the dex, roster, and action lists responsive on slow devices
+static OVERSCAN: usize = 3;
+
+// the rows that should be rendered for a viewport scrolled to `scroll_top`
+pub fn visible_range(count: usize, scroll_top: f64, height: f64, row_height: f64) -> Range<usize> {
+    if count == 0 || row_height <= 0.0 {
+        return 0..0;
+    }
+    let first = (scroll_top.max(0.0) / row_height) as usize;
+    let visible = (height.max(0.0) / row_height).ceil() as usize + 1;
+    let start = first.saturating_sub(OVERSCAN).min(count);
+    let end = (first + visible + OVERSCAN).min(count);
+    start..end
+}
+
+#[derive(Properties, PartialEq)]
+pub struct VirtualListProps {
+    pub row_height: f64,
+    pub height: f64,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+#[function_component(VirtualList)]
+pub fn virtual_list(VirtualListProps { row_height, height, children }: &VirtualListProps) -> Html {
+    let scroll_top = use_state(|| 0.0);
+    let onscroll = {
+        let scroll_top = scroll_top.clone();
+        Callback::from(move |e: Event| {
+            let element: Element = e.target_unchecked_into();
+            scroll_top.set(element.scroll_top() as f64);
+        })
+    };
+
+    let count = children.len();
+    let range = visible_range(count, *scroll_top, *height, *row_height);
+    // the spacers keep the scrollbar sized as if every row was rendered
+    let before = range.start as f64 * row_height;
+    let after = (count - range.end) as f64 * row_height;
+    html! {
+        <div class="virtual-list" style={ format!("height:{}px;overflow-y:auto;", height) } { onscroll }>
+            <div style={ format!("height:{}px;", before) }/>
+            { for children.iter().skip(range.start).take(range.len()).map(|child| html! {
+                <div style={ format!("height:{}px;overflow:hidden;", row_height) }>{ child }</div>
+            }) }
+            <div style={ format!("height:{}px;", after) }/>
+        </div>
+    }
+}
+
+// pixels to a module of a QR code, and the blank modules around it that scanners need
+static QR_SCALE: usize = 4;
+static QR_QUIET: usize = 4;
+
+#[derive(Properties, PartialEq)]
+pub struct QrProps {
+    // modules to a row, and the modules row by row, dark ones true
+    pub width: usize,
+    pub modules: Vec<bool>,
+}
+
+// a QR code drawn on a canvas once it's in the page
+#[function_component(QrCanvas)]
+pub fn qr_canvas(QrProps { width, modules }: &QrProps) -> Html {
+    let canvas = use_node_ref();
+    {
+        let canvas = canvas.clone();
+        use_effect_with_deps(move |(width, modules)| {
+            let context = canvas.cast::<HtmlCanvasElement>()
+                .and_then(|canvas| canvas.get_context("2d").ok().flatten())
+                .map(|context| context.unchecked_into::<CanvasRenderingContext2d>());
+            if let Some(context) = context {
+                let width = (*width).max(1);
+                // the canvas starts out clear, and the css gives it a white background
+                for (i, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+                    let (x, y) = (i % width + QR_QUIET, i / width + QR_QUIET);
+                    context.fill_rect((x * QR_SCALE) as f64, (y * QR_SCALE) as f64, QR_SCALE as f64, QR_SCALE as f64);
+                }
+            }
+            || ()
+        }, (*width, modules.clone()));
+    }
+    let size = (width + 2 * QR_QUIET) * QR_SCALE;
+    html! {
+        <canvas class="qr" ref={ canvas } width={ size.to_string() } height={ size.to_string() }></canvas>
+    }
+}
+
+#[cfg(test)]
+mod virtual_list_tests {
+    use super::*;
+
+    #[test]
+    fn empty_list_test() {
+        assert_eq!(visible_range(0, 0.0, 100.0, 10.0), 0..0);
+        assert_eq!(visible_range(10, 0.0, 100.0, 0.0), 0..0);
+    }
+
+    #[test]
+    fn visible_range_test() {
+        // top of the list
+        assert_eq!(visible_range(1000, 0.0, 100.0, 10.0), 0..14);
+        // scrolled into the middle
+        assert_eq!(visible_range(1000, 500.0, 100.0, 10.0), 47..64);
+        // bottom of the list
+        assert_eq!(visible_range(1000, 9900.0, 100.0, 10.0), 987..1000);
+        // short list
+        assert_eq!(visible_range(5, 0.0, 100.0, 10.0), 0..5);
+    }
+
+    #[test]
+    fn overscrolled_test() {
+        assert_eq!(visible_range(10, -50.0, 100.0, 10.0), 0..10);
+        assert_eq!(visible_range(10, 5000.0, 100.0, 10.0), 10..10);
+    }
+}
@@ -0,0 +1,177 @@
+use rand::RngCore;
+
+use crate::ai::{Difficulty, Habits};
+use crate::core::{Name, States};
+use crate::onion::{OnionBattle, OnionCharacter, OnionWorld};
+use crate::rules::Ruleset;
+use crate::sim::play_out;
+use crate::snapshot::Side;
+
+// one kaizo in a squad, and the ai that fights for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Soldier {
+    pub character: OnionCharacter,
+    pub strategy: Difficulty,
+}
+
+// one of the 1v1s a war is made of
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bout {
+    pub player: Name,
+    pub enemy: Name,
+    // nobody wins a draw, and both kaizo are out
+    pub winner: Option<Side>,
+    pub turns: u32,
+    // the health each side took off the other
+    pub player_damage: u32,
+    pub enemy_damage: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WarReport {
+    pub bouts: Vec<Bout>,
+    // a draw if both squads ran out at once
+    pub winner: Option<Side>,
+    // how many of each squad were still standing at the end
+    pub player_left: usize,
+    pub enemy_left: usize,
+}
+
+impl WarReport {
+    // the match report, a line per bout and the result
+    pub fn lines(&self) -> States {
+        let mut lines: States = self.bouts.iter()
+            .enumerate()
+            .map(|(index, bout)| format!(
+                "Bout {}: {} vs {}, {} after {} turns ({} to {} damage).",
+                index + 1,
+                bout.player,
+                bout.enemy,
+                match bout.winner {
+                    Some(Side::Player) => format!("{} won", bout.player),
+                    Some(Side::Enemy) => format!("{} won", bout.enemy),
+                    None => "a draw".to_string(),
+                },
+                bout.turns,
+                bout.player_damage,
+                bout.enemy_damage,
+            ))
+            .collect();
+        lines.push(match self.winner {
+            Some(Side::Player) => format!("Your squad won the war with {} left standing.", self.player_left),
+            Some(Side::Enemy) => format!("Your squad lost the war; the enemy had {} left standing.", self.enemy_left),
+            None => "The war was a draw.".to_string(),
+        });
+        lines
+    }
+}
+
+// the two squads fight in order, with nobody at the controls: whoever wins a bout stays in, on the
+// health it had left, to fight the other squad's next kaizo
+pub fn war(player: &[Soldier], enemy: &[Soldier], world: &OnionWorld, rng: &mut dyn RngCore) -> WarReport {
+    // nobody watches a war, so the adaptive ai has no habits to go on
+    let habits = Habits::default();
+    let mut bouts = Vec::new();
+    let (mut player_next, mut enemy_next) = (0, 0);
+    let mut player_fighter = player.first().map(|soldier| soldier.character.clone());
+    let mut enemy_fighter = enemy.first().map(|soldier| soldier.character.clone());
+    while let (Some(player_character), Some(enemy_character)) = (player_fighter.take(), enemy_fighter.take()) {
+        let mut battle = OnionBattle::new(player_character, enemy_character);
+        Ruleset::default().start_battle(&mut battle, world, rng);
+        battle.start();
+        let player_policy = player[player_next].strategy.policy(&habits, false);
+        let enemy_policy = enemy[enemy_next].strategy.policy(&habits, false);
+        let result = play_out(&mut battle, player_policy.as_ref(), enemy_policy.as_ref(), &world.actions, rng);
+        bouts.push(Bout {
+            player: battle.player.name.clone(),
+            enemy: battle.enemy.name.clone(),
+            winner: result.winner,
+            turns: result.turns,
+            player_damage: result.player_damage,
+            enemy_damage: result.enemy_damage,
+        });
+        match result.winner {
+            Some(Side::Player) => player_fighter = Some(rest(battle.player)),
+            Some(Side::Enemy) => enemy_fighter = Some(rest(battle.enemy)),
+            None => (),
+        }
+        if player_fighter.is_none() {
+            player_next += 1;
+            player_fighter = player.get(player_next).map(|soldier| soldier.character.clone());
+        }
+        if enemy_fighter.is_none() {
+            enemy_next += 1;
+            enemy_fighter = enemy.get(enemy_next).map(|soldier| soldier.character.clone());
+        }
+    }
+    let player_left = player.len().saturating_sub(player_next);
+    let enemy_left = enemy.len().saturating_sub(enemy_next);
+    let winner = match (player_left, enemy_left) {
+        (0, 0) => None,
+        (_, 0) => Some(Side::Player),
+        _ => Some(Side::Enemy),
+    };
+    WarReport { bouts, winner, player_left, enemy_left }
+}
+
+// a winner goes into its next bout on the health it has left, but nothing else from the last one
+fn rest(mut character: OnionCharacter) -> OnionCharacter {
+    let health = character.state.health;
+    character.refresh();
+    character.state.health = health;
+    character
+}
+
+// an enemy squad for the party to fight, a kaizo for each of theirs at the same level
+pub fn muster(world: &OnionWorld, levels: &[u32], strategy: Difficulty, rng: &mut dyn RngCore) -> Vec<Soldier> {
+    levels.iter().map(|level| Soldier { character: world.sample_at_level(*level, rng), strategy }).collect()
+}
+
+#[cfg(test)]
+mod war_tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn war_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let player = muster(&world, &[20, 20, 20], Difficulty::Normal, &mut rng);
+        let enemy = muster(&world, &[5, 5], Difficulty::Easy, &mut rng);
+
+        let report = war(&player, &enemy, &world, &mut rng);
+        assert_eq!(report.winner, Some(Side::Player));
+        assert_eq!(report.enemy_left, 0);
+        assert!(report.player_left > 0);
+        // every bout knocks someone out, unless it's a draw that knocks out both
+        let knocked_out = player.len() - report.player_left + enemy.len() - report.enemy_left;
+        assert!(report.bouts.len() <= knocked_out);
+        assert_eq!(report.lines().len(), report.bouts.len() + 1);
+    }
+
+    #[test]
+    fn winner_stays_test() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let player = muster(&world, &[50], Difficulty::Normal, &mut rng);
+        let enemy = muster(&world, &[1, 1, 1], Difficulty::Easy, &mut rng);
+
+        let report = war(&player, &enemy, &world, &mut rng);
+        assert_eq!(report.bouts.len(), 3);
+        assert!(report.bouts.iter().all(|bout| bout.player == player[0].character.name));
+        assert_eq!(report.lines().last().unwrap(), "Your squad won the war with 1 left standing.");
+    }
+
+    #[test]
+    fn empty_squad_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let world: OnionWorld = Standard.sample(&mut rng);
+        let enemy = muster(&world, &[5], Difficulty::Easy, &mut rng);
+        let report = war(&[], &enemy, &world, &mut rng);
+        assert!(report.bouts.is_empty());
+        assert_eq!(report.winner, Some(Side::Enemy));
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::States;
+use crate::onion::{Alignment, Field, OnionCharacter};
+
+// how many turns weather lasts once an action sets it
+pub static WEATHER_TURNS: u32 = 5;
+// in tenths, like stab
+static RAIN_BOOST: u32 = 15;
+static SANDSTORM_FRACTION: i32 = 16;
+
+// battle-wide, and gone once its turns run out
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Weather {
+    // paper attacks hit harder
+    Rain,
+    // everything that isn't rock is chipped every turn
+    Sandstorm,
+}
+
+impl Weather {
+    pub fn description(&self) -> String {
+        match self {
+            Weather::Rain => "Paper attacks are stronger.",
+            Weather::Sandstorm => "Kaizo that aren't Rock are hurt every turn.",
+        }.to_string()
+    }
+
+    // the resource it's shown with
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Weather::Rain => "rain",
+            Weather::Sandstorm => "sandstorm",
+        }
+    }
+
+    fn started(&self) -> &'static str {
+        match self {
+            Weather::Rain => "It started to rain.",
+            Weather::Sandstorm => "A sandstorm kicked up.",
+        }
+    }
+
+    fn stopped(&self) -> &'static str {
+        match self {
+            Weather::Rain => "The rain stopped.",
+            Weather::Sandstorm => "The sandstorm subsided.",
+        }
+    }
+
+    // in tenths, for attacks of that alignment
+    pub fn boost(&self, alignment: Alignment) -> u32 {
+        match (self, alignment) {
+            (Weather::Rain, Alignment::Paper) => RAIN_BOOST,
+            _ => 10,
+        }
+    }
+
+    fn on_end_turn(&self, holder: &mut OnionCharacter) -> States {
+        match self {
+            Weather::Sandstorm if !holder.state.alignments().contains(&Alignment::Rock) && holder.state.health > 0 => {
+                let damage = std::cmp::max(1, holder.attributes.stats.health as i32 / SANDSTORM_FRACTION);
+                holder.state.health = std::cmp::max(0, holder.state.health - damage);
+                vec![format!("{} is buffeted by the sandstorm.", holder.name)]
+            },
+            _ => vec![],
+        }
+    }
+}
+
+// replaces whatever weather there was
+pub fn set(field: &mut Field, weather: Weather, turns: u32) -> States {
+    if field.weather == Some(weather) {
+        return vec!["But nothing happened.".to_string()];
+    }
+    field.weather = Some(weather);
+    field.weather_turns = turns;
+    vec![weather.started().to_string()]
+}
+
+// after both sides have acted: the weather hits each of them, then counts down
+pub fn end_turn(field: &mut Field, player: &mut OnionCharacter, enemy: &mut OnionCharacter) -> States {
+    let weather = match field.weather {
+        Some(weather) => weather,
+        None => return vec![],
+    };
+    let mut logs = weather.on_end_turn(player);
+    logs.extend(weather.on_end_turn(enemy));
+    field.weather_turns = field.weather_turns.saturating_sub(1);
+    if field.weather_turns == 0 {
+        field.weather = None;
+        logs.push(weather.stopped().to_string());
+    }
+    logs
+}
+
+#[cfg(test)]
+mod weather_tests {
+    use super::*;
+
+    use crate::core::Action;
+    use crate::onion::{Attack, testing};
+
+    #[test]
+    fn set_test() {
+        let mut field = Field::default();
+        assert_eq!(set(&mut field, Weather::Rain, 2), vec!["It started to rain."]);
+        assert_eq!(set(&mut field, Weather::Rain, 2), vec!["But nothing happened."]);
+        assert_eq!(field.weather.unwrap().boost(Alignment::Paper), 15);
+        assert_eq!(field.weather.unwrap().boost(Alignment::Rock), 10);
+
+        let (mut player, mut enemy) = (testing::fake_character(), testing::fake_character());
+        assert!(end_turn(&mut field, &mut player, &mut enemy).is_empty());
+        assert_eq!(end_turn(&mut field, &mut player, &mut enemy), vec!["The rain stopped."]);
+        assert_eq!(field.weather, None);
+    }
+
+    #[test]
+    fn sandstorm_test() {
+        let mut field = Field::default();
+        set(&mut field, Weather::Sandstorm, WEATHER_TURNS);
+        let (mut player, mut enemy) = (testing::fake_character(), testing::fake_character());
+        player.attributes.stats.health = 160;
+        player.refresh();
+        player.state.alignment = Alignment::Paper;
+        enemy.refresh();
+        enemy.state.alignment = Alignment::Rock;
+
+        // rock kaizo weather it
+        assert_eq!(end_turn(&mut field, &mut player, &mut enemy), vec!["fake is buffeted by the sandstorm."]);
+        assert_eq!(player.state.health, 150);
+        assert_eq!(enemy.state.health, enemy.attributes.stats.health as i32);
+        assert_eq!(field.weather_turns, WEATHER_TURNS - 1);
+    }
+
+    #[test]
+    fn rain_test() {
+        let fight = |field: &mut Field, alignment| {
+            let mut user = testing::fake_character_with_bst(300);
+            let mut target = testing::fake_character_with_bst(300);
+            user.attributes.stats = testing::fake_stats_with_value(100);
+            target.attributes.stats = testing::fake_stats_with_value(1000);
+            target.refresh();
+            Attack::new("fake", 40, alignment, 0).act(&mut user, &mut target, field);
+            target.attributes.stats.health as i32 - target.state.health
+        };
+        let (mut clear, mut rain) = (Field::default(), Field::default());
+        set(&mut rain, Weather::Rain, WEATHER_TURNS);
+        // rock against rock, so only the weather differs
+        assert_eq!(fight(&mut rain, Alignment::Rock), fight(&mut clear, Alignment::Rock));
+        assert!(fight(&mut rain, Alignment::Paper) > fight(&mut clear, Alignment::Paper));
+    }
+}
@@ -0,0 +1,143 @@
+// end-to-end flows through the public api, the way a frontend other than the yew app would use it
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use kaizo_quest::ai::Greedy;
+use kaizo_quest::prelude::*;
+
+static WORLD: &str = r#"{
+    "species": [
+        {
+            "name": "Pebble",
+            "bst": 300,
+            "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 },
+            "alignment": "Rock"
+        },
+        {
+            "name": "Scrap",
+            "bst": 300,
+            "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 },
+            "alignment": "Paper"
+        }
+    ],
+    "actions": {
+        "attack": [
+            { "name": "Tackle", "power": 40, "alignment": "Rock", "priority": 0 },
+            { "name": "Fold", "power": 40, "alignment": "Paper", "priority": 0 }
+        ],
+        "pure_attack": [],
+        "defend": [{ "name": "Block" }],
+        "bleed": [],
+        "stun": [],
+        "padding": 0
+    }
+}"#;
+
+static PLAYER: &str = "
+=== Player ===
+
+Pebble
+Level: 20
+- Tackle
+- Block
+";
+
+static ENEMY: &str = "
+=== Enemy ===
+
+Scrap
+Level: 10
+- Fold
+- Block
+";
+
+fn world() -> OnionWorld {
+    serde_json::from_str(WORLD).unwrap()
+}
+
+fn action(world: &OnionWorld, name: &str) -> ActionId {
+    world.actions.ids().find(|id| world.actions[*id].name() == name).unwrap()
+}
+
+fn lead(world: &OnionWorld, text: &str) -> OnionCharacter {
+    let team = Team::from_text(text, world).unwrap();
+    assert_eq!(team.validate(world, &[]), Ok(()));
+    team.build(world).remove(0)
+}
+
+fn battle(world: &OnionWorld) -> OnionBattle {
+    OnionBattle::new(lead(world, PLAYER), lead(world, ENEMY))
+}
+
+#[test]
+fn world_from_definition_test() {
+    let world = world();
+
+    assert_eq!(world.species().len(), 2);
+    assert_eq!(&*world.species()[1].name, "Scrap");
+    assert_eq!(world.actions.ids().count(), 3);
+    assert_eq!(world.actions[action(&world, "Block")].name(), "Block");
+    assert_eq!(world.chart(), &TypeChart::default());
+
+    // the same world built in code, with its own chart
+    let inverse = OnionWorld::new(world.species().iter().map(|species| species.as_ref().clone()).collect(), world.actions.clone(), TypeChart::default().inverse());
+    assert_eq!(inverse.species(), world.species());
+    assert_eq!(inverse.chart(), &TypeChart::default().inverse());
+}
+
+#[test]
+fn scripted_battle_test() {
+    let world = world();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut battle = battle(&world);
+    assert_eq!(battle.player.attributes.level, 20);
+    assert_eq!(battle.enemy.attributes.level, 10);
+
+    // the enemy blocks the first hit and then trades blows
+    let tackle = &world.actions[action(&world, "Tackle")];
+    let logs = battle.enemy_turn(&world.actions[action(&world, "Block")], &mut rng);
+    assert_eq!(logs, vec!["Scrap is defending."]);
+    let logs = battle.player_turn(tackle, &mut rng);
+    assert_eq!(logs, vec!["Pebble used Tackle.", "Scrap blocked Pebble's Tackle."]);
+    assert_eq!(battle.enemy.state.health, battle.enemy.attributes.stats.health as i32);
+    assert_eq!(battle.end_turn().0, OnionBattleState::InProcess);
+
+    let fold = &world.actions[action(&world, "Fold")];
+    let mut turns = 1;
+    let (state, logs) = loop {
+        battle.player_turn(tackle, &mut rng);
+        battle.enemy_turn(fold, &mut rng);
+        turns += 1;
+        match battle.end_turn() {
+            (OnionBattleState::InProcess, _) => assert!(turns < 100, "the battle never ended"),
+            result => break result,
+        }
+    };
+    assert_eq!(state, OnionBattleState::Victory);
+    assert_eq!(logs[0], "Defeated Scrap!");
+    assert_eq!(battle.enemy.state.health, 0);
+}
+
+#[test]
+fn ai_battle_test() {
+    let world = world();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut battle = battle(&world);
+    Ruleset::default().start_battle(&mut battle, &world, &mut rng);
+
+    let mut turns = 0;
+    let state = loop {
+        let player = Greedy.choose(&battle.player, &battle.enemy, &battle.field, &world.actions, &mut rng);
+        let enemy = Greedy.choose(&battle.enemy, &battle.player, &battle.field, &world.actions, &mut rng);
+        battle.player_turn(&world.actions[player], &mut rng);
+        battle.enemy_turn(&world.actions[enemy], &mut rng);
+        turns += 1;
+        match battle.end_turn() {
+            (OnionBattleState::InProcess, _) => assert!(turns < 100, "the battle never ended"),
+            (state, _) => break state,
+        }
+    };
+    // both sides always attack, and the player is twice the level
+    assert_eq!(state, OnionBattleState::Victory);
+}
@@ -0,0 +1,142 @@
+// a run driven through `GameSession` the way a frontend drives it, battle after battle, so changes
+// to how the game flows between the menu and battles don't go unnoticed
+
+use kaizo_quest::config::{GameConfig, Preset};
+use kaizo_quest::core::States;
+use kaizo_quest::onion::{OnionBattleState, OnionWorld};
+use kaizo_quest::seed::Seed;
+use kaizo_quest::session::{GameSession, SessionError};
+use kaizo_quest::team::Team;
+
+// pebbles learn fold at level 21
+static WORLD: &str = r#"{
+    "species": [
+        {
+            "name": "Pebble",
+            "bst": 300,
+            "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 },
+            "alignment": "Rock",
+            "learnset": [[21, 1]]
+        },
+        {
+            "name": "Scrap",
+            "bst": 300,
+            "stats": { "health": 0.25, "attack": 0.25, "defense": 0.25, "special_attack": 0.25, "special_defense": 0.25, "speed": 0.25 },
+            "alignment": "Paper"
+        }
+    ],
+    "actions": {
+        "attack": [
+            { "name": "Tackle", "power": 40, "alignment": "Rock", "priority": 0 },
+            { "name": "Fold", "power": 40, "alignment": "Paper", "priority": 0 }
+        ],
+        "pure_attack": [],
+        "defend": [{ "name": "Block" }],
+        "bleed": [],
+        "stun": [],
+        "padding": 0
+    }
+}"#;
+
+static PLAYER: &str = "
+=== Player ===
+
+Pebble
+Level: 20
+- Tackle
+- Block
+";
+
+// most wins a run is given to reach the next level
+static MAX_WINS: u32 = 100;
+
+fn session(seed: Seed) -> GameSession {
+    let world: OnionWorld = serde_json::from_str(WORLD).unwrap();
+    let mut player = Team::from_text(PLAYER, &world).unwrap().build(&world).remove(0);
+    // strong enough that every battle is a win, however the enemy plays, at every level it reaches
+    player.attributes.bonus.attack = 1000;
+    player.attributes.bonus.health = 1000;
+    player.recalculate_stats();
+    player.refresh();
+    let mut session = GameSession::new(world, player, seed);
+    // and healed between battles, so none of them wear it down
+    session.config = GameConfig::preset(Preset::Casual);
+    session
+}
+
+// from the menu into a battle and back out, tackling until it's over. wild kaizo can run off
+// before they're beaten, in which case the next one is fought instead
+fn win(session: &mut GameSession) -> States {
+    loop {
+        let upcoming = session.upcoming().name.clone();
+        let mut logs = session.start_battle().unwrap();
+        assert_eq!(logs[0], format!("A wild {} appeared!", upcoming));
+        assert!(session.battle().is_some());
+
+        let mut turns = 0;
+        let state = loop {
+            let (state, turn_logs) = session.choose(0).unwrap();
+            logs.extend(turn_logs);
+            turns += 1;
+            match state {
+                OnionBattleState::InProcess => assert!(turns < 100, "the battle never ended"),
+                state => break state,
+            }
+        };
+        match state {
+            OnionBattleState::Escaped => assert!(session.battle().is_none()),
+            state => {
+                assert_eq!(state, OnionBattleState::Victory);
+                return logs;
+            },
+        }
+    }
+}
+
+// the enemy's name, from the line that announced it
+fn enemy(logs: &States) -> &str {
+    logs[0].trim_start_matches("A wild ").trim_end_matches(" appeared!")
+}
+
+#[test]
+fn run_test() {
+    let mut session = session(Seed(0));
+    let level = session.player().attributes.level;
+
+    let mut learned = false;
+    while !learned {
+        let wins = session.wins();
+        assert!(wins < MAX_WINS, "the kaizo never levelled up");
+        let logs = win(&mut session);
+        assert!(logs.contains(&format!("Defeated {}!", enemy(&logs))));
+
+        // back at the menu, healed, with the win counted and the next enemy at the kaizo's level
+        assert!(session.battle().is_none());
+        assert_eq!(session.choose(0), Err(SessionError::NoBattle));
+        assert_eq!(session.wins(), wins + 1);
+        assert_eq!(session.run().wins(), session.wins() as usize);
+        assert_eq!(session.player().state.health, session.player().attributes.stats.health as i32);
+        assert_eq!(session.upcoming().attributes.level, session.player().attributes.level);
+
+        learned = logs.contains(&"Pebble learned Fold!".to_string());
+    }
+    // the move comes with the level up, and there was room for it
+    assert!(session.player().attributes.level > level);
+    assert!(session.player().attributes.actions.iter().any(|action| session.world().actions[*action].name() == "Fold"));
+    assert_eq!(session.learning(), None);
+
+    // and the run carries on from there
+    win(&mut session);
+    assert!(session.battle().is_none());
+}
+
+#[test]
+fn replay_test() {
+    let play = |seed| {
+        let mut session = session(seed);
+        (0..5).flat_map(|_| win(&mut session)).collect::<States>()
+    };
+    // the same seed plays out the same way, battle after battle
+    assert_eq!(play(Seed(0)), play(Seed(0)));
+    assert_ne!(play(Seed(0)), play(Seed(1)));
+}